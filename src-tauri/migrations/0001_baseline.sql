@@ -0,0 +1,582 @@
+-- Migration 0001: Baseline schema
+-- Consolidates the original schema.sql, its ALTER TABLE sync columns,
+-- migration_v2 (Firebase columns) and migration_v3 (two-factor auth).
+
+-- Users Table
+CREATE TABLE users (
+    user_id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    name               TEXT NOT NULL,
+    role               TEXT CHECK(role IN ('Judge', 'Magistrate', 'Court Admin', 'Court Clerk', 'Other')) NOT NULL,
+    email              TEXT,
+    phone_number       TEXT UNIQUE,
+    password_hash      TEXT NOT NULL,
+    professional_title TEXT,
+    status             TEXT CHECK(status IN ('Active', 'Inactive')) NOT NULL DEFAULT 'Active',
+    last_modified      TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status        TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted         BOOLEAN DEFAULT FALSE,
+    sync_version       INTEGER DEFAULT 1,
+    firestore_id       TEXT
+);
+
+-- Staff Table
+CREATE TABLE staff (
+    staff_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id        INTEGER NOT NULL,
+    role           TEXT NOT NULL,
+    contact_number TEXT,
+    status         TEXT CHECK(status IN ('Active', 'Absent')) NOT NULL DEFAULT 'Active',
+    last_modified  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status    TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted     BOOLEAN DEFAULT FALSE,
+    sync_version   INTEGER DEFAULT 1,
+    firestore_id   TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
+
+-- Attendance Table
+CREATE TABLE attendance (
+    attendance_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    staff_id      INTEGER NOT NULL,
+    date          DATE NOT NULL,
+    status        TEXT CHECK(status IN ('Present', 'Absent')) NOT NULL,
+    reason        TEXT,
+    half_day      BOOLEAN NOT NULL DEFAULT 0,
+    comments      TEXT,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status   TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    sync_version  INTEGER DEFAULT 1,
+    firestore_id  TEXT,
+    FOREIGN KEY (staff_id) REFERENCES staff(staff_id) ON DELETE CASCADE
+);
+
+-- Cases Table (with Firebase and mobile-aligned columns)
+CREATE TABLE cases (
+    case_id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    title                 TEXT NOT NULL,
+    status                TEXT CHECK(status IN ('Open', 'In Progress', 'Closed')) NOT NULL DEFAULT 'Open',
+    assigned_staff_id     INTEGER,
+    priority              TEXT CHECK(priority IN ('Low', 'Medium', 'High')) NOT NULL DEFAULT 'Medium',
+    date_created          TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified         TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status           TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted            BOOLEAN DEFAULT FALSE,
+    sync_version          INTEGER DEFAULT 1,
+    firestore_id          TEXT UNIQUE,
+    court_id              TEXT,
+    case_number           TEXT,
+    case_type             TEXT,
+    description           TEXT,
+    court_name            TEXT,
+    date_filed            TEXT,
+    date_of_judgment      TEXT,
+    judge_name            TEXT,
+    complainant_name      TEXT,
+    accused_name          TEXT,
+    charge_description    TEXT,
+    applicable_law        TEXT,
+    verdict               TEXT,
+    sentence              TEXT,
+    mitigation_notes      TEXT,
+    prosecution_counsel   TEXT,
+    defense_witnesses     TEXT,
+    prosecution_witnesses TEXT,
+    evidence_summary      TEXT,
+    appeal_status         TEXT,
+    location_of_offence   TEXT,
+    hearing_dates         TEXT,
+    court_assistant       TEXT,
+    uploaded_by_id        TEXT,
+    is_new                BOOLEAN DEFAULT 0,
+    has_changes           BOOLEAN DEFAULT 0,
+    last_synced_at        INTEGER,
+    version               INTEGER DEFAULT 1,
+    FOREIGN KEY (assigned_staff_id) REFERENCES staff(staff_id) ON DELETE SET NULL
+);
+
+CREATE TABLE files (
+    file_id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_number             TEXT NOT NULL,
+    case_type               TEXT CHECK(case_type IN ('Civil', 'Criminal', 'Succession', 'Children', 'Other')) NOT NULL, -- type of case
+    purpose                 TEXT CHECK(purpose IN ('Ruling', 'Judgement', 'Hearing', 'Mention', 'Other')) NOT NULL,
+    uploaded_by             INTEGER, -- ID of the user who uploaded the file
+    current_location        TEXT NOT NULL, -- Current location of the file (e.g., court, archive, etc.)
+    notes                   TEXT NOT NULL, -- Notes or comments about the file
+    date_recieved           TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- Date the file was recorded/uploaded
+    required_on             TIMESTAMP NOT NULL, -- Date the file is needed by
+    required_on_signature   TEXT, -- Signature of the person who recorded the file
+    date_returned           TIMESTAMP, -- Date the file was returned
+    date_returned_signature TEXT, -- Signature of the person who returned the file
+    deleted                 INT DEFAULT 0, -- 1 for true, 0 for false
+    last_modified           TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status             TEXT DEFAULT 'pending' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted              BOOLEAN DEFAULT FALSE,
+    sync_version            INTEGER DEFAULT 1,
+    firestore_id            TEXT
+);
+
+-- Indexes for faster searching and filtering on the files table
+CREATE INDEX idx_files_case_number ON files(case_number);
+CREATE INDEX idx_files_purpose ON files(purpose);
+CREATE INDEX idx_files_uploaded_by ON files(uploaded_by);
+CREATE INDEX idx_files_current_location ON files(current_location);
+CREATE INDEX idx_files_date_recieved ON files(date_recieved);
+CREATE INDEX idx_files_required_on ON files(required_on);
+CREATE INDEX idx_files_date_returned ON files(date_returned);
+CREATE INDEX idx_files_deleted ON files(deleted);
+
+-- Notifications Table
+CREATE TABLE notifications (
+    notification_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    message         TEXT NOT NULL,
+    type            TEXT CHECK(type IN ('Info', 'Warning', 'Error', 'Success')) NOT NULL,
+    date_created    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    read_status     BOOLEAN NOT NULL DEFAULT 0,
+    user_id         INTEGER,
+    last_modified   TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status     TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted      BOOLEAN DEFAULT FALSE,
+    sync_version    INTEGER DEFAULT 1,
+    firestore_id    TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
+
+-- Contacts Table
+CREATE TABLE contacts (
+    contact_id    INTEGER PRIMARY KEY AUTOINCREMENT,
+    name          TEXT NOT NULL,
+    role          TEXT CHECK(role IN ('Court Admin', 'Super Admin')) NOT NULL,
+    phone_number  TEXT NOT NULL,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status   TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    sync_version  INTEGER DEFAULT 1,
+    firestore_id  TEXT
+);
+
+-- Reports & Analytics Table
+CREATE TABLE reports_analytics (
+    report_id     INTEGER PRIMARY KEY AUTOINCREMENT,
+    type          TEXT CHECK(type IN ('Attendance', 'Cases', 'Files Processed')) NOT NULL,
+    data_json     TEXT NOT NULL,
+    generated_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status   TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    sync_version  INTEGER DEFAULT 1,
+    firestore_id  TEXT
+);
+
+-- News Table
+CREATE TABLE news (
+    news_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    title          TEXT NOT NULL,
+    content        TEXT NOT NULL,
+    published_date TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status    TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted     BOOLEAN DEFAULT FALSE,
+    sync_version   INTEGER DEFAULT 1,
+    firestore_id   TEXT
+);
+
+-- Themes Table
+CREATE TABLE themes (
+    theme_id      INTEGER PRIMARY KEY AUTOINCREMENT,
+    name          TEXT NOT NULL,
+    config_json   TEXT NOT NULL,
+    user_id       INTEGER,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status   TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    sync_version  INTEGER DEFAULT 1,
+    firestore_id  TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+-- Summaries Table
+CREATE TABLE summaries (
+    summary_id    INTEGER PRIMARY KEY AUTOINCREMENT,
+    title         TEXT NOT NULL,
+    content       TEXT NOT NULL,
+    category      TEXT CHECK(category IN ('Cases', 'Files', 'Attendance', 'Notifications', 'Overall')) NOT NULL,
+    generated_at  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    generated_by  TEXT NOT NULL,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status   TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    sync_version  INTEGER DEFAULT 1,
+    firestore_id  TEXT
+);
+
+-- Offenders Table (with Firebase and mobile-aligned columns)
+CREATE TABLE offenders (
+    offender_id                    INTEGER PRIMARY KEY AUTOINCREMENT,
+    full_name                      TEXT NOT NULL,
+    national_id                    TEXT,
+    date_of_birth                  DATE,
+    gender                         TEXT,
+    photo_path                     TEXT, -- Path to photo file stored on disk
+    notes                          TEXT,
+    date_created                   TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    file_id                        INTEGER,
+    penalty                        TEXT, -- e.g. Fine, Locked Up, Community Service, etc.
+    penalty_notes                  TEXT, -- additional notes on penalty
+    last_modified                  TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sync_status                    TEXT DEFAULT 'synced' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted                     BOOLEAN DEFAULT FALSE,
+    sync_version                   INTEGER DEFAULT 1,
+    firestore_id                   TEXT UNIQUE,
+    court_id                       TEXT,
+    alias                          TEXT,
+    nationality                    TEXT,
+    marital_status                 TEXT,
+    occupation                     TEXT,
+    address                        TEXT,
+    first_offender                 BOOLEAN,
+    criminal_history               TEXT,
+    known_associates               TEXT,
+    arresting_officer              TEXT,
+    place_of_arrest                TEXT,
+    arrest_date                    TEXT,
+    case_number                    TEXT,
+    eye_color                      TEXT,
+    hair_color                     TEXT,
+    phone_number                   TEXT,
+    emergency_contact_name         TEXT,
+    emergency_contact_phone        TEXT,
+    emergency_contact_relationship TEXT,
+    legal_representation           TEXT,
+    medical_conditions             TEXT,
+    risk_level                     TEXT,
+    distinguishing_marks           TEXT,
+    type                           TEXT,
+    status                         TEXT DEFAULT 'Active',
+    facility                       TEXT,
+    offense_type                   TEXT,
+    uploaded_by_id                 TEXT,
+    photo_storage_url              TEXT,
+    is_new                         BOOLEAN DEFAULT 0,
+    has_changes                    BOOLEAN DEFAULT 0,
+    last_synced_at                 INTEGER,
+    sync_retry_count               INTEGER DEFAULT 0,
+    version                        INTEGER DEFAULT 1,
+    FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE SET NULL
+);
+
+-- Offender History Table
+CREATE TABLE offender_history (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    offender_id   INTEGER NOT NULL,
+    file_id       INTEGER,
+    case_id       INTEGER,
+    offense_date  DATE,
+    penalty       TEXT,
+    penalty_notes TEXT,
+    notes         TEXT,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE CASCADE,
+    FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE SET NULL,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL
+);
+
+-- HISTORY ( Activated By Triggers )
+-- Tracks history of when the file is required on
+CREATE TABLE history_required_on_in_files (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_id     INTEGER NOT NULL,
+    required_on TIMESTAMP NOT NULL,
+    note        TEXT, -- optional: why the change happened
+    FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE CASCADE
+);
+
+-- Tracks history of the notes on files
+CREATE TABLE history_notes_in_files (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_id    INTEGER NOT NULL,
+    note       TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    created_by INTEGER,
+    FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(user_id) ON DELETE SET NULL
+);
+
+-- Track sync sessions
+CREATE TABLE sync_sessions (
+    session_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at        TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at      TIMESTAMP,
+    direction         TEXT NOT NULL CHECK(direction IN ('upload', 'download')),
+    records_processed INTEGER DEFAULT 0,
+    status            TEXT CHECK(status IN ('in-progress', 'completed', 'failed')),
+    error_message     TEXT
+);
+
+-- Track conflicts that need manual resolution
+CREATE TABLE sync_conflicts (
+    conflict_id   INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name    TEXT NOT NULL,
+    record_id     INTEGER NOT NULL,
+    online_data   TEXT,
+    local_data    TEXT,
+    conflict_type TEXT NOT NULL,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    resolved_at   TIMESTAMP,
+    resolution    TEXT CHECK(resolution IN ('keep_online', 'keep_local', 'merged')),
+    resolved_by   INTEGER REFERENCES users(user_id),
+    resolved      BOOLEAN DEFAULT FALSE
+);
+
+-- Track last sync times
+CREATE TABLE sync_metadata (
+    id        INTEGER PRIMARY KEY DEFAULT 1,
+    last_sync TIMESTAMP,
+    CONSTRAINT one_row CHECK (id = 1)
+);
+
+INSERT INTO sync_metadata (id, last_sync) VALUES (1, NULL);
+
+-- Two-factor authentication (TOTP) for administrator accounts
+CREATE TABLE user_totp (
+    email          TEXT PRIMARY KEY, -- lower-cased login email
+    secret         TEXT NOT NULL, -- base32 shared secret
+    confirmed      BOOLEAN NOT NULL DEFAULT 0, -- 1 once the user has proven a first code
+    last_used_step INTEGER, -- last accepted time step, blocks code replay
+    created_at     TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    confirmed_at   TIMESTAMP
+);
+
+-- One-time recovery codes (stored hashed)
+CREATE TABLE user_recovery_codes (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    email     TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at   TIMESTAMP,
+    FOREIGN KEY (email) REFERENCES user_totp(email) ON DELETE CASCADE
+);
+
+-- INDEXES
+CREATE INDEX idx_history_required_on_file_id ON history_required_on_in_files(file_id);
+CREATE INDEX idx_history_notes_file_id ON history_notes_in_files(file_id);
+CREATE INDEX idx_user_recovery_codes_email ON user_recovery_codes(email);
+
+-- Indexes for sync performance
+CREATE INDEX idx_users_sync ON users(sync_status, last_modified);
+CREATE INDEX idx_staff_sync ON staff(sync_status, last_modified);
+CREATE INDEX idx_attendance_sync ON attendance(sync_status, last_modified);
+CREATE INDEX idx_cases_sync ON cases(sync_status, last_modified);
+CREATE INDEX idx_files_sync ON files(sync_status, last_modified);
+CREATE INDEX idx_notifications_sync ON notifications(sync_status, last_modified);
+CREATE INDEX idx_contacts_sync ON contacts(sync_status, last_modified);
+CREATE INDEX idx_reports_sync ON reports_analytics(sync_status, last_modified);
+CREATE INDEX idx_news_sync ON news(sync_status, last_modified);
+CREATE INDEX idx_themes_sync ON themes(sync_status, last_modified);
+CREATE INDEX idx_summaries_sync ON summaries(sync_status, last_modified);
+CREATE INDEX idx_offenders_name ON offenders(full_name);
+CREATE INDEX idx_offenders_firestore_id ON offenders(firestore_id);
+CREATE INDEX idx_offenders_court_id ON offenders(court_id);
+CREATE INDEX idx_cases_firestore_id ON cases(firestore_id);
+CREATE INDEX idx_cases_court_id ON cases(court_id);
+
+-- TRIGGERS
+-- Record changes to required_on and notes, and bump last_modified
+CREATE TRIGGER history_of_files
+AFTER UPDATE ON files
+FOR EACH ROW
+BEGIN
+    -- Record changes to required_on
+    INSERT INTO history_required_on_in_files (file_id, required_on, note)
+    SELECT NEW.file_id, NEW.required_on, 'required_on changed'
+    WHERE NEW.required_on IS NOT NULL
+      AND (OLD.required_on IS NULL OR NEW.required_on <> OLD.required_on);
+
+    -- Record changes to notes
+    INSERT INTO history_notes_in_files (file_id, note, created_at, created_by)
+    SELECT NEW.file_id, NEW.notes, CURRENT_TIMESTAMP, NEW.uploaded_by
+    WHERE NEW.notes IS NOT NULL
+      AND NEW.notes <> OLD.notes;
+
+    -- Update last_modified when relevant fields change
+    UPDATE files SET last_modified = CURRENT_TIMESTAMP
+    WHERE file_id = NEW.file_id AND (
+        OLD.case_number <> NEW.case_number OR
+        OLD.case_type <> NEW.case_type OR
+        OLD.purpose <> NEW.purpose OR
+        OLD.current_location <> NEW.current_location OR
+        OLD.notes <> NEW.notes OR
+        OLD.required_on <> NEW.required_on OR
+        OLD.deleted <> NEW.deleted
+    );
+END;
+
+-- Update last_modified on the other tables
+CREATE TRIGGER update_user_modtime
+AFTER UPDATE ON users
+FOR EACH ROW
+BEGIN
+    UPDATE users SET last_modified = CURRENT_TIMESTAMP
+    WHERE user_id = NEW.user_id AND (
+        OLD.name <> NEW.name OR
+        OLD.role <> NEW.role OR
+        OLD.email <> NEW.email OR
+        OLD.phone_number <> NEW.phone_number OR
+        OLD.status <> NEW.status OR
+        OLD.is_deleted <> NEW.is_deleted
+    );
+END;
+
+CREATE TRIGGER update_staff_modtime
+AFTER UPDATE ON staff
+FOR EACH ROW
+BEGIN
+    UPDATE staff SET last_modified = CURRENT_TIMESTAMP
+    WHERE staff_id = NEW.staff_id AND (
+        OLD.role <> NEW.role OR
+        OLD.contact_number <> NEW.contact_number OR
+        OLD.status <> NEW.status OR
+        OLD.is_deleted <> NEW.is_deleted
+    );
+END;
+
+CREATE TRIGGER update_attendance_modtime
+AFTER UPDATE ON attendance
+FOR EACH ROW
+BEGIN
+    UPDATE attendance SET last_modified = CURRENT_TIMESTAMP
+    WHERE attendance_id = NEW.attendance_id AND (
+        OLD.staff_id <> NEW.staff_id OR
+        OLD.date <> NEW.date OR
+        OLD.status <> NEW.status OR
+        OLD.reason <> NEW.reason OR
+        OLD.half_day <> NEW.half_day OR
+        OLD.comments <> NEW.comments
+    );
+END;
+
+CREATE TRIGGER update_cases_modtime
+AFTER UPDATE ON cases
+FOR EACH ROW
+BEGIN
+    UPDATE cases SET last_modified = CURRENT_TIMESTAMP
+    WHERE case_id = NEW.case_id AND (
+        OLD.title <> NEW.title OR
+        OLD.status <> NEW.status OR
+        OLD.assigned_staff_id <> NEW.assigned_staff_id OR
+        OLD.priority <> NEW.priority
+    );
+END;
+
+CREATE TRIGGER update_files_modtime
+AFTER UPDATE ON files
+FOR EACH ROW
+BEGIN
+    UPDATE files SET last_modified = CURRENT_TIMESTAMP
+    WHERE file_id = NEW.file_id AND (
+        OLD.case_number <> NEW.case_number OR
+        OLD.case_type <> NEW.case_type OR
+        OLD.purpose <> NEW.purpose OR
+        OLD.current_location <> NEW.current_location OR
+        OLD.notes <> NEW.notes OR
+        OLD.required_on <> NEW.required_on OR
+        OLD.deleted <> NEW.deleted
+    );
+END;
+
+CREATE TRIGGER update_notifications_modtime
+AFTER UPDATE ON notifications
+FOR EACH ROW
+BEGIN
+    UPDATE notifications SET last_modified = CURRENT_TIMESTAMP
+    WHERE notification_id = NEW.notification_id AND (
+        OLD.message <> NEW.message OR
+        OLD.type <> NEW.type OR
+        OLD.read_status <> NEW.read_status
+    );
+END;
+
+CREATE TRIGGER update_contacts_modtime
+AFTER UPDATE ON contacts
+FOR EACH ROW
+BEGIN
+    UPDATE contacts SET last_modified = CURRENT_TIMESTAMP
+    WHERE contact_id = NEW.contact_id AND (
+        OLD.name <> NEW.name OR
+        OLD.role <> NEW.role OR
+        OLD.phone_number <> NEW.phone_number
+    );
+END;
+
+CREATE TRIGGER update_reports_analytics_modtime
+AFTER UPDATE ON reports_analytics
+FOR EACH ROW
+BEGIN
+    UPDATE reports_analytics SET last_modified = CURRENT_TIMESTAMP
+    WHERE report_id = NEW.report_id AND (
+        OLD.type <> NEW.type OR
+        OLD.data_json <> NEW.data_json
+    );
+END;
+
+CREATE TRIGGER update_news_modtime
+AFTER UPDATE ON news
+FOR EACH ROW
+BEGIN
+    UPDATE news SET last_modified = CURRENT_TIMESTAMP
+    WHERE news_id = NEW.news_id AND (
+        OLD.title <> NEW.title OR
+        OLD.content <> NEW.content
+    );
+END;
+
+CREATE TRIGGER update_themes_modtime
+AFTER UPDATE ON themes
+FOR EACH ROW
+BEGIN
+    UPDATE themes SET last_modified = CURRENT_TIMESTAMP
+    WHERE theme_id = NEW.theme_id AND (
+        OLD.name <> NEW.name OR
+        OLD.config_json <> NEW.config_json
+    );
+END;
+
+CREATE TRIGGER update_summaries_modtime
+AFTER UPDATE ON summaries
+FOR EACH ROW
+BEGIN
+    UPDATE summaries SET last_modified = CURRENT_TIMESTAMP
+    WHERE summary_id = NEW.summary_id AND (
+        OLD.title <> NEW.title OR
+        OLD.content <> NEW.content OR
+        OLD.category <> NEW.category
+    );
+END;
+
+CREATE TRIGGER update_offenders_modtime
+AFTER UPDATE ON offenders
+FOR EACH ROW
+BEGIN
+    UPDATE offenders SET last_modified = CURRENT_TIMESTAMP
+    WHERE offender_id = NEW.offender_id AND (
+        OLD.full_name <> NEW.full_name OR
+        OLD.national_id <> NEW.national_id OR
+        OLD.date_of_birth <> NEW.date_of_birth OR
+        OLD.gender <> NEW.gender OR
+        OLD.photo_path <> NEW.photo_path OR
+        OLD.notes <> NEW.notes
+    );
+END;
+
+-- SPECIAL REQUEST: In a day, there should be a maximum of 6 Criminal Cases.
+-- If the limit is reached, the trigger will prevent the insertion of new records.
+CREATE TRIGGER check_criminal_case_limit
+BEFORE INSERT ON files
+WHEN NEW.case_type = 'Criminal' AND (
+    (SELECT COUNT(*)
+     FROM files
+     WHERE case_type = 'Criminal'
+       AND DATE(required_on) = DATE('now')) >= 6
+)
+BEGIN
+    SELECT RAISE(FAIL, 'Maximum limit of 6 Criminal cases reached for today.');
+END;
@@ -0,0 +1,122 @@
+-- Full-text search over files, cases and offenders.
+--
+-- One FTS5 table holds every searchable record so hits of all kinds rank
+-- against each other. The rowid encodes the record: id * 4 + 1 for a file,
+-- + 2 for a case, + 3 for an offender, so triggers can replace an entry
+-- without scanning the index. Deleted records are kept out of it.
+
+CREATE VIRTUAL TABLE search_index USING fts5(
+    kind      UNINDEXED, -- 'file', 'case' or 'offender'
+    record_id UNINDEXED,
+    label     UNINDEXED, -- what a hit is shown as
+    name,                -- case title; offender name and alias
+    reference,           -- case number; national id
+    body,                -- notes, parties, charges and descriptions
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Files: case number and notes
+
+CREATE TRIGGER search_files_insert AFTER INSERT ON files
+WHEN COALESCE(NEW.deleted, 0) = 0 AND COALESCE(NEW.is_deleted, 0) = 0
+BEGIN
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    VALUES (NEW.file_id * 4 + 1, 'file', NEW.file_id, NEW.case_number, NULL, NEW.case_number, NEW.notes);
+END;
+
+CREATE TRIGGER search_files_update AFTER UPDATE OF case_number, notes, deleted, is_deleted ON files
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.file_id * 4 + 1;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT NEW.file_id * 4 + 1, 'file', NEW.file_id, NEW.case_number, NULL, NEW.case_number, NEW.notes
+    WHERE COALESCE(NEW.deleted, 0) = 0 AND COALESCE(NEW.is_deleted, 0) = 0;
+END;
+
+CREATE TRIGGER search_files_delete AFTER DELETE ON files
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.file_id * 4 + 1;
+END;
+
+-- Cases: number, title, parties, charges and description
+
+CREATE TRIGGER search_cases_insert AFTER INSERT ON cases
+WHEN COALESCE(NEW.is_deleted, 0) = 0
+BEGIN
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    VALUES (
+        NEW.case_id * 4 + 2, 'case', NEW.case_id,
+        COALESCE(NEW.case_number || ' - ', '') || NEW.title,
+        NEW.title, NEW.case_number,
+        COALESCE(NEW.complainant_name, '') || char(10) || COALESCE(NEW.accused_name, '') || char(10) ||
+        COALESCE(NEW.charge_description, '') || char(10) || COALESCE(NEW.description, '')
+    );
+END;
+
+CREATE TRIGGER search_cases_update AFTER UPDATE OF title, case_number, complainant_name, accused_name, charge_description, description, is_deleted ON cases
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.case_id * 4 + 2;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT
+        NEW.case_id * 4 + 2, 'case', NEW.case_id,
+        COALESCE(NEW.case_number || ' - ', '') || NEW.title,
+        NEW.title, NEW.case_number,
+        COALESCE(NEW.complainant_name, '') || char(10) || COALESCE(NEW.accused_name, '') || char(10) ||
+        COALESCE(NEW.charge_description, '') || char(10) || COALESCE(NEW.description, '')
+    WHERE COALESCE(NEW.is_deleted, 0) = 0;
+END;
+
+CREATE TRIGGER search_cases_delete AFTER DELETE ON cases
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.case_id * 4 + 2;
+END;
+
+-- Offenders: name, alias, national id and notes
+
+CREATE TRIGGER search_offenders_insert AFTER INSERT ON offenders
+WHEN COALESCE(NEW.is_deleted, 0) = 0
+BEGIN
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    VALUES (
+        NEW.offender_id * 4 + 3, 'offender', NEW.offender_id, NEW.full_name,
+        NEW.full_name || COALESCE(char(10) || NEW.alias, ''), NEW.national_id, NEW.notes
+    );
+END;
+
+CREATE TRIGGER search_offenders_update AFTER UPDATE OF full_name, alias, national_id, notes, is_deleted ON offenders
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.offender_id * 4 + 3;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT
+        NEW.offender_id * 4 + 3, 'offender', NEW.offender_id, NEW.full_name,
+        NEW.full_name || COALESCE(char(10) || NEW.alias, ''), NEW.national_id, NEW.notes
+    WHERE COALESCE(NEW.is_deleted, 0) = 0;
+END;
+
+CREATE TRIGGER search_offenders_delete AFTER DELETE ON offenders
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.offender_id * 4 + 3;
+END;
+
+-- Index the records that already exist
+
+INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+SELECT file_id * 4 + 1, 'file', file_id, case_number, NULL, case_number, notes
+FROM files
+WHERE COALESCE(deleted, 0) = 0 AND COALESCE(is_deleted, 0) = 0;
+
+INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+SELECT
+    case_id * 4 + 2, 'case', case_id,
+    COALESCE(case_number || ' - ', '') || title,
+    title, case_number,
+    COALESCE(complainant_name, '') || char(10) || COALESCE(accused_name, '') || char(10) ||
+    COALESCE(charge_description, '') || char(10) || COALESCE(description, '')
+FROM cases
+WHERE COALESCE(is_deleted, 0) = 0;
+
+INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+SELECT
+    offender_id * 4 + 3, 'offender', offender_id, full_name,
+    full_name || COALESCE(char(10) || alias, ''), national_id, notes
+FROM offenders
+WHERE COALESCE(is_deleted, 0) = 0;
@@ -0,0 +1,20 @@
+-- Audit trail of merged duplicate offenders.
+--
+-- No foreign keys: the merged record is purged once its deletion has synced,
+-- and the trail must outlive both records.
+
+CREATE TABLE offender_merges (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    survivor_id         INTEGER NOT NULL,
+    merged_id           INTEGER NOT NULL,
+    merged_firestore_id TEXT,
+    merged_snapshot     TEXT NOT NULL, -- JSON of the merged record as it was
+    field_changes       TEXT NOT NULL, -- JSON list of {field, before, after, source}
+    history_moved       INTEGER NOT NULL DEFAULT 0,
+    photo_source        TEXT CHECK(photo_source IN ('survivor', 'merged')),
+    merged_by           TEXT,
+    merged_at           TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_offender_merges_survivor_id ON offender_merges(survivor_id);
+CREATE INDEX idx_offender_merges_merged_id ON offender_merges(merged_id);
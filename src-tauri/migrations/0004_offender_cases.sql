@@ -0,0 +1,65 @@
+-- Offenders linked to the cases they are party to, and in what role.
+--
+-- No foreign keys: offenders and cases are purged locally once deleted, but a
+-- link that has reached Firestore must stay behind as a tombstone until its
+-- deletion has synced. The triggers below stand in for ON DELETE CASCADE.
+-- Only one live link per offender, case and role; tombstones do not count.
+-- last_modified carries milliseconds: sync marks a pushed link synced only if
+-- it has not changed since it was read, and a second is too coarse for that.
+
+CREATE TABLE offender_cases (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    offender_id   INTEGER NOT NULL,
+    case_id       INTEGER NOT NULL,
+    role          TEXT NOT NULL CHECK(role IN ('accused', 'complainant', 'witness')),
+    notes         TEXT,
+    created_by    TEXT,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified TIMESTAMP DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    sync_status   TEXT DEFAULT 'pending' CHECK(sync_status IN ('synced', 'pending', 'conflict')),
+    is_deleted    BOOLEAN DEFAULT FALSE,
+    firestore_id  TEXT UNIQUE,
+    court_id      TEXT,
+    version       INTEGER DEFAULT 1
+);
+
+CREATE UNIQUE INDEX idx_offender_cases_live ON offender_cases(offender_id, case_id, role)
+    WHERE is_deleted = 0;
+CREATE INDEX idx_offender_cases_case_id ON offender_cases(case_id);
+
+-- When either side goes, links Firestore has never seen are dropped and the
+-- rest are tombstoned for the next sync
+
+CREATE TRIGGER offender_cases_offender_delete AFTER DELETE ON offenders
+BEGIN
+    DELETE FROM offender_cases WHERE offender_id = OLD.offender_id AND firestore_id IS NULL;
+    UPDATE offender_cases
+    SET is_deleted = 1, sync_status = 'pending', last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE offender_id = OLD.offender_id AND is_deleted = 0;
+END;
+
+CREATE TRIGGER offender_cases_offender_tombstone AFTER UPDATE OF is_deleted ON offenders
+WHEN COALESCE(NEW.is_deleted, 0) = 1 AND COALESCE(OLD.is_deleted, 0) = 0
+BEGIN
+    DELETE FROM offender_cases WHERE offender_id = OLD.offender_id AND firestore_id IS NULL;
+    UPDATE offender_cases
+    SET is_deleted = 1, sync_status = 'pending', last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE offender_id = OLD.offender_id AND is_deleted = 0;
+END;
+
+CREATE TRIGGER offender_cases_case_delete AFTER DELETE ON cases
+BEGIN
+    DELETE FROM offender_cases WHERE case_id = OLD.case_id AND firestore_id IS NULL;
+    UPDATE offender_cases
+    SET is_deleted = 1, sync_status = 'pending', last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE case_id = OLD.case_id AND is_deleted = 0;
+END;
+
+CREATE TRIGGER offender_cases_case_tombstone AFTER UPDATE OF is_deleted ON cases
+WHEN COALESCE(NEW.is_deleted, 0) = 1 AND COALESCE(OLD.is_deleted, 0) = 0
+BEGIN
+    DELETE FROM offender_cases WHERE case_id = OLD.case_id AND firestore_id IS NULL;
+    UPDATE offender_cases
+    SET is_deleted = 1, sync_status = 'pending', last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE case_id = OLD.case_id AND is_deleted = 0;
+END;
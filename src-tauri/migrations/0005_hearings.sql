@@ -0,0 +1,83 @@
+-- Hearings calendar, replacing the free-text cases.hearing_dates.
+--
+-- One row per sitting. Adjourning a hearing closes it with a reason and
+-- schedules the next sitting as a new row, linked through adjourned_to, so
+-- the whole chain of adjournments stays on record.
+
+CREATE TABLE hearings (
+    hearing_id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id            INTEGER NOT NULL,
+    hearing_date       TEXT NOT NULL, -- YYYY-MM-DD
+    hearing_time       TEXT,          -- HH:MM, when known
+    courtroom          TEXT,
+    presiding_officer  TEXT,
+    purpose            TEXT CHECK(purpose IN ('Mention', 'Hearing', 'Ruling', 'Judgement')) NOT NULL DEFAULT 'Hearing',
+    status             TEXT CHECK(status IN ('Scheduled', 'Adjourned', 'Concluded')) NOT NULL DEFAULT 'Scheduled',
+    outcome            TEXT,
+    adjournment_reason TEXT,
+    next_date          TEXT,          -- where an adjourned hearing went
+    adjourned_to       INTEGER,       -- the hearing it went to
+    notes              TEXT,
+    created_by         TEXT,
+    created_at         TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified      TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE CASCADE,
+    FOREIGN KEY (adjourned_to) REFERENCES hearings(hearing_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_hearings_date ON hearings(hearing_date, courtroom);
+CREATE INDEX idx_hearings_case_id ON hearings(case_id);
+
+-- Carry over the dates that can be read from hearing_dates: ISO dates, with
+-- or without a time, and day/month/year dates, separated by commas,
+-- semicolons or new lines. Past dates come across as concluded, later ones
+-- as scheduled. The text itself is left as it was.
+INSERT INTO hearings (case_id, hearing_date, hearing_time, status, notes)
+WITH RECURSIVE
+    parts(case_id, item, rest) AS (
+        SELECT case_id, '',
+               replace(replace(replace(hearing_dates, ';', ','), char(10), ','), char(13), ',') || ','
+        FROM cases
+        WHERE trim(COALESCE(hearing_dates, '')) <> '' AND COALESCE(is_deleted, 0) = 0
+        UNION ALL
+        SELECT case_id, trim(substr(rest, 1, instr(rest, ',') - 1)), substr(rest, instr(rest, ',') + 1)
+        FROM parts
+        WHERE rest <> ''
+    ),
+    iso(case_id, day, time) AS (
+        SELECT case_id, substr(item, 1, 10),
+               CASE WHEN item GLOB '????-??-??[ T][0-2][0-9]:[0-5][0-9]*'
+                    THEN substr(item, 12, 5) END
+        FROM parts
+        WHERE item GLOB '[12][0-9][0-9][0-9]-[01][0-9]-[0-3][0-9]*'
+    ),
+    dmy(case_id, item) AS (
+        SELECT case_id, replace(replace(item, '-', '/'), '.', '/')
+        FROM parts
+        WHERE item NOT GLOB '[12][0-9][0-9][0-9]-*'
+    ),
+    dmy_split(case_id, day, month, year) AS (
+        SELECT case_id,
+               substr(item, 1, instr(item, '/') - 1),
+               substr(substr(item, instr(item, '/') + 1), 1,
+                      instr(substr(item, instr(item, '/') + 1), '/') - 1),
+               substr(substr(item, instr(item, '/') + 1),
+                      instr(substr(item, instr(item, '/') + 1), '/') + 1)
+        FROM dmy
+        WHERE item GLOB '[0-9]/[0-9]/[12][0-9][0-9][0-9]'
+           OR item GLOB '[0-9][0-9]/[0-9]/[12][0-9][0-9][0-9]'
+           OR item GLOB '[0-9]/[0-9][0-9]/[12][0-9][0-9][0-9]'
+           OR item GLOB '[0-9][0-9]/[0-9][0-9]/[12][0-9][0-9][0-9]'
+    ),
+    parsed(case_id, day, time) AS (
+        SELECT case_id, day, time FROM iso WHERE date(day) = day
+        UNION
+        SELECT case_id, printf('%04d-%02d-%02d', year, month, day), NULL
+        FROM dmy_split
+        WHERE date(printf('%04d-%02d-%02d', year, month, day)) = printf('%04d-%02d-%02d', year, month, day)
+    )
+SELECT case_id, day, time,
+       CASE WHEN day < date('now', 'localtime') THEN 'Concluded' ELSE 'Scheduled' END,
+       'Imported from hearing dates'
+FROM parsed
+ORDER BY case_id, day, time;
@@ -0,0 +1,20 @@
+-- Case status transitions.
+--
+-- cases.status still holds the current state; every change made through
+-- the status workflow adds a row here saying who moved the case, from what,
+-- to what and why. Rows are never updated.
+
+CREATE TABLE case_status_history (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id         INTEGER NOT NULL,
+    from_status     TEXT NOT NULL,
+    to_status       TEXT NOT NULL,
+    transition      TEXT NOT NULL, -- start, return, close, reopen
+    reason          TEXT,
+    changed_by      TEXT,
+    changed_by_role TEXT,
+    changed_at      TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_case_status_history_case_id ON case_status_history(case_id, changed_at);
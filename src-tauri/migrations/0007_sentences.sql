@@ -0,0 +1,49 @@
+-- Structured sentences.
+--
+-- A sentence is passed on an offender, in a case, or both, and may belong
+-- to one offender_history entry. The dates are worked out from the terms
+-- when the sentence is saved and kept here so they can be queried. The free
+-- text in cases.sentence, offenders.penalty and offender_history.penalty is
+-- rewritten from these rows and is what syncs. A sentence outlives the
+-- offender record: deleting the offender clears offender_id and the name
+-- the sentence was passed on stays.
+
+CREATE TABLE sentences (
+    sentence_id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id              INTEGER,
+    offender_id          INTEGER,
+    offender_name        TEXT,    -- as sentenced
+    history_id           INTEGER,
+    penalty              TEXT NOT NULL CHECK(penalty IN (
+                             'Fine', 'Locked Up', 'Community Service', 'Probation',
+                             'Suspended Sentence', 'Discharge Under Section 35 of The Penal Code',
+                             'Withdrawal Under Section 204 CPC', 'Withdrawal Under Section 87a CPC',
+                             'Other')),
+    fine_amount_cents    INTEGER CHECK(fine_amount_cents IS NULL OR fine_amount_cents >= 0),
+    custodial_term       INTEGER,
+    custodial_unit       TEXT CHECK(custodial_unit IN ('days', 'weeks', 'months', 'years')),
+    default_term         INTEGER, -- imprisonment if the fine is not paid
+    default_unit         TEXT CHECK(default_unit IN ('days', 'weeks', 'months', 'years')),
+    supervision_term     INTEGER, -- probation, community service or suspension period
+    supervision_unit     TEXT CHECK(supervision_unit IN ('days', 'weeks', 'months', 'years')),
+    concurrency          TEXT NOT NULL DEFAULT 'concurrent' CHECK(concurrency IN ('concurrent', 'consecutive')),
+    consecutive_to       INTEGER, -- the sentence this one starts after
+    start_date           TEXT,    -- YYYY-MM-DD, as ordered
+    effective_start_date TEXT,    -- after any sentence it is consecutive to
+    release_date         TEXT,
+    default_release_date TEXT,
+    supervision_end_date TEXT,
+    summary              TEXT NOT NULL,
+    notes                TEXT,
+    created_by           TEXT,
+    created_at           TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL,
+    FOREIGN KEY (history_id) REFERENCES offender_history(id) ON DELETE SET NULL,
+    FOREIGN KEY (consecutive_to) REFERENCES sentences(sentence_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_sentences_case_id ON sentences(case_id);
+CREATE INDEX idx_sentences_offender_id ON sentences(offender_id);
+CREATE INDEX idx_sentences_release_date ON sentences(release_date);
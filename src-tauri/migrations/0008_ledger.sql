@@ -0,0 +1,73 @@
+-- Fines and fees ledger.
+--
+-- An assessment is an amount owed: a fine, or a fee on a file or case. It
+-- is paid down by payments, each with a receipt number. Payments are never
+-- edited or deleted; a wrong one is cancelled by a reversal, a second row
+-- with the negative amount pointing at it. Balances are always worked out
+-- from the rows. All amounts are integer cents.
+
+CREATE TABLE ledger_assessments (
+    assessment_id  INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind           TEXT NOT NULL CHECK(kind IN ('Fine', 'Filing Fee', 'Court Fee', 'Other')),
+    description    TEXT,
+    case_id        INTEGER,
+    offender_id    INTEGER,
+    file_id        INTEGER,
+    sentence_id    INTEGER, -- the sentence that imposed a fine
+    reference      TEXT,    -- case or file number when assessed, kept if they go
+    amount_cents   INTEGER NOT NULL CHECK(amount_cents > 0),
+    assessed_on    TEXT NOT NULL, -- YYYY-MM-DD
+    due_date       TEXT,
+    -- Settled other than by payment: a fine served as the default term, or
+    -- a fee waived
+    closure        TEXT CHECK(closure IN ('Converted', 'Waived')),
+    closure_reason TEXT,
+    closed_on      TEXT,
+    closed_by      TEXT,
+    created_by     TEXT,
+    created_at     TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL,
+    FOREIGN KEY (file_id) REFERENCES files(file_id) ON DELETE SET NULL,
+    FOREIGN KEY (sentence_id) REFERENCES sentences(sentence_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_ledger_assessments_case_id ON ledger_assessments(case_id);
+CREATE INDEX idx_ledger_assessments_offender_id ON ledger_assessments(offender_id);
+CREATE INDEX idx_ledger_assessments_file_id ON ledger_assessments(file_id);
+
+CREATE TABLE ledger_payments (
+    payment_id     INTEGER PRIMARY KEY AUTOINCREMENT,
+    assessment_id  INTEGER NOT NULL,
+    amount_cents   INTEGER NOT NULL,
+    receipt_number TEXT UNIQUE,
+    method         TEXT NOT NULL CHECK(method IN ('Cash', 'M-Pesa', 'Bank', 'Cheque', 'Other')),
+    reference      TEXT, -- transaction code, cheque number and the like
+    paid_on        TEXT NOT NULL, -- YYYY-MM-DD
+    reversal_of    INTEGER UNIQUE, -- the payment this row cancels
+    reason         TEXT,
+    recorded_by    TEXT,
+    recorded_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK((reversal_of IS NULL AND amount_cents > 0 AND receipt_number IS NOT NULL)
+       OR (reversal_of IS NOT NULL AND amount_cents < 0 AND reason IS NOT NULL)),
+    FOREIGN KEY (assessment_id) REFERENCES ledger_assessments(assessment_id),
+    FOREIGN KEY (reversal_of) REFERENCES ledger_payments(payment_id)
+);
+
+CREATE INDEX idx_ledger_payments_assessment_id ON ledger_payments(assessment_id);
+CREATE INDEX idx_ledger_payments_paid_on ON ledger_payments(paid_on);
+
+CREATE TRIGGER ledger_payments_no_update BEFORE UPDATE ON ledger_payments
+BEGIN
+    SELECT RAISE(ABORT, 'Payments cannot be changed; reverse the payment instead');
+END;
+
+CREATE TRIGGER ledger_payments_no_delete BEFORE DELETE ON ledger_payments
+BEGIN
+    SELECT RAISE(ABORT, 'Payments cannot be deleted; reverse the payment instead');
+END;
+
+CREATE TRIGGER ledger_assessments_no_delete BEFORE DELETE ON ledger_assessments
+BEGIN
+    SELECT RAISE(ABORT, 'Assessments cannot be deleted; waive them instead');
+END;
@@ -0,0 +1,34 @@
+-- Custody register.
+--
+-- One row per spell in custody: where the person is held, on what warrant
+-- and until when, when they must next be produced in court and when they
+-- were released. A person has at most one open spell at a time. The register
+-- outlives the offender record: deleting the offender clears offender_id and
+-- the name taken down at committal stays.
+
+CREATE TABLE custody_records (
+    custody_id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    offender_id          INTEGER,
+    offender_name        TEXT,          -- as committed
+    case_id              INTEGER,
+    custody_type         TEXT NOT NULL DEFAULT 'Remand' CHECK(custody_type IN ('Remand', 'Sentenced')),
+    facility             TEXT NOT NULL,
+    committed_on         TEXT NOT NULL, -- YYYY-MM-DD
+    warrant_number       TEXT,
+    warrant_issued_on    TEXT,
+    warrant_expires_on   TEXT,
+    next_production_date TEXT,
+    last_produced_on     TEXT,
+    released_on          TEXT,
+    release_reason       TEXT,
+    notes                TEXT,
+    created_by           TEXT,
+    created_at           TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX idx_custody_open ON custody_records(offender_id) WHERE released_on IS NULL;
+CREATE INDEX idx_custody_production ON custody_records(next_production_date) WHERE released_on IS NULL;
+CREATE INDEX idx_custody_case_id ON custody_records(case_id);
@@ -0,0 +1,87 @@
+-- Bail and bond register.
+--
+-- One row per grant of bail to an accused in a case, with its terms and
+-- where it stands: granted, posted (the accused is out), then discharged,
+-- cancelled or forfeited. Sureties, the documents deposited with the court
+-- and every hearing the accused missed while out hang off the bond.
+--
+-- The register outlives the case and offender records: deleting either
+-- clears the link and the case number and name taken down at grant stay. A
+-- bond with sureties, documents or missed hearings cannot be deleted.
+
+CREATE TABLE bonds (
+    bond_id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id         INTEGER,
+    case_number     TEXT,          -- as granted
+    offender_id     INTEGER,
+    offender_name   TEXT,          -- as granted
+    bond_type       TEXT NOT NULL DEFAULT 'Bond' CHECK(bond_type IN ('Cash Bail', 'Bond', 'Personal Bond')),
+    amount_cents    INTEGER NOT NULL CHECK(amount_cents >= 0),
+    conditions      TEXT,
+    granted_on      TEXT NOT NULL, -- YYYY-MM-DD
+    granted_by      TEXT,          -- the judicial officer
+    status          TEXT NOT NULL DEFAULT 'Granted' CHECK(status IN ('Granted', 'Posted', 'Cancelled', 'Forfeited', 'Discharged')),
+    posted_on       TEXT,
+    closed_on       TEXT,          -- when it was cancelled, forfeited or discharged
+    closure_reason  TEXT,
+    forfeited_cents INTEGER CHECK(forfeited_cents IS NULL OR forfeited_cents >= 0),
+    notes           TEXT,
+    created_by      TEXT,
+    created_at      TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified   TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL
+);
+
+-- At most one live bond per accused per case
+CREATE UNIQUE INDEX idx_bonds_live ON bonds(case_id, offender_id) WHERE status IN ('Granted', 'Posted');
+CREATE INDEX idx_bonds_offender_id ON bonds(offender_id);
+
+CREATE TABLE bond_sureties (
+    surety_id     INTEGER PRIMARY KEY AUTOINCREMENT,
+    bond_id       INTEGER NOT NULL,
+    full_name     TEXT NOT NULL,
+    id_number     TEXT,
+    phone_number  TEXT,
+    address       TEXT,
+    relationship  TEXT,          -- to the accused
+    amount_cents  INTEGER CHECK(amount_cents IS NULL OR amount_cents >= 0),
+    notes         TEXT,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (bond_id) REFERENCES bonds(bond_id)
+);
+
+CREATE INDEX idx_bond_sureties_bond_id ON bond_sureties(bond_id);
+
+-- Title deeds, log books, identity cards and the like, held until returned
+CREATE TABLE bond_documents (
+    document_id   INTEGER PRIMARY KEY AUTOINCREMENT,
+    bond_id       INTEGER NOT NULL,
+    surety_id     INTEGER,
+    document_type TEXT NOT NULL,
+    reference     TEXT,
+    description   TEXT,
+    received_on   TEXT NOT NULL,
+    returned_on   TEXT,
+    created_by    TEXT,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (bond_id) REFERENCES bonds(bond_id),
+    FOREIGN KEY (surety_id) REFERENCES bond_sureties(surety_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_bond_documents_bond_id ON bond_documents(bond_id);
+
+-- A hearing the accused did not attend while out on the bond
+CREATE TABLE bond_absences (
+    absence_id      INTEGER PRIMARY KEY AUTOINCREMENT,
+    bond_id         INTEGER NOT NULL,
+    hearing_id      INTEGER,
+    hearing_date    TEXT NOT NULL,
+    notes           TEXT,
+    notification_id INTEGER,
+    recorded_by     TEXT,
+    recorded_at     TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (bond_id, hearing_id),
+    FOREIGN KEY (bond_id) REFERENCES bonds(bond_id),
+    FOREIGN KEY (hearing_id) REFERENCES hearings(hearing_id) ON DELETE SET NULL
+);
@@ -0,0 +1,36 @@
+-- Warrants of arrest, replacing the paper warrant book.
+--
+-- One row per warrant: who it is for, in which case, who issued it and
+-- which station is to execute it. A warrant stays issued until it is
+-- executed (the person is arrested) or lifted by the court. The book
+-- outlives the offender record: deleting the offender clears offender_id
+-- and the name on the warrant stays.
+
+CREATE TABLE warrants (
+    warrant_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    warrant_number    TEXT NOT NULL UNIQUE,
+    warrant_type      TEXT NOT NULL DEFAULT 'Bench Warrant' CHECK(warrant_type IN ('Bench Warrant', 'Warrant of Arrest')),
+    offender_id       INTEGER,
+    offender_name     TEXT,          -- as named on the warrant
+    case_id           INTEGER,
+    issued_on         TEXT NOT NULL, -- YYYY-MM-DD
+    issued_by         TEXT NOT NULL, -- the issuing judicial officer
+    executing_station TEXT,
+    reason            TEXT,
+    status            TEXT NOT NULL DEFAULT 'Issued' CHECK(status IN ('Issued', 'Executed', 'Lifted')),
+    executed_on       TEXT,
+    executed_by       TEXT,          -- the arresting officer
+    place_of_arrest   TEXT,
+    lifted_on         TEXT,
+    lift_reason       TEXT,
+    notes             TEXT,
+    created_by        TEXT,
+    created_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified     TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_warrants_outstanding ON warrants(executing_station, issued_on) WHERE status = 'Issued';
+CREATE INDEX idx_warrants_offender_id ON warrants(offender_id);
+CREATE INDEX idx_warrants_case_id ON warrants(case_id);
@@ -0,0 +1,70 @@
+-- Exhibit register and chain of custody, replacing cases.evidence_summary
+-- for anything physically held.
+--
+-- One row per exhibit, with where it is kept and where it stands. Every
+-- movement in and out of the store is a row in exhibit_movements, signed by
+-- the person handing it over and the person taking it. Movements are never
+-- changed or deleted, and neither are exhibits: the register is the chain
+-- of custody.
+
+CREATE TABLE exhibits (
+    exhibit_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    exhibit_number   TEXT NOT NULL UNIQUE,
+    case_id          INTEGER,
+    offender_id      INTEGER,
+    category         TEXT NOT NULL DEFAULT 'Other' CHECK(category IN ('Weapon', 'Drugs', 'Document', 'Money', 'Electronic', 'Vehicle', 'Other')),
+    description      TEXT NOT NULL,
+    quantity         TEXT,          -- e.g. 2 pieces, 1.5 kg
+    received_on      TEXT NOT NULL, -- YYYY-MM-DD
+    received_by      TEXT NOT NULL, -- the exhibit store officer
+    produced_by      TEXT NOT NULL, -- who brought it in, e.g. the investigating officer
+    storage_location TEXT NOT NULL,
+    status           TEXT NOT NULL DEFAULT 'In Store' CHECK(status IN ('In Store', 'Out', 'Disposed')),
+    disposed_on      TEXT,
+    disposal_method  TEXT,
+    disposal_order   TEXT,          -- the court order reference
+    notes            TEXT,
+    created_by       TEXT,
+    created_at       TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE SET NULL,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_exhibits_case_id ON exhibits(case_id);
+CREATE INDEX idx_exhibits_status ON exhibits(status, storage_location);
+
+CREATE TABLE exhibit_movements (
+    movement_id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    exhibit_id            INTEGER NOT NULL,
+    movement              TEXT NOT NULL CHECK(movement IN ('Received', 'Out', 'Returned', 'Transferred', 'Disposed')),
+    moved_on              TEXT NOT NULL, -- YYYY-MM-DD
+    purpose               TEXT,          -- e.g. production in court, analysis at the government chemist
+    released_by           TEXT NOT NULL,
+    released_by_signature TEXT NOT NULL,
+    received_by           TEXT NOT NULL,
+    received_by_signature TEXT NOT NULL,
+    storage_location      TEXT,          -- where it is kept after the move, while in store
+    court_order           TEXT,
+    notes                 TEXT,
+    recorded_by           TEXT,
+    recorded_at           TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (exhibit_id) REFERENCES exhibits(exhibit_id)
+);
+
+CREATE INDEX idx_exhibit_movements_exhibit_id ON exhibit_movements(exhibit_id, moved_on);
+
+CREATE TRIGGER exhibit_movements_no_update BEFORE UPDATE ON exhibit_movements
+BEGIN
+    SELECT RAISE(ABORT, 'Exhibit movements cannot be changed; record a further movement instead');
+END;
+
+CREATE TRIGGER exhibit_movements_no_delete BEFORE DELETE ON exhibit_movements
+BEGIN
+    SELECT RAISE(ABORT, 'Exhibit movements cannot be deleted');
+END;
+
+CREATE TRIGGER exhibits_no_delete BEFORE DELETE ON exhibits
+BEGIN
+    SELECT RAISE(ABORT, 'Exhibits cannot be deleted; dispose of them under a court order instead');
+END;
@@ -0,0 +1,128 @@
+-- Charges, one row per count.
+--
+-- Replaces the single cases.charge_description and applicable_law: each
+-- count has its offence, the statute and section it is brought under, the
+-- plea and the verdict. A count may be against one accused or, with no
+-- offender, against all of them. Sentences can now be passed on a count.
+-- cases.verdict and cases.sentence are rewritten from the counts and their
+-- sentences and are what syncs.
+
+CREATE TABLE charges (
+    charge_id     INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id       INTEGER NOT NULL,
+    offender_id   INTEGER,
+    count_number  INTEGER NOT NULL CHECK(count_number > 0),
+    offence       TEXT NOT NULL,
+    statute       TEXT,          -- the Act, e.g. Penal Code
+    section       TEXT,          -- e.g. 268(1) as read with 275
+    particulars   TEXT,
+    plea          TEXT CHECK(plea IN ('Guilty', 'Not Guilty')),
+    plea_date     TEXT,          -- YYYY-MM-DD
+    verdict       TEXT CHECK(verdict IN ('Convicted', 'Acquitted', 'Withdrawn', 'Dismissed')),
+    verdict_date  TEXT,
+    notes         TEXT,
+    created_by    TEXT,
+    created_at    TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_modified TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (case_id, count_number),
+    FOREIGN KEY (case_id) REFERENCES cases(case_id) ON DELETE CASCADE,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_charges_offender_id ON charges(offender_id);
+
+ALTER TABLE sentences ADD COLUMN charge_id INTEGER REFERENCES charges(charge_id) ON DELETE SET NULL;
+
+CREATE INDEX idx_sentences_charge_id ON sentences(charge_id);
+
+-- The existing charge of each case becomes its count 1. Verdicts that read
+-- as one of the recognised outcomes come across; cases.verdict itself is
+-- left as it was.
+INSERT INTO charges (case_id, count_number, offence, statute, verdict, verdict_date, notes)
+SELECT case_id, 1,
+       COALESCE(NULLIF(trim(charge_description), ''), trim(applicable_law)),
+       NULLIF(trim(applicable_law), ''),
+       CASE lower(trim(COALESCE(verdict, '')))
+           WHEN 'convicted' THEN 'Convicted'
+           WHEN 'guilty' THEN 'Convicted'
+           WHEN 'acquitted' THEN 'Acquitted'
+           WHEN 'not guilty' THEN 'Acquitted'
+           WHEN 'withdrawn' THEN 'Withdrawn'
+           WHEN 'dismissed' THEN 'Dismissed'
+       END,
+       CASE WHEN lower(trim(COALESCE(verdict, ''))) IN
+                 ('convicted', 'guilty', 'acquitted', 'not guilty', 'withdrawn', 'dismissed')
+             AND date(date_of_judgment) = date_of_judgment
+            THEN date_of_judgment END,
+       'Carried over from the case charge'
+FROM cases
+WHERE trim(COALESCE(charge_description, '')) <> '' OR trim(COALESCE(applicable_law, '')) <> '';
+
+-- The search entry of a case (migration 0002) also carries the offence,
+-- statute and section of each of its counts, so a case can be found by
+-- what is charged on it. The view builds the entry; the case triggers are
+-- rebuilt on it and the charge triggers refresh the entry of their case.
+
+CREATE VIEW search_case_entries AS
+SELECT
+    c.case_id * 4 + 2 AS entry_id, 'case' AS kind, c.case_id AS record_id,
+    COALESCE(c.case_number || ' - ', '') || c.title AS label,
+    c.title AS name, c.case_number AS reference,
+    COALESCE(c.complainant_name, '') || char(10) || COALESCE(c.accused_name, '') || char(10) ||
+    COALESCE(c.charge_description, '') || char(10) || COALESCE(c.description, '') || char(10) ||
+    COALESCE((
+        SELECT group_concat(
+            ch.offence || COALESCE(' ' || ch.statute, '') || COALESCE(' s. ' || ch.section, ''),
+            char(10)
+        )
+        FROM charges ch WHERE ch.case_id = c.case_id
+    ), '') AS body
+FROM cases c
+WHERE COALESCE(c.is_deleted, 0) = 0;
+
+DROP TRIGGER search_cases_insert;
+DROP TRIGGER search_cases_update;
+
+CREATE TRIGGER search_cases_insert AFTER INSERT ON cases
+BEGIN
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT entry_id, kind, record_id, label, name, reference, body
+    FROM search_case_entries WHERE record_id = NEW.case_id;
+END;
+
+CREATE TRIGGER search_cases_update AFTER UPDATE OF title, case_number, complainant_name, accused_name, charge_description, description, is_deleted ON cases
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.case_id * 4 + 2;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT entry_id, kind, record_id, label, name, reference, body
+    FROM search_case_entries WHERE record_id = NEW.case_id;
+END;
+
+CREATE TRIGGER search_charges_insert AFTER INSERT ON charges
+BEGIN
+    DELETE FROM search_index WHERE rowid = NEW.case_id * 4 + 2;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT entry_id, kind, record_id, label, name, reference, body
+    FROM search_case_entries WHERE record_id = NEW.case_id;
+END;
+
+CREATE TRIGGER search_charges_update AFTER UPDATE OF case_id, offence, statute, section ON charges
+BEGIN
+    DELETE FROM search_index WHERE rowid IN (OLD.case_id * 4 + 2, NEW.case_id * 4 + 2);
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT entry_id, kind, record_id, label, name, reference, body
+    FROM search_case_entries WHERE record_id IN (OLD.case_id, NEW.case_id);
+END;
+
+CREATE TRIGGER search_charges_delete AFTER DELETE ON charges
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.case_id * 4 + 2;
+    INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+    SELECT entry_id, kind, record_id, label, name, reference, body
+    FROM search_case_entries WHERE record_id = OLD.case_id;
+END;
+
+-- Re-index the cases whose counts were carried over above
+DELETE FROM search_index WHERE kind = 'case';
+INSERT INTO search_index (rowid, kind, record_id, label, name, reference, body)
+SELECT entry_id, kind, record_id, label, name, reference, body FROM search_case_entries;
@@ -0,0 +1,67 @@
+-- The offence catalogue: the Acts, their sections and the offences under
+-- them, with the prescribed penalty ranges.
+--
+-- The app ships with a catalogue and installs it at startup when it is newer
+-- than the one in the database; later editions are imported from a JSON
+-- file. Entries are updated in place by their code and never deleted, since
+-- charges and offender history point at them. An offence that is no longer
+-- law is marked repealed instead.
+
+CREATE TABLE catalogue_acts (
+    act_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    code         TEXT NOT NULL UNIQUE, -- e.g. PC
+    title        TEXT NOT NULL,        -- e.g. Penal Code
+    chapter      TEXT,                 -- e.g. Cap. 63
+    is_repealed  BOOLEAN NOT NULL DEFAULT 0,
+    updated_at   TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE catalogue_offences (
+    catalogue_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    code               TEXT NOT NULL UNIQUE, -- e.g. PC-268
+    act_id             INTEGER NOT NULL,
+    section            TEXT NOT NULL,        -- e.g. 268(1) as read with 275
+    offence            TEXT NOT NULL,
+    min_fine_cents     INTEGER CHECK(min_fine_cents >= 0),
+    max_fine_cents     INTEGER CHECK(max_fine_cents >= 0),
+    min_custody_months INTEGER CHECK(min_custody_months >= 0),
+    max_custody_months INTEGER CHECK(max_custody_months >= 0),
+    -- A maximum beyond a term of months
+    max_penalty        TEXT CHECK(max_penalty IN ('Life imprisonment', 'Death')),
+    penalty_notes      TEXT,
+    is_repealed        BOOLEAN NOT NULL DEFAULT 0,
+    updated_at         TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (act_id) REFERENCES catalogue_acts(act_id)
+);
+
+CREATE INDEX idx_catalogue_offences_act_id ON catalogue_offences(act_id, section);
+
+-- One row per catalogue edition installed; the highest is the current one
+CREATE TABLE catalogue_versions (
+    version          INTEGER PRIMARY KEY,
+    name             TEXT,
+    published_on     TEXT,    -- YYYY-MM-DD
+    source           TEXT NOT NULL CHECK(source IN ('Bundled', 'Imported')),
+    acts_added       INTEGER NOT NULL DEFAULT 0,
+    acts_updated     INTEGER NOT NULL DEFAULT 0,
+    offences_added   INTEGER NOT NULL DEFAULT 0,
+    offences_updated INTEGER NOT NULL DEFAULT 0,
+    installed_by     TEXT,
+    installed_at     TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE charges ADD COLUMN catalogue_id INTEGER REFERENCES catalogue_offences(catalogue_id);
+ALTER TABLE offender_history ADD COLUMN catalogue_id INTEGER REFERENCES catalogue_offences(catalogue_id);
+
+CREATE INDEX idx_charges_catalogue_id ON charges(catalogue_id);
+CREATE INDEX idx_offender_history_catalogue_id ON offender_history(catalogue_id);
+
+CREATE TRIGGER catalogue_offences_no_delete BEFORE DELETE ON catalogue_offences
+BEGIN
+    SELECT RAISE(ABORT, 'Catalogue offences cannot be deleted; mark them repealed instead');
+END;
+
+CREATE TRIGGER catalogue_acts_no_delete BEFORE DELETE ON catalogue_acts
+BEGIN
+    SELECT RAISE(ABORT, 'Catalogue Acts cannot be deleted; mark them repealed instead');
+END;
@@ -0,0 +1,21 @@
+-- Who attended each hearing.
+--
+-- One row per accused per hearing, recorded with the outcome or, for an
+-- adjourned hearing, on its own. An accused out on bond who did not attend
+-- is flagged on the bond (bond_absences) with a warning notification.
+-- Deleting the offender clears offender_id; the row stays with the name.
+
+CREATE TABLE hearing_attendance (
+    attendance_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    hearing_id    INTEGER NOT NULL,
+    offender_id   INTEGER,
+    offender_name TEXT,
+    attended      BOOLEAN NOT NULL,
+    recorded_by   TEXT,
+    recorded_at   TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (hearing_id, offender_id),
+    FOREIGN KEY (hearing_id) REFERENCES hearings(hearing_id) ON DELETE CASCADE,
+    FOREIGN KEY (offender_id) REFERENCES offenders(offender_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_hearing_attendance_offender_id ON hearing_attendance(offender_id);
@@ -0,0 +1,7 @@
+-- Lockout after repeated wrong two-factor codes.
+--
+-- Wrong codes are counted per email and cleared by a right one. From the
+-- fifth in a row, locked_until (unix seconds) holds off further attempts.
+
+ALTER TABLE user_totp ADD COLUMN failed_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE user_totp ADD COLUMN locked_until INTEGER;
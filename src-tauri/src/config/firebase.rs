@@ -2,6 +2,9 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::settings::SyncConfig;
+use crate::error::{AppError, AppResult, ResultExt};
+
 // ============================================================================
 // Firestore Value Types
 // ============================================================================
@@ -223,23 +226,29 @@ pub struct RunQueryResponseItem {
 #[derive(Clone)]
 pub struct FirestoreClient {
     client: Client,
+    endpoint: String,
     project_id: String,
     api_key: String,
 }
 
 impl FirestoreClient {
-    pub fn new(project_id: &str, api_key: &str) -> Self {
+    pub fn new(endpoint: &str, project_id: &str, api_key: &str) -> Self {
         FirestoreClient {
             client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
             project_id: project_id.to_string(),
             api_key: api_key.to_string(),
         }
     }
 
+    pub fn from_config(sync: &SyncConfig) -> Self {
+        Self::new(&sync.endpoint, &sync.project_id, &sync.api_key)
+    }
+
     fn base_url(&self) -> String {
         format!(
-            "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents",
-            self.project_id
+            "{}/projects/{}/databases/(default)/documents",
+            self.endpoint, self.project_id
         )
     }
 
@@ -250,7 +259,7 @@ impl FirestoreClient {
     pub async fn get_document(
         &self,
         path: &str,
-    ) -> Result<FirestoreDocument, String> {
+    ) -> AppResult<FirestoreDocument> {
         let url = self.url_with_key(path);
 
         let response = self
@@ -258,17 +267,17 @@ impl FirestoreClient {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Firestore GET failed: {}", e))?;
+            .context("Firestore GET failed")?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| e.to_string())?;
+        let text = response.text().await?;
 
         if !status.is_success() {
-            return Err(format!("Firestore GET error (HTTP {}): {}", status, text));
+            return Err(http_error("Firestore GET", status, text));
         }
 
         serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse Firestore document: {}", e))
+            .context("Failed to parse Firestore document")
     }
 
     pub async fn create_document(
@@ -276,7 +285,7 @@ impl FirestoreClient {
         collection_path: &str,
         document_id: &str,
         fields: &HashMap<String, FirestoreValue>,
-    ) -> Result<FirestoreDocument, String> {
+    ) -> AppResult<FirestoreDocument> {
         let url = format!(
             "{}/{}?documentId={}&key={}",
             self.base_url(),
@@ -294,27 +303,24 @@ impl FirestoreClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| format!("Firestore CREATE failed: {}", e))?;
+            .context("Firestore CREATE failed")?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| e.to_string())?;
+        let text = response.text().await?;
 
         if !status.is_success() {
-            return Err(format!(
-                "Firestore CREATE error (HTTP {}): {}",
-                status, text
-            ));
+            return Err(http_error("Firestore CREATE", status, text));
         }
 
         serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse Firestore response: {}", e))
+            .context("Failed to parse Firestore response")
     }
 
     pub async fn update_document(
         &self,
         path: &str,
         fields: &HashMap<String, FirestoreValue>,
-    ) -> Result<FirestoreDocument, String> {
+    ) -> AppResult<FirestoreDocument> {
         let url = self.url_with_key(path);
 
         let body = serde_json::json!({ "fields": fields });
@@ -326,23 +332,20 @@ impl FirestoreClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| format!("Firestore UPDATE failed: {}", e))?;
+            .context("Firestore UPDATE failed")?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| e.to_string())?;
+        let text = response.text().await?;
 
         if !status.is_success() {
-            return Err(format!(
-                "Firestore UPDATE error (HTTP {}): {}",
-                status, text
-            ));
+            return Err(http_error("Firestore UPDATE", status, text));
         }
 
         serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse Firestore response: {}", e))
+            .context("Failed to parse Firestore response")
     }
 
-    pub async fn delete_document(&self, path: &str) -> Result<(), String> {
+    pub async fn delete_document(&self, path: &str) -> AppResult<()> {
         let url = self.url_with_key(path);
 
         let response = self
@@ -350,15 +353,12 @@ impl FirestoreClient {
             .delete(&url)
             .send()
             .await
-            .map_err(|e| format!("Firestore DELETE failed: {}", e))?;
+            .context("Firestore DELETE failed")?;
 
         let status = response.status();
         if !status.is_success() {
-            let text = response.text().await.map_err(|e| e.to_string())?;
-            return Err(format!(
-                "Firestore DELETE error (HTTP {}): {}",
-                status, text
-            ));
+            let text = response.text().await?;
+            return Err(http_error("Firestore DELETE", status, text));
         }
 
         Ok(())
@@ -369,7 +369,7 @@ impl FirestoreClient {
         collection_path: &str,
         page_size: Option<i32>,
         page_token: Option<&str>,
-    ) -> Result<ListDocumentsResponse, String> {
+    ) -> AppResult<ListDocumentsResponse> {
         let mut url = format!(
             "{}/{}?key={}",
             self.base_url(),
@@ -389,27 +389,24 @@ impl FirestoreClient {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Firestore LIST failed: {}", e))?;
+            .context("Firestore LIST failed")?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| e.to_string())?;
+        let text = response.text().await?;
 
         if !status.is_success() {
-            return Err(format!(
-                "Firestore LIST error (HTTP {}): {}",
-                status, text
-            ));
+            return Err(http_error("Firestore LIST", status, text));
         }
 
         serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse Firestore list response: {}", e))
+            .context("Failed to parse Firestore list response")
     }
 
     pub async fn run_query(
         &self,
         parent_path: &str,
         query: StructuredQuery,
-    ) -> Result<Vec<FirestoreDocument>, String> {
+    ) -> AppResult<Vec<FirestoreDocument>> {
         let url = format!(
             "{}/{}:runQuery?key={}",
             self.base_url(),
@@ -428,20 +425,17 @@ impl FirestoreClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| format!("Firestore query failed: {}", e))?;
+            .context("Firestore query failed")?;
 
         let status = response.status();
-        let text = response.text().await.map_err(|e| e.to_string())?;
+        let text = response.text().await?;
 
         if !status.is_success() {
-            return Err(format!(
-                "Firestore query error (HTTP {}): {}",
-                status, text
-            ));
+            return Err(http_error("Firestore query", status, text));
         }
 
         let items: Vec<RunQueryResponseItem> = serde_json::from_str(&text)
-            .map_err(|e| format!("Failed to parse query response: {}", e))?;
+            .context("Failed to parse query response")?;
 
         Ok(items
             .into_iter()
@@ -450,6 +444,19 @@ impl FirestoreClient {
     }
 }
 
+/// Map a non-success Firestore response to an error the caller can branch on
+fn http_error(operation: &str, status: reqwest::StatusCode, body: String) -> AppError {
+    let message = format!("{} error (HTTP {}): {}", operation, status, body);
+    match status.as_u16() {
+        404 => AppError::NotFound(message),
+        409 => AppError::Conflict(message),
+        code => AppError::Remote {
+            status: code,
+            message,
+        },
+    }
+}
+
 /// Extract document ID from a full Firestore resource name
 /// e.g., "projects/foo/databases/(default)/documents/courts/X/offenders/ABC" -> "ABC"
 pub fn extract_document_id(name: &str) -> String {
@@ -1 +1,2 @@
 pub mod firebase;
+pub mod settings;
@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult, FieldError, ResultExt};
+
+// ============================================================================
+// Application configuration
+//
+// Effective settings are layered, later layers winning:
+//   1. Bundled defaults (`AppConfig::default()`)
+//   2. `config.toml` (or `config.json`) in the app data directory
+//   3. `RECORDS_*` environment variables
+// ============================================================================
+
+pub const CONFIG_FILE_TOML: &str = "config.toml";
+pub const CONFIG_FILE_JSON: &str = "config.json";
+
+const DEFAULT_FIRESTORE_ENDPOINT: &str = "https://firestore.googleapis.com/v1";
+const DEFAULT_FIRESTORE_PROJECT_ID: &str = "records-and-tracking";
+
+const MIN_SYNC_INTERVAL_SECS: u64 = 30;
+const MAX_SYNC_INTERVAL_SECS: u64 = 86_400;
+
+const MAX_BACKUP_INTERVAL_HOURS: u64 = 24 * 31;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Base URL of the Firestore REST API, e.g. an emulator or staging proxy
+    pub endpoint: String,
+    pub project_id: String,
+    /// Firebase web API key. None is bundled: sync stays off until one is
+    /// set here or in `RECORDS_FIRESTORE_API_KEY`
+    pub api_key: String,
+    /// Seconds between background sync passes
+    pub interval_secs: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            endpoint: DEFAULT_FIRESTORE_ENDPOINT.to_string(),
+            project_id: DEFAULT_FIRESTORE_PROJECT_ID.to_string(),
+            api_key: String::new(),
+            interval_secs: 300,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Whether there is a key to sync with
+    pub fn is_configured(&self) -> bool {
+        !self.api_key.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Where snapshots are written. Defaults to `<app dir>/backups`
+    pub directory: Option<PathBuf>,
+    /// Hours between scheduled snapshots; 0 turns scheduled backups off
+    pub interval_hours: u64,
+    /// How many snapshots to keep; older ones are deleted after each backup
+    pub retention: u32,
+    /// Whether snapshots also carry the offender photo directory
+    pub include_photos: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        BackupConfig {
+            directory: None,
+            interval_hours: 24,
+            retention: 14,
+            include_photos: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhotoConfig {
+    /// Where offender photos are stored. Defaults to `<app dir>/offender_photos`
+    pub directory: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// One of: off, error, warn, info, debug, trace
+    pub level: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: "info".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub sync: SyncConfig,
+    pub backup: BackupConfig,
+    pub photos: PhotoConfig,
+    pub logging: LoggingConfig,
+}
+
+impl AppConfig {
+    /// Build the effective configuration for `app_dir` and validate it.
+    /// Returns the config together with the names of the environment
+    /// variables that overrode file values.
+    pub fn load(app_dir: &Path) -> AppResult<(AppConfig, Vec<String>)> {
+        let mut config = Self::load_file(app_dir)?;
+        let overrides = config.apply_env_overrides()?;
+        config.validate()?;
+        Ok((config, overrides))
+    }
+
+    /// Read the on-disk layer only (defaults + file), without env overrides.
+    pub fn load_file(app_dir: &Path) -> AppResult<AppConfig> {
+        let toml_path = app_dir.join(CONFIG_FILE_TOML);
+        let json_path = app_dir.join(CONFIG_FILE_JSON);
+
+        if toml_path.exists() {
+            let text = fs::read_to_string(&toml_path)
+                .context(&format!("Failed to read {}", toml_path.display()))?;
+            toml::from_str(&text).map_err(|e| {
+                AppError::Config(format!("Invalid config in {}: {}", toml_path.display(), e))
+            })
+        } else if json_path.exists() {
+            let text = fs::read_to_string(&json_path)
+                .context(&format!("Failed to read {}", json_path.display()))?;
+            serde_json::from_str(&text).map_err(|e| {
+                AppError::Config(format!("Invalid config in {}: {}", json_path.display(), e))
+            })
+        } else {
+            Ok(AppConfig::default())
+        }
+    }
+
+    /// Persist this config to the app data directory, keeping whichever
+    /// format is already in use (TOML when neither file exists yet).
+    pub fn save(&self, app_dir: &Path) -> AppResult<PathBuf> {
+        self.validate()?;
+
+        let json_path = app_dir.join(CONFIG_FILE_JSON);
+        let toml_path = app_dir.join(CONFIG_FILE_TOML);
+
+        let (path, text) = if json_path.exists() && !toml_path.exists() {
+            let text = serde_json::to_string_pretty(self)
+                .map_err(|e| AppError::internal(format!("Failed to serialize config: {}", e)))?;
+            (json_path, text)
+        } else {
+            let text = toml::to_string_pretty(self)
+                .map_err(|e| AppError::internal(format!("Failed to serialize config: {}", e)))?;
+            (toml_path, text)
+        };
+
+        fs::write(&path, text).context(&format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Persist `edited`, an edit of the effective config, as the file layer.
+    /// Fields set by `RECORDS_*` variables cannot be changed here; the file
+    /// keeps its own value for them, so an override never lands on disk.
+    pub fn save_edit(edited: &AppConfig, app_dir: &Path) -> AppResult<PathBuf> {
+        let (effective, env_overrides) = Self::load(app_dir)?;
+
+        let mut errors = Vec::new();
+        for var in &env_overrides {
+            let mut unchanged = edited.clone();
+            let field = unchanged.copy_env_field(var, &effective);
+            if unchanged != *edited {
+                errors.push(FieldError {
+                    field: field.to_string(),
+                    message: format!("is set by {}; change or unset it there", var),
+                });
+            }
+        }
+        AppError::from_field_errors(errors)?;
+
+        let on_disk = Self::load_file(app_dir)?;
+        let mut file = edited.clone();
+        for var in &env_overrides {
+            file.copy_env_field(var, &on_disk);
+        }
+        file.save(app_dir)
+    }
+
+    /// Copy the field that env variable `var` overrides from `from`.
+    /// Returns the field's name.
+    fn copy_env_field(&mut self, var: &str, from: &AppConfig) -> &'static str {
+        match var {
+            "RECORDS_SYNC_ENDPOINT" => {
+                self.sync.endpoint = from.sync.endpoint.clone();
+                "sync.endpoint"
+            }
+            "RECORDS_FIRESTORE_PROJECT_ID" => {
+                self.sync.project_id = from.sync.project_id.clone();
+                "sync.project_id"
+            }
+            "RECORDS_FIRESTORE_API_KEY" => {
+                self.sync.api_key = from.sync.api_key.clone();
+                "sync.api_key"
+            }
+            "RECORDS_SYNC_INTERVAL_SECS" => {
+                self.sync.interval_secs = from.sync.interval_secs;
+                "sync.interval_secs"
+            }
+            "RECORDS_BACKUP_DIR" => {
+                self.backup.directory = from.backup.directory.clone();
+                "backup.directory"
+            }
+            "RECORDS_BACKUP_INTERVAL_HOURS" => {
+                self.backup.interval_hours = from.backup.interval_hours;
+                "backup.interval_hours"
+            }
+            "RECORDS_BACKUP_RETENTION" => {
+                self.backup.retention = from.backup.retention;
+                "backup.retention"
+            }
+            "RECORDS_BACKUP_INCLUDE_PHOTOS" => {
+                self.backup.include_photos = from.backup.include_photos;
+                "backup.include_photos"
+            }
+            "RECORDS_PHOTO_DIR" => {
+                self.photos.directory = from.photos.directory.clone();
+                "photos.directory"
+            }
+            "RECORDS_LOG_LEVEL" => {
+                self.logging.level = from.logging.level.clone();
+                "logging.level"
+            }
+            _ => "",
+        }
+    }
+
+    fn apply_env_overrides(&mut self) -> AppResult<Vec<String>> {
+        let mut applied = Vec::new();
+
+        if let Some(v) = env_var("RECORDS_SYNC_ENDPOINT", &mut applied) {
+            self.sync.endpoint = v;
+        }
+        if let Some(v) = env_var("RECORDS_FIRESTORE_PROJECT_ID", &mut applied) {
+            self.sync.project_id = v;
+        }
+        if let Some(v) = env_var("RECORDS_FIRESTORE_API_KEY", &mut applied) {
+            self.sync.api_key = v;
+        }
+        if let Some(v) = env_var("RECORDS_SYNC_INTERVAL_SECS", &mut applied) {
+            self.sync.interval_secs = v.parse().map_err(|_| {
+                AppError::Config(format!("RECORDS_SYNC_INTERVAL_SECS is not a number: {}", v))
+            })?;
+        }
+        if let Some(v) = env_var("RECORDS_BACKUP_DIR", &mut applied) {
+            self.backup.directory = Some(PathBuf::from(v));
+        }
+        if let Some(v) = env_var("RECORDS_BACKUP_INTERVAL_HOURS", &mut applied) {
+            self.backup.interval_hours = v.parse().map_err(|_| {
+                AppError::Config(format!(
+                    "RECORDS_BACKUP_INTERVAL_HOURS is not a number: {}",
+                    v
+                ))
+            })?;
+        }
+        if let Some(v) = env_var("RECORDS_BACKUP_RETENTION", &mut applied) {
+            self.backup.retention = v.parse().map_err(|_| {
+                AppError::Config(format!("RECORDS_BACKUP_RETENTION is not a number: {}", v))
+            })?;
+        }
+        if let Some(v) = env_var("RECORDS_BACKUP_INCLUDE_PHOTOS", &mut applied) {
+            self.backup.include_photos = v.parse().map_err(|_| {
+                AppError::Config(format!(
+                    "RECORDS_BACKUP_INCLUDE_PHOTOS must be true or false: {}",
+                    v
+                ))
+            })?;
+        }
+        if let Some(v) = env_var("RECORDS_PHOTO_DIR", &mut applied) {
+            self.photos.directory = Some(PathBuf::from(v));
+        }
+        if let Some(v) = env_var("RECORDS_LOG_LEVEL", &mut applied) {
+            self.logging.level = v;
+        }
+
+        Ok(applied)
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let mut errors = Vec::new();
+        let mut invalid = |field: &str, message: String| {
+            errors.push(FieldError {
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        if !(self.sync.endpoint.starts_with("https://")
+            || self.sync.endpoint.starts_with("http://"))
+        {
+            invalid(
+                "sync.endpoint",
+                format!("must be an http(s) URL, got '{}'", self.sync.endpoint),
+            );
+        }
+        if self.sync.project_id.trim().is_empty() {
+            invalid("sync.project_id", "must not be empty".to_string());
+        }
+        if !(MIN_SYNC_INTERVAL_SECS..=MAX_SYNC_INTERVAL_SECS).contains(&self.sync.interval_secs) {
+            invalid(
+                "sync.interval_secs",
+                format!(
+                    "must be between {} and {}",
+                    MIN_SYNC_INTERVAL_SECS, MAX_SYNC_INTERVAL_SECS
+                ),
+            );
+        }
+        if let Some(dir) = &self.backup.directory {
+            if !dir.is_absolute() {
+                invalid("backup.directory", "must be an absolute path".to_string());
+            }
+        }
+        if self.backup.interval_hours > MAX_BACKUP_INTERVAL_HOURS {
+            invalid(
+                "backup.interval_hours",
+                format!("must be at most {}", MAX_BACKUP_INTERVAL_HOURS),
+            );
+        }
+        if self.backup.retention == 0 {
+            invalid(
+                "backup.retention",
+                "must keep at least 1 backup".to_string(),
+            );
+        }
+        if let Some(dir) = &self.photos.directory {
+            if !dir.is_absolute() {
+                invalid("photos.directory", "must be an absolute path".to_string());
+            }
+        }
+        if self.logging.level.parse::<log::LevelFilter>().is_err() {
+            invalid(
+                "logging.level",
+                format!(
+                    "must be one of off, error, warn, info, debug, trace; got '{}'",
+                    self.logging.level
+                ),
+            );
+        }
+
+        AppError::from_field_errors(errors)
+    }
+
+    pub fn log_level(&self) -> log::LevelFilter {
+        self.logging.level.parse().unwrap_or(log::LevelFilter::Info)
+    }
+
+    pub fn backup_dir(&self, app_dir: &Path) -> PathBuf {
+        self.backup
+            .directory
+            .clone()
+            .unwrap_or_else(|| app_dir.join("backups"))
+    }
+
+    pub fn photo_dir(&self, app_dir: &Path) -> PathBuf {
+        self.photos
+            .directory
+            .clone()
+            .unwrap_or_else(|| app_dir.join("offender_photos"))
+    }
+}
+
+fn env_var(name: &str, applied: &mut Vec<String>) -> Option<String> {
+    match env::var(name) {
+        Ok(v) if !v.trim().is_empty() => {
+            applied.push(name.to_string());
+            Some(v.trim().to_string())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,735 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::backup::Backup;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db::{cipher, migrations};
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::photos::PhotoStore;
+
+// ============================================================================
+// Backups
+//
+// A backup is a gzipped tar archive holding a manifest, a consistent copy of
+// the database taken with SQLite's online backup API and, optionally, the
+// offender photos. Next to each archive sits a `<archive>.sha256` file in
+// `sha256sum` format so a copy can also be checked by hand.
+//
+// Restoring verifies the archive checksum, the database checksum recorded in
+// the manifest, `PRAGMA integrity_check` and the schema version before the
+// live database is overwritten (again through the backup API, so the pooled
+// connections stay valid). Older schemas are migrated forward afterwards.
+//
+// Backups taken just before a restore or repair are named `safety-…`
+// rather than `records-…` and are never pruned, so the copy of what a
+// restore overwrote cannot be pushed out by the backups that follow it.
+//
+// With encryption at rest on, the database copy stays encrypted under the
+// same key and photos are archived as stored, so a backup on a USB drive is
+// no easier to read than the PC it came from.
+// ============================================================================
+
+const FILE_PREFIX: &str = "records-";
+const SAFETY_PREFIX: &str = "safety-";
+const ARCHIVE_EXTENSION: &str = ".tar.gz";
+const CHECKSUM_EXTENSION: &str = ".sha256";
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "records_and_tracking.db";
+const PHOTOS_ENTRY: &str = "offender_photos";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupTrigger {
+    Scheduled,
+    Manual,
+    /// Taken automatically just before a restore overwrites the database
+    PreRestore,
+    /// Taken automatically just before an integrity repair changes data
+    PreRepair,
+}
+
+impl BackupTrigger {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupTrigger::Scheduled => "scheduled",
+            BackupTrigger::Manual => "manual",
+            BackupTrigger::PreRestore => "pre-restore",
+            BackupTrigger::PreRepair => "pre-repair",
+        }
+    }
+
+    /// Taken to undo an operation, and so kept out of pruning
+    pub fn is_safety(&self) -> bool {
+        matches!(self, BackupTrigger::PreRestore | BackupTrigger::PreRepair)
+    }
+
+    fn file_prefix(&self) -> &'static str {
+        if self.is_safety() {
+            SAFETY_PREFIX
+        } else {
+            FILE_PREFIX
+        }
+    }
+}
+
+/// Stored as the first entry of every archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub created_at: DateTime<Utc>,
+    pub trigger: BackupTrigger,
+    pub app_version: String,
+    pub schema_version: i64,
+    /// SHA-256 of the database entry
+    pub database_sha256: String,
+    pub includes_photos: bool,
+    pub photo_count: usize,
+    /// Whether the database copy is SQLCipher-encrypted
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    /// Archive file name, used to pick a backup to restore
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Recorded archive checksum, `None` when the `.sha256` file is missing
+    pub sha256: Option<String>,
+    /// `None` when the archive cannot be read
+    pub manifest: Option<BackupManifest>,
+}
+
+pub struct BackupOptions {
+    pub directory: PathBuf,
+    /// Photo directory to include, or `None` to back up the database only
+    pub photo_dir: Option<PathBuf>,
+    /// Number of backups to keep after this one is written
+    pub retention: usize,
+    /// SQLCipher key of the live database, if it is encrypted
+    pub database_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub restored: String,
+    pub backup_schema_version: i64,
+    pub schema_version: i64,
+    pub photos_restored: usize,
+}
+
+/// A verified backup unpacked into a scratch directory, removed on drop
+pub struct StagedBackup {
+    pub name: String,
+    pub manifest: BackupManifest,
+    dir: PathBuf,
+}
+
+impl StagedBackup {
+    fn database(&self) -> PathBuf {
+        self.dir.join(DATABASE_ENTRY)
+    }
+
+    fn photos(&self) -> PathBuf {
+        self.dir.join(PHOTOS_ENTRY)
+    }
+}
+
+impl Drop for StagedBackup {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// Scratch directory inside `directory`, so staging never crosses devices
+fn staging_dir(directory: &Path) -> AppResult<PathBuf> {
+    let dir = directory.join(format!(".staging-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).context(&format!("Failed to create {}", dir.display()))?;
+    Ok(dir)
+}
+
+fn sha256_file(path: &Path) -> AppResult<String> {
+    let mut file = File::open(path).context(&format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).context(&format!("Failed to read {}", path.display()))?;
+    Ok(HEXLOWER.encode(&hasher.finalize()))
+}
+
+fn checksum_path(archive: &Path) -> PathBuf {
+    let mut name = archive.as_os_str().to_owned();
+    name.push(CHECKSUM_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// The hash recorded in `<archive>.sha256`, if present
+fn recorded_checksum(archive: &Path) -> Option<String> {
+    fs::read_to_string(checksum_path(archive))
+        .ok()
+        .and_then(|text| text.split_whitespace().next().map(str::to_lowercase))
+}
+
+fn is_backup_name(name: &str) -> bool {
+    (name.starts_with(FILE_PREFIX) || name.starts_with(SAFETY_PREFIX))
+        && name.ends_with(ARCHIVE_EXTENSION)
+}
+
+/// The sortable UTC timestamp a backup name starts with, after its prefix
+fn name_timestamp(name: &str) -> &str {
+    name.strip_prefix(FILE_PREFIX)
+        .or_else(|| name.strip_prefix(SAFETY_PREFIX))
+        .unwrap_or(name)
+}
+
+/// Resolve a backup `name` from `list` to its path, rejecting anything that
+/// is not a plain archive file name in `directory`
+pub fn resolve(directory: &Path, name: &str) -> AppResult<PathBuf> {
+    let mut components = Path::new(name).components();
+    let plain = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    if !plain || !is_backup_name(name) {
+        return Err(AppError::field("name", "Not a backup file name."));
+    }
+    let path = directory.join(name);
+    if !path.is_file() {
+        return Err(AppError::not_found(format!("Backup {} not found", name)));
+    }
+    Ok(path)
+}
+
+fn photo_files(photo_dir: &Path) -> AppResult<Vec<PathBuf>> {
+    if !photo_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in
+        fs::read_dir(photo_dir).context(&format!("Failed to read {}", photo_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+const BACKUP_PAGES_PER_STEP: i32 = 256;
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(10);
+
+/// Copy the database behind `from` into `to` with the online backup API
+fn copy_database(from: &Connection, to: &mut Connection) -> AppResult<()> {
+    Backup::new(from, to)?.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+    Ok(())
+}
+
+/// Copy the live database to `dest`, encrypted with `key` when given. The
+/// copy is switched out of WAL mode so it is a single self-contained file.
+fn snapshot(conn: &Connection, dest: &Path, key: Option<&str>) -> AppResult<()> {
+    let mut copy = Connection::open(dest).context("Failed to create snapshot")?;
+    if let Some(key) = key {
+        cipher::apply_key(&copy, key)?;
+    }
+    copy_database(conn, &mut copy).context("Failed to snapshot database")?;
+    copy.query_row("PRAGMA journal_mode = DELETE", [], |row| {
+        row.get::<_, String>(0)
+    })
+    .context("Failed to finalise snapshot")?;
+    Ok(())
+}
+
+fn append_file<W: io::Write>(
+    archive: &mut tar::Builder<W>,
+    path: &Path,
+    name: &str,
+) -> AppResult<()> {
+    archive
+        .append_path_with_name(path, name)
+        .context(&format!("Failed to archive {}", path.display()))
+}
+
+/// Snapshot the database (and photos, if configured) into a new archive,
+/// then apply the retention policy unless it is a safety backup
+pub fn create(
+    conn: &Connection,
+    options: &BackupOptions,
+    trigger: BackupTrigger,
+) -> AppResult<BackupInfo> {
+    fs::create_dir_all(&options.directory)
+        .context(&format!("Failed to create {}", options.directory.display()))?;
+
+    let created_at = Utc::now();
+    let name = format!(
+        "{}{}-{}{}",
+        trigger.file_prefix(),
+        created_at.format("%Y%m%d-%H%M%S"),
+        trigger.as_str(),
+        ARCHIVE_EXTENSION
+    );
+    let path = options.directory.join(&name);
+    if path.exists() {
+        return Err(AppError::Conflict(format!(
+            "Backup {} already exists. Try again in a moment.",
+            name
+        )));
+    }
+
+    let staging = staging_dir(&options.directory)?;
+    let result = write_archive(conn, options, trigger, created_at, &staging, &path);
+    fs::remove_dir_all(&staging).ok();
+    let manifest = result?;
+
+    let sha256 = sha256_file(&path)?;
+    let checksum_file = checksum_path(&path);
+    fs::write(&checksum_file, format!("{}  {}\n", sha256, name))
+        .context(&format!("Failed to write {}", checksum_file.display()))?;
+
+    if !trigger.is_safety() {
+        prune(&options.directory, options.retention)?;
+    }
+
+    Ok(BackupInfo {
+        size_bytes: fs::metadata(&path)?.len(),
+        name,
+        path,
+        sha256: Some(sha256),
+        manifest: Some(manifest),
+    })
+}
+
+fn write_archive(
+    conn: &Connection,
+    options: &BackupOptions,
+    trigger: BackupTrigger,
+    created_at: DateTime<Utc>,
+    staging: &Path,
+    path: &Path,
+) -> AppResult<BackupManifest> {
+    let database = staging.join(DATABASE_ENTRY);
+    snapshot(conn, &database, options.database_key.as_deref())?;
+
+    let photos = match &options.photo_dir {
+        Some(dir) => photo_files(dir)?,
+        None => Vec::new(),
+    };
+    let manifest = BackupManifest {
+        created_at,
+        trigger,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: migrations::current_version(conn)?,
+        database_sha256: sha256_file(&database)?,
+        includes_photos: options.photo_dir.is_some(),
+        photo_count: photos.len(),
+        encrypted: options.database_key.is_some(),
+    };
+    let manifest_path = staging.join(MANIFEST_ENTRY);
+    fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+
+    // Written under a temporary name so a half-written archive is never listed
+    let partial = staging.join("archive.partial");
+    let file =
+        File::create(&partial).context(&format!("Failed to create {}", partial.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    append_file(&mut archive, &manifest_path, MANIFEST_ENTRY)?;
+    append_file(&mut archive, &database, DATABASE_ENTRY)?;
+    for photo in &photos {
+        if let Some(file_name) = photo.file_name().and_then(|n| n.to_str()) {
+            append_file(
+                &mut archive,
+                photo,
+                &format!("{}/{}", PHOTOS_ENTRY, file_name),
+            )?;
+        }
+    }
+    let file = archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .context("Failed to finish backup archive")?;
+    file.sync_all().context("Failed to flush backup archive")?;
+    drop(file);
+
+    // `rename` cannot cross devices, and the backup directory may well be a
+    // USB drive or network share, so fall back to a copy
+    if fs::rename(&partial, path).is_err() {
+        fs::copy(&partial, path).context(&format!("Failed to write {}", path.display()))?;
+    }
+    Ok(manifest)
+}
+
+/// Read just the manifest (the first entry) of an archive
+fn read_manifest(path: &Path) -> AppResult<BackupManifest> {
+    let file = File::open(path).context(&format!("Failed to open {}", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut entry = archive
+        .entries()?
+        .next()
+        .ok_or_else(|| AppError::validation("Backup archive is empty."))??;
+    if entry.path()?.as_ref() != Path::new(MANIFEST_ENTRY) {
+        return Err(AppError::validation("Backup archive has no manifest."));
+    }
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Backups in `directory`, newest first
+pub fn list(directory: &Path) -> AppResult<Vec<BackupInfo>> {
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in
+        fs::read_dir(directory).context(&format!("Failed to read {}", directory.display()))?
+    {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_backup_name(&name) {
+            continue;
+        }
+        let path = entry.path();
+        backups.push(BackupInfo {
+            size_bytes: entry.metadata()?.len(),
+            sha256: recorded_checksum(&path),
+            manifest: read_manifest(&path).ok(),
+            name,
+            path,
+        });
+    }
+    // Names embed a sortable UTC timestamp
+    backups.sort_by(|a, b| name_timestamp(&b.name).cmp(name_timestamp(&a.name)));
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` backups, leaving safety backups alone.
+/// Returns the names removed.
+pub fn prune(directory: &Path, keep: usize) -> AppResult<Vec<String>> {
+    let mut removed = Vec::new();
+    let regular = list(directory)?
+        .into_iter()
+        .filter(|backup| backup.name.starts_with(FILE_PREFIX));
+    for backup in regular.skip(keep.max(1)) {
+        fs::remove_file(&backup.path)
+            .context(&format!("Failed to remove {}", backup.path.display()))?;
+        fs::remove_file(checksum_path(&backup.path)).ok();
+        removed.push(backup.name);
+    }
+    Ok(removed)
+}
+
+/// Unpack only the entries a backup is expected to contain
+fn unpack(archive_path: &Path, staging: &Path) -> AppResult<()> {
+    let file = File::open(archive_path)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let expected = path == Path::new(MANIFEST_ENTRY)
+            || path == Path::new(DATABASE_ENTRY)
+            || (path.parent() == Some(Path::new(PHOTOS_ENTRY))
+                && entry.header().entry_type().is_file());
+        if !expected {
+            return Err(AppError::validation(format!(
+                "Backup archive contains an unexpected entry: {}",
+                path.display()
+            )));
+        }
+        entry
+            .unpack_in(staging)
+            .context("Failed to unpack backup archive")?;
+    }
+    Ok(())
+}
+
+/// Check an archive's checksums, database integrity and schema version,
+/// leaving it unpacked in a scratch directory next to it. `key` is the
+/// current database key, needed to read an encrypted backup.
+pub fn verify(archive_path: &Path, key: Option<&str>) -> AppResult<StagedBackup> {
+    let name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let recorded = recorded_checksum(archive_path)
+        .ok_or_else(|| AppError::validation(format!("Backup {} has no checksum file.", name)))?;
+    if sha256_file(archive_path)? != recorded {
+        return Err(AppError::validation(format!(
+            "Backup {} does not match its checksum; the file is damaged.",
+            name
+        )));
+    }
+
+    let directory = archive_path.parent().unwrap_or(Path::new("."));
+    let staged = StagedBackup {
+        dir: staging_dir(directory)?,
+        name: name.clone(),
+        manifest: read_manifest(archive_path)?,
+    };
+    unpack(archive_path, &staged.dir)?;
+
+    let database = staged.database();
+    if !database.is_file() {
+        return Err(AppError::validation(format!(
+            "Backup {} has no database.",
+            name
+        )));
+    }
+    if sha256_file(&database)? != staged.manifest.database_sha256 {
+        return Err(AppError::validation(format!(
+            "The database in backup {} does not match its manifest.",
+            name
+        )));
+    }
+
+    let conn = Connection::open_with_flags(&database, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .context("Failed to open backup database")?;
+    if staged.manifest.encrypted {
+        let key = key.ok_or_else(|| {
+            AppError::validation(format!(
+                "Backup {} is encrypted, but encryption is not enabled here.",
+                name
+            ))
+        })?;
+        cipher::apply_key(&conn, key).map_err(|_| {
+            AppError::validation(format!(
+                "Backup {} was encrypted with a different key.",
+                name
+            ))
+        })?;
+    }
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .context("Failed to check backup database")?;
+    if integrity != "ok" {
+        return Err(AppError::validation(format!(
+            "The database in backup {} is corrupt: {}",
+            name, integrity
+        )));
+    }
+
+    let version = migrations::current_version(&conn)?;
+    if version == 0 {
+        return Err(AppError::validation(format!(
+            "Backup {} is not a records database.",
+            name
+        )));
+    }
+    if version > migrations::latest_version() {
+        return Err(AppError::validation(format!(
+            "Backup {} is at schema version {}, newer than this app supports ({}). Update the app first.",
+            name,
+            version,
+            migrations::latest_version()
+        )));
+    }
+    if version != staged.manifest.schema_version {
+        return Err(AppError::validation(format!(
+            "The schema version of backup {} does not match its manifest.",
+            name
+        )));
+    }
+
+    Ok(staged)
+}
+
+/// Overwrite the live database (through `conn`, the writer) with a verified
+/// backup, migrate it to the current schema and put its photos back. `key`
+/// is the live database key; a plaintext backup is encrypted with it first.
+pub fn restore(
+    conn: &mut Connection,
+    staged: &StagedBackup,
+    photos: &PhotoStore,
+    key: Option<&str>,
+) -> AppResult<RestoreReport> {
+    let source_path = staged.database();
+    if let (Some(key), false) = (key, staged.manifest.encrypted) {
+        cipher::encrypt_in_place(&source_path, key)?;
+    }
+    let source = Connection::open(&source_path).context("Failed to open backup database")?;
+    if let Some(key) = key {
+        cipher::apply_key(&source, key)?;
+    }
+    copy_database(&source, conn).context("Failed to restore database")?;
+    migrations::run(conn)?;
+
+    let mut photos_restored = 0;
+    if staged.manifest.includes_photos {
+        let photo_dir = photos.dir();
+        fs::create_dir_all(photo_dir)
+            .context(&format!("Failed to create {}", photo_dir.display()))?;
+        for photo in photo_files(&staged.photos())? {
+            if let Some(file_name) = photo.file_name() {
+                fs::copy(&photo, photo_dir.join(file_name))
+                    .context(&format!("Failed to restore photo {}", photo.display()))?;
+                photos_restored += 1;
+            }
+        }
+        relink_photos(conn, photo_dir)?;
+        photos.encrypt_existing()?;
+    }
+
+    Ok(RestoreReport {
+        restored: staged.name.clone(),
+        backup_schema_version: staged.manifest.schema_version,
+        schema_version: migrations::current_version(conn)?,
+        photos_restored,
+    })
+}
+
+/// Point `offenders.photo_path` at `photo_dir`, in case the backup was taken
+/// on another machine or with a different photo directory
+fn relink_photos(conn: &Connection, photo_dir: &Path) -> AppResult<()> {
+    let mut stmt = conn
+        .prepare("SELECT offender_id, photo_path FROM offenders WHERE photo_path IS NOT NULL")
+        .context("Failed to read photo paths")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (offender_id, old_path) in rows {
+        let Some(file_name) = Path::new(&old_path).file_name() else {
+            continue;
+        };
+        let new_path = photo_dir.join(file_name);
+        if new_path != Path::new(&old_path) && new_path.is_file() {
+            conn.execute(
+                "UPDATE offenders SET photo_path = ?1 WHERE offender_id = ?2",
+                params![new_path.to_string_lossy(), offender_id],
+            )
+            .context("Failed to update photo path")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn options(directory: &Path, retention: usize) -> BackupOptions {
+        BackupOptions {
+            directory: directory.to_path_buf(),
+            photo_dir: None,
+            retention,
+            database_key: None,
+        }
+    }
+
+    /// Backup names carry the time to the second; stand in for the clock
+    fn backdate(directory: &Path, name: &str, stamp: &str) -> String {
+        let renamed = name.replacen(name_timestamp(name).get(..15).unwrap(), stamp, 1);
+        fs::rename(directory.join(name), directory.join(&renamed)).unwrap();
+        fs::rename(
+            checksum_path(&directory.join(name)),
+            checksum_path(&directory.join(&renamed)),
+        )
+        .unwrap();
+        renamed
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rat-{}-{}", name, uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn restore_brings_back_the_database_and_photos() {
+        let mut conn = db::open_in_memory().unwrap();
+        let (dir, photo_dir, new_photo_dir) =
+            (scratch("backups"), scratch("photos"), scratch("photos"));
+        fs::create_dir_all(&photo_dir).unwrap();
+        let photo = photo_dir.join("abc.jpg");
+        fs::write(&photo, b"photo").unwrap();
+        conn.execute(
+            "INSERT INTO offenders (offender_id, full_name, photo_path) VALUES (1, 'Jane Doe', ?1)",
+            params![photo.to_string_lossy()],
+        )
+        .unwrap();
+
+        let backup = create(
+            &conn,
+            &BackupOptions {
+                photo_dir: Some(photo_dir.clone()),
+                ..options(&dir, 5)
+            },
+            BackupTrigger::Manual,
+        )
+        .unwrap();
+        let manifest = backup.manifest.unwrap();
+        assert_eq!((manifest.includes_photos, manifest.photo_count), (true, 1));
+
+        conn.execute_batch(
+            "DELETE FROM offenders;
+             INSERT INTO offenders (offender_id, full_name) VALUES (2, 'After the backup');",
+        )
+        .unwrap();
+
+        let staged = verify(&resolve(&dir, &backup.name).unwrap(), None).unwrap();
+        let photos = PhotoStore::new(new_photo_dir.clone(), None);
+        let report = restore(&mut conn, &staged, &photos, None).unwrap();
+        assert_eq!(report.photos_restored, 1);
+        assert_eq!(report.schema_version, migrations::latest_version());
+
+        let (name, path): (String, String) = conn
+            .query_row("SELECT full_name, photo_path FROM offenders", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(name, "Jane Doe");
+        assert_eq!(Path::new(&path), new_photo_dir.join("abc.jpg"));
+        assert_eq!(fs::read(new_photo_dir.join("abc.jpg")).unwrap(), b"photo");
+
+        for d in [&dir, &photo_dir, &new_photo_dir] {
+            let _ = fs::remove_dir_all(d);
+        }
+    }
+
+    #[test]
+    fn a_damaged_archive_is_refused() {
+        let conn = db::open_in_memory().unwrap();
+        let dir = scratch("backups");
+        let backup = create(&conn, &options(&dir, 5), BackupTrigger::Manual).unwrap();
+
+        let mut bytes = fs::read(&backup.path).unwrap();
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xff;
+        fs::write(&backup.path, bytes).unwrap();
+
+        assert!(matches!(
+            verify(&backup.path, None),
+            Err(AppError::Validation { .. })
+        ));
+        assert!(resolve(&dir, "../records-x.tar.gz").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn safety_backups_are_never_pruned() {
+        let conn = db::open_in_memory().unwrap();
+        let dir = scratch("backups");
+
+        let safety = create(&conn, &options(&dir, 1), BackupTrigger::PreRestore).unwrap();
+        assert!(safety.name.starts_with(SAFETY_PREFIX));
+        let safety = backdate(&dir, &safety.name, "20260101-000000");
+        let first = create(&conn, &options(&dir, 1), BackupTrigger::Manual).unwrap();
+        backdate(&dir, &first.name, "20260102-000000");
+        let second = create(&conn, &options(&dir, 1), BackupTrigger::Manual).unwrap();
+
+        let names: Vec<String> = list(&dir).unwrap().into_iter().map(|b| b.name).collect();
+        assert_eq!(names, vec![second.name, safety]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// SQLCipher helpers
+//
+// The app links SQLCipher, which reads plaintext databases unchanged until a
+// key is set. An encrypted file needs `PRAGMA key` before anything else runs
+// on the connection; a wrong key only shows up on the first read.
+// ============================================================================
+
+/// Key a freshly opened connection and check the key actually opens it
+pub fn apply_key(conn: &Connection, key: &str) -> AppResult<()> {
+    conn.pragma_update(None, "key", key)
+        .context("Failed to set database key")?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AppError::Forbidden("The database cannot be opened with this key.".to_string()))?;
+    Ok(())
+}
+
+/// True if `path` opens without a key (a missing file counts as plaintext)
+pub fn is_plaintext(path: &Path) -> AppResult<bool> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    let conn = Connection::open(path).context(&format!("Failed to open {}", path.display()))?;
+    Ok(conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .is_ok())
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Overwrite a file with zeros before removing it, so the plaintext does
+/// not linger in the freed blocks (best effort on SSDs and journaling
+/// file systems)
+fn shred(path: &Path) -> AppResult<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .context(&format!("Failed to open {}", path.display()))?;
+    let zeros = vec![0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..n])?;
+        remaining -= n as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path).context(&format!("Failed to remove {}", path.display()))
+}
+
+/// Convert the plaintext database at `path` into an encrypted one under the
+/// same name. Nothing else may have the file open.
+pub fn encrypt_in_place(path: &Path, key: &str) -> AppResult<()> {
+    let encrypted = sibling(path, ".encrypting");
+    fs::remove_file(&encrypted).ok();
+
+    {
+        let conn = Connection::open(path).context(&format!("Failed to open {}", path.display()))?;
+        // Fold any WAL content into the main file so the export sees it all
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .context("Failed to checkpoint database")?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            params![encrypted.to_string_lossy(), key],
+        )
+        .context("Failed to create encrypted database")?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .context("Failed to encrypt database")?;
+        let user_version: i64 = conn
+            .query_row("PRAGMA main.user_version", [], |row| row.get(0))
+            .context("Failed to read user_version")?;
+        conn.execute_batch(&format!(
+            "PRAGMA encrypted.user_version = {}; DETACH DATABASE encrypted;",
+            user_version
+        ))
+        .context("Failed to finish encrypted database")?;
+    }
+
+    // Make sure the copy is readable before the original goes
+    {
+        let check = Connection::open(&encrypted).context("Failed to open encrypted database")?;
+        apply_key(&check, key)?;
+        let integrity: String = check
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .context("Failed to check encrypted database")?;
+        if integrity != "ok" {
+            fs::remove_file(&encrypted).ok();
+            return Err(AppError::Database(format!(
+                "Encrypted copy failed its integrity check: {}",
+                integrity
+            )));
+        }
+    }
+
+    // A leftover WAL would be replayed into the encrypted file
+    for suffix in ["-wal", "-shm", "-journal"] {
+        shred(&sibling(path, suffix))?;
+    }
+
+    // Swap the files with renames only, so a crash at any point leaves
+    // something `finish_interrupted` can recover from
+    let plaintext = sibling(path, ".plaintext");
+    fs::rename(path, &plaintext).context(&format!("Failed to move {}", path.display()))?;
+    fs::rename(&encrypted, path).context(&format!("Failed to replace {}", path.display()))?;
+    shred_plaintext(path)
+}
+
+fn shred_plaintext(path: &Path) -> AppResult<()> {
+    shred(&sibling(path, ".plaintext"))
+}
+
+/// Clean up after an `encrypt_in_place` that was cut short: put the
+/// plaintext back if the swap never happened, or shred it if it did
+pub fn finish_interrupted(path: &Path) -> AppResult<()> {
+    let plaintext = sibling(path, ".plaintext");
+    if !plaintext.exists() {
+        return Ok(());
+    }
+    if path.exists() {
+        shred_plaintext(path)
+    } else {
+        fs::remove_file(sibling(path, ".encrypting")).ok();
+        fs::rename(&plaintext, path).context(&format!("Failed to restore {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_in_place_keeps_the_data_behind_the_key() {
+        let dir = std::env::temp_dir().join(format!("rat-cipher-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("records.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('kept');")
+            .unwrap();
+        assert!(is_plaintext(&path).unwrap());
+
+        let key = "x'00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff'";
+        encrypt_in_place(&path, key).unwrap();
+        assert!(!is_plaintext(&path).unwrap());
+        assert!(!sibling(&path, ".plaintext").exists());
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, key).unwrap();
+        let v: String = conn
+            .query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(v, "kept");
+
+        let wrong = Connection::open(&path).unwrap();
+        assert!(matches!(
+            apply_key(
+                &wrong,
+                "x'ffeeddccbbaa99887766554433221100ffeeddccbbaa99887766554433221100'"
+            ),
+            Err(AppError::Forbidden(_))
+        ));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
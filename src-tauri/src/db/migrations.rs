@@ -0,0 +1,668 @@
+use data_encoding::HEXLOWER;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Versioned schema migrations
+//
+// Every migration is embedded in the binary and applied in order, each in its
+// own transaction, with a row in `schema_version` recording its checksum.
+// A database that was created by an older release (schema.sql plus the ad-hoc
+// migration_v2/v3 scripts) is adopted onto the baseline in a single step so
+// that new and upgraded installs end up with an identical schema.
+// ============================================================================
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations, oldest first. Never edit a released entry — add a new one.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        sql: include_str!("../../migrations/0001_baseline.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "search_index",
+        sql: include_str!("../../migrations/0002_search_index.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "offender_merges",
+        sql: include_str!("../../migrations/0003_offender_merges.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "offender_cases",
+        sql: include_str!("../../migrations/0004_offender_cases.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "hearings",
+        sql: include_str!("../../migrations/0005_hearings.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "case_status_history",
+        sql: include_str!("../../migrations/0006_case_status_history.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "sentences",
+        sql: include_str!("../../migrations/0007_sentences.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "ledger",
+        sql: include_str!("../../migrations/0008_ledger.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "custody",
+        sql: include_str!("../../migrations/0009_custody.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "bail",
+        sql: include_str!("../../migrations/0010_bail.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "warrants",
+        sql: include_str!("../../migrations/0011_warrants.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "exhibits",
+        sql: include_str!("../../migrations/0012_exhibits.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "charges",
+        sql: include_str!("../../migrations/0013_charges.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "offence_catalogue",
+        sql: include_str!("../../migrations/0014_offence_catalogue.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "hearing_attendance",
+        sql: include_str!("../../migrations/0015_hearing_attendance.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "two_factor_lockout",
+        sql: include_str!("../../migrations/0016_two_factor_lockout.sql"),
+    },
+];
+
+const HISTORY_TABLE_SQL: &str = "CREATE TABLE schema_version (
+    version    INTEGER PRIMARY KEY,
+    name       TEXT NOT NULL,
+    checksum   TEXT NOT NULL,
+    applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)";
+
+/// Prefix given to pre-migration tables while their rows are copied across
+const LEGACY_PREFIX: &str = "legacy_";
+
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+}
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn checksum(sql: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(sql.as_bytes()))
+}
+
+fn table_exists(conn: &Connection, name: &str) -> AppResult<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .context("Failed to inspect schema")
+}
+
+fn table_columns(conn: &Connection, table: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info(\"{}\")", table))
+        .context(&format!("Failed to inspect table {}", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .context(&format!("Failed to inspect table {}", table))?
+        .collect::<Result<Vec<_>, _>>()
+        .context(&format!("Failed to inspect table {}", table))?;
+    Ok(columns)
+}
+
+/// Names of the user tables (excluding SQLite internals and the history table)
+fn user_tables(conn: &Connection) -> AppResult<Vec<String>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name <> 'schema_version'
+             ORDER BY name",
+        )
+        .context("Failed to inspect schema")?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .context("Failed to inspect schema")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to inspect schema")?;
+    Ok(names)
+}
+
+/// The highest applied version, or 0 for an empty database
+pub fn current_version(conn: &Connection) -> AppResult<i64> {
+    if !has_history_table(conn)? {
+        return Ok(0);
+    }
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+    .context("Failed to read schema version")
+}
+
+/// True when `schema_version` exists in the current (checksummed) format
+fn has_history_table(conn: &Connection) -> AppResult<bool> {
+    Ok(table_exists(conn, "schema_version")?
+        && table_columns(conn, "schema_version")?
+            .iter()
+            .any(|c| c == "checksum"))
+}
+
+/// Bring the database up to date. Returns the migrations applied by this call.
+pub fn run(conn: &mut Connection) -> AppResult<Vec<AppliedMigration>> {
+    // Table rebuilds must not cascade; foreign keys are toggled outside
+    // the transactions because SQLite ignores the pragma inside one.
+    let foreign_keys: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .context("Failed to read foreign_keys pragma")?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF")
+        .context("Failed to disable foreign keys")?;
+
+    let result = run_pending(conn);
+
+    if foreign_keys {
+        conn.execute_batch("PRAGMA foreign_keys = ON")
+            .context("Failed to re-enable foreign keys")?;
+    }
+    result
+}
+
+fn run_pending(conn: &mut Connection) -> AppResult<Vec<AppliedMigration>> {
+    let mut applied = Vec::new();
+
+    if !has_history_table(conn)? {
+        if user_tables(conn)?.is_empty() {
+            conn.execute_batch(HISTORY_TABLE_SQL)
+                .context("Failed to create schema_version")?;
+        } else {
+            adopt_legacy_schema(conn)?;
+            applied.push(AppliedMigration {
+                version: MIGRATIONS[0].version,
+                name: format!("{} (adopted existing database)", MIGRATIONS[0].name),
+            });
+        }
+    }
+
+    verify_history(conn)?;
+
+    let current = current_version(conn)?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn
+            .transaction()
+            .context(&format!("Failed to start migration {}", migration.version))?;
+        tx.execute_batch(migration.sql).context(&format!(
+            "Migration {} ({}) failed",
+            migration.version, migration.name
+        ))?;
+        record(&tx, migration)?;
+        tx.commit()
+            .context(&format!("Failed to commit migration {}", migration.version))?;
+
+        applied.push(AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+        });
+    }
+
+    Ok(applied)
+}
+
+fn record(tx: &Transaction, migration: &Migration) -> AppResult<()> {
+    tx.execute(
+        "INSERT INTO schema_version (version, name, checksum) VALUES (?1, ?2, ?3)",
+        params![migration.version, migration.name, checksum(migration.sql)],
+    )
+    .context(&format!("Failed to record migration {}", migration.version))?;
+    Ok(())
+}
+
+/// Refuse to run against a database from a newer app, or one whose applied
+/// migrations no longer match what this build ships.
+fn verify_history(conn: &Connection) -> AppResult<()> {
+    let mut stmt = conn
+        .prepare("SELECT version, name, checksum FROM schema_version ORDER BY version")
+        .context("Failed to read schema history")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .context("Failed to read schema history")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read schema history")?;
+
+    for (version, name, stored) in rows {
+        if version > latest_version() {
+            return Err(AppError::Conflict(format!(
+                "Database schema version {} is newer than this app supports ({}). \
+                 Please install the latest version of the app.",
+                version,
+                latest_version()
+            )));
+        }
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.version == version)
+            .ok_or_else(|| {
+                AppError::Database(format!(
+                    "Unknown migration {} ({}) in schema history",
+                    version, name
+                ))
+            })?;
+        if checksum(migration.sql) != stored {
+            return Err(AppError::Database(format!(
+                "Migration {} ({}) has been modified since it was applied",
+                version, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild a pre-versioning database onto the baseline schema, keeping every
+/// row. Runs as a single transaction: either the whole adoption lands or the
+/// database is left exactly as it was.
+fn adopt_legacy_schema(conn: &mut Connection) -> AppResult<()> {
+    let baseline = &MIGRATIONS[0];
+    let baseline_tables = {
+        let scratch = Connection::open_in_memory().context("Failed to open scratch database")?;
+        scratch
+            .execute_batch(baseline.sql)
+            .context("Baseline migration is invalid")?;
+        user_tables(&scratch)?
+    };
+
+    let tx = conn
+        .transaction()
+        .context("Failed to start schema adoption")?;
+    let adopting = "Failed to adopt existing database";
+
+    // The old version table only listed script numbers
+    tx.execute_batch("DROP TABLE IF EXISTS schema_version")
+        .context(adopting)?;
+
+    // Triggers and explicit indexes are recreated by the baseline
+    let objects: Vec<(String, String)> = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT type, name FROM sqlite_master
+                 WHERE type IN ('trigger', 'index') AND sql IS NOT NULL",
+            )
+            .context(adopting)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context(adopting)?
+            .collect::<Result<Vec<_>, _>>()
+            .context(adopting)?;
+        rows
+    };
+    for (kind, name) in &objects {
+        tx.execute_batch(&format!(
+            "DROP {} IF EXISTS \"{}\"",
+            kind.to_uppercase(),
+            name
+        ))
+        .context(adopting)?;
+    }
+
+    let existing = user_tables(&tx)?;
+    let carried: Vec<&String> = baseline_tables
+        .iter()
+        .filter(|t| existing.contains(t))
+        .collect();
+    for table in &carried {
+        tx.execute_batch(&format!(
+            "ALTER TABLE \"{}\" RENAME TO \"{}{}\"",
+            table, LEGACY_PREFIX, table
+        ))
+        .context(adopting)?;
+    }
+
+    tx.execute_batch(baseline.sql)
+        .context("Failed to create baseline schema on existing database")?;
+
+    // Hold the baseline triggers back while rows are copied, so that the
+    // daily criminal-case limit and modtime updates don't fire on old data.
+    let triggers: Vec<(String, String)> = {
+        let mut stmt = tx
+            .prepare("SELECT name, sql FROM sqlite_master WHERE type = 'trigger' ORDER BY rowid")
+            .context(adopting)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context(adopting)?
+            .collect::<Result<Vec<_>, _>>()
+            .context(adopting)?;
+        rows
+    };
+    for (name, _) in &triggers {
+        tx.execute_batch(&format!("DROP TRIGGER \"{}\"", name))
+            .context(adopting)?;
+    }
+
+    for table in &carried {
+        let legacy = format!("{}{}", LEGACY_PREFIX, table);
+        let legacy_columns = table_columns(&tx, &legacy)?;
+        let columns = table_columns(&tx, table)?
+            .into_iter()
+            .filter(|c| legacy_columns.contains(c))
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        tx.execute_batch(&format!(
+            "DELETE FROM \"{table}\";
+             INSERT INTO \"{table}\" ({columns}) SELECT {columns} FROM \"{legacy}\";",
+        ))
+        .context(&format!("Failed to carry over rows of {}", table))?;
+
+        // Keep AUTOINCREMENT counters so deleted ids are never reused
+        let seq: Option<i64> = tx
+            .query_row(
+                "SELECT seq FROM sqlite_sequence WHERE name = ?1",
+                params![legacy],
+                |row| row.get(0),
+            )
+            .optional()
+            .context(adopting)?;
+        if let Some(seq) = seq {
+            let updated = tx
+                .execute(
+                    "UPDATE sqlite_sequence SET seq = MAX(seq, ?1) WHERE name = ?2",
+                    params![seq, table],
+                )
+                .context(adopting)?;
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO sqlite_sequence (name, seq) VALUES (?1, ?2)",
+                    params![table, seq],
+                )
+                .context(adopting)?;
+            }
+        }
+
+        tx.execute_batch(&format!("DROP TABLE \"{}\"", legacy))
+            .context(adopting)?;
+    }
+
+    for (_, sql) in &triggers {
+        tx.execute_batch(sql).context(adopting)?;
+    }
+
+    tx.execute_batch(HISTORY_TABLE_SQL).context(adopting)?;
+    record(&tx, baseline)?;
+
+    tx.commit().context("Failed to commit schema adoption")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database as created by schema.sql and migration_v2 before versioning
+    const LEGACY_SQL: &str = "
+        CREATE TABLE users (
+            user_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+            name          TEXT NOT NULL,
+            role          TEXT NOT NULL,
+            email         TEXT,
+            password_hash TEXT NOT NULL,
+            status        TEXT NOT NULL DEFAULT 'Active'
+        );
+        CREATE TABLE offenders (
+            offender_id  INTEGER PRIMARY KEY AUTOINCREMENT,
+            full_name    TEXT NOT NULL,
+            national_id  TEXT,
+            date_created TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE schema_version (
+            version    INTEGER PRIMARY KEY,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        INSERT INTO schema_version (version) VALUES (2);
+        ALTER TABLE offenders ADD COLUMN alias TEXT;
+        CREATE INDEX idx_offenders_name ON offenders(full_name);
+        CREATE TRIGGER update_user_modtime AFTER UPDATE ON users
+        BEGIN SELECT 1; END;
+
+        INSERT INTO users (name, role, email, password_hash)
+        VALUES ('Jane Mwangi', 'Magistrate', 'jane@example.com', 'hash');
+        INSERT INTO offenders (full_name, national_id, alias) VALUES ('John Doe', '123', 'JD');
+        INSERT INTO offenders (full_name) VALUES ('Deleted');
+        DELETE FROM offenders WHERE full_name = 'Deleted';
+    ";
+
+    fn schema(conn: &Connection) -> Vec<(String, String, Option<String>)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT type, name, sql FROM sqlite_master
+                 WHERE name <> 'schema_version' ORDER BY type, name",
+            )
+            .unwrap();
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        rows
+    }
+
+    fn legacy_database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SQL).unwrap();
+        conn
+    }
+
+    /// A database that stopped at `version`, as an older release left it
+    fn database_at(version: i64) -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(HISTORY_TABLE_SQL).unwrap();
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            let tx = conn.transaction().unwrap();
+            tx.execute_batch(migration.sql).unwrap();
+            record(&tx, migration).unwrap();
+            tx.commit().unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn fresh_database_applies_every_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let applied = run(&mut conn).unwrap();
+
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(run(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn legacy_database_is_adopted_keeping_its_rows() {
+        let mut conn = legacy_database();
+        let applied = run(&mut conn).unwrap();
+
+        assert_eq!(applied[0].name, "baseline (adopted existing database)");
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let (name, email): (String, String) = conn
+            .query_row("SELECT name, email FROM users", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(name, "Jane Mwangi");
+        assert_eq!(email, "jane@example.com");
+
+        let (full_name, alias): (String, String) = conn
+            .query_row(
+                "SELECT full_name, alias FROM offenders WHERE offender_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(full_name, "John Doe");
+        assert_eq!(alias, "JD");
+
+        // The deleted offender's id is not handed out again
+        conn.execute("INSERT INTO offenders (full_name) VALUES ('Next')", [])
+            .unwrap();
+        assert_eq!(conn.last_insert_rowid(), 3);
+
+        assert!(run(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn upgraded_database_matches_a_fresh_one() {
+        let mut fresh = Connection::open_in_memory().unwrap();
+        run(&mut fresh).unwrap();
+        let mut upgraded = legacy_database();
+        run(&mut upgraded).unwrap();
+
+        assert_eq!(schema(&upgraded), schema(&fresh));
+    }
+
+    #[test]
+    fn modified_migration_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+        conn.execute(
+            "UPDATE schema_version SET checksum = 'edited' WHERE version = 2",
+            [],
+        )
+        .unwrap();
+
+        let err = run(&mut conn).unwrap_err();
+        assert!(matches!(err, AppError::Database(_)), "{:?}", err);
+        assert!(err.message().contains("has been modified"), "{}", err);
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, checksum) VALUES (?1, 'future', '')",
+            params![latest_version() + 1],
+        )
+        .unwrap();
+
+        let err = run(&mut conn).unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{:?}", err);
+        assert!(
+            err.message().contains("newer than this app supports"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn hearing_dates_are_carried_into_the_calendar() {
+        let mut conn = database_at(4);
+        for (hearing_dates, is_deleted) in [
+            (
+                "2020-01-15, 2099-06-01 09:30; 15/07/2099\n3.8.2099\r\n2099-06-01 09:30",
+                false,
+            ),
+            (
+                "31/02/2099, next month, 2099-13-01, 2099-02-30, 1/2/99",
+                false,
+            ),
+            ("2099-09-09", true),
+        ] {
+            conn.execute(
+                "INSERT INTO cases (title, hearing_dates, is_deleted) VALUES ('R v Doe', ?1, ?2)",
+                params![hearing_dates, is_deleted],
+            )
+            .unwrap();
+        }
+        run(&mut conn).unwrap();
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT case_id, hearing_date, hearing_time, status FROM hearings
+                 ORDER BY hearing_id",
+            )
+            .unwrap();
+        let hearings = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let hearing = |day: &str, time: Option<&str>, status: &str| {
+            (
+                1,
+                day.to_string(),
+                time.map(str::to_string),
+                status.to_string(),
+            )
+        };
+        assert_eq!(
+            hearings,
+            vec![
+                hearing("2020-01-15", None, "Concluded"),
+                hearing("2099-06-01", Some("09:30"), "Scheduled"),
+                hearing("2099-07-15", None, "Scheduled"),
+                hearing("2099-08-03", None, "Scheduled"),
+            ]
+        );
+
+        // The free text is left as it was
+        let text: String = conn
+            .query_row(
+                "SELECT hearing_dates FROM cases WHERE case_id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(text.starts_with("31/02/2099"));
+    }
+}
@@ -0,0 +1,26 @@
+pub mod backup;
+pub mod cipher;
+pub mod migrations;
+pub mod pool;
+
+use rusqlite::Connection;
+
+use crate::error::{AppResult, ResultExt};
+
+/// Turn on foreign key enforcement. SQLite leaves it off unless asked, per
+/// connection, so every connection the app opens goes through here.
+pub fn enable_foreign_keys(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch("PRAGMA foreign_keys = ON")
+        .context("Failed to enable foreign keys")
+}
+
+/// A private in-memory database with the full schema applied and the bundled
+/// offence catalogue installed, for driving `services::*` without touching
+/// the app's data directory.
+pub fn open_in_memory() -> AppResult<Connection> {
+    let mut conn = Connection::open_in_memory()?;
+    enable_foreign_keys(&conn)?;
+    migrations::run(&mut conn)?;
+    crate::services::offence_catalogue::install_bundled(&mut conn)?;
+    Ok(conn)
+}
@@ -0,0 +1,228 @@
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::db::{self, cipher};
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Database access
+//
+// One writer connection and a small pool of read-only connections, all on
+// the same WAL-mode file so reads never wait behind a sync pass. Work runs on
+// the blocking thread pool; callers only ever `await` a closure's result.
+//
+// A panic inside a closure is reported as an error to that caller. Locks are
+// recovered instead of staying poisoned, and an open transaction left behind
+// by the panic is rolled back before the connection is handed out again.
+//
+// Foreign keys are enforced on every connection, so ON DELETE clauses in
+// the schema hold for the writer and the readers alike.
+//
+// An encrypted database starts out locked: every call fails with
+// `DatabaseLocked` until `unlock` opens the connections with the key.
+// ============================================================================
+
+const READ_CONNECTIONS: usize = 4;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+pub struct Database {
+    inner: Arc<OnceLock<Inner>>,
+}
+
+struct Inner {
+    writer: Mutex<Connection>,
+    readers: Mutex<Vec<Connection>>,
+    reader_returned: Condvar,
+}
+
+/// A read connection checked out of the pool, returned on drop
+struct ReaderGuard<'a> {
+    inner: &'a Inner,
+    conn: Option<Connection>,
+}
+
+impl Drop for ReaderGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            lock(&self.inner.readers).push(conn);
+            self.inner.reader_returned.notify_one();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Database {
+    /// Open the database file with one writer and a pool of readers,
+    /// keyed with `key` if it is encrypted. The schema must already be migrated.
+    pub fn open(path: &Path, key: Option<&str>) -> AppResult<Database> {
+        let db = Database::locked();
+        db.unlock(path, key)?;
+        Ok(db)
+    }
+
+    /// A database whose connections are opened later by `unlock`
+    pub fn locked() -> Database {
+        Database {
+            inner: Arc::new(OnceLock::new()),
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.inner.get().is_some()
+    }
+
+    /// Open the connections of a locked database
+    pub fn unlock(&self, path: &Path, key: Option<&str>) -> AppResult<()> {
+        let inner = Inner::open(path, key)?;
+        self.inner
+            .set(inner)
+            .map_err(|_| AppError::Conflict("The database is already unlocked.".to_string()))
+    }
+
+    /// Run `f` on a read-only connection
+    pub async fn read<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> AppResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let guard = opened(&inner)?.checkout_reader();
+            let conn = guard.conn.as_ref().expect("reader checked out");
+            f(conn)
+        })
+        .await
+        .map_err(|e| AppError::internal(format!("Database read failed: {}", e)))?
+    }
+
+    /// Run `f` on the single writer connection. Writes are serialised, so
+    /// keep the closure short and never wait on the network inside it.
+    pub async fn write<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&mut Connection) -> AppResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut conn = lock(&opened(&inner)?.writer);
+            if !conn.is_autocommit() {
+                // A previous closure panicked mid-transaction
+                conn.execute_batch("ROLLBACK")
+                    .context("Failed to roll back stale transaction")?;
+            }
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| AppError::internal(format!("Database write failed: {}", e)))?
+    }
+}
+
+fn opened(inner: &OnceLock<Inner>) -> AppResult<&Inner> {
+    inner.get().ok_or_else(|| {
+        AppError::DatabaseLocked(
+            "The database is encrypted. Enter the passphrase to unlock it.".to_string(),
+        )
+    })
+}
+
+impl Inner {
+    fn open(path: &Path, key: Option<&str>) -> AppResult<Inner> {
+        let writer =
+            Connection::open(path).context(&format!("Failed to open {}", path.display()))?;
+        if let Some(key) = key {
+            cipher::apply_key(&writer, key)?;
+        }
+        writer
+            .busy_timeout(BUSY_TIMEOUT)
+            .context("Failed to set busy timeout")?;
+        let journal_mode: String = writer
+            .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+            .context("Failed to enable WAL mode")?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            return Err(AppError::Config(format!(
+                "Database does not support WAL mode (journal mode is {})",
+                journal_mode
+            )));
+        }
+        writer
+            .execute_batch("PRAGMA synchronous = NORMAL")
+            .context("Failed to configure database")?;
+        db::enable_foreign_keys(&writer)?;
+
+        let mut readers = Vec::with_capacity(READ_CONNECTIONS);
+        for _ in 0..READ_CONNECTIONS {
+            let reader = Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY
+                    | OpenFlags::SQLITE_OPEN_URI
+                    | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .context("Failed to open read connection")?;
+            if let Some(key) = key {
+                cipher::apply_key(&reader, key)?;
+            }
+            reader
+                .busy_timeout(BUSY_TIMEOUT)
+                .context("Failed to set busy timeout")?;
+            db::enable_foreign_keys(&reader)?;
+            readers.push(reader);
+        }
+
+        Ok(Inner {
+            writer: Mutex::new(writer),
+            readers: Mutex::new(readers),
+            reader_returned: Condvar::new(),
+        })
+    }
+
+    fn checkout_reader(&self) -> ReaderGuard<'_> {
+        let mut readers = lock(&self.readers);
+        loop {
+            if let Some(conn) = readers.pop() {
+                return ReaderGuard {
+                    inner: self,
+                    conn: Some(conn),
+                };
+            }
+            readers = self
+                .reader_returned
+                .wait(readers)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_connection_enforces_foreign_keys() {
+        let path = std::env::temp_dir().join(format!("rat-pool-{}.db", uuid::Uuid::new_v4()));
+        let inner = Inner::open(&path, None).unwrap();
+
+        let enabled = |conn: &Connection| -> bool {
+            conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert!(enabled(&lock(&inner.writer)));
+        let readers = lock(&inner.readers);
+        assert_eq!(readers.len(), READ_CONNECTIONS);
+        assert!(readers.iter().all(enabled));
+
+        drop(readers);
+        drop(inner);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+// ============================================================================
+// Command errors
+//
+// Every command fails with an `AppError`, which reaches the frontend as
+//   { "code": "NOT_FOUND", "message": "...", "field_errors": [...] }
+// `code` is stable and meant for branching; `message` is for people.
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// Input failed validation; `field_errors` says which fields and why
+    Validation {
+        message: String,
+        field_errors: Vec<FieldError>,
+    },
+    NotFound(String),
+    /// No signed-in user (or the session has expired)
+    Unauthenticated(String),
+    /// Signed in, but the role may not do this
+    Forbidden(String),
+    /// Unique constraint or concurrent modification
+    Conflict(String),
+    /// SQLite stayed busy/locked past the busy timeout
+    DatabaseBusy(String),
+    /// The database is encrypted and has not been unlocked yet
+    DatabaseLocked(String),
+    Database(String),
+    /// Could not reach a remote service
+    Network(String),
+    /// A remote service answered with an error
+    Remote {
+        status: u16,
+        message: String,
+    },
+    Io(String),
+    Config(String),
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation { .. } => "VALIDATION_FAILED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Unauthenticated(_) => "UNAUTHENTICATED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::DatabaseBusy(_) => "DATABASE_BUSY",
+            AppError::DatabaseLocked(_) => "DATABASE_LOCKED",
+            AppError::Database(_) => "DATABASE_ERROR",
+            AppError::Network(_) => "NETWORK_ERROR",
+            AppError::Remote { .. } => "REMOTE_ERROR",
+            AppError::Io(_) => "IO_ERROR",
+            AppError::Config(_) => "CONFIG_ERROR",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Validation { message, .. } | AppError::Remote { message, .. } => message,
+            AppError::NotFound(m)
+            | AppError::Unauthenticated(m)
+            | AppError::Forbidden(m)
+            | AppError::Conflict(m)
+            | AppError::DatabaseBusy(m)
+            | AppError::DatabaseLocked(m)
+            | AppError::Database(m)
+            | AppError::Network(m)
+            | AppError::Io(m)
+            | AppError::Config(m)
+            | AppError::Internal(m) => m,
+        }
+    }
+
+    fn message_mut(&mut self) -> &mut String {
+        match self {
+            AppError::Validation { message, .. } | AppError::Remote { message, .. } => message,
+            AppError::NotFound(m)
+            | AppError::Unauthenticated(m)
+            | AppError::Forbidden(m)
+            | AppError::Conflict(m)
+            | AppError::DatabaseBusy(m)
+            | AppError::DatabaseLocked(m)
+            | AppError::Database(m)
+            | AppError::Network(m)
+            | AppError::Io(m)
+            | AppError::Config(m)
+            | AppError::Internal(m) => m,
+        }
+    }
+
+    /// A validation error without field detail
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            field_errors: Vec::new(),
+        }
+    }
+
+    /// A validation error for a single field
+    pub fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        AppError::Validation {
+            message: message.clone(),
+            field_errors: vec![FieldError {
+                field: field.into(),
+                message,
+            }],
+        }
+    }
+
+    /// Combine field errors into one validation error, or `Ok` if there are none
+    pub fn from_field_errors(field_errors: Vec<FieldError>) -> AppResult<()> {
+        if field_errors.is_empty() {
+            return Ok(());
+        }
+        let message = field_errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err(AppError::Validation {
+            message,
+            field_errors,
+        })
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        AppError::NotFound(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal(message.into())
+    }
+
+    /// Prefix the message with what was being attempted, keeping the code
+    pub fn context(mut self, context: &str) -> Self {
+        let message = self.message_mut();
+        *message = format!("{}: {}", context, message);
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", self.message())?;
+        match self {
+            AppError::Validation { field_errors, .. } => {
+                s.serialize_field("field_errors", field_errors)?
+            }
+            _ => s.serialize_field("field_errors", &[] as &[FieldError])?,
+        }
+        s.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound("Record not found".into()),
+            rusqlite::Error::SqliteFailure(err, _) => match err.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => {
+                    AppError::DatabaseBusy(e.to_string())
+                }
+                ErrorCode::ConstraintViolation => {
+                    let message = e.to_string();
+                    if message.contains("UNIQUE") {
+                        AppError::Conflict(message)
+                    } else {
+                        // CHECK / NOT NULL / trigger RAISE(...) messages describe the rule
+                        AppError::validation(message)
+                    }
+                }
+                _ => AppError::Database(e.to_string()),
+            },
+            rusqlite::Error::FromSqlConversionFailure(..)
+            | rusqlite::Error::InvalidColumnType(..)
+            | rusqlite::Error::IntegralValueOutOfRange(..) => {
+                AppError::Database(format!("Stored record could not be read: {}", e))
+            }
+            _ => AppError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => AppError::Remote {
+                status: status.as_u16(),
+                message: e.to_string(),
+            },
+            None => AppError::Network(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(e.to_string()),
+            _ => AppError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Internal(format!("Invalid JSON: {}", e))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+/// `.context("Failed to fetch files")` on any result whose error converts
+/// into `AppError`, so call sites keep their wording and gain a code.
+pub trait ResultExt<T> {
+    fn context(self, context: &str) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: &str) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+}
@@ -5,7 +5,9 @@ use sha2::{Digest, Sha256};
 use tauri::AppHandle;
 use tauri::Manager;
 
+use crate::error::{AppError, AppResult, ResultExt};
 use crate::AppState;
+use crate::services::two_factor::{self, role_requires_two_factor};
 use crate::config::firebase::{
     CollectionSelector, Filter, FirestoreValue, StructuredQuery,
 };
@@ -19,16 +21,33 @@ fn hash_password(password: &str, salt: &[u8]) -> String {
     BASE64.encode(result)
 }
 
+pub(crate) use crate::services::users::is_admin_role;
+
+/// Fail unless the signed-in user holds an administrator role
+pub(crate) fn require_admin(state: &AppState) -> AppResult<()> {
+    let role = state.user_role.read()?;
+    match role.as_deref() {
+        Some(r) if is_admin_role(r) => Ok(()),
+        Some(_) => Err(AppError::Forbidden(
+            "This action requires an administrator account.".to_string(),
+        )),
+        None => Err(AppError::Unauthenticated(
+            "Not authenticated. Please log in first.".to_string(),
+        )),
+    }
+}
+
 #[tauri::command]
 pub async fn firebase_login(
     app: AppHandle,
     email: String,
     password: String,
     court_id: Option<String>,
-) -> Result<serde_json::Value, String> {
+    totp_code: Option<String>,
+) -> AppResult<serde_json::Value> {
     let state = app.state::<AppState>();
 
-    let court_id = court_id.ok_or_else(|| "Court ID is required.".to_string())?;
+    let court_id = court_id.ok_or_else(|| AppError::field("court_id", "Court ID is required."))?;
 
     // Query Firestore for user by email in courts/{courtId}/users
     let parent = format!("courts/{}", court_id);
@@ -50,11 +69,11 @@ pub async fn firebase_login(
         .firestore
         .run_query(&parent, query)
         .await
-        .map_err(|e| format!("Failed to query users: {}", e))?;
+        .context("Failed to query users")?;
 
     let doc = docs
         .first()
-        .ok_or_else(|| "No account found with that email in this court.".to_string())?;
+        .ok_or_else(|| AppError::not_found("No account found with that email in this court."))?;
 
     let fields = &doc.fields;
 
@@ -62,22 +81,22 @@ pub async fn firebase_login(
     let stored_hash = fields
         .get("passwordHash")
         .and_then(|v| v.as_string())
-        .ok_or_else(|| "User record missing password hash.".to_string())?;
+        .ok_or_else(|| AppError::internal("User record missing password hash."))?;
 
     let salt_b64 = fields
         .get("salt")
         .and_then(|v| v.as_string())
-        .ok_or_else(|| "User record missing salt.".to_string())?;
+        .ok_or_else(|| AppError::internal("User record missing salt."))?;
 
     let salt_bytes = BASE64
         .decode(salt_b64)
-        .map_err(|e| format!("Invalid salt encoding: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Invalid salt encoding: {}", e)))?;
 
     // Hash the provided password and compare
     let computed_hash = hash_password(&password, &salt_bytes);
 
     if computed_hash != stored_hash {
-        return Err("Invalid password.".to_string());
+        return Err(AppError::Unauthenticated("Invalid password.".to_string()));
     }
 
     // Check user status
@@ -87,17 +106,9 @@ pub async fn firebase_login(
         .unwrap_or("ACTIVE");
 
     if status != "ACTIVE" {
-        return Err("Account is not active. Please contact administrator.".to_string());
-    }
-
-    // Authentication successful — store court_id and user_email
-    {
-        let mut court = state.court_id.write().map_err(|e| e.to_string())?;
-        *court = Some(court_id.clone());
-    }
-    {
-        let mut user = state.user_email.write().map_err(|e| e.to_string())?;
-        *user = Some(email.clone());
+        return Err(AppError::Forbidden(
+            "Account is not active. Please contact administrator.".to_string(),
+        ));
     }
 
     let full_name = fields
@@ -109,6 +120,55 @@ pub async fn firebase_login(
         .and_then(|v| v.as_string())
         .unwrap_or("");
 
+    // Second factor for roles that require it. Checked against the local
+    // enrolment, so no further network access is needed.
+    if role_requires_two_factor(role) {
+        let enrolled = {
+            let email = email.clone();
+            state
+                .db
+                .read(move |conn| two_factor::is_enrolled(conn, &email))
+                .await?
+        };
+        if enrolled {
+            match totp_code.map(|code| code.trim().to_string()) {
+                Some(code) if !code.is_empty() => {
+                    let email = email.clone();
+                    let valid = state
+                        .db
+                        .write(move |conn| two_factor::verify_second_factor(conn, &email, &code))
+                        .await?;
+                    if !valid {
+                        return Err(AppError::Unauthenticated(
+                            "Invalid two-factor code.".to_string(),
+                        ));
+                    }
+                }
+                _ => {
+                    return Ok(json!({
+                        "success": false,
+                        "two_factor_required": true,
+                        "message": "Enter the code from your authenticator app"
+                    }));
+                }
+            }
+        }
+    }
+
+    // Authentication successful — store court_id, user_email and role
+    {
+        let mut court = state.court_id.write()?;
+        *court = Some(court_id.clone());
+    }
+    {
+        let mut user = state.user_email.write()?;
+        *user = Some(email.clone());
+    }
+    {
+        let mut user_role = state.user_role.write()?;
+        *user_role = Some(role.to_string());
+    }
+
     println!("Login successful: {} (court: {})", email, court_id);
 
     Ok(json!({
@@ -121,16 +181,20 @@ pub async fn firebase_login(
 }
 
 #[tauri::command]
-pub async fn firebase_logout(app: AppHandle) -> Result<serde_json::Value, String> {
+pub async fn firebase_logout(app: AppHandle) -> AppResult<serde_json::Value> {
     let state = app.state::<AppState>();
     {
-        let mut court = state.court_id.write().map_err(|e| e.to_string())?;
+        let mut court = state.court_id.write()?;
         *court = None;
     }
     {
-        let mut user = state.user_email.write().map_err(|e| e.to_string())?;
+        let mut user = state.user_email.write()?;
         *user = None;
     }
+    {
+        let mut role = state.user_role.write()?;
+        *role = None;
+    }
 
     Ok(json!({
         "success": true,
@@ -139,10 +203,10 @@ pub async fn firebase_logout(app: AppHandle) -> Result<serde_json::Value, String
 }
 
 #[tauri::command]
-pub async fn set_court_id(app: AppHandle, court_id: String) -> Result<serde_json::Value, String> {
+pub async fn set_court_id(app: AppHandle, court_id: String) -> AppResult<serde_json::Value> {
     let state = app.state::<AppState>();
     {
-        let mut court = state.court_id.write().map_err(|e| e.to_string())?;
+        let mut court = state.court_id.write()?;
         *court = Some(court_id.clone());
     }
 
@@ -154,7 +218,7 @@ pub async fn set_court_id(app: AppHandle, court_id: String) -> Result<serde_json
 }
 
 #[tauri::command]
-pub async fn get_courts(app: AppHandle) -> Result<serde_json::Value, String> {
+pub async fn get_courts(app: AppHandle) -> AppResult<serde_json::Value> {
     let state = app.state::<AppState>();
 
     let list_resp = state
@@ -196,7 +260,7 @@ pub async fn get_courts(app: AppHandle) -> Result<serde_json::Value, String> {
 }
 
 #[tauri::command]
-pub async fn trigger_sync(app: AppHandle) -> Result<serde_json::Value, String> {
+pub async fn trigger_sync(app: AppHandle) -> AppResult<serde_json::Value> {
     let state = app.state::<AppState>();
 
     let has_auth = state
@@ -206,12 +270,21 @@ pub async fn trigger_sync(app: AppHandle) -> Result<serde_json::Value, String> {
         .unwrap_or(false);
 
     if !has_auth {
-        return Err("Not authenticated. Please log in first.".to_string());
+        return Err(AppError::Unauthenticated(
+            "Not authenticated. Please log in first.".to_string(),
+        ));
+    }
+    if !state.config.read()?.sync.is_configured() {
+        return Err(AppError::Config(
+            "Sync is not set up. Add the Firebase API key (sync.api_key or \
+             RECORDS_FIRESTORE_API_KEY) and restart the app."
+                .to_string(),
+        ));
     }
 
     let engine = crate::sync::engine::SyncEngine::new(
         state.firestore.clone(),
-        state.conn.clone(),
+        state.db.clone(),
         state.court_id.clone(),
     );
 
@@ -0,0 +1,126 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+use tauri::State;
+
+use crate::db::backup::{self, BackupInfo, BackupOptions, BackupTrigger};
+use crate::error::{AppError, AppResult};
+use crate::methods::auth::require_admin;
+use crate::methods::offenders::photo_store;
+use crate::AppState;
+
+pub(crate) fn backup_options(state: &AppState) -> AppResult<BackupOptions> {
+    let app_dir = crate::get_app_dir();
+    let config = state.config.read()?;
+    Ok(BackupOptions {
+        directory: config.backup_dir(&app_dir),
+        photo_dir: config
+            .backup
+            .include_photos
+            .then(|| config.photo_dir(&app_dir)),
+        retention: config.backup.retention as usize,
+        database_key: state.keys.read()?.as_ref().map(|keys| keys.database_key()),
+    })
+}
+
+async fn take_backup(
+    state: &AppState,
+    options: BackupOptions,
+    trigger: BackupTrigger,
+) -> AppResult<BackupInfo> {
+    state
+        .db
+        .read(move |conn| backup::create(conn, &options, trigger))
+        .await
+}
+
+/// Back up the database before an operation that overwrites or repairs it.
+/// Safety backups prune nothing and are never pruned, so the safety copy
+/// can neither push out the backup being restored nor be pushed out later.
+pub(crate) async fn take_safety_backup(
+    state: &AppState,
+    options: BackupOptions,
+    trigger: BackupTrigger,
+) -> AppResult<BackupInfo> {
+    take_backup(state, options, trigger).await
+}
+
+/// Take a scheduled backup if the newest one is older than the configured
+/// interval. Returns `None` when scheduling is off, the database is still
+/// locked or nothing is due yet.
+pub(crate) async fn run_scheduled_backup(state: &AppState) -> AppResult<Option<BackupInfo>> {
+    let interval_hours = state.config.read()?.backup.interval_hours;
+    if interval_hours == 0 || !state.db.is_unlocked() {
+        return Ok(None);
+    }
+    let options = backup_options(state)?;
+
+    let directory = options.directory.clone();
+    let newest = tauri::async_runtime::spawn_blocking(move || backup::list(&directory))
+        .await
+        .map_err(|e| AppError::internal(format!("Backup listing failed: {}", e)))??
+        .into_iter()
+        .filter_map(|b| b.manifest)
+        .filter(|m| matches!(m.trigger, BackupTrigger::Scheduled | BackupTrigger::Manual))
+        .map(|m| m.created_at)
+        .max();
+    let due = newest.is_none_or(|at| Utc::now() - at >= Duration::hours(interval_hours as i64));
+    if !due {
+        return Ok(None);
+    }
+
+    take_backup(state, options, BackupTrigger::Scheduled)
+        .await
+        .map(Some)
+}
+
+// Take a backup now (admins only)
+#[tauri::command]
+pub async fn create_backup(state: State<'_, AppState>) -> AppResult<BackupInfo> {
+    require_admin(&state)?;
+    let options = backup_options(&state)?;
+    take_backup(&state, options, BackupTrigger::Manual).await
+}
+
+// List backups in the configured directory, newest first (admins only)
+#[tauri::command]
+pub async fn list_backups(state: State<'_, AppState>) -> AppResult<Vec<BackupInfo>> {
+    require_admin(&state)?;
+    let directory = backup_options(&state)?.directory;
+    tauri::async_runtime::spawn_blocking(move || backup::list(&directory))
+        .await
+        .map_err(|e| AppError::internal(format!("Backup listing failed: {}", e)))?
+}
+
+// Restore a backup by name (admins only). The archive is verified first and
+// the current database is backed up before it is overwritten.
+#[tauri::command]
+pub async fn restore_backup(
+    state: State<'_, AppState>,
+    name: String,
+) -> AppResult<serde_json::Value> {
+    require_admin(&state)?;
+    let options = backup_options(&state)?;
+    let photos = photo_store(&state)?;
+
+    let archive = backup::resolve(&options.directory, &name)?;
+    let key = options.database_key.clone();
+    let staged =
+        tauri::async_runtime::spawn_blocking(move || backup::verify(&archive, key.as_deref()))
+            .await
+            .map_err(|e| AppError::internal(format!("Backup verification failed: {}", e)))??;
+
+    let key = options.database_key.clone();
+    let safety = take_safety_backup(&state, options, BackupTrigger::PreRestore).await?;
+
+    let report = state
+        .db
+        .write(move |conn| backup::restore(conn, &staged, &photos, key.as_deref()))
+        .await?;
+
+    Ok(json!({
+        "message": "Backup restored successfully",
+        "status": "success",
+        "report": report,
+        "safety_backup": safety.name,
+    }))
+}
@@ -0,0 +1,124 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::bail::{
+    self, Absence, Bond, BondClosure, BondDetail, BondDocument, BondFilter, NewBond,
+    NewBondDocument, NewSurety, Surety,
+};
+use crate::AppState;
+
+// Record bail granted to an accused in a case, with its sureties
+#[tauri::command]
+pub async fn grant_bond(state: State<'_, AppState>, bond: NewBond) -> AppResult<Bond> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| bail::grant(conn, bond, created_by.as_deref()))
+        .await
+}
+
+// The terms are met and the accused is released
+#[tauri::command]
+pub async fn post_bond(
+    state: State<'_, AppState>,
+    bond_id: i64,
+    posted_on: Option<String>,
+) -> AppResult<Bond> {
+    state
+        .db
+        .write(move |conn| bail::post(conn, bond_id, posted_on.as_deref()))
+        .await
+}
+
+// Discharge, cancel or forfeit a bond
+#[tauri::command]
+pub async fn close_bond(
+    state: State<'_, AppState>,
+    bond_id: i64,
+    closure: BondClosure,
+) -> AppResult<Bond> {
+    state
+        .db
+        .write(move |conn| bail::close(conn, bond_id, closure))
+        .await
+}
+
+#[tauri::command]
+pub async fn add_surety(
+    state: State<'_, AppState>,
+    bond_id: i64,
+    surety: NewSurety,
+) -> AppResult<Surety> {
+    state
+        .db
+        .write(move |conn| bail::add_surety(conn, bond_id, surety))
+        .await
+}
+
+#[tauri::command]
+pub async fn remove_surety(state: State<'_, AppState>, surety_id: i64) -> AppResult<()> {
+    state
+        .db
+        .write(move |conn| bail::remove_surety(conn, surety_id))
+        .await
+}
+
+// Record a document deposited with the court as security
+#[tauri::command]
+pub async fn add_bond_document(
+    state: State<'_, AppState>,
+    bond_id: i64,
+    document: NewBondDocument,
+) -> AppResult<BondDocument> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| bail::add_document(conn, bond_id, document, created_by.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn return_bond_document(
+    state: State<'_, AppState>,
+    document_id: i64,
+    returned_on: Option<String>,
+) -> AppResult<BondDocument> {
+    state
+        .db
+        .write(move |conn| bail::return_document(conn, document_id, returned_on.as_deref()))
+        .await
+}
+
+// Record that an accused on bond missed a hearing; raises a warning
+// notification
+#[tauri::command]
+pub async fn record_bond_absence(
+    state: State<'_, AppState>,
+    hearing_id: i64,
+    offender_id: i64,
+    notes: Option<String>,
+) -> AppResult<Absence> {
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            bail::record_absence(conn, hearing_id, offender_id, notes, recorded_by.as_deref())
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn get_bond(state: State<'_, AppState>, bond_id: i64) -> AppResult<BondDetail> {
+    state.db.read(move |conn| bail::detail(conn, bond_id)).await
+}
+
+#[tauri::command]
+pub async fn list_bonds(
+    state: State<'_, AppState>,
+    filter: Option<BondFilter>,
+) -> AppResult<Vec<Bond>> {
+    state
+        .db
+        .read(move |conn| bail::list(conn, &filter.unwrap_or_default()))
+        .await
+}
@@ -1,140 +1,121 @@
-use rusqlite::params;
-use rusqlite::OptionalExtension;
-use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::State;
 
+use crate::error::{AppError, AppResult};
+use crate::services::case_status::{self, StatusChange, Transition};
+use crate::services::cases::{self, Case};
 use crate::AppState; // Import AppState
 
-#[derive(Serialize, Deserialize)]
-pub struct Case {
-    pub case_id: i32,
-    pub title: String,
-    pub status: String,
-    pub assigned_staff_id: Option<i32>,
-    pub priority: String,
-    pub date_created: String,
-}
-
 // ✅ Get All Cases
 #[tauri::command]
-pub fn get_all_cases(state: State<AppState>) -> Result<Vec<Case>, String> {
-    let conn = state.conn.lock().unwrap();
-    let mut stmt = conn
-        .prepare(
-            "SELECT case_id, title, status, assigned_staff_id, priority, date_created FROM cases",
-        )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let case_iter = stmt
-        .query_map([], |row| {
-            Ok(Case {
-                case_id: row.get(0)?,
-                title: row.get(1)?,
-                status: row.get(2)?,
-                assigned_staff_id: row.get(3)?,
-                priority: row.get(4)?,
-                date_created: row.get(5)?,
-            })
-        })
-        .map_err(|e| format!("Failed to fetch cases: {}", e))?;
-
-    let cases: Vec<Case> = case_iter.filter_map(Result::ok).collect();
-
-    Ok(cases)
+pub async fn get_all_cases(state: State<'_, AppState>) -> AppResult<Vec<Case>> {
+    state.db.read(cases::list).await
 }
 
 // ✅ Create a Case
 #[tauri::command]
-pub fn create_case(
-    state: State<AppState>,
+pub async fn create_case(
+    state: State<'_, AppState>,
     title: String,
     assigned_staff_id: Option<i32>,
     priority: String,
-) -> Result<serde_json::Value, String> {
-    let conn = state.conn.lock().unwrap();
-
-    match conn.execute(
-        "INSERT INTO cases (title, status, assigned_staff_id, priority) VALUES (?1, 'Open', ?2, ?3)",
-        params![title, assigned_staff_id, priority],
-    ) {
-        Ok(_) => {
-            let new_case_id = conn.last_insert_rowid();
-            Ok(json!({
-                "message": "Case created successfully",
-                "status": "success",
-                "case_id": new_case_id
-            }))
-        }
-        Err(e) => Err(format!("Failed to create case: {}", e)),
-    }
+) -> AppResult<serde_json::Value> {
+    let new_case_id = state
+        .db
+        .write(move |conn| cases::create(conn, &title, assigned_staff_id, &priority))
+        .await?;
+    Ok(json!({
+        "message": "Case created successfully",
+        "status": "success",
+        "case_id": new_case_id
+    }))
 }
 
 // ✅ Get Case by ID
 #[tauri::command]
-pub fn get_case(state: State<AppState>, case_id: i32) -> Result<Option<Case>, String> {
-    let conn = state.conn.lock().unwrap();
-    let mut stmt = conn
-        .prepare("SELECT case_id, title, status, assigned_staff_id, priority, date_created FROM cases WHERE case_id = ?1")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let case = stmt
-        .query_row(params![case_id], |row| {
-            Ok(Case {
-                case_id: row.get(0)?,
-                title: row.get(1)?,
-                status: row.get(2)?,
-                assigned_staff_id: row.get(3)?,
-                priority: row.get(4)?,
-                date_created: row.get(5)?,
-            })
-        })
-        .optional()
-        .map_err(|e| format!("Failed to fetch case: {}", e))?;
+pub async fn get_case(state: State<'_, AppState>, case_id: i32) -> AppResult<Option<Case>> {
+    state.db.read(move |conn| cases::get(conn, case_id)).await
+}
 
-    Ok(case)
+// The signed-in user's role, which decides the status changes they may make
+fn current_role(state: &AppState) -> AppResult<String> {
+    state.user_role.read()?.clone().ok_or_else(|| {
+        AppError::Unauthenticated("Not authenticated. Please log in first.".to_string())
+    })
 }
 
 // ✅ Update Case Status
+// Moves the case through the status workflow; reopening needs a reason
 #[tauri::command]
-pub fn update_case_status(
-    state: State<AppState>,
+pub async fn update_case_status(
+    state: State<'_, AppState>,
     case_id: i32,
     new_status: String,
-) -> Result<String, String> {
-    let conn = state.conn.lock().unwrap();
-    match conn.execute(
-        "UPDATE cases SET status = ?1 WHERE case_id = ?2",
-        params![new_status, case_id],
-    ) {
-        Ok(_) => Ok("Case status updated successfully".to_string()),
-        Err(e) => Err(format!("Failed to update case status: {}", e)),
-    }
+    reason: Option<String>,
+) -> AppResult<String> {
+    let role = current_role(&state)?;
+    let changed_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            case_status::change(
+                conn,
+                case_id.into(),
+                &new_status,
+                reason.as_deref(),
+                changed_by.as_deref(),
+                &role,
+            )
+        })
+        .await?;
+    Ok("Case status updated successfully".to_string())
+}
+
+// Status changes the signed-in user can make on the case now
+#[tauri::command]
+pub async fn get_case_transitions(
+    state: State<'_, AppState>,
+    case_id: i32,
+) -> AppResult<Vec<Transition>> {
+    let role = current_role(&state)?;
+    state
+        .db
+        .read(move |conn| case_status::available(conn, case_id.into(), &role))
+        .await
+}
+
+// Every status change of the case, oldest first
+#[tauri::command]
+pub async fn get_case_status_history(
+    state: State<'_, AppState>,
+    case_id: i32,
+) -> AppResult<Vec<StatusChange>> {
+    state
+        .db
+        .read(move |conn| case_status::history(conn, case_id.into()))
+        .await
 }
 
 // ✅ Assign Staff to Case
 #[tauri::command]
-pub fn assign_staff_to_case(
-    state: State<AppState>,
+pub async fn assign_staff_to_case(
+    state: State<'_, AppState>,
     case_id: i32,
     staff_id: Option<i32>,
-) -> Result<String, String> {
-    let conn = state.conn.lock().unwrap();
-    match conn.execute(
-        "UPDATE cases SET assigned_staff_id = ?1 WHERE case_id = ?2",
-        params![staff_id, case_id],
-    ) {
-        Ok(_) => Ok("Staff assigned to case successfully".to_string()),
-        Err(e) => Err(format!("Failed to assign staff to case: {}", e)),
-    }
+) -> AppResult<String> {
+    state
+        .db
+        .write(move |conn| cases::assign_staff(conn, case_id, staff_id))
+        .await?;
+    Ok("Staff assigned to case successfully".to_string())
 }
 
 // ✅ Delete Case
 #[tauri::command]
-pub fn delete_case(state: State<AppState>, case_id: i32) -> Result<String, String> {
-    let conn = state.conn.lock().unwrap();
-    match conn.execute("DELETE FROM cases WHERE case_id = ?1", params![case_id]) {
-        Ok(_) => Ok("Case deleted successfully".to_string()),
-        Err(e) => Err(format!("Failed to delete case: {}", e)),
-    }
+pub async fn delete_case(state: State<'_, AppState>, case_id: i32) -> AppResult<String> {
+    state
+        .db
+        .write(move |conn| cases::delete(conn, case_id))
+        .await?;
+    Ok("Case deleted successfully".to_string())
 }
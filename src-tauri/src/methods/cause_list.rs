@@ -0,0 +1,40 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::cause_list::{self, CauseList};
+use crate::AppState;
+
+// The cause list for a day (YYYY-MM-DD), optionally for one courtroom
+#[tauri::command]
+pub async fn get_cause_list(
+    state: State<'_, AppState>,
+    date: String,
+    courtroom: Option<String>,
+) -> AppResult<CauseList> {
+    state
+        .db
+        .read(move |conn| cause_list::generate(conn, &date, courtroom.as_deref()))
+        .await
+}
+
+// The cause list as a printable HTML page
+#[tauri::command]
+pub async fn get_cause_list_html(
+    state: State<'_, AppState>,
+    date: String,
+    courtroom: Option<String>,
+) -> AppResult<String> {
+    let list = get_cause_list(state, date, courtroom).await?;
+    Ok(cause_list::to_html(&list))
+}
+
+// The cause list as an A4 PDF
+#[tauri::command]
+pub async fn get_cause_list_pdf(
+    state: State<'_, AppState>,
+    date: String,
+    courtroom: Option<String>,
+) -> AppResult<Vec<u8>> {
+    let list = get_cause_list(state, date, courtroom).await?;
+    Ok(cause_list::to_pdf(&list))
+}
@@ -0,0 +1,73 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::charges::{self, Charge, NewCharge, Plea, Verdict};
+use crate::AppState;
+
+// Add a count to a case; the next free count number unless one is given
+#[tauri::command]
+pub async fn add_charge(state: State<'_, AppState>, charge: NewCharge) -> AppResult<Charge> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| charges::add(conn, charge, created_by.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn update_charge(
+    state: State<'_, AppState>,
+    charge_id: i64,
+    charge: NewCharge,
+) -> AppResult<Charge> {
+    state
+        .db
+        .write(move |conn| charges::update(conn, charge_id, charge))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_charge(state: State<'_, AppState>, charge_id: i64) -> AppResult<()> {
+    state
+        .db
+        .write(move |conn| charges::delete(conn, charge_id))
+        .await
+}
+
+// Pass no plea to clear one entered by mistake
+#[tauri::command]
+pub async fn record_plea(
+    state: State<'_, AppState>,
+    charge_id: i64,
+    plea: Option<Plea>,
+    plea_date: Option<String>,
+) -> AppResult<Charge> {
+    state
+        .db
+        .write(move |conn| charges::record_plea(conn, charge_id, plea, plea_date.as_deref()))
+        .await
+}
+
+// Also rewrites the case verdict
+#[tauri::command]
+pub async fn record_verdict(
+    state: State<'_, AppState>,
+    charge_id: i64,
+    verdict: Option<Verdict>,
+    verdict_date: Option<String>,
+) -> AppResult<Charge> {
+    state
+        .db
+        .write(move |conn| {
+            charges::record_verdict(conn, charge_id, verdict, verdict_date.as_deref())
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn list_case_charges(state: State<'_, AppState>, case_id: i64) -> AppResult<Vec<Charge>> {
+    state
+        .db
+        .read(move |conn| charges::list_for_case(conn, case_id))
+        .await
+}
@@ -0,0 +1,84 @@
+use chrono::Local;
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::custody::{
+    self, Custody, CustodyFilter, NewCustody, Production, ProductionDue, Release,
+};
+use crate::AppState;
+
+// Record that an offender is held on remand or under sentence
+#[tauri::command]
+pub async fn admit_to_custody(
+    state: State<'_, AppState>,
+    custody: NewCustody,
+) -> AppResult<Custody> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| custody::admit(conn, custody, created_by.as_deref()))
+        .await
+}
+
+// Record a production in court and the next production date
+#[tauri::command]
+pub async fn record_production(
+    state: State<'_, AppState>,
+    custody_id: i64,
+    production: Production,
+) -> AppResult<Custody> {
+    state
+        .db
+        .write(move |conn| custody::record_production(conn, custody_id, production))
+        .await
+}
+
+#[tauri::command]
+pub async fn transfer_custody(
+    state: State<'_, AppState>,
+    custody_id: i64,
+    facility: String,
+) -> AppResult<Custody> {
+    state
+        .db
+        .write(move |conn| custody::transfer(conn, custody_id, &facility))
+        .await
+}
+
+#[tauri::command]
+pub async fn release_from_custody(
+    state: State<'_, AppState>,
+    custody_id: i64,
+    release: Release,
+) -> AppResult<Custody> {
+    state
+        .db
+        .write(move |conn| custody::release(conn, custody_id, release))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_custody(
+    state: State<'_, AppState>,
+    filter: Option<CustodyFilter>,
+) -> AppResult<Vec<Custody>> {
+    state
+        .db
+        .read(move |conn| custody::list(conn, &filter.unwrap_or_default()))
+        .await
+}
+
+// People to be produced in court over the coming days (a week unless
+// `days` says otherwise), for the production order sent to the prison
+#[tauri::command]
+pub async fn list_due_for_production(
+    state: State<'_, AppState>,
+    from: Option<String>,
+    days: Option<u32>,
+) -> AppResult<Vec<ProductionDue>> {
+    let from = from.unwrap_or_else(|| Local::now().date_naive().format("%Y-%m-%d").to_string());
+    state
+        .db
+        .read(move |conn| custody::due_for_production(conn, &from, days.unwrap_or(7)))
+        .await
+}
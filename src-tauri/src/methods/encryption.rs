@@ -0,0 +1,134 @@
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::error::{AppError, AppResult};
+use crate::methods::auth::require_admin;
+use crate::methods::offenders::photo_store;
+use crate::security::encryption::{self, KeyFile, KeySource};
+use crate::AppState;
+
+/// Serialises unlock attempts, so two cannot both convert the database
+static UNLOCKING: Mutex<()> = Mutex::new(());
+
+#[derive(Deserialize)]
+pub struct EnableEncryption {
+    pub key_source: KeySource,
+    /// Required for `passphrase`; ignored for `machine`
+    pub passphrase: Option<String>,
+}
+
+// Whether encryption at rest is on and the database unlocked. Needs no
+// sign-in, because the unlock screen comes before the login screen.
+#[tauri::command]
+pub fn get_encryption_status(state: State<AppState>) -> AppResult<serde_json::Value> {
+    let key_file = KeyFile::load(&crate::get_app_dir())?;
+    Ok(json!({
+        "enabled": key_file.is_some(),
+        "key_source": key_file.as_ref().map(|k| k.key_source),
+        "database_encrypted": key_file.as_ref().is_some_and(|k| k.database_encrypted),
+        "unlocked": state.db.is_unlocked(),
+        "unlock_error": state.unlock_error,
+    }))
+}
+
+// Turn on encryption at rest (admins only). Writes the key file; the
+// existing database and photos are encrypted in place on the next start
+// (after the passphrase is entered, for passphrase keys).
+#[tauri::command]
+pub async fn enable_encryption(
+    state: State<'_, AppState>,
+    request: EnableEncryption,
+) -> AppResult<serde_json::Value> {
+    require_admin(&state)?;
+
+    let app_dir = crate::get_app_dir();
+    if KeyFile::load(&app_dir)?.is_some() {
+        return Err(AppError::Conflict(
+            "Encryption at rest is already enabled.".to_string(),
+        ));
+    }
+
+    let passphrase = match request.key_source {
+        KeySource::Passphrase => {
+            let passphrase = request.passphrase.unwrap_or_default();
+            if passphrase.chars().count() < encryption::MIN_PASSPHRASE_CHARS {
+                return Err(AppError::field(
+                    "passphrase",
+                    format!(
+                        "Passphrase must be at least {} characters.",
+                        encryption::MIN_PASSPHRASE_CHARS
+                    ),
+                ));
+            }
+            Some(passphrase)
+        }
+        KeySource::Machine => None,
+    };
+
+    let key_source = request.key_source;
+    tauri::async_runtime::spawn_blocking(move || {
+        let key_file = match passphrase {
+            Some(passphrase) => KeyFile::create(key_source, passphrase.as_bytes()).0,
+            None => KeyFile::create_machine()?.0,
+        };
+        key_file.save(&app_dir)
+    })
+    .await
+    .map_err(|e| AppError::internal(format!("Key derivation failed: {}", e)))??;
+
+    Ok(json!({
+        "message": "Encryption will be applied when the app restarts",
+        "status": "success",
+        "key_source": key_source,
+        "restart_required": true,
+    }))
+}
+
+// Unlock a passphrase-encrypted database. The first unlock after
+// `enable_encryption` also encrypts the existing data in place.
+#[tauri::command]
+pub async fn unlock_database(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> AppResult<serde_json::Value> {
+    if state.db.is_unlocked() {
+        return Err(AppError::Conflict(
+            "The database is already unlocked.".to_string(),
+        ));
+    }
+    let key_file = KeyFile::load(&crate::get_app_dir())?
+        .filter(|k| k.key_source == KeySource::Passphrase)
+        .ok_or_else(|| {
+            AppError::Conflict("The database is not protected by a passphrase.".to_string())
+        })?;
+
+    let db = state.db.clone();
+    let keys = tauri::async_runtime::spawn_blocking(move || {
+        let _unlocking = UNLOCKING.lock()?;
+        if db.is_unlocked() {
+            return Err(AppError::Conflict(
+                "The database is already unlocked.".to_string(),
+            ));
+        }
+        let keys = key_file.unlock(passphrase.as_bytes())?;
+        crate::open_db(&db, Some(&keys))?;
+        Ok::<_, AppError>(keys)
+    })
+    .await
+    .map_err(|e| AppError::internal(format!("Unlock failed: {}", e)))??;
+
+    *state.keys.write()? = Some(keys);
+    let photos = photo_store(&state)?;
+    let photos_encrypted = tauri::async_runtime::spawn_blocking(move || photos.encrypt_existing())
+        .await
+        .map_err(|e| AppError::internal(format!("Photo encryption failed: {}", e)))??;
+
+    Ok(json!({
+        "message": "Database unlocked",
+        "status": "success",
+        "photos_encrypted": photos_encrypted,
+    }))
+}
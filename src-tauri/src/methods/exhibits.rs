@@ -0,0 +1,78 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::exhibits::{
+    self, Exhibit, ExhibitDetail, ExhibitFilter, ExhibitReport, NewExhibit, NewMovement,
+};
+use crate::AppState;
+
+// Enter an exhibit in the register, received into the store with both
+// signatures
+#[tauri::command]
+pub async fn register_exhibit(
+    state: State<'_, AppState>,
+    exhibit: NewExhibit,
+) -> AppResult<ExhibitDetail> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| exhibits::register(conn, exhibit, created_by.as_deref()))
+        .await
+}
+
+// Take an exhibit out, return it, move it within the store or dispose of it
+#[tauri::command]
+pub async fn record_exhibit_movement(
+    state: State<'_, AppState>,
+    exhibit_id: i64,
+    movement: NewMovement,
+) -> AppResult<ExhibitDetail> {
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            exhibits::record_movement(conn, exhibit_id, movement, recorded_by.as_deref())
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn get_exhibit(state: State<'_, AppState>, exhibit_id: i64) -> AppResult<ExhibitDetail> {
+    state
+        .db
+        .read(move |conn| exhibits::detail(conn, exhibit_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_exhibits(
+    state: State<'_, AppState>,
+    filter: Option<ExhibitFilter>,
+) -> AppResult<Vec<Exhibit>> {
+    state
+        .db
+        .read(move |conn| exhibits::list(conn, &filter.unwrap_or_default()))
+        .await
+}
+
+// Every exhibit of a case with its chain of custody
+#[tauri::command]
+pub async fn get_case_exhibit_report(
+    state: State<'_, AppState>,
+    case_id: i64,
+) -> AppResult<ExhibitReport> {
+    state
+        .db
+        .read(move |conn| exhibits::case_report(conn, case_id))
+        .await
+}
+
+// The case exhibit report as a printable HTML page
+#[tauri::command]
+pub async fn get_case_exhibit_report_html(
+    state: State<'_, AppState>,
+    case_id: i64,
+) -> AppResult<String> {
+    let report = get_case_exhibit_report(state, case_id).await?;
+    Ok(exhibits::report_html(&report))
+}
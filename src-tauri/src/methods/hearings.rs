@@ -0,0 +1,118 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::bail;
+use crate::services::hearings::{
+    self, Adjourned, Adjournment, Attendance, Hearing, HearingFilter, HearingStatus, NewHearing,
+};
+use crate::AppState;
+
+// Put a hearing of a case in the calendar
+#[tauri::command]
+pub async fn schedule_hearing(
+    state: State<'_, AppState>,
+    hearing: NewHearing,
+) -> AppResult<Hearing> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| hearings::schedule(conn, hearing, created_by.as_deref()))
+        .await
+}
+
+// Adjourn a scheduled hearing with a reason, scheduling the next sitting.
+// Returns both hearings.
+#[tauri::command]
+pub async fn adjourn_hearing(
+    state: State<'_, AppState>,
+    hearing_id: i64,
+    adjournment: Adjournment,
+) -> AppResult<Adjourned> {
+    let adjourned_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            hearings::adjourn(conn, hearing_id, adjournment, adjourned_by.as_deref())
+        })
+        .await
+}
+
+// Record what happened at a hearing, concluding it, and who of the accused
+// attended. An absent accused who is out on bond raises a warning.
+#[tauri::command]
+pub async fn record_hearing_outcome(
+    state: State<'_, AppState>,
+    hearing_id: i64,
+    outcome: String,
+    attendance: Option<Vec<Attendance>>,
+) -> AppResult<Hearing> {
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            let hearing = hearings::record_outcome(
+                conn,
+                hearing_id,
+                &outcome,
+                &attendance.unwrap_or_default(),
+                recorded_by.as_deref(),
+            )?;
+            bail::flag_missed_hearings(conn, recorded_by.as_deref())?;
+            Ok(hearing)
+        })
+        .await
+}
+
+// Record who of the accused attended a hearing without concluding it, e.g.
+// one that was adjourned
+#[tauri::command]
+pub async fn record_hearing_attendance(
+    state: State<'_, AppState>,
+    hearing_id: i64,
+    attendance: Vec<Attendance>,
+) -> AppResult<Vec<Attendance>> {
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            let recorded =
+                hearings::record_attendance(conn, hearing_id, &attendance, recorded_by.as_deref())?;
+            bail::flag_missed_hearings(conn, recorded_by.as_deref())?;
+            Ok(recorded)
+        })
+        .await
+}
+
+// The calendar: hearings between two dates (inclusive), optionally in one
+// courtroom or with one status
+#[tauri::command]
+pub async fn list_hearings(
+    state: State<'_, AppState>,
+    from: Option<String>,
+    to: Option<String>,
+    courtroom: Option<String>,
+    status: Option<HearingStatus>,
+) -> AppResult<Vec<Hearing>> {
+    let filter = HearingFilter {
+        from,
+        to,
+        courtroom,
+        status,
+    };
+    state
+        .db
+        .read(move |conn| hearings::list(conn, &filter))
+        .await
+}
+
+// Every hearing of a case, oldest first
+#[tauri::command]
+pub async fn list_case_hearings(
+    state: State<'_, AppState>,
+    case_id: i64,
+) -> AppResult<Vec<Hearing>> {
+    state
+        .db
+        .read(move |conn| hearings::list_for_case(conn, case_id))
+        .await
+}
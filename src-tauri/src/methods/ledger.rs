@@ -0,0 +1,114 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::methods::auth::require_admin;
+use crate::services::ledger::{
+    self, Assessment, AssessmentDetail, AssessmentFilter, Closure, DailyTotal, NewAssessment,
+    NewPayment, Outstanding, Payment,
+};
+use crate::AppState;
+
+// Record a fine or fee owed on a case, offender or file
+#[tauri::command]
+pub async fn create_assessment(
+    state: State<'_, AppState>,
+    assessment: NewAssessment,
+) -> AppResult<Assessment> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| ledger::assess(conn, assessment, created_by.as_deref()))
+        .await
+}
+
+// Take a payment against an assessment and issue its receipt number
+#[tauri::command]
+pub async fn record_payment(state: State<'_, AppState>, payment: NewPayment) -> AppResult<Payment> {
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| ledger::pay(conn, payment, recorded_by.as_deref()))
+        .await
+}
+
+// Cancel a payment with a reversing entry (admin only)
+#[tauri::command]
+pub async fn reverse_payment(
+    state: State<'_, AppState>,
+    payment_id: i64,
+    reason: String,
+) -> AppResult<Payment> {
+    require_admin(&state)?;
+    let recorded_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| ledger::reverse(conn, payment_id, &reason, recorded_by.as_deref()))
+        .await
+}
+
+// Settle an assessment without payment: a fine converted to its default
+// term, or a fee waived (admin only)
+#[tauri::command]
+pub async fn close_assessment(
+    state: State<'_, AppState>,
+    assessment_id: i64,
+    closure: Closure,
+    reason: String,
+) -> AppResult<Assessment> {
+    require_admin(&state)?;
+    let closed_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            ledger::close(conn, assessment_id, closure, &reason, closed_by.as_deref())
+        })
+        .await
+}
+
+// An assessment with its payments and reversals
+#[tauri::command]
+pub async fn get_assessment(
+    state: State<'_, AppState>,
+    assessment_id: i64,
+) -> AppResult<AssessmentDetail> {
+    state
+        .db
+        .read(move |conn| ledger::detail(conn, assessment_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_assessments(
+    state: State<'_, AppState>,
+    filter: Option<AssessmentFilter>,
+) -> AppResult<Vec<Assessment>> {
+    state
+        .db
+        .read(move |conn| ledger::list(conn, &filter.unwrap_or_default()))
+        .await
+}
+
+// What is still owed, optionally for one case, offender, file or kind
+#[tauri::command]
+pub async fn get_outstanding_balances(
+    state: State<'_, AppState>,
+    filter: Option<AssessmentFilter>,
+) -> AppResult<Outstanding> {
+    state
+        .db
+        .read(move |conn| ledger::outstanding(conn, filter.unwrap_or_default()))
+        .await
+}
+
+// Takings per day between two dates, inclusive
+#[tauri::command]
+pub async fn get_daily_collections(
+    state: State<'_, AppState>,
+    from: String,
+    to: String,
+) -> AppResult<Vec<DailyTotal>> {
+    state
+        .db
+        .read(move |conn| ledger::daily_totals(conn, &from, &to))
+        .await
+}
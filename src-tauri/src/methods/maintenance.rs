@@ -0,0 +1,48 @@
+use serde_json::json;
+use tauri::State;
+
+use crate::db::backup::BackupTrigger;
+use crate::error::AppResult;
+use crate::methods::auth::require_admin;
+use crate::methods::backup::{backup_options, take_safety_backup};
+use crate::methods::offenders::photo_store;
+use crate::services::maintenance::{self, IntegrityReport};
+use crate::AppState;
+
+// Check the database and photo directory for corruption and orphaned
+// records (admins only). Changes nothing.
+#[tauri::command]
+pub async fn check_database_integrity(state: State<'_, AppState>) -> AppResult<IntegrityReport> {
+    require_admin(&state)?;
+    let photos = photo_store(&state)?;
+    state
+        .db
+        .read(move |conn| maintenance::check(conn, &photos))
+        .await
+}
+
+// Apply the safe repairs from `check_database_integrity` (admins only). The
+// database is backed up first, so a repair can always be undone by restoring.
+#[tauri::command]
+pub async fn repair_database(state: State<'_, AppState>) -> AppResult<serde_json::Value> {
+    require_admin(&state)?;
+    let options = backup_options(&state)?;
+    let photos = photo_store(&state)?;
+
+    let safety = take_safety_backup(&state, options, BackupTrigger::PreRepair).await?;
+    let report = state
+        .db
+        .write(move |conn| maintenance::repair(conn, &photos))
+        .await?;
+
+    Ok(json!({
+        "message": if report.after.is_healthy() {
+            "Database repaired"
+        } else {
+            "Some problems could not be repaired automatically"
+        },
+        "status": "success",
+        "report": report,
+        "safety_backup": safety.name,
+    }))
+}
@@ -1,8 +1,24 @@
 pub mod attendance;
 pub mod auth;
+pub mod backup;
+pub mod bail;
 pub mod cases;
+pub mod cause_list;
+pub mod charges;
+pub mod custody;
+pub mod encryption;
+pub mod exhibits;
 pub mod files;
+pub mod hearings;
+pub mod ledger;
+pub mod maintenance;
 pub mod notifications;
+pub mod offence_catalogue;
 pub mod offenders;
+pub mod search;
+pub mod sentences;
+pub mod settings;
 pub mod staff;
+pub mod two_factor;
 pub mod users;
+pub mod warrants;
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::methods::auth::require_admin;
+use crate::services::offence_catalogue::{
+    self, CatalogueAct, CatalogueFilter, CatalogueInfo, CatalogueOffence, ImportReport,
+};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_catalogue_offence(
+    state: State<'_, AppState>,
+    catalogue_id: i64,
+) -> AppResult<CatalogueOffence> {
+    state
+        .db
+        .read(move |conn| offence_catalogue::get(conn, catalogue_id))
+        .await
+}
+
+// The offences under a section of an Act, e.g. ("Penal Code", "268")
+#[tauri::command]
+pub async fn lookup_catalogue_offence(
+    state: State<'_, AppState>,
+    act: String,
+    section: String,
+) -> AppResult<Vec<CatalogueOffence>> {
+    state
+        .db
+        .read(move |conn| offence_catalogue::lookup(conn, &act, &section))
+        .await
+}
+
+#[tauri::command]
+pub async fn search_offence_catalogue(
+    state: State<'_, AppState>,
+    filter: Option<CatalogueFilter>,
+) -> AppResult<Vec<CatalogueOffence>> {
+    state
+        .db
+        .read(move |conn| offence_catalogue::search(conn, &filter.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_catalogue_acts(state: State<'_, AppState>) -> AppResult<Vec<CatalogueAct>> {
+    state.db.read(offence_catalogue::list_acts).await
+}
+
+#[tauri::command]
+pub async fn get_offence_catalogue_info(state: State<'_, AppState>) -> AppResult<CatalogueInfo> {
+    state.db.read(offence_catalogue::info).await
+}
+
+// Install a newer catalogue edition from a JSON file (admins only)
+#[tauri::command]
+pub async fn import_offence_catalogue(
+    state: State<'_, AppState>,
+    path: String,
+) -> AppResult<ImportReport> {
+    require_admin(&state)?;
+    let installed_by = state.user_email.read()?.clone();
+    let path = PathBuf::from(path);
+    state
+        .db
+        .write(move |conn| offence_catalogue::import_file(conn, &path, installed_by.as_deref()))
+        .await
+}
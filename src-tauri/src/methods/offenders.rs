@@ -1,110 +1,119 @@
-use rusqlite::{params, Connection};
-use std::fs;
-use std::path::PathBuf;
+use tauri::AppHandle;
 use tauri::Manager;
-use tauri::{AppHandle, State, Window};
 
-use serde::{Deserialize, Serialize};
+use crate::error::{AppError, AppResult};
+use crate::methods::auth::require_admin;
+use crate::services::duplicates::{
+    self, DuplicateCandidate, MergeOptions, MergeOutcome, MergeRecord,
+};
+use crate::services::offender_cases::{self, CaseRole, OffenderCase};
+use crate::services::offenders::{
+    self, HistoryDetails, NameMatch, Offender, OffenderDetails, OffenderHistory, Photo,
+};
+use crate::services::photos::PhotoStore;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Offender {
-    pub offender_id: Option<i64>,
-    pub full_name: String,
-    pub national_id: Option<String>,
-    pub date_of_birth: Option<String>,
-    pub gender: Option<String>,
-    pub photo_path: Option<String>,
-    pub notes: Option<String>,
-    pub date_created: Option<String>,
-    pub file_id: Option<i64>, // Foreign key to files
-    pub penalty: Option<String>,
-    pub penalty_notes: Option<String>,
+// Helper: the photo store (directory configurable via `photos.directory`,
+// encrypted when encryption at rest is on)
+pub(crate) fn photo_store(state: &crate::AppState) -> AppResult<PhotoStore> {
+    let app_dir = crate::get_app_dir();
+    let dir = state.config.read()?.photo_dir(&app_dir);
+    std::fs::create_dir_all(&dir).ok();
+    Ok(PhotoStore::new(dir, state.keys.read()?.clone()))
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct OffenderHistory {
-    pub id: Option<i64>,
-    pub offender_id: i64,
-    pub file_id: Option<i64>,
-    pub case_id: Option<i64>,
-    pub offense_date: Option<String>,
-    pub penalty: Option<String>,
-    pub penalty_notes: Option<String>,
-    pub notes: Option<String>,
-    pub created_at: Option<String>,
-}
-
-// Helper: get photo storage dir
-fn get_photo_dir(_app: &tauri::AppHandle) -> PathBuf {
-    // Use the same logic as get_app_dir in lib.rs
-    let app_name = env!("CARGO_PKG_NAME");
-    let mut dir = dirs::data_dir().expect("No app data dir");
-    dir.push(app_name);
-    dir.push("offender_photos");
-    std::fs::create_dir_all(&dir).ok();
-    dir
+fn uploaded_photo(photo: Option<Vec<u8>>, photo_filename: Option<String>) -> Option<Photo> {
+    photo
+        .zip(photo_filename)
+        .map(|(bytes, filename)| Photo { bytes, filename })
 }
 
 // List all offenders
 #[tauri::command]
-pub async fn list_offenders(app: AppHandle) -> Result<Vec<Offender>, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn
-        .prepare(
-            "SELECT offender_id, full_name, national_id, date_of_birth, gender, photo_path, notes, date_created, file_id, penalty, penalty_notes FROM offenders",
-        )
-        .map_err(|e| e.to_string())?;
-    let offenders = stmt
-        .query_map([], |row| {
-            Ok(Offender {
-                offender_id: row.get(0).ok(),
-                full_name: row.get(1).unwrap_or_default(),
-                national_id: row.get(2).ok(),
-                date_of_birth: row.get(3).ok(),
-                gender: row.get(4).ok(),
-                photo_path: row.get(5).ok(),
-                notes: row.get(6).ok(),
-                date_created: row.get(7).ok(),
-                file_id: row.get(8).ok(),
-                penalty: row.get(9).ok(),
-                penalty_notes: row.get(10).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-    Ok(offenders)
+pub async fn list_offenders(app: AppHandle) -> AppResult<Vec<Offender>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(offenders::list).await
 }
 
 // Get single offender
 #[tauri::command]
-pub async fn get_offender(app: AppHandle, offender_id: i64) -> Result<Offender, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn
-        .prepare(
-            "SELECT offender_id, full_name, national_id, date_of_birth, gender, photo_path, notes, date_created, file_id, penalty, penalty_notes FROM offenders WHERE offender_id = ?1",
-        )
-        .map_err(|e| e.to_string())?;
-    let offender = stmt
-        .query_row(params![offender_id], |row| {
-            Ok(Offender {
-                offender_id: row.get(0).ok(),
-                full_name: row.get(1).unwrap_or_default(),
-                national_id: row.get(2).ok(),
-                date_of_birth: row.get(3).ok(),
-                gender: row.get(4).ok(),
-                photo_path: row.get(5).ok(),
-                notes: row.get(6).ok(),
-                date_created: row.get(7).ok(),
-                file_id: row.get(8).ok(),
-                penalty: row.get(9).ok(),
-                penalty_notes: row.get(10).ok(),
-            })
+pub async fn get_offender(app: AppHandle, offender_id: i64) -> AppResult<Offender> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| offenders::get(conn, offender_id)).await
+}
+
+// Find offenders by a name that may be spelled differently, best match
+// first. Checks the full name and every alias.
+#[tauri::command]
+pub async fn search_offenders_by_name(
+    app: AppHandle,
+    name: String,
+    min_score: Option<f64>,
+    limit: Option<usize>,
+) -> AppResult<Vec<NameMatch>> {
+    let min_score = min_score.unwrap_or(offenders::DEFAULT_NAME_MATCH_SCORE);
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(AppError::field(
+            "min_score",
+            "Minimum score must be between 0 and 1",
+        ));
+    }
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| offenders::search_by_name(conn, &name, min_score, limit))
+        .await
+}
+
+// Likely duplicate offenders, best first. With `offender_id`, only the
+// records that may be the same person as that offender.
+#[tauri::command]
+pub async fn find_duplicate_offenders(
+    app: AppHandle,
+    offender_id: Option<i64>,
+    min_score: Option<f64>,
+) -> AppResult<Vec<DuplicateCandidate>> {
+    let min_score = min_score.unwrap_or(duplicates::DEFAULT_DUPLICATE_SCORE);
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(AppError::field(
+            "min_score",
+            "Minimum score must be between 0 and 1",
+        ));
+    }
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| duplicates::find(conn, offender_id, min_score))
+        .await
+}
+
+// Merge a duplicate into the record that is kept (admins only). History
+// moves to the survivor and the duplicate is deleted, on Firestore too.
+#[tauri::command]
+pub async fn merge_offenders(
+    app: AppHandle,
+    survivor_id: i64,
+    merged_id: i64,
+    options: Option<MergeOptions>,
+) -> AppResult<MergeOutcome> {
+    let state = app.state::<crate::AppState>();
+    require_admin(&state)?;
+    let merged_by = state.user_email.read()?.clone();
+    let options = options.unwrap_or_default();
+    state
+        .db
+        .write(move |conn| {
+            duplicates::merge(conn, survivor_id, merged_id, &options, merged_by.as_deref())
         })
-        .map_err(|e| e.to_string())?;
-    Ok(offender)
+        .await
+}
+
+// The merge audit trail, newest first; with `offender_id`, only merges that
+// offender took part in
+#[tauri::command]
+pub async fn list_offender_merges(
+    app: AppHandle,
+    offender_id: Option<i64>,
+) -> AppResult<Vec<MergeRecord>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| duplicates::list_merges(conn, offender_id))
+        .await
 }
 
 // Create offender (with photo upload)
@@ -121,36 +130,22 @@ pub async fn create_offender(
     penalty_notes: Option<String>,
     photo: Option<Vec<u8>>, // photo as bytes
     photo_filename: Option<String>,
-) -> Result<Offender, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut photo_path: Option<String> = None;
-    if let (Some(bytes), Some(filename)) = (photo, photo_filename) {
-        let dir = get_photo_dir(&app);
-        let mut path = dir.clone();
-        path.push(format!("{}_{}", chrono::Utc::now().timestamp(), filename));
-        fs::write(&path, &bytes).map_err(|e| e.to_string())?;
-        photo_path = Some(path.to_string_lossy().to_string());
-    }
-    conn.execute(
-        "INSERT INTO offenders (full_name, national_id, date_of_birth, gender, photo_path, notes, file_id, penalty, penalty_notes) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        params![full_name, national_id, date_of_birth, gender, photo_path, notes, file_id, penalty, penalty_notes],
-    )
-    .map_err(|e| e.to_string())?;
-    let id = conn.last_insert_rowid();
-    Ok(Offender {
-        offender_id: Some(id),
-        full_name,
+) -> AppResult<Offender> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let photos = photo_store(&app.state::<crate::AppState>())?;
+    let details = OffenderDetails {
+        full_name: Some(full_name),
         national_id,
         date_of_birth,
         gender,
-        photo_path,
         notes,
-        date_created: None,
         file_id,
         penalty,
         penalty_notes,
-    })
+    };
+    let photo = uploaded_photo(photo, photo_filename);
+    db.write(move |conn| offenders::create(conn, &photos, details, photo))
+        .await
 }
 
 // Update offender (with optional photo update)
@@ -168,120 +163,55 @@ pub async fn update_offender(
     penalty_notes: Option<String>,
     photo: Option<Vec<u8>>, // photo as bytes
     photo_filename: Option<String>,
-) -> Result<Offender, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut photo_path: Option<String> = None;
-    if let (Some(bytes), Some(filename)) = (photo, photo_filename) {
-        let dir = get_photo_dir(&app);
-        let mut path = dir.clone();
-        path.push(format!("{}_{}", chrono::Utc::now().timestamp(), filename));
-        std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
-        photo_path = Some(path.to_string_lossy().to_string());
-        // Remove old photo
-        let mut stmt = conn
-            .prepare("SELECT photo_path FROM offenders WHERE offender_id = ?1")
-            .map_err(|e| e.to_string())?;
-        let old_path: Option<String> = stmt.query_row(params![offender_id], |row| row.get(0)).ok();
-        if let Some(old) = old_path {
-            let _ = std::fs::remove_file(old);
-        }
-    }
-    conn.execute(
-        "UPDATE offenders SET full_name = COALESCE(?2, full_name), national_id = COALESCE(?3, national_id), date_of_birth = COALESCE(?4, date_of_birth), gender = COALESCE(?5, gender), notes = COALESCE(?6, notes), photo_path = COALESCE(?7, photo_path), file_id = COALESCE(?8, file_id), penalty = COALESCE(?9, penalty), penalty_notes = COALESCE(?10, penalty_notes) WHERE offender_id = ?1",
-        params![offender_id, full_name, national_id, date_of_birth, gender, notes, photo_path, file_id, penalty, penalty_notes],
-    ).map_err(|e| e.to_string())?;
-    drop(conn); // Explicitly drop the lock before await
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT offender_id, full_name, national_id, date_of_birth, gender, photo_path, notes, date_created, file_id, penalty, penalty_notes FROM offenders WHERE offender_id = ?1"
-    ).map_err(|e| e.to_string())?;
-    let offender = stmt
-        .query_row(rusqlite::params![offender_id], |row| {
-            Ok(Offender {
-                offender_id: row.get(0).ok(),
-                full_name: row.get(1).unwrap_or_default(),
-                national_id: row.get(2).ok(),
-                date_of_birth: row.get(3).ok(),
-                gender: row.get(4).ok(),
-                photo_path: row.get(5).ok(),
-                notes: row.get(6).ok(),
-                date_created: row.get(7).ok(),
-                file_id: row.get(8).ok(),
-                penalty: row.get(9).ok(),
-                penalty_notes: row.get(10).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    Ok(offender)
+) -> AppResult<Offender> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let photos = photo_store(&app.state::<crate::AppState>())?;
+    let changes = OffenderDetails {
+        full_name,
+        national_id,
+        date_of_birth,
+        gender,
+        notes,
+        file_id,
+        penalty,
+        penalty_notes,
+    };
+    let photo = uploaded_photo(photo, photo_filename);
+    db.write(move |conn| offenders::update(conn, &photos, offender_id, changes, photo))
+        .await
 }
 
 // Delete offender
 #[tauri::command]
-pub async fn delete_offender(app: AppHandle, offender_id: i64) -> Result<(), String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    // Remove photo file if exists
-    let mut stmt = conn
-        .prepare("SELECT photo_path FROM offenders WHERE offender_id = ?1")
-        .map_err(|e| e.to_string())?;
-    let photo_path: Option<String> = stmt.query_row(params![offender_id], |row| row.get(0)).ok();
-    if let Some(path) = photo_path {
-        let _ = std::fs::remove_file(path);
-    }
-    conn.execute(
-        "DELETE FROM offenders WHERE offender_id = ?1",
-        params![offender_id],
-    )
-    .map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn delete_offender(app: AppHandle, offender_id: i64) -> AppResult<()> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.write(move |conn| offenders::delete(conn, offender_id))
+        .await
 }
 
 // Download offender photo
 #[tauri::command]
-pub async fn get_offender_photo(app: AppHandle, offender_id: i64) -> Result<Vec<u8>, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn
-        .prepare("SELECT photo_path FROM offenders WHERE offender_id = ?1")
-        .map_err(|e| e.to_string())?;
-    let photo_path: Option<String> = stmt.query_row(params![offender_id], |row| row.get(0)).ok();
-    if let Some(path) = photo_path {
-        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
-        Ok(bytes)
-    } else {
-        Err("No photo found".into())
-    }
+pub async fn get_offender_photo(app: AppHandle, offender_id: i64) -> AppResult<Vec<u8>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let photos = photo_store(&app.state::<crate::AppState>())?;
+    db.read(move |conn| offenders::photo(conn, &photos, offender_id))
+        .await
 }
 
-// Fetch all offender history records
+// Download a small offender photo for list views
 #[tauri::command]
-pub async fn fetch_all_histories(app: AppHandle) -> Result<Vec<OffenderHistory>, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT id, offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes, created_at FROM offender_history ORDER BY offense_date DESC, created_at DESC",
-    ).map_err(|e| e.to_string())?;
-
-    let histories = stmt
-        .query_map([], |row| {
-            Ok(OffenderHistory {
-                id: row.get(0).ok(),
-                offender_id: row.get(1).unwrap_or_default(),
-                file_id: row.get(2).ok(),
-                case_id: row.get(3).ok(),
-                offense_date: row.get(4).ok(),
-                penalty: row.get(5).ok(),
-                penalty_notes: row.get(6).ok(),
-                notes: row.get(7).ok(),
-                created_at: row.get(8).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+pub async fn get_offender_photo_thumbnail(app: AppHandle, offender_id: i64) -> AppResult<Vec<u8>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let photos = photo_store(&app.state::<crate::AppState>())?;
+    db.read(move |conn| offenders::photo_thumbnail(conn, &photos, offender_id))
+        .await
+}
 
-    Ok(histories)
+// Fetch all offender history records
+#[tauri::command]
+pub async fn fetch_all_histories(app: AppHandle) -> AppResult<Vec<OffenderHistory>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(offenders::list_all_history).await
 }
 
 // List offender history
@@ -289,30 +219,10 @@ pub async fn fetch_all_histories(app: AppHandle) -> Result<Vec<OffenderHistory>,
 pub async fn list_offender_history(
     app: AppHandle,
     offender_id: i64,
-) -> Result<Vec<OffenderHistory>, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT id, offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes, created_at FROM offender_history WHERE offender_id = ?1 ORDER BY offense_date DESC, created_at DESC"
-    ).map_err(|e| e.to_string())?;
-    let history = stmt
-        .query_map(params![offender_id], |row| {
-            Ok(OffenderHistory {
-                id: row.get(0).ok(),
-                offender_id: row.get(1).unwrap_or_default(),
-                file_id: row.get(2).ok(),
-                case_id: row.get(3).ok(),
-                offense_date: row.get(4).ok(),
-                penalty: row.get(5).ok(),
-                penalty_notes: row.get(6).ok(),
-                notes: row.get(7).ok(),
-                created_at: row.get(8).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-    Ok(history)
+) -> AppResult<Vec<OffenderHistory>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| offenders::list_history(conn, offender_id))
+        .await
 }
 
 // Add offender history record
@@ -326,25 +236,21 @@ pub async fn add_offender_history(
     penalty: Option<String>,
     penalty_notes: Option<String>,
     notes: Option<String>,
-) -> Result<OffenderHistory, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    conn.execute(
-        "INSERT INTO offender_history (offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes) VALUES (?, ?, ?, ?, ?, ?, ?)",
-        params![offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes],
-    ).map_err(|e| e.to_string())?;
-    let id = conn.last_insert_rowid();
-    Ok(OffenderHistory {
-        id: Some(id),
-        offender_id,
+    catalogue_id: Option<i64>,
+) -> AppResult<OffenderHistory> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let entry = HistoryDetails {
+        offender_id: Some(offender_id),
         file_id,
         case_id,
         offense_date,
         penalty,
         penalty_notes,
         notes,
-        created_at: None,
-    })
+        catalogue_id,
+    };
+    db.write(move |conn| offenders::add_history(conn, entry))
+        .await
 }
 
 // Update offender history record
@@ -359,40 +265,83 @@ pub async fn update_offender_history(
     penalty: Option<String>,
     penalty_notes: Option<String>,
     notes: Option<String>,
-) -> Result<OffenderHistory, String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    conn.execute(
-        "UPDATE offender_history SET offender_id = COALESCE(?2, offender_id), file_id = COALESCE(?3, file_id), case_id = COALESCE(?4, case_id), offense_date = COALESCE(?5, offense_date), penalty = COALESCE(?6, penalty), penalty_notes = COALESCE(?7, penalty_notes), notes = COALESCE(?8, notes) WHERE id = ?1",
-        params![id, offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes],
-    ).map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare(
-        "SELECT id, offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes, created_at FROM offender_history WHERE id = ?1"
-    ).map_err(|e| e.to_string())?;
-    let history = stmt
-        .query_row(rusqlite::params![id], |row| {
-            Ok(OffenderHistory {
-                id: row.get(0).ok(),
-                offender_id: row.get(1).unwrap_or_default(),
-                file_id: row.get(2).ok(),
-                case_id: row.get(3).ok(),
-                offense_date: row.get(4).ok(),
-                penalty: row.get(5).ok(),
-                penalty_notes: row.get(6).ok(),
-                notes: row.get(7).ok(),
-                created_at: row.get(8).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    Ok(history)
+    catalogue_id: Option<i64>,
+) -> AppResult<OffenderHistory> {
+    let db = app.state::<crate::AppState>().db.clone();
+    let changes = HistoryDetails {
+        offender_id,
+        file_id,
+        case_id,
+        offense_date,
+        penalty,
+        penalty_notes,
+        notes,
+        catalogue_id,
+    };
+    db.write(move |conn| offenders::update_history(conn, id, changes))
+        .await
 }
 
 // Delete offender history record
 #[tauri::command]
-pub async fn delete_offender_history(app: AppHandle, id: i64) -> Result<(), String> {
-    let db = app.state::<crate::AppState>();
-    let conn = db.conn.lock().unwrap();
-    conn.execute("DELETE FROM offender_history WHERE id = ?1", params![id])
-        .map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn delete_offender_history(app: AppHandle, id: i64) -> AppResult<()> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.write(move |conn| offenders::delete_history(conn, id))
+        .await
+}
+
+// Link an offender to a case as accused, complainant or witness
+#[tauri::command]
+pub async fn link_offender_case(
+    app: AppHandle,
+    offender_id: i64,
+    case_id: i64,
+    role: CaseRole,
+    notes: Option<String>,
+) -> AppResult<OffenderCase> {
+    let state = app.state::<crate::AppState>();
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| {
+            offender_cases::link(
+                conn,
+                offender_id,
+                case_id,
+                role,
+                notes.as_deref(),
+                created_by.as_deref(),
+            )
+        })
+        .await
+}
+
+// Unlink an offender from a case: in one role, or in every role when `role`
+// is left out. Returns how many links were removed.
+#[tauri::command]
+pub async fn unlink_offender_case(
+    app: AppHandle,
+    offender_id: i64,
+    case_id: i64,
+    role: Option<CaseRole>,
+) -> AppResult<usize> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.write(move |conn| offender_cases::unlink(conn, offender_id, case_id, role))
+        .await
+}
+
+// The cases an offender is party to
+#[tauri::command]
+pub async fn list_offender_cases(app: AppHandle, offender_id: i64) -> AppResult<Vec<OffenderCase>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| offender_cases::list_for_offender(conn, offender_id))
+        .await
+}
+
+// The offenders party to a case
+#[tauri::command]
+pub async fn list_case_offenders(app: AppHandle, case_id: i64) -> AppResult<Vec<OffenderCase>> {
+    let db = app.state::<crate::AppState>().db.clone();
+    db.read(move |conn| offender_cases::list_for_case(conn, case_id))
+        .await
 }
@@ -0,0 +1,25 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::search::{self, SearchHit, SearchKind, SearchQuery};
+use crate::AppState;
+
+// Search file notes and case numbers, cases and offenders at once. Hits are
+// ranked best first; `kinds` narrows the search to some record types.
+#[tauri::command]
+pub async fn global_search(
+    state: State<'_, AppState>,
+    query: String,
+    kinds: Option<Vec<SearchKind>>,
+    limit: Option<usize>,
+) -> AppResult<Vec<SearchHit>> {
+    let request = SearchQuery {
+        query,
+        kinds: kinds.unwrap_or_default(),
+        limit,
+    };
+    state
+        .db
+        .read(move |conn| search::search(conn, &request))
+        .await
+}
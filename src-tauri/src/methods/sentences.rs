@@ -0,0 +1,62 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::sentences::{self, NewSentence, Sentence};
+use crate::AppState;
+
+// Record a sentence on an offender and/or case; the release and supervision
+// dates come back worked out
+#[tauri::command]
+pub async fn record_sentence(
+    state: State<'_, AppState>,
+    sentence: NewSentence,
+) -> AppResult<Sentence> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| sentences::record(conn, sentence, created_by.as_deref()))
+        .await
+}
+
+// Replace a sentence, e.g. after an appeal varies it
+#[tauri::command]
+pub async fn update_sentence(
+    state: State<'_, AppState>,
+    sentence_id: i64,
+    sentence: NewSentence,
+) -> AppResult<Sentence> {
+    state
+        .db
+        .write(move |conn| sentences::update(conn, sentence_id, sentence))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_sentence(state: State<'_, AppState>, sentence_id: i64) -> AppResult<()> {
+    state
+        .db
+        .write(move |conn| sentences::delete(conn, sentence_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_case_sentences(
+    state: State<'_, AppState>,
+    case_id: i64,
+) -> AppResult<Vec<Sentence>> {
+    state
+        .db
+        .read(move |conn| sentences::list_for_case(conn, case_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_offender_sentences(
+    state: State<'_, AppState>,
+    offender_id: i64,
+) -> AppResult<Vec<Sentence>> {
+    state
+        .db
+        .read(move |conn| sentences::list_for_offender(conn, offender_id))
+        .await
+}
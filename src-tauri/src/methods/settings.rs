@@ -0,0 +1,83 @@
+use serde_json::json;
+use tauri::State;
+
+use crate::config::settings::AppConfig;
+use crate::error::AppResult;
+use crate::methods::auth::require_admin;
+use crate::methods::offenders::photo_store;
+use crate::AppState;
+
+// Get the effective application configuration (admins only)
+#[tauri::command]
+pub fn get_app_config(state: State<AppState>) -> AppResult<serde_json::Value> {
+    require_admin(&state)?;
+
+    let app_dir = crate::get_app_dir();
+    let config = state.config.read()?.clone();
+    let (_, env_overrides) = AppConfig::load(&app_dir)?;
+
+    Ok(json!({
+        "config": config,
+        "env_overrides": env_overrides,
+        "config_dir": app_dir,
+    }))
+}
+
+// Validate, persist and apply a new configuration (admins only).
+// Sync interval, log level, backup settings and directories take effect
+// immediately; a changed sync endpoint, project or key needs an app restart.
+// Fields set by RECORDS_* variables are refused, and a new photo directory
+// takes the existing photos with it.
+#[tauri::command]
+pub async fn update_app_config(
+    state: State<'_, AppState>,
+    config: AppConfig,
+) -> AppResult<serde_json::Value> {
+    require_admin(&state)?;
+
+    let app_dir = crate::get_app_dir();
+    let previous_file = AppConfig::load_file(&app_dir)?;
+    let path = AppConfig::save_edit(&config, &app_dir)?;
+
+    // Re-layer env overrides on top of what was just saved
+    let (effective, env_overrides) = AppConfig::load(&app_dir)?;
+
+    let photos = photo_store(&state)?;
+    let new_photo_dir = effective.photo_dir(&app_dir);
+    let photos_moved = if new_photo_dir == photos.dir() {
+        0
+    } else {
+        match state
+            .db
+            .write(move |conn| photos.relocate(conn, &new_photo_dir))
+            .await
+        {
+            Ok(moved) => moved,
+            Err(e) => {
+                // Keep the photos where they are and the config pointing at them
+                previous_file.save(&app_dir)?;
+                return Err(e);
+            }
+        }
+    };
+
+    let restart_required = {
+        let mut current = state.config.write()?;
+        let restart_required = current.sync.endpoint != effective.sync.endpoint
+            || current.sync.project_id != effective.sync.project_id
+            || current.sync.api_key != effective.sync.api_key;
+        *current = effective.clone();
+        restart_required
+    };
+    log::set_max_level(effective.log_level());
+
+    Ok(json!({
+        "message": "Configuration saved",
+        "status": "success",
+        "path": path,
+        "config": effective,
+        "env_overrides": env_overrides,
+        "photos_moved": photos_moved,
+        "restart_required": restart_required,
+    }))
+}
@@ -0,0 +1,128 @@
+use serde_json::json;
+use tauri::State;
+
+use crate::error::{AppError, AppResult};
+use crate::security::totp;
+use crate::services::two_factor::{self, role_requires_two_factor};
+use crate::AppState;
+
+/// The signed-in user's email, if their role may enrol in two-factor
+fn current_enrollable_user(state: &AppState) -> AppResult<String> {
+    let email = state.user_email.read()?.clone().ok_or_else(|| {
+        AppError::Unauthenticated("Not authenticated. Please log in first.".to_string())
+    })?;
+    let role = state.user_role.read()?.clone().unwrap_or_default();
+
+    if !role_requires_two_factor(&role) {
+        return Err(AppError::Forbidden(
+            "Two-factor authentication is only available for administrator accounts.".to_string(),
+        ));
+    }
+    Ok(email)
+}
+
+// Start enrolment: generate a secret and the otpauth URI for the QR code.
+// Nothing is enforced until the user confirms a first code.
+#[tauri::command]
+pub async fn begin_totp_enrollment(state: State<'_, AppState>) -> AppResult<serde_json::Value> {
+    let email = current_enrollable_user(&state)?;
+    state
+        .db
+        .write(move |conn| {
+            let secret = two_factor::begin_enrollment(conn, &email)?;
+
+            Ok(json!({
+                "secret": secret,
+                "otpauth_uri": totp::otpauth_uri(&secret, &email),
+                "issuer": totp::ISSUER,
+                "digits": totp::DIGITS,
+                "period": totp::PERIOD_SECS,
+            }))
+        })
+        .await
+}
+
+// Finish enrolment with a code from the authenticator app.
+// Returns the recovery codes; they are only ever shown this once.
+#[tauri::command]
+pub async fn confirm_totp_enrollment(
+    state: State<'_, AppState>,
+    code: String,
+) -> AppResult<serde_json::Value> {
+    let email = current_enrollable_user(&state)?;
+    state
+        .db
+        .write(move |conn| {
+            let recovery_codes = two_factor::confirm_enrollment(conn, &email, &code)?;
+
+            Ok(json!({
+                "message": "Two-factor authentication enabled",
+                "status": "success",
+                "recovery_codes": recovery_codes,
+            }))
+        })
+        .await
+}
+
+// Replace all recovery codes (requires a current code)
+#[tauri::command]
+pub async fn regenerate_recovery_codes(
+    state: State<'_, AppState>,
+    code: String,
+) -> AppResult<serde_json::Value> {
+    let email = current_enrollable_user(&state)?;
+    state
+        .db
+        .write(move |conn| {
+            if !two_factor::verify_second_factor(conn, &email, &code)? {
+                return Err(AppError::field("code", "Invalid two-factor code."));
+            }
+            let recovery_codes = two_factor::replace_recovery_codes(conn, &email)?;
+
+            Ok(json!({
+                "message": "Recovery codes regenerated",
+                "status": "success",
+                "recovery_codes": recovery_codes,
+            }))
+        })
+        .await
+}
+
+// Turn two-factor off (requires a current code or recovery code)
+#[tauri::command]
+pub async fn disable_totp(state: State<'_, AppState>, code: String) -> AppResult<String> {
+    let email = current_enrollable_user(&state)?;
+    state
+        .db
+        .write(move |conn| {
+            if !two_factor::verify_second_factor(conn, &email, &code)? {
+                return Err(AppError::field("code", "Invalid two-factor code."));
+            }
+            two_factor::disable(conn, &email)?;
+
+            Ok("Two-factor authentication disabled".to_string())
+        })
+        .await
+}
+
+// Two-factor status for the signed-in user
+#[tauri::command]
+pub async fn get_totp_status(state: State<'_, AppState>) -> AppResult<serde_json::Value> {
+    let email = state.user_email.read()?.clone().ok_or_else(|| {
+        AppError::Unauthenticated("Not authenticated. Please log in first.".to_string())
+    })?;
+    let role = state.user_role.read()?.clone().unwrap_or_default();
+    state
+        .db
+        .read(move |conn| {
+            let enrolled = two_factor::is_enrolled(conn, &email)?;
+            let recovery_codes_remaining = two_factor::recovery_codes_remaining(conn, &email)?;
+
+            Ok(json!({
+                "available": role_requires_two_factor(&role),
+                "enrolled": enrolled,
+                "recovery_codes_remaining": recovery_codes_remaining,
+            }))
+        })
+        .await
+}
@@ -0,0 +1,65 @@
+use tauri::State;
+
+use crate::error::AppResult;
+use crate::services::warrants::{
+    self, Execution, NewWarrant, OutstandingWarrant, Warrant, WarrantFilter,
+};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn issue_warrant(state: State<'_, AppState>, warrant: NewWarrant) -> AppResult<Warrant> {
+    let created_by = state.user_email.read()?.clone();
+    state
+        .db
+        .write(move |conn| warrants::issue(conn, warrant, created_by.as_deref()))
+        .await
+}
+
+// Record the arrest; marks the offender arrested and adds it to their history
+#[tauri::command]
+pub async fn execute_warrant(
+    state: State<'_, AppState>,
+    warrant_id: i64,
+    execution: Execution,
+) -> AppResult<Warrant> {
+    state
+        .db
+        .write(move |conn| warrants::execute(conn, warrant_id, execution))
+        .await
+}
+
+#[tauri::command]
+pub async fn lift_warrant(
+    state: State<'_, AppState>,
+    warrant_id: i64,
+    lifted_on: Option<String>,
+    reason: String,
+) -> AppResult<Warrant> {
+    state
+        .db
+        .write(move |conn| warrants::lift(conn, warrant_id, lifted_on.as_deref(), &reason))
+        .await
+}
+
+#[tauri::command]
+pub async fn list_warrants(
+    state: State<'_, AppState>,
+    filter: Option<WarrantFilter>,
+) -> AppResult<Vec<Warrant>> {
+    state
+        .db
+        .read(move |conn| warrants::list(conn, &filter.unwrap_or_default()))
+        .await
+}
+
+// Warrants still to be executed, oldest first, for the station follow-up list
+#[tauri::command]
+pub async fn list_outstanding_warrants(
+    state: State<'_, AppState>,
+    executing_station: Option<String>,
+) -> AppResult<Vec<OutstandingWarrant>> {
+    state
+        .db
+        .read(move |conn| warrants::outstanding(conn, executing_station.as_deref()))
+        .await
+}
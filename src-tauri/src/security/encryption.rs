@@ -0,0 +1,396 @@
+use std::fs;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Encryption at rest
+//
+// A 256-bit master key is derived with PBKDF2-HMAC-SHA256 from either an
+// administrator passphrase or a random machine secret, plus a random salt
+// kept in `encryption.json` next to the database. Separate subkeys are
+// derived from it for SQLCipher and for the photo store, so neither key ever
+// unlocks the other. The machine secret is sealed with DPAPI before it is
+// written to the key file, so only the Windows account that enabled
+// encryption can unseal it, on this PC. Otherwise the key file holds the
+// salt and a verifier that tells a wrong passphrase apart from a damaged
+// database.
+// ============================================================================
+
+pub const KEY_FILE: &str = "encryption.json";
+const KDF_ITERATIONS: u32 = 310_000;
+const SALT_BYTES: usize = 16;
+const MACHINE_SECRET_BYTES: usize = 32;
+pub const MIN_PASSPHRASE_CHARS: usize = 12;
+
+/// Prefix of every encrypted photo file, followed by the 12-byte nonce
+const PHOTO_MAGIC: &[u8] = b"RTPHOTO1";
+const NONCE_BYTES: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    /// Entered by an administrator each time the app starts
+    Passphrase,
+    /// A random secret sealed to this PC and Windows account; the app
+    /// unlocks itself on start
+    Machine,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyFile {
+    pub key_source: KeySource,
+    salt: String,
+    iterations: u32,
+    verifier: String,
+    /// Machine keys only: the secret, sealed with DPAPI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_secret: Option<String>,
+    /// False until the existing plaintext database has been converted
+    pub database_encrypted: bool,
+}
+
+/// Subkeys for the database and the photo store. Wiped on drop.
+#[derive(Clone)]
+pub struct Keys {
+    database: [u8; 32],
+    photos: [u8; 32],
+}
+
+impl Drop for Keys {
+    fn drop(&mut self) {
+        self.database.zeroize();
+        self.photos.zeroize();
+    }
+}
+
+impl std::fmt::Debug for Keys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Keys(..)")
+    }
+}
+
+fn subkey(master: &[u8; 32], purpose: &str) -> [u8; 32] {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(master).expect("HMAC accepts any key length");
+    mac.update(purpose.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+impl Keys {
+    fn derive(secret: &[u8], salt: &[u8], iterations: u32) -> (Keys, String) {
+        let mut master = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(secret, salt, iterations, &mut master);
+        let keys = Keys {
+            database: subkey(&master, "records-and-tracking/database"),
+            photos: subkey(&master, "records-and-tracking/photos"),
+        };
+        let verifier = HEXLOWER.encode(&subkey(&master, "records-and-tracking/verify"));
+        master.zeroize();
+        (keys, verifier)
+    }
+
+    /// Raw key in the form SQLCipher expects for `PRAGMA key`
+    pub fn database_key(&self) -> String {
+        format!("x'{}'", HEXLOWER.encode(&self.database))
+    }
+
+    fn photo_cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new_from_slice(&self.photos).expect("32-byte key")
+    }
+
+    pub fn encrypt_photo(&self, plaintext: &[u8]) -> AppResult<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_BYTES];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .photo_cipher()
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| AppError::internal("Failed to encrypt photo"))?;
+
+        let mut out = Vec::with_capacity(PHOTO_MAGIC.len() + NONCE_BYTES + ciphertext.len());
+        out.extend_from_slice(PHOTO_MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    pub fn decrypt_photo(&self, data: &[u8]) -> AppResult<Vec<u8>> {
+        let body = data
+            .strip_prefix(PHOTO_MAGIC)
+            .filter(|body| body.len() > NONCE_BYTES)
+            .ok_or_else(|| AppError::validation("Photo is not in the encrypted format."))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_BYTES);
+        self.photo_cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                AppError::Forbidden("Photo cannot be decrypted with the current key.".to_string())
+            })
+    }
+}
+
+pub fn is_encrypted_photo(data: &[u8]) -> bool {
+    data.starts_with(PHOTO_MAGIC)
+}
+
+impl KeyFile {
+    /// The key file in `app_dir`, or `None` when encryption is off
+    pub fn load(app_dir: &Path) -> AppResult<Option<KeyFile>> {
+        let path = app_dir.join(KEY_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text =
+            fs::read_to_string(&path).context(&format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, app_dir: &Path) -> AppResult<()> {
+        let path = app_dir.join(KEY_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .context(&format!("Failed to write {}", path.display()))
+    }
+
+    /// New key material for a passphrase
+    pub fn create(key_source: KeySource, secret: &[u8]) -> (KeyFile, Keys) {
+        let mut salt = [0u8; SALT_BYTES];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let (keys, verifier) = Keys::derive(secret, &salt, KDF_ITERATIONS);
+        let key_file = KeyFile {
+            key_source,
+            salt: HEXLOWER.encode(&salt),
+            iterations: KDF_ITERATIONS,
+            verifier,
+            sealed_secret: None,
+            database_encrypted: false,
+        };
+        (key_file, keys)
+    }
+
+    /// New key material for a machine key: a random secret, sealed to this
+    /// PC and Windows account
+    pub fn create_machine() -> AppResult<(KeyFile, Keys)> {
+        let mut secret = [0u8; MACHINE_SECRET_BYTES];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let sealed = seal(&secret);
+        let (mut key_file, keys) = KeyFile::create(KeySource::Machine, &secret);
+        secret.zeroize();
+        key_file.sealed_secret = Some(HEXLOWER.encode(&sealed?));
+        Ok((key_file, keys))
+    }
+
+    /// Unseal the machine secret and derive the keys from it
+    pub fn unlock_machine(&self) -> AppResult<Keys> {
+        let sealed = self
+            .sealed_secret
+            .as_deref()
+            .filter(|_| self.key_source == KeySource::Machine)
+            .ok_or_else(|| AppError::Config(format!("{} holds no machine key.", KEY_FILE)))?;
+        let sealed = HEXLOWER
+            .decode(sealed.as_bytes())
+            .map_err(|e| AppError::Config(format!("Invalid machine key in {}: {}", KEY_FILE, e)))?;
+        let mut secret = unseal(&sealed)?;
+        let keys = self.unlock(&secret);
+        secret.zeroize();
+        keys
+    }
+
+    /// Derive the keys for `secret`, failing if it is not the right one
+    pub fn unlock(&self, secret: &[u8]) -> AppResult<Keys> {
+        let salt = HEXLOWER
+            .decode(self.salt.as_bytes())
+            .map_err(|e| AppError::Config(format!("Invalid salt in {}: {}", KEY_FILE, e)))?;
+        let (keys, verifier) = Keys::derive(secret, &salt, self.iterations);
+        if verifier != self.verifier {
+            return Err(match self.key_source {
+                KeySource::Passphrase => {
+                    AppError::Unauthenticated("Incorrect encryption passphrase.".to_string())
+                }
+                KeySource::Machine => AppError::Config(
+                    "The machine key does not match. The data may have been copied from another PC."
+                        .to_string(),
+                ),
+            });
+        }
+        Ok(keys)
+    }
+}
+
+/// Seal `secret` with DPAPI for the current Windows account
+#[cfg(windows)]
+fn seal(secret: &[u8]) -> AppResult<Vec<u8>> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Security::Cryptography::{
+        CryptProtectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: secret.len() as u32,
+        pbData: secret.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptProtectData(
+            &input,
+            PCWSTR::null(),
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    }
+    .map_err(|e| AppError::Config(format!("Failed to seal the machine key: {}", e)))?;
+    Ok(take_blob(output))
+}
+
+/// Unseal a secret sealed by `seal`
+#[cfg(windows)]
+fn unseal(sealed: &[u8]) -> AppResult<Vec<u8>> {
+    use windows::Win32::Security::Cryptography::{
+        CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: sealed.len() as u32,
+        pbData: sealed.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptUnprotectData(
+            &input,
+            None,
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    }
+    .map_err(|e| {
+        AppError::Config(format!(
+            "The machine key cannot be unsealed by this Windows account on this PC: {}",
+            e
+        ))
+    })?;
+    Ok(take_blob(output))
+}
+
+/// Copy out a blob DPAPI allocated, then wipe and free it
+#[cfg(windows)]
+fn take_blob(blob: windows::Win32::Security::Cryptography::CRYPT_INTEGER_BLOB) -> Vec<u8> {
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+
+    unsafe {
+        let data = std::slice::from_raw_parts_mut(blob.pbData, blob.cbData as usize);
+        let copy = data.to_vec();
+        data.zeroize();
+        let _ = LocalFree(Some(HLOCAL(blob.pbData.cast())));
+        copy
+    }
+}
+
+#[cfg(not(windows))]
+fn seal(_secret: &[u8]) -> AppResult<Vec<u8>> {
+    Err(machine_key_unsupported())
+}
+
+#[cfg(not(windows))]
+fn unseal(_sealed: &[u8]) -> AppResult<Vec<u8>> {
+    Err(machine_key_unsupported())
+}
+
+#[cfg(not(windows))]
+fn machine_key_unsupported() -> AppError {
+    AppError::Config(
+        "A machine key needs Windows to seal it. Use a passphrase on this system.".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A passphrase key file with few KDF rounds; the real count takes
+    /// seconds per derivation in a debug build
+    fn key_file(passphrase: &[u8], salt: u8) -> (KeyFile, Keys) {
+        let salt = [salt; SALT_BYTES];
+        let (keys, verifier) = Keys::derive(passphrase, &salt, 1_000);
+        let key_file = KeyFile {
+            key_source: KeySource::Passphrase,
+            salt: HEXLOWER.encode(&salt),
+            iterations: 1_000,
+            verifier,
+            sealed_secret: None,
+            database_encrypted: false,
+        };
+        (key_file, keys)
+    }
+
+    #[test]
+    fn passphrase_unlocks_keys_saved_in_the_key_file() {
+        let dir = std::env::temp_dir().join(format!("rat-keys-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let (key_file, keys) = key_file(b"correct horse battery", 1);
+        key_file.save(&dir).unwrap();
+
+        let loaded = KeyFile::load(&dir).unwrap().unwrap();
+        let unlocked = loaded.unlock(b"correct horse battery").unwrap();
+        assert_eq!(unlocked.database_key(), keys.database_key());
+        assert!(matches!(
+            loaded.unlock(b"wrong horse battery"),
+            Err(AppError::Unauthenticated(_))
+        ));
+        // The photo subkey is not the database key
+        assert_ne!(keys.photos, keys.database);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn photos_decrypt_only_with_their_key_and_unaltered() {
+        let (_, keys) = key_file(b"correct horse battery", 1);
+        // Same passphrase, different salt
+        let (_, other) = key_file(b"correct horse battery", 2);
+
+        let sealed = keys.encrypt_photo(b"jpeg bytes").unwrap();
+        assert!(is_encrypted_photo(&sealed));
+        assert!(!is_encrypted_photo(b"jpeg bytes"));
+        assert_eq!(keys.decrypt_photo(&sealed).unwrap(), b"jpeg bytes");
+        // A fresh nonce each time
+        assert_ne!(keys.encrypt_photo(b"jpeg bytes").unwrap(), sealed);
+
+        assert!(matches!(
+            other.decrypt_photo(&sealed),
+            Err(AppError::Forbidden(_))
+        ));
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            keys.decrypt_photo(&tampered),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            keys.decrypt_photo(b"jpeg bytes"),
+            Err(AppError::Validation { .. })
+        ));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn machine_keys_need_windows() {
+        assert!(matches!(
+            KeyFile::create_machine(),
+            Err(AppError::Config(_))
+        ));
+    }
+}
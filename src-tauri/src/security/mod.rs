@@ -0,0 +1,2 @@
+pub mod encryption;
+pub mod totp;
@@ -0,0 +1,213 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+// ============================================================================
+// RFC 6238 time-based one-time passwords (HMAC-SHA1, 6 digits, 30s step)
+// — the defaults every authenticator app understands.
+// ============================================================================
+
+pub const ISSUER: &str = "Records and Tracking";
+pub const DIGITS: u32 = 6;
+pub const PERIOD_SECS: u64 = 30;
+/// Accept codes from one step either side to tolerate clock drift
+const SKEW_STEPS: u64 = 1;
+const SECRET_BYTES: usize = 20;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Generate a new random shared secret, base32-encoded for authenticator apps
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// Build the `otpauth://` URI that the frontend renders as a QR code
+pub fn otpauth_uri(secret: &str, account: &str) -> String {
+    let label = urlencoding::encode(&format!("{}:{}", ISSUER, account)).into_owned();
+    format!(
+        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        label,
+        secret,
+        urlencoding::encode(ISSUER),
+        DIGITS,
+        PERIOD_SECS
+    )
+}
+
+pub fn time_step(unix_secs: u64) -> u64 {
+    unix_secs / PERIOD_SECS
+}
+
+/// Compute the code for a given time step (RFC 4226 dynamic truncation)
+pub fn code_at_step(secret: &str, step: u64) -> Result<String, String> {
+    let key = BASE32_NOPAD
+        .decode(secret.trim_end_matches('=').to_uppercase().as_bytes())
+        .map_err(|e| format!("Invalid TOTP secret: {}", e))?;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).map_err(|e| e.to_string())?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = ((digest[offset] as u32 & 0x7f) << 24)
+        | ((digest[offset + 1] as u32) << 16)
+        | ((digest[offset + 2] as u32) << 8)
+        | (digest[offset + 3] as u32);
+
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// Check `code` against the steps around `unix_secs`. Steps at or before
+/// `last_used_step` are rejected so a code cannot be replayed.
+/// Returns the matched step on success.
+pub fn verify(
+    secret: &str,
+    code: &str,
+    unix_secs: u64,
+    last_used_step: Option<u64>,
+) -> Result<Option<u64>, String> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let current = time_step(unix_secs);
+    for step in current.saturating_sub(SKEW_STEPS)..=current + SKEW_STEPS {
+        if last_used_step.is_some_and(|last| step <= last) {
+            continue;
+        }
+        if constant_time_eq(code_at_step(secret, step)?.as_bytes(), code.as_bytes()) {
+            return Ok(Some(step));
+        }
+    }
+    Ok(None)
+}
+
+/// One-time recovery codes in `xxxxx-xxxxx` form
+pub fn generate_recovery_codes() -> Vec<String> {
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut code: String = (0..10)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect();
+            code.insert(5, '-');
+            code
+        })
+        .collect()
+}
+
+/// Recovery codes are stored hashed; normalise case and separators first
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    BASE64.encode(Sha256::digest(normalized.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The RFC 6238 SHA1 seed, "12345678901234567890", in base32
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_rfc_6238_vectors() {
+        // Appendix B, truncated from 8 to 6 digits
+        let vectors = [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ];
+        for (unix_secs, expected) in vectors {
+            assert_eq!(
+                code_at_step(RFC_SECRET, time_step(unix_secs)).unwrap(),
+                expected,
+                "T = {}",
+                unix_secs
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_lowercase_and_padded_secrets() {
+        let padded = format!("{}====", RFC_SECRET.to_lowercase());
+        assert_eq!(code_at_step(&padded, 1).unwrap(), "287082");
+    }
+
+    #[test]
+    fn accepts_one_step_of_drift_either_side() {
+        let now = 1111111111;
+        let step = time_step(now);
+        for drift in [step - 1, step, step + 1] {
+            let code = code_at_step(RFC_SECRET, drift).unwrap();
+            assert_eq!(verify(RFC_SECRET, &code, now, None).unwrap(), Some(drift));
+        }
+        for too_far in [step - 2, step + 2] {
+            let code = code_at_step(RFC_SECRET, too_far).unwrap();
+            assert_eq!(verify(RFC_SECRET, &code, now, None).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn refuses_replayed_codes() {
+        let now = 1234567890;
+        let step = time_step(now);
+
+        assert_eq!(verify(RFC_SECRET, "005924", now, None).unwrap(), Some(step));
+        assert_eq!(verify(RFC_SECRET, "005924", now, Some(step)).unwrap(), None);
+
+        // An older code is refused too once a later one has been used
+        let previous = code_at_step(RFC_SECRET, step - 1).unwrap();
+        assert_eq!(
+            verify(RFC_SECRET, &previous, now, Some(step)).unwrap(),
+            None
+        );
+
+        let next = code_at_step(RFC_SECRET, step + 1).unwrap();
+        assert_eq!(
+            verify(RFC_SECRET, &next, now, Some(step)).unwrap(),
+            Some(step + 1)
+        );
+    }
+
+    #[test]
+    fn ignores_spaces_and_rejects_malformed_codes() {
+        let now = 1234567890;
+        assert!(verify(RFC_SECRET, "005 924", now, None).unwrap().is_some());
+        assert_eq!(verify(RFC_SECRET, "05924", now, None).unwrap(), None);
+        assert_eq!(verify(RFC_SECRET, "00592a", now, None).unwrap(), None);
+    }
+
+    #[test]
+    fn recovery_codes_hash_the_same_however_typed() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+
+        let code = &codes[0];
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', "").to_uppercase())
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+#[derive(Serialize, Deserialize)]
+pub struct Attendance {
+    pub attendance_id: i32,
+    pub staff_id: i32,
+    pub date: String,   // Use ISO-8601 format (YYYY-MM-DD)
+    pub status: String, // "Present" or "Absent"
+    pub reason: Option<String>,
+    pub half_day: bool,
+    pub comments: Option<String>,
+}
+
+const ATTENDANCE_COLUMNS: &str =
+    "attendance_id, staff_id, date, status, reason, half_day, comments";
+
+impl Attendance {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Attendance {
+            attendance_id: row.get(0)?,
+            staff_id: row.get(1)?,
+            date: row.get(2)?,
+            status: row.get(3)?,
+            reason: row.get(4)?,
+            half_day: row.get(5)?,
+            comments: row.get(6)?,
+        })
+    }
+}
+
+fn validate_status(status: &str) -> AppResult<()> {
+    if status != "Present" && status != "Absent" {
+        return Err(AppError::field(
+            "status",
+            "Invalid status. Must be 'Present' or 'Absent'.",
+        ));
+    }
+    Ok(())
+}
+
+fn query(
+    conn: &Connection,
+    filter: &str,
+    args: impl rusqlite::Params,
+) -> AppResult<Vec<Attendance>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM attendance {}",
+            ATTENDANCE_COLUMNS, filter
+        ))
+        .context("Failed to prepare statement")?;
+
+    let attendance = stmt
+        .query_map(args, Attendance::from_row)
+        .context("Failed to fetch attendance records")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read attendance records")?;
+    Ok(attendance)
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<Attendance>> {
+    query(conn, "", [])
+}
+
+pub fn list_for_staff(conn: &Connection, staff_id: i32) -> AppResult<Vec<Attendance>> {
+    query(conn, "WHERE staff_id = ?1", params![staff_id])
+}
+
+pub fn get(conn: &Connection, attendance_id: i32) -> AppResult<Option<Attendance>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM attendance WHERE attendance_id = ?1",
+            ATTENDANCE_COLUMNS
+        ),
+        params![attendance_id],
+        Attendance::from_row,
+    )
+    .optional()
+    .context("Failed to fetch attendance record")
+}
+
+/// Record attendance for a staff member and return the record id
+pub fn add(
+    conn: &Connection,
+    staff_id: i32,
+    date: &str,
+    status: &str,
+    reason: Option<&str>,
+    half_day: bool,
+    comments: Option<&str>,
+) -> AppResult<i64> {
+    validate_status(status)?;
+    conn.execute(
+        "INSERT INTO attendance (staff_id, date, status, reason, half_day, comments) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![staff_id, date, status, reason, half_day, comments],
+    )
+    .context("Failed to add attendance record")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update(
+    conn: &Connection,
+    attendance_id: i32,
+    status: &str,
+    reason: Option<&str>,
+    half_day: bool,
+    comments: Option<&str>,
+) -> AppResult<()> {
+    validate_status(status)?;
+    conn.execute(
+        "UPDATE attendance SET status = ?1, reason = ?2, half_day = ?3, comments = ?4 WHERE attendance_id = ?5",
+        params![status, reason, half_day, comments, attendance_id],
+    )
+    .context("Failed to update attendance record")?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, attendance_id: i32) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM attendance WHERE attendance_id = ?1",
+        params![attendance_id],
+    )
+    .context("Failed to delete attendance record")?;
+    Ok(())
+}
@@ -0,0 +1,1151 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::{self, parse_date, Hearing};
+use crate::services::sentences::format_cents;
+use crate::services::{custody, notifications};
+
+// ============================================================================
+// Bail and bond register
+//
+// Each grant of bail to an accused in a case is a bond (migration 0010):
+// granted with its amount and conditions, posted once the terms are met and
+// the accused is out, and finally discharged, cancelled or forfeited.
+// Posting closes any open custody spell. Sureties and the documents they
+// deposit hang off the bond, and so does every hearing the accused missed
+// while out, each of which raises a warning notification. A missed hearing
+// is picked up from the attendance recorded on the hearing, or recorded by
+// hand.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BondType {
+    #[serde(rename = "Cash Bail")]
+    CashBail,
+    #[default]
+    Bond,
+    /// The accused's own undertaking, without sureties
+    #[serde(rename = "Personal Bond")]
+    PersonalBond,
+}
+
+impl BondType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BondType::CashBail => "Cash Bail",
+            BondType::Bond => "Bond",
+            BondType::PersonalBond => "Personal Bond",
+        }
+    }
+
+    pub fn parse(bond_type: &str) -> Option<BondType> {
+        match bond_type {
+            "Cash Bail" => Some(BondType::CashBail),
+            "Bond" => Some(BondType::Bond),
+            "Personal Bond" => Some(BondType::PersonalBond),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BondStatus {
+    /// Terms set, not yet met
+    Granted,
+    /// The accused is out on the bond
+    Posted,
+    Cancelled,
+    Forfeited,
+    /// Released at the end of the case
+    Discharged,
+}
+
+impl BondStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BondStatus::Granted => "Granted",
+            BondStatus::Posted => "Posted",
+            BondStatus::Cancelled => "Cancelled",
+            BondStatus::Forfeited => "Forfeited",
+            BondStatus::Discharged => "Discharged",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<BondStatus> {
+        match status {
+            "Granted" => Some(BondStatus::Granted),
+            "Posted" => Some(BondStatus::Posted),
+            "Cancelled" => Some(BondStatus::Cancelled),
+            "Forfeited" => Some(BondStatus::Forfeited),
+            "Discharged" => Some(BondStatus::Discharged),
+            _ => None,
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        matches!(self, BondStatus::Granted | BondStatus::Posted)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bond {
+    pub bond_id: i64,
+    /// None once the case record has been deleted
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    /// None once the offender record has been deleted
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub bond_type: BondType,
+    pub amount_cents: i64,
+    pub conditions: Option<String>,
+    pub granted_on: String,
+    pub granted_by: Option<String>,
+    pub status: BondStatus,
+    pub posted_on: Option<String>,
+    pub closed_on: Option<String>,
+    pub closure_reason: Option<String>,
+    pub forfeited_cents: Option<i64>,
+    /// Hearings missed while out on this bond
+    pub absences: i64,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const BOND_SELECT: &str = "SELECT b.bond_id, b.case_id,
+        COALESCE(c.case_number, b.case_number), b.offender_id,
+        COALESCE(o.full_name, b.offender_name), b.bond_type, b.amount_cents, b.conditions, b.granted_on, b.granted_by,
+        b.status, b.posted_on, b.closed_on, b.closure_reason, b.forfeited_cents,
+        (SELECT COUNT(*) FROM bond_absences a WHERE a.bond_id = b.bond_id), b.notes,
+        b.created_by, b.created_at
+    FROM bonds b
+    LEFT JOIN cases c ON c.case_id = b.case_id
+    LEFT JOIN offenders o ON o.offender_id = b.offender_id";
+
+impl Bond {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let bond_type: String = row.get(5)?;
+        let status: String = row.get(10)?;
+        Ok(Bond {
+            bond_id: row.get(0)?,
+            case_id: row.get(1)?,
+            case_number: row.get(2)?,
+            offender_id: row.get(3)?,
+            offender_name: row.get(4)?,
+            bond_type: BondType::parse(&bond_type).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    5,
+                    rusqlite::types::Type::Text,
+                    format!("unknown bond type {}", bond_type).into(),
+                )
+            })?,
+            amount_cents: row.get(6)?,
+            conditions: row.get(7)?,
+            granted_on: row.get(8)?,
+            granted_by: row.get(9)?,
+            status: BondStatus::parse(&status).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    10,
+                    rusqlite::types::Type::Text,
+                    format!("unknown bond status {}", status).into(),
+                )
+            })?,
+            posted_on: row.get(11)?,
+            closed_on: row.get(12)?,
+            closure_reason: row.get(13)?,
+            forfeited_cents: row.get(14)?,
+            absences: row.get(15)?,
+            notes: row.get(16)?,
+            created_by: row.get(17)?,
+            created_at: row.get(18)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Surety {
+    pub surety_id: i64,
+    pub bond_id: i64,
+    pub full_name: String,
+    pub id_number: Option<String>,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    pub relationship: Option<String>,
+    /// What the surety stands bound for, when it differs from the bond
+    pub amount_cents: Option<i64>,
+    pub notes: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const SURETY_SELECT: &str = "SELECT surety_id, bond_id, full_name, id_number, phone_number,
+        address, relationship, amount_cents, notes, created_at
+    FROM bond_sureties";
+
+impl Surety {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Surety {
+            surety_id: row.get(0)?,
+            bond_id: row.get(1)?,
+            full_name: row.get(2)?,
+            id_number: row.get(3)?,
+            phone_number: row.get(4)?,
+            address: row.get(5)?,
+            relationship: row.get(6)?,
+            amount_cents: row.get(7)?,
+            notes: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+}
+
+/// A document deposited with the court as security
+#[derive(Debug, Clone, Serialize)]
+pub struct BondDocument {
+    pub document_id: i64,
+    pub bond_id: i64,
+    pub surety_id: Option<i64>,
+    pub document_type: String,
+    pub reference: Option<String>,
+    pub description: Option<String>,
+    pub received_on: String,
+    pub returned_on: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const DOCUMENT_SELECT: &str = "SELECT document_id, bond_id, surety_id, document_type,
+        reference, description, received_on, returned_on, created_by, created_at
+    FROM bond_documents";
+
+impl BondDocument {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(BondDocument {
+            document_id: row.get(0)?,
+            bond_id: row.get(1)?,
+            surety_id: row.get(2)?,
+            document_type: row.get(3)?,
+            reference: row.get(4)?,
+            description: row.get(5)?,
+            received_on: row.get(6)?,
+            returned_on: row.get(7)?,
+            created_by: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+}
+
+/// A hearing the accused missed while out on the bond
+#[derive(Debug, Clone, Serialize)]
+pub struct Absence {
+    pub absence_id: i64,
+    pub bond_id: i64,
+    pub hearing_id: Option<i64>,
+    pub hearing_date: String,
+    pub notes: Option<String>,
+    /// The warning raised for it
+    pub notification_id: Option<i64>,
+    pub recorded_by: Option<String>,
+    pub recorded_at: Option<String>,
+}
+
+const ABSENCE_SELECT: &str = "SELECT absence_id, bond_id, hearing_id, hearing_date, notes,
+        notification_id, recorded_by, recorded_at
+    FROM bond_absences";
+
+impl Absence {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Absence {
+            absence_id: row.get(0)?,
+            bond_id: row.get(1)?,
+            hearing_id: row.get(2)?,
+            hearing_date: row.get(3)?,
+            notes: row.get(4)?,
+            notification_id: row.get(5)?,
+            recorded_by: row.get(6)?,
+            recorded_at: row.get(7)?,
+        })
+    }
+}
+
+/// A bond with everything that hangs off it
+#[derive(Debug, Clone, Serialize)]
+pub struct BondDetail {
+    pub bond: Bond,
+    pub sureties: Vec<Surety>,
+    pub documents: Vec<BondDocument>,
+    pub absences: Vec<Absence>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewBond {
+    pub case_id: i64,
+    pub offender_id: i64,
+    #[serde(default)]
+    pub bond_type: BondType,
+    pub amount_cents: i64,
+    pub conditions: Option<String>,
+    /// YYYY-MM-DD, today if not given
+    pub granted_on: Option<String>,
+    pub granted_by: Option<String>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub sureties: Vec<NewSurety>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSurety {
+    pub full_name: String,
+    pub id_number: Option<String>,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    pub relationship: Option<String>,
+    pub amount_cents: Option<i64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewBondDocument {
+    /// The surety who deposited it, if not the accused
+    pub surety_id: Option<i64>,
+    /// e.g. title deed, log book, national ID
+    pub document_type: String,
+    pub reference: Option<String>,
+    pub description: Option<String>,
+    /// YYYY-MM-DD, today if not given
+    pub received_on: Option<String>,
+}
+
+/// How a bond ends: discharged, cancelled or forfeited
+#[derive(Debug, Clone, Deserialize)]
+pub struct BondClosure {
+    pub status: BondStatus,
+    /// YYYY-MM-DD, today if not given
+    pub closed_on: Option<String>,
+    /// Needed to cancel or forfeit
+    pub reason: Option<String>,
+    /// How much is forfeited, the whole bond if not given
+    pub forfeited_cents: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BondFilter {
+    pub case_id: Option<i64>,
+    pub offender_id: Option<i64>,
+    pub status: Option<BondStatus>,
+    /// Only bonds granted or posted
+    #[serde(default)]
+    pub live_only: bool,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+pub fn get(conn: &Connection, bond_id: i64) -> AppResult<Bond> {
+    conn.query_row(
+        &format!("{} WHERE b.bond_id = ?1", BOND_SELECT),
+        params![bond_id],
+        Bond::from_row,
+    )
+    .optional()
+    .context("Failed to fetch bond")?
+    .ok_or_else(|| AppError::not_found("Bond not found"))
+}
+
+fn get_live(conn: &Connection, bond_id: i64) -> AppResult<Bond> {
+    let bond = get(conn, bond_id)?;
+    if !bond.status.is_live() {
+        return Err(AppError::Conflict(format!(
+            "The bond was {} on {}.",
+            bond.status.as_str().to_lowercase(),
+            bond.closed_on.as_deref().unwrap_or("an earlier date")
+        )));
+    }
+    Ok(bond)
+}
+
+pub fn detail(conn: &Connection, bond_id: i64) -> AppResult<BondDetail> {
+    let bond = get(conn, bond_id)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE bond_id = ?1 ORDER BY surety_id",
+            SURETY_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let sureties = stmt
+        .query_map(params![bond_id], Surety::from_row)
+        .context("Failed to fetch sureties")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read sureties")?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE bond_id = ?1 ORDER BY received_on, document_id",
+            DOCUMENT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let documents = stmt
+        .query_map(params![bond_id], BondDocument::from_row)
+        .context("Failed to fetch bond documents")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read bond documents")?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE bond_id = ?1 ORDER BY hearing_date, absence_id",
+            ABSENCE_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let absences = stmt
+        .query_map(params![bond_id], Absence::from_row)
+        .context("Failed to fetch missed hearings")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read missed hearings")?;
+
+    Ok(BondDetail {
+        bond,
+        sureties,
+        documents,
+        absences,
+    })
+}
+
+fn insert_surety(conn: &Connection, bond_id: i64, surety: NewSurety) -> AppResult<i64> {
+    let full_name = surety.full_name.trim();
+    if full_name.is_empty() {
+        return Err(AppError::field("full_name", "Enter the surety's name"));
+    }
+    if surety.amount_cents.is_some_and(|a| a < 0) {
+        return Err(AppError::field(
+            "amount_cents",
+            "The amount cannot be negative",
+        ));
+    }
+    conn.execute(
+        "INSERT INTO bond_sureties (bond_id, full_name, id_number, phone_number, address,
+             relationship, amount_cents, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            bond_id,
+            full_name,
+            optional_text(surety.id_number),
+            optional_text(surety.phone_number),
+            optional_text(surety.address),
+            optional_text(surety.relationship),
+            surety.amount_cents,
+            optional_text(surety.notes)
+        ],
+    )
+    .context("Failed to add surety")?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Record a grant of bail, with any sureties already approved
+pub fn grant(conn: &mut Connection, bond: NewBond, created_by: Option<&str>) -> AppResult<Bond> {
+    if bond.amount_cents < 0 {
+        return Err(AppError::field(
+            "amount_cents",
+            "The amount cannot be negative",
+        ));
+    }
+    if bond.amount_cents == 0 && bond.bond_type != BondType::PersonalBond {
+        return Err(AppError::field("amount_cents", "Enter the amount"));
+    }
+    if bond.bond_type == BondType::PersonalBond && !bond.sureties.is_empty() {
+        return Err(AppError::field(
+            "sureties",
+            "A personal bond has no sureties",
+        ));
+    }
+    let granted_on = optional_date("granted_on", bond.granted_on.as_deref())?.unwrap_or_else(today);
+
+    let tx = conn.transaction()?;
+    let case: Option<bool> = tx
+        .query_row(
+            "SELECT COALESCE(is_deleted, 0) = 0 FROM cases WHERE case_id = ?1",
+            params![bond.case_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch case")?;
+    if case != Some(true) {
+        return Err(AppError::not_found("Case not found"));
+    }
+    let offender: Option<i64> = tx
+        .query_row(
+            "SELECT offender_id FROM offenders WHERE offender_id = ?1",
+            params![bond.offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch offender")?;
+    if offender.is_none() {
+        return Err(AppError::not_found("Offender not found"));
+    }
+    let live: Option<String> = tx
+        .query_row(
+            "SELECT status FROM bonds
+             WHERE case_id = ?1 AND offender_id = ?2 AND status IN ('Granted', 'Posted')",
+            params![bond.case_id, bond.offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check bonds")?;
+    if let Some(live) = live {
+        return Err(AppError::Conflict(format!(
+            "This person already has a {} bond in the case; cancel it first.",
+            live.to_lowercase()
+        )));
+    }
+
+    tx.execute(
+        "INSERT INTO bonds (case_id, case_number, offender_id, offender_name, bond_type,
+             amount_cents, conditions, granted_on, granted_by, notes, created_by)
+         VALUES (?1, (SELECT case_number FROM cases WHERE case_id = ?1), ?2,
+             (SELECT full_name FROM offenders WHERE offender_id = ?2), ?3, ?4, ?5, ?6, ?7, ?8,
+             ?9)",
+        params![
+            bond.case_id,
+            bond.offender_id,
+            bond.bond_type.as_str(),
+            bond.amount_cents,
+            optional_text(bond.conditions),
+            granted_on,
+            optional_text(bond.granted_by),
+            optional_text(bond.notes),
+            created_by
+        ],
+    )
+    .context("Failed to record bond")?;
+    let id = tx.last_insert_rowid();
+    for surety in bond.sureties {
+        insert_surety(&tx, id, surety)?;
+    }
+    let granted = get(&tx, id)?;
+    tx.commit()?;
+    Ok(granted)
+}
+
+/// The terms are met and the accused is out. Any open custody spell is
+/// closed on the same date.
+pub fn post(conn: &mut Connection, bond_id: i64, posted_on: Option<&str>) -> AppResult<Bond> {
+    let posted_on = optional_date("posted_on", posted_on)?.unwrap_or_else(today);
+    let tx = conn.transaction()?;
+    let bond = get(&tx, bond_id)?;
+    if bond.status != BondStatus::Granted {
+        return Err(AppError::Conflict(format!(
+            "Only a granted bond can be posted; this one is {}.",
+            bond.status.as_str().to_lowercase()
+        )));
+    }
+    if posted_on < bond.granted_on {
+        return Err(AppError::field(
+            "posted_on",
+            "A bond cannot be posted before it was granted",
+        ));
+    }
+    if bond.bond_type == BondType::Bond {
+        let sureties: i64 = tx
+            .query_row(
+                "SELECT COUNT(*) FROM bond_sureties WHERE bond_id = ?1",
+                params![bond_id],
+                |row| row.get(0),
+            )
+            .context("Failed to count sureties")?;
+        if sureties == 0 {
+            return Err(AppError::validation(
+                "Add the sureties before posting the bond.",
+            ));
+        }
+    }
+
+    tx.execute(
+        "UPDATE bonds SET status = 'Posted', posted_on = ?2, last_modified = CURRENT_TIMESTAMP
+         WHERE bond_id = ?1",
+        params![bond_id, posted_on],
+    )
+    .context("Failed to post bond")?;
+    if let Some(offender_id) = bond.offender_id {
+        custody::release_offender(
+            &tx,
+            offender_id,
+            &posted_on,
+            &format!("Released on {}", bond.bond_type.as_str().to_lowercase()),
+        )?;
+    }
+    let posted = get(&tx, bond_id)?;
+    tx.commit()?;
+    Ok(posted)
+}
+
+/// End a bond. A bond can be cancelled before or after posting; only a
+/// posted bond can be forfeited or discharged. Discharge hands back every
+/// document still held.
+pub fn close(conn: &mut Connection, bond_id: i64, closure: BondClosure) -> AppResult<Bond> {
+    let reason = optional_text(closure.reason);
+    let closed_on = optional_date("closed_on", closure.closed_on.as_deref())?.unwrap_or_else(today);
+
+    let tx = conn.transaction()?;
+    let bond = get_live(&tx, bond_id)?;
+    let forfeited_cents = match closure.status {
+        BondStatus::Cancelled => None,
+        BondStatus::Forfeited | BondStatus::Discharged if bond.status != BondStatus::Posted => {
+            return Err(AppError::Conflict(format!(
+                "The bond was never posted; cancel it instead of marking it {}.",
+                closure.status.as_str().to_lowercase()
+            )));
+        }
+        BondStatus::Forfeited => {
+            let forfeited = closure.forfeited_cents.unwrap_or(bond.amount_cents);
+            if forfeited < 0 || forfeited > bond.amount_cents {
+                return Err(AppError::field(
+                    "forfeited_cents",
+                    format!(
+                        "The forfeiture must be between 0 and the bond of {}",
+                        format_cents(bond.amount_cents)
+                    ),
+                ));
+            }
+            Some(forfeited)
+        }
+        BondStatus::Discharged => None,
+        BondStatus::Granted | BondStatus::Posted => {
+            return Err(AppError::field(
+                "status",
+                "A bond ends as Discharged, Cancelled or Forfeited",
+            ));
+        }
+    };
+    if reason.is_none() && closure.status != BondStatus::Discharged {
+        return Err(AppError::field(
+            "reason",
+            format!(
+                "Give the reason the bond is {}",
+                closure.status.as_str().to_lowercase()
+            ),
+        ));
+    }
+    let opened_on = bond.posted_on.as_deref().unwrap_or(&bond.granted_on);
+    if closed_on.as_str() < opened_on {
+        return Err(AppError::field(
+            "closed_on",
+            format!("The bond cannot end before {}", opened_on),
+        ));
+    }
+
+    tx.execute(
+        "UPDATE bonds SET status = ?2, closed_on = ?3, closure_reason = ?4,
+             forfeited_cents = ?5, last_modified = CURRENT_TIMESTAMP
+         WHERE bond_id = ?1",
+        params![
+            bond_id,
+            closure.status.as_str(),
+            closed_on,
+            reason,
+            forfeited_cents
+        ],
+    )
+    .context("Failed to close bond")?;
+    if closure.status == BondStatus::Discharged {
+        tx.execute(
+            "UPDATE bond_documents SET returned_on = ?2
+             WHERE bond_id = ?1 AND returned_on IS NULL",
+            params![bond_id, closed_on],
+        )
+        .context("Failed to return bond documents")?;
+    }
+    let closed = get(&tx, bond_id)?;
+    tx.commit()?;
+    Ok(closed)
+}
+
+/// Add a surety to a bond that is still live
+pub fn add_surety(conn: &Connection, bond_id: i64, surety: NewSurety) -> AppResult<Surety> {
+    let bond = get_live(conn, bond_id)?;
+    if bond.bond_type == BondType::PersonalBond {
+        return Err(AppError::validation("A personal bond has no sureties."));
+    }
+    let id = insert_surety(conn, bond_id, surety)?;
+    conn.query_row(
+        &format!("{} WHERE surety_id = ?1", SURETY_SELECT),
+        params![id],
+        Surety::from_row,
+    )
+    .context("Failed to fetch surety")
+}
+
+/// Take a surety off a bond not yet posted; the documents they deposited
+/// stay on record
+pub fn remove_surety(conn: &Connection, surety_id: i64) -> AppResult<()> {
+    let bond_id: i64 = conn
+        .query_row(
+            "SELECT bond_id FROM bond_sureties WHERE surety_id = ?1",
+            params![surety_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch surety")?
+        .ok_or_else(|| AppError::not_found("Surety not found"))?;
+    if get(conn, bond_id)?.status != BondStatus::Granted {
+        return Err(AppError::Conflict(
+            "Sureties of a posted or ended bond stay on record.".to_string(),
+        ));
+    }
+    conn.execute(
+        "DELETE FROM bond_sureties WHERE surety_id = ?1",
+        params![surety_id],
+    )
+    .context("Failed to remove surety")?;
+    Ok(())
+}
+
+/// Record a document deposited with the court for a live bond
+pub fn add_document(
+    conn: &Connection,
+    bond_id: i64,
+    document: NewBondDocument,
+    created_by: Option<&str>,
+) -> AppResult<BondDocument> {
+    get_live(conn, bond_id)?;
+    let document_type = document.document_type.trim();
+    if document_type.is_empty() {
+        return Err(AppError::field(
+            "document_type",
+            "Enter what the document is",
+        ));
+    }
+    if let Some(surety_id) = document.surety_id {
+        let surety_bond: Option<i64> = conn
+            .query_row(
+                "SELECT bond_id FROM bond_sureties WHERE surety_id = ?1",
+                params![surety_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch surety")?;
+        if surety_bond != Some(bond_id) {
+            return Err(AppError::field(
+                "surety_id",
+                "The surety is not on this bond",
+            ));
+        }
+    }
+    let received_on =
+        optional_date("received_on", document.received_on.as_deref())?.unwrap_or_else(today);
+
+    conn.execute(
+        "INSERT INTO bond_documents (bond_id, surety_id, document_type, reference, description,
+             received_on, created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            bond_id,
+            document.surety_id,
+            document_type,
+            optional_text(document.reference),
+            optional_text(document.description),
+            received_on,
+            created_by
+        ],
+    )
+    .context("Failed to record bond document")?;
+    get_document(conn, conn.last_insert_rowid())
+}
+
+fn get_document(conn: &Connection, document_id: i64) -> AppResult<BondDocument> {
+    conn.query_row(
+        &format!("{} WHERE document_id = ?1", DOCUMENT_SELECT),
+        params![document_id],
+        BondDocument::from_row,
+    )
+    .optional()
+    .context("Failed to fetch bond document")?
+    .ok_or_else(|| AppError::not_found("Bond document not found"))
+}
+
+/// Hand a deposited document back
+pub fn return_document(
+    conn: &Connection,
+    document_id: i64,
+    returned_on: Option<&str>,
+) -> AppResult<BondDocument> {
+    let document = get_document(conn, document_id)?;
+    if let Some(returned_on) = &document.returned_on {
+        return Err(AppError::Conflict(format!(
+            "The document was returned on {}.",
+            returned_on
+        )));
+    }
+    let returned_on = optional_date("returned_on", returned_on)?.unwrap_or_else(today);
+    if returned_on < document.received_on {
+        return Err(AppError::field(
+            "returned_on",
+            "A document cannot be returned before it was received",
+        ));
+    }
+    conn.execute(
+        "UPDATE bond_documents SET returned_on = ?2 WHERE document_id = ?1",
+        params![document_id, returned_on],
+    )
+    .context("Failed to return bond document")?;
+    get_document(conn, document_id)
+}
+
+/// Record that an accused out on bond did not attend a hearing of the case,
+/// and raise a warning so the court can act on the bond
+pub fn record_absence(
+    conn: &mut Connection,
+    hearing_id: i64,
+    offender_id: i64,
+    notes: Option<String>,
+    recorded_by: Option<&str>,
+) -> AppResult<Absence> {
+    let tx = conn.transaction()?;
+    let hearing = hearings::get(&tx, hearing_id)?;
+    if hearing.hearing_date > today() {
+        return Err(AppError::validation("The hearing has not taken place yet."));
+    }
+    let bond = tx
+        .query_row(
+            &format!(
+                "{} WHERE b.case_id = ?1 AND b.offender_id = ?2 AND b.status = 'Posted'",
+                BOND_SELECT
+            ),
+            params![hearing.case_id, offender_id],
+            Bond::from_row,
+        )
+        .optional()
+        .context("Failed to fetch bond")?
+        .ok_or_else(|| AppError::not_found("This person is not out on bond in the case"))?;
+    if bond
+        .posted_on
+        .as_ref()
+        .is_some_and(|posted| hearing.hearing_date < *posted)
+    {
+        return Err(AppError::validation(
+            "The hearing was before the bond was posted.",
+        ));
+    }
+    let recorded: Option<i64> = tx
+        .query_row(
+            "SELECT absence_id FROM bond_absences WHERE bond_id = ?1 AND hearing_id = ?2",
+            params![bond.bond_id, hearing_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check missed hearings")?;
+    if recorded.is_some() {
+        return Err(AppError::Conflict(
+            "This missed hearing is already recorded.".to_string(),
+        ));
+    }
+    tx.execute(
+        "INSERT INTO hearing_attendance (hearing_id, offender_id, offender_name, attended,
+             recorded_by)
+         VALUES (?1, ?2, (SELECT full_name FROM offenders WHERE offender_id = ?2), 0, ?3)
+         ON CONFLICT(hearing_id, offender_id) DO UPDATE SET
+             attended = 0, recorded_by = excluded.recorded_by, recorded_at = CURRENT_TIMESTAMP",
+        params![hearing_id, offender_id, recorded_by],
+    )
+    .context("Failed to record attendance")?;
+
+    let absence = raise_absence(&tx, &bond, &hearing, optional_text(notes), recorded_by)?;
+    tx.commit()?;
+    Ok(absence)
+}
+
+/// Raise a warning for every accused out on a posted bond who is recorded
+/// as not attending a hearing of the case since the bond was posted, and
+/// record it on the bond. Hearings already flagged are left alone, so this
+/// is safe to run after every outcome and at startup.
+pub fn flag_missed_hearings(
+    conn: &mut Connection,
+    recorded_by: Option<&str>,
+) -> AppResult<Vec<Absence>> {
+    let tx = conn.transaction()?;
+    let missed: Vec<(i64, i64)> = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT b.bond_id, h.hearing_id
+                 FROM hearing_attendance a
+                 JOIN hearings h ON h.hearing_id = a.hearing_id
+                 JOIN bonds b ON b.case_id = h.case_id AND b.offender_id = a.offender_id
+                 WHERE a.attended = 0 AND b.status = 'Posted'
+                   AND h.hearing_date <= ?1
+                   AND (b.posted_on IS NULL OR h.hearing_date >= b.posted_on)
+                   AND NOT EXISTS (SELECT 1 FROM bond_absences x
+                                   WHERE x.bond_id = b.bond_id AND x.hearing_id = h.hearing_id)
+                 ORDER BY h.hearing_date, h.hearing_id",
+            )
+            .context("Failed to prepare statement")?;
+        let rows = stmt
+            .query_map(params![today()], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context("Failed to find missed hearings")?
+            .collect::<Result<_, _>>()
+            .context("Failed to read missed hearings")?;
+        rows
+    };
+
+    let mut absences = Vec::new();
+    for (bond_id, hearing_id) in missed {
+        let bond = get(&tx, bond_id)?;
+        let hearing = hearings::get(&tx, hearing_id)?;
+        absences.push(raise_absence(&tx, &bond, &hearing, None, recorded_by)?);
+    }
+    tx.commit()?;
+    Ok(absences)
+}
+
+fn raise_absence(
+    conn: &Connection,
+    bond: &Bond,
+    hearing: &Hearing,
+    notes: Option<String>,
+    recorded_by: Option<&str>,
+) -> AppResult<Absence> {
+    let message = format!(
+        "{} did not attend the {} in {} on {} while out on {} of {}{}.",
+        bond.offender_name.as_deref().unwrap_or("An accused"),
+        hearing.purpose.as_str().to_lowercase(),
+        bond.case_number.as_deref().unwrap_or("a case"),
+        hearing.hearing_date,
+        bond.bond_type.as_str().to_lowercase(),
+        format_cents(bond.amount_cents),
+        match bond.absences {
+            0 => String::new(),
+            n => format!(" ({} hearings missed so far)", n + 1),
+        }
+    );
+    let notification_id = notifications::create(conn, &message, "Warning", None)?;
+    conn.execute(
+        "INSERT INTO bond_absences (bond_id, hearing_id, hearing_date, notes, notification_id,
+             recorded_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            bond.bond_id,
+            hearing.hearing_id,
+            hearing.hearing_date,
+            notes,
+            notification_id,
+            recorded_by
+        ],
+    )
+    .context("Failed to record missed hearing")?;
+    let id = conn.last_insert_rowid();
+    conn.query_row(
+        &format!("{} WHERE absence_id = ?1", ABSENCE_SELECT),
+        params![id],
+        Absence::from_row,
+    )
+    .context("Failed to fetch missed hearing")
+}
+
+pub fn list(conn: &Connection, filter: &BondFilter) -> AppResult<Vec<Bond>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR b.case_id = ?1)
+                 AND (?2 IS NULL OR b.offender_id = ?2)
+                 AND (?3 IS NULL OR b.status = ?3)
+                 AND (?4 = 0 OR b.status IN ('Granted', 'Posted'))
+             ORDER BY b.status NOT IN ('Granted', 'Posted'), b.granted_on DESC, b.bond_id DESC",
+            BOND_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let bonds = stmt
+        .query_map(
+            params![
+                filter.case_id,
+                filter.offender_id,
+                filter.status.map(|s| s.as_str()),
+                filter.live_only
+            ],
+            Bond::from_row,
+        )
+        .context("Failed to fetch bonds")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read bonds")?;
+    Ok(bonds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::services::hearings::Attendance;
+
+    struct Fixture {
+        conn: Connection,
+        offender_id: i64,
+        hearing_id: i64,
+        bond_id: i64,
+    }
+
+    /// An accused out on a bond posted on 2026-01-10, and a hearing of the
+    /// case on 2026-02-03
+    fn fixture() -> Fixture {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO cases (title, case_number) VALUES ('Republic v Kamau', 'MCCR/E012/2026')",
+            [],
+        )
+        .unwrap();
+        let case_id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO offenders (full_name) VALUES ('John Kamau')",
+            [],
+        )
+        .unwrap();
+        let offender_id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date, purpose) VALUES (?1, '2026-02-03', 'Mention')",
+            params![case_id],
+        )
+        .unwrap();
+        let hearing_id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO bonds (case_id, case_number, offender_id, offender_name, amount_cents,
+                 granted_on, status, posted_on)
+             VALUES (?1, 'MCCR/E012/2026', ?2, 'John Kamau', 5000000, '2026-01-08', 'Posted',
+                 '2026-01-10')",
+            params![case_id, offender_id],
+        )
+        .unwrap();
+        let bond_id = conn.last_insert_rowid();
+        Fixture {
+            conn,
+            offender_id,
+            hearing_id,
+            bond_id,
+        }
+    }
+
+    fn warnings(conn: &Connection) -> i64 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM notifications WHERE type = 'Warning'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn absence_recorded_with_the_outcome_flags_the_bond_once() {
+        let mut f = fixture();
+        let attendance = [Attendance {
+            offender_id: f.offender_id,
+            attended: false,
+        }];
+        hearings::record_outcome(
+            &mut f.conn,
+            f.hearing_id,
+            "Mention; accused absent",
+            &attendance,
+            None,
+        )
+        .unwrap();
+
+        let flagged = flag_missed_hearings(&mut f.conn, None).unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].bond_id, f.bond_id);
+        assert_eq!(flagged[0].hearing_id, Some(f.hearing_id));
+        assert_eq!(warnings(&f.conn), 1);
+        assert_eq!(get(&f.conn, f.bond_id).unwrap().absences, 1);
+
+        assert!(flag_missed_hearings(&mut f.conn, None).unwrap().is_empty());
+        assert_eq!(warnings(&f.conn), 1);
+    }
+
+    #[test]
+    fn attendance_does_not_flag_the_bond() {
+        let mut f = fixture();
+        let attendance = [Attendance {
+            offender_id: f.offender_id,
+            attended: true,
+        }];
+        hearings::record_outcome(&mut f.conn, f.hearing_id, "Mention", &attendance, None).unwrap();
+
+        assert!(flag_missed_hearings(&mut f.conn, None).unwrap().is_empty());
+        assert_eq!(warnings(&f.conn), 0);
+    }
+
+    #[test]
+    fn hearings_before_posting_are_not_flagged() {
+        let mut f = fixture();
+        f.conn
+            .execute(
+                "UPDATE bonds SET posted_on = '2026-02-10' WHERE bond_id = ?1",
+                params![f.bond_id],
+            )
+            .unwrap();
+        let attendance = [Attendance {
+            offender_id: f.offender_id,
+            attended: false,
+        }];
+        hearings::record_attendance(&mut f.conn, f.hearing_id, &attendance, None).unwrap();
+
+        assert!(flag_missed_hearings(&mut f.conn, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn absence_recorded_by_hand_is_not_flagged_again() {
+        let mut f = fixture();
+        record_absence(&mut f.conn, f.hearing_id, f.offender_id, None, None).unwrap();
+
+        assert!(flag_missed_hearings(&mut f.conn, None).unwrap().is_empty());
+        assert_eq!(warnings(&f.conn), 1);
+    }
+
+    #[test]
+    fn register_outlives_the_case_and_offender_records() {
+        let mut f = fixture();
+        add_surety(
+            &f.conn,
+            f.bond_id,
+            NewSurety {
+                full_name: "Mary Wambui".to_string(),
+                id_number: None,
+                phone_number: None,
+                address: None,
+                relationship: Some("Sister".to_string()),
+                amount_cents: None,
+                notes: None,
+            },
+        )
+        .unwrap();
+        record_absence(&mut f.conn, f.hearing_id, f.offender_id, None, None).unwrap();
+
+        f.conn
+            .execute_batch("DELETE FROM offenders; DELETE FROM cases;")
+            .unwrap();
+
+        let kept = detail(&f.conn, f.bond_id).unwrap();
+        assert_eq!(kept.bond.case_id, None);
+        assert_eq!(kept.bond.offender_id, None);
+        assert_eq!(kept.bond.case_number.as_deref(), Some("MCCR/E012/2026"));
+        assert_eq!(kept.bond.offender_name.as_deref(), Some("John Kamau"));
+        assert_eq!(kept.sureties.len(), 1);
+        assert_eq!(kept.absences.len(), 1);
+        assert_eq!(kept.absences[0].hearing_date, "2026-02-03");
+        assert!(f
+            .conn
+            .execute("DELETE FROM bonds WHERE bond_id = ?1", params![f.bond_id])
+            .is_err());
+    }
+}
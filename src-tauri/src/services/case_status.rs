@@ -0,0 +1,442 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::users::{is_admin_role, normalize_role};
+
+// ============================================================================
+// Case status workflow
+//
+// A case is Open, In Progress or Closed, and moves between them only by the
+// transitions below, each open to a fixed set of roles. Every move is kept
+// in `case_status_history` (migration 0006) with who made it and when.
+// Reopening a closed case needs a reason.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaseStatus {
+    Open,
+    #[serde(rename = "In Progress")]
+    InProgress,
+    Closed,
+}
+
+impl CaseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseStatus::Open => "Open",
+            CaseStatus::InProgress => "In Progress",
+            CaseStatus::Closed => "Closed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<CaseStatus> {
+        match status {
+            "Open" => Some(CaseStatus::Open),
+            "In Progress" => Some(CaseStatus::InProgress),
+            "Closed" => Some(CaseStatus::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// Roles as `normalize_role` spells them
+const JUDICIAL_OFFICERS: &[&str] = &["JUDGE", "MAGISTRATE"];
+const CLERKS: &[&str] = &["COURT_CLERK"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransitionKind {
+    /// Open → In Progress
+    Start,
+    /// In Progress → Open, e.g. when a matter is stood over
+    Return,
+    /// Open or In Progress → Closed
+    Close,
+    /// Closed → Open
+    Reopen,
+}
+
+impl TransitionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransitionKind::Start => "start",
+            TransitionKind::Return => "return",
+            TransitionKind::Close => "close",
+            TransitionKind::Reopen => "reopen",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<TransitionKind> {
+        match kind {
+            "start" => Some(TransitionKind::Start),
+            "return" => Some(TransitionKind::Return),
+            "close" => Some(TransitionKind::Close),
+            "reopen" => Some(TransitionKind::Reopen),
+            _ => None,
+        }
+    }
+}
+
+/// One allowed move between statuses
+#[derive(Debug, Clone, Serialize)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub from: CaseStatus,
+    pub to: CaseStatus,
+    pub requires_reason: bool,
+    /// Roles allowed besides administrators, who may make every move
+    #[serde(skip)]
+    roles: &'static [&'static [&'static str]],
+}
+
+impl Transition {
+    /// Whether a user with `role` may make this move
+    pub fn allows(&self, role: &str) -> bool {
+        if is_admin_role(role) {
+            return true;
+        }
+        let role = normalize_role(role);
+        self.roles
+            .iter()
+            .any(|group| group.contains(&role.as_str()))
+    }
+}
+
+const TRANSITIONS: &[Transition] = &[
+    Transition {
+        kind: TransitionKind::Start,
+        from: CaseStatus::Open,
+        to: CaseStatus::InProgress,
+        requires_reason: false,
+        roles: &[JUDICIAL_OFFICERS, CLERKS],
+    },
+    Transition {
+        kind: TransitionKind::Return,
+        from: CaseStatus::InProgress,
+        to: CaseStatus::Open,
+        requires_reason: false,
+        roles: &[JUDICIAL_OFFICERS, CLERKS],
+    },
+    Transition {
+        kind: TransitionKind::Close,
+        from: CaseStatus::Open,
+        to: CaseStatus::Closed,
+        requires_reason: false,
+        roles: &[JUDICIAL_OFFICERS],
+    },
+    Transition {
+        kind: TransitionKind::Close,
+        from: CaseStatus::InProgress,
+        to: CaseStatus::Closed,
+        requires_reason: false,
+        roles: &[JUDICIAL_OFFICERS],
+    },
+    Transition {
+        kind: TransitionKind::Reopen,
+        from: CaseStatus::Closed,
+        to: CaseStatus::Open,
+        requires_reason: true,
+        roles: &[JUDICIAL_OFFICERS],
+    },
+];
+
+fn find(from: CaseStatus, to: CaseStatus) -> Option<&'static Transition> {
+    TRANSITIONS.iter().find(|t| t.from == from && t.to == to)
+}
+
+/// A recorded status change
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChange {
+    pub id: i64,
+    pub case_id: i64,
+    pub from_status: String,
+    pub to_status: String,
+    pub transition: TransitionKind,
+    pub reason: Option<String>,
+    pub changed_by: Option<String>,
+    pub changed_by_role: Option<String>,
+    pub changed_at: Option<String>,
+}
+
+const CHANGE_SELECT: &str = "SELECT id, case_id, from_status, to_status, transition, reason,
+        changed_by, changed_by_role, changed_at
+    FROM case_status_history";
+
+impl StatusChange {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let transition: String = row.get(4)?;
+        Ok(StatusChange {
+            id: row.get(0)?,
+            case_id: row.get(1)?,
+            from_status: row.get(2)?,
+            to_status: row.get(3)?,
+            transition: TransitionKind::parse(&transition).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    4,
+                    rusqlite::types::Type::Text,
+                    format!("unknown status transition {}", transition).into(),
+                )
+            })?,
+            reason: row.get(5)?,
+            changed_by: row.get(6)?,
+            changed_by_role: row.get(7)?,
+            changed_at: row.get(8)?,
+        })
+    }
+}
+
+fn current_status(conn: &Connection, case_id: i64) -> AppResult<CaseStatus> {
+    let status: String = conn
+        .query_row(
+            "SELECT status FROM cases WHERE case_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+            params![case_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch case status")?
+        .ok_or_else(|| AppError::not_found("Case not found"))?;
+    CaseStatus::parse(&status)
+        .ok_or_else(|| AppError::internal(format!("Case has an unknown status {}", status)))
+}
+
+/// The moves a user with `role` can make on a case right now
+pub fn available(conn: &Connection, case_id: i64, role: &str) -> AppResult<Vec<Transition>> {
+    let status = current_status(conn, case_id)?;
+    Ok(TRANSITIONS
+        .iter()
+        .filter(|t| t.from == status && t.allows(role))
+        .cloned()
+        .collect())
+}
+
+/// Move a case to `to`, if the workflow allows it from where the case is
+/// and `role` may make that move, and record the change.
+pub fn change(
+    conn: &mut Connection,
+    case_id: i64,
+    to: &str,
+    reason: Option<&str>,
+    changed_by: Option<&str>,
+    role: &str,
+) -> AppResult<StatusChange> {
+    let to = CaseStatus::parse(to).ok_or_else(|| {
+        AppError::field("new_status", "Status must be Open, In Progress or Closed")
+    })?;
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+
+    let tx = conn.transaction()?;
+    let from = current_status(&tx, case_id)?;
+    if from == to {
+        return Err(AppError::Conflict(format!(
+            "The case is already {}.",
+            to.as_str()
+        )));
+    }
+    let transition = find(from, to).ok_or_else(|| {
+        AppError::validation(format!(
+            "A case cannot go from {} to {}.",
+            from.as_str(),
+            to.as_str()
+        ))
+    })?;
+    if !transition.allows(role) {
+        return Err(AppError::Forbidden(format!(
+            "Your role cannot {} a case.",
+            transition.kind.as_str()
+        )));
+    }
+    if transition.requires_reason && reason.is_none() {
+        return Err(AppError::field(
+            "reason",
+            format!("Give a reason to {} the case", transition.kind.as_str()),
+        ));
+    }
+
+    tx.execute(
+        "UPDATE cases SET status = ?1, sync_status = 'pending', has_changes = 1,
+         last_modified = CURRENT_TIMESTAMP
+         WHERE case_id = ?2",
+        params![to.as_str(), case_id],
+    )
+    .context("Failed to update case status")?;
+    tx.execute(
+        "INSERT INTO case_status_history
+            (case_id, from_status, to_status, transition, reason, changed_by, changed_by_role)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            case_id,
+            from.as_str(),
+            to.as_str(),
+            transition.kind.as_str(),
+            reason,
+            changed_by,
+            role
+        ],
+    )
+    .context("Failed to record case status change")?;
+    let id = tx.last_insert_rowid();
+    let change = tx
+        .query_row(
+            &format!("{} WHERE id = ?1", CHANGE_SELECT),
+            params![id],
+            StatusChange::from_row,
+        )
+        .context("Failed to fetch case status change")?;
+    tx.commit()?;
+    Ok(change)
+}
+
+/// Every recorded status change of a case, oldest first
+pub fn history(conn: &Connection, case_id: i64) -> AppResult<Vec<StatusChange>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE case_id = ?1 ORDER BY changed_at, id",
+            CHANGE_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let changes = stmt
+        .query_map(params![case_id], StatusChange::from_row)
+        .context("Failed to fetch case status history")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read case status history")?;
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn case(conn: &Connection) -> i64 {
+        conn.execute("INSERT INTO cases (title) VALUES ('R v Doe')", [])
+            .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn kinds(transitions: &[Transition]) -> Vec<(TransitionKind, CaseStatus)> {
+        transitions.iter().map(|t| (t.kind, t.to)).collect()
+    }
+
+    #[test]
+    fn transition_table_is_enforced() {
+        use CaseStatus::*;
+        let allowed = [
+            (Open, InProgress, Some(TransitionKind::Start)),
+            (Open, Closed, Some(TransitionKind::Close)),
+            (InProgress, Open, Some(TransitionKind::Return)),
+            (InProgress, Closed, Some(TransitionKind::Close)),
+            (Closed, Open, Some(TransitionKind::Reopen)),
+            (Closed, InProgress, None),
+        ];
+        for (from, to, kind) in allowed {
+            assert_eq!(
+                find(from, to).map(|t| t.kind),
+                kind,
+                "{:?} → {:?}",
+                from,
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn roles_see_only_their_moves() {
+        let conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+
+        assert_eq!(
+            kinds(&available(&conn, case_id, "Magistrate").unwrap()),
+            vec![
+                (TransitionKind::Start, CaseStatus::InProgress),
+                (TransitionKind::Close, CaseStatus::Closed)
+            ]
+        );
+        assert_eq!(
+            kinds(&available(&conn, case_id, "Court Clerk").unwrap()),
+            vec![(TransitionKind::Start, CaseStatus::InProgress)]
+        );
+        assert_eq!(available(&conn, case_id, "Court Admin").unwrap().len(), 2);
+        assert!(available(&conn, case_id, "Other").unwrap().is_empty());
+    }
+
+    #[test]
+    fn change_records_history_and_marks_case_for_sync() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+        conn.execute(
+            "UPDATE cases SET sync_status = 'synced', has_changes = 0 WHERE case_id = ?1",
+            params![case_id],
+        )
+        .unwrap();
+
+        change(
+            &mut conn,
+            case_id,
+            "In Progress",
+            None,
+            Some("clerk"),
+            "Court Clerk",
+        )
+        .unwrap();
+        change(&mut conn, case_id, "Closed", None, Some("judge"), "JUDGE").unwrap();
+
+        let (status, sync_status, has_changes): (String, String, bool) = conn
+            .query_row(
+                "SELECT status, sync_status, has_changes FROM cases WHERE case_id = ?1",
+                params![case_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(status, "Closed");
+        assert_eq!(sync_status, "pending");
+        assert!(has_changes);
+
+        let history = history(&conn, case_id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].transition, TransitionKind::Start);
+        assert_eq!(history[0].changed_by.as_deref(), Some("clerk"));
+        assert_eq!(history[1].from_status, "In Progress");
+        assert_eq!(history[1].to_status, "Closed");
+    }
+
+    #[test]
+    fn change_refuses_moves_outside_the_workflow() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+
+        assert!(matches!(
+            change(&mut conn, case_id, "Open", None, None, "Judge"),
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            change(&mut conn, case_id, "Closed", None, None, "Court Clerk"),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            change(&mut conn, case_id, "Pending", None, None, "Judge"),
+            Err(AppError::Validation { .. })
+        ));
+
+        change(&mut conn, case_id, "Closed", None, None, "Judge").unwrap();
+        assert!(matches!(
+            change(&mut conn, case_id, "In Progress", None, None, "Judge"),
+            Err(AppError::Validation { .. })
+        ));
+        assert!(matches!(
+            change(&mut conn, case_id, "Open", Some("  "), None, "Judge"),
+            Err(AppError::Validation { .. })
+        ));
+        change(
+            &mut conn,
+            case_id,
+            "Open",
+            Some("New evidence"),
+            None,
+            "Court Admin",
+        )
+        .unwrap();
+
+        assert_eq!(history(&conn, case_id).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppResult, ResultExt};
+
+#[derive(Serialize, Deserialize)]
+pub struct Case {
+    pub case_id: i32,
+    pub title: String,
+    pub status: String,
+    pub assigned_staff_id: Option<i32>,
+    pub priority: String,
+    pub date_created: String,
+}
+
+const CASE_COLUMNS: &str = "case_id, title, status, assigned_staff_id, priority, date_created";
+
+impl Case {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Case {
+            case_id: row.get(0)?,
+            title: row.get(1)?,
+            status: row.get(2)?,
+            assigned_staff_id: row.get(3)?,
+            priority: row.get(4)?,
+            date_created: row.get(5)?,
+        })
+    }
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<Case>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM cases WHERE COALESCE(is_deleted, 0) = 0",
+            CASE_COLUMNS
+        ))
+        .context("Failed to prepare statement")?;
+
+    let cases = stmt
+        .query_map([], Case::from_row)
+        .context("Failed to fetch cases")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read cases")?;
+    Ok(cases)
+}
+
+pub fn get(conn: &Connection, case_id: i32) -> AppResult<Option<Case>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM cases WHERE case_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+            CASE_COLUMNS
+        ),
+        params![case_id],
+        Case::from_row,
+    )
+    .optional()
+    .context("Failed to fetch case")
+}
+
+/// Open a new case and return its id
+pub fn create(
+    conn: &Connection,
+    title: &str,
+    assigned_staff_id: Option<i32>,
+    priority: &str,
+) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO cases (title, status, assigned_staff_id, priority) VALUES (?1, 'Open', ?2, ?3)",
+        params![title, assigned_staff_id, priority],
+    )
+    .context("Failed to create case")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn assign_staff(conn: &Connection, case_id: i32, staff_id: Option<i32>) -> AppResult<()> {
+    conn.execute(
+        "UPDATE cases SET assigned_staff_id = ?1 WHERE case_id = ?2",
+        params![staff_id, case_id],
+    )
+    .context("Failed to assign staff to case")?;
+    Ok(())
+}
+
+/// Mark the case deleted. The row stays as a tombstone so the deletion
+/// syncs and its hearings, status history and counts are kept.
+pub fn delete(conn: &Connection, case_id: i32) -> AppResult<()> {
+    conn.execute(
+        "UPDATE cases SET is_deleted = 1, sync_status = 'pending', has_changes = 1,
+             last_modified = CURRENT_TIMESTAMP
+         WHERE case_id = ?1",
+        params![case_id],
+    )
+    .context("Failed to delete case")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[test]
+    fn deleted_case_is_hidden_but_keeps_its_hearings() {
+        let conn = db::open_in_memory().unwrap();
+        let case_id = create(&conn, "Republic v Kamau", None, "High").unwrap();
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date, purpose) VALUES (?1, '2026-02-03', 'Mention')",
+            params![case_id],
+        )
+        .unwrap();
+
+        delete(&conn, case_id as i32).unwrap();
+
+        assert!(get(&conn, case_id as i32).unwrap().is_none());
+        assert!(list(&conn).unwrap().is_empty());
+        let (sync_status, hearings): (String, i64) = conn
+            .query_row(
+                "SELECT sync_status, (SELECT COUNT(*) FROM hearings WHERE case_id = ?1)
+                 FROM cases WHERE case_id = ?1",
+                params![case_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(sync_status, "pending");
+        assert_eq!(hearings, 1);
+    }
+}
@@ -0,0 +1,905 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::error::{AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+use crate::services::offender_cases::{self, CaseRole};
+use crate::services::pdf::{self, Font, PdfWriter};
+
+// ============================================================================
+// Daily cause list
+//
+// The matters before the court on one day: every hearing in the calendar for
+// that date, plus every file the registry has marked as required that day.
+// A hearing and a file for the same case number are one matter. Matters are
+// grouped by purpose and ordered by case priority, then case number. A
+// matter is flagged when an earlier movement of its file was never returned,
+// since the file may not make it to court.
+// ============================================================================
+
+/// Purposes in the order they are called
+const PURPOSES: [(&str, &str); 5] = [
+    ("Mention", "Mentions"),
+    ("Hearing", "Hearings"),
+    ("Ruling", "Rulings"),
+    ("Judgement", "Judgements"),
+    ("Other", "Other matters"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Party {
+    pub name: String,
+    pub role: CaseRole,
+    /// Set when the party is a linked offender rather than a name on the case
+    pub offender_id: Option<i64>,
+}
+
+/// An earlier movement of a matter's file that was never returned
+#[derive(Debug, Clone, Serialize)]
+pub struct FileOut {
+    pub file_id: i64,
+    pub required_on: String,
+    pub current_location: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Matter {
+    /// Position on the list, from 1
+    pub number: usize,
+    pub case_id: Option<i64>,
+    pub case_number: String,
+    pub title: Option<String>,
+    pub case_type: Option<String>,
+    pub priority: String,
+    pub time: Option<String>,
+    pub courtroom: Option<String>,
+    pub presiding_officer: Option<String>,
+    pub hearing_id: Option<i64>,
+    pub file_id: Option<i64>,
+    pub parties: Vec<Party>,
+    pub file_not_returned: Option<FileOut>,
+    #[serde(skip)]
+    purpose: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CauseListGroup {
+    pub purpose: String,
+    pub title: String,
+    pub matters: Vec<Matter>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CauseList {
+    pub date: String,
+    pub courtroom: Option<String>,
+    /// The presiding officer, when every matter is before the same one
+    pub presiding_officer: Option<String>,
+    pub generated_at: String,
+    pub groups: Vec<CauseListGroup>,
+    pub total: usize,
+    pub files_not_returned: usize,
+}
+
+struct CaseInfo {
+    case_id: i64,
+    case_number: Option<String>,
+    title: Option<String>,
+    case_type: Option<String>,
+    priority: String,
+    complainant_name: Option<String>,
+    accused_name: Option<String>,
+}
+
+impl CaseInfo {
+    const COLUMNS: &'static str =
+        "c.case_id, c.case_number, c.title, c.case_type, c.priority, c.complainant_name, c.accused_name";
+
+    fn from_row(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Self> {
+        Ok(CaseInfo {
+            case_id: row.get(offset)?,
+            case_number: row.get(offset + 1)?,
+            title: row.get(offset + 2)?,
+            case_type: row.get(offset + 3)?,
+            priority: row.get(offset + 4)?,
+            complainant_name: row.get(offset + 5)?,
+            accused_name: row.get(offset + 6)?,
+        })
+    }
+}
+
+/// Case numbers are typed by hand; compare them without case or spacing
+fn case_key(case_number: &str) -> String {
+    case_number
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+/// Compare case numbers with their numbers taken as numbers, so that
+/// "CR 9/2025" comes before "CR 10/2025"
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn chunks(s: &str) -> Vec<(bool, String)> {
+        let mut out: Vec<(bool, String)> = Vec::new();
+        for c in s.chars() {
+            let digit = c.is_ascii_digit();
+            match out.last_mut() {
+                Some((d, chunk)) if *d == digit => chunk.push(c),
+                _ => out.push((digit, c.to_string())),
+            }
+        }
+        out
+    }
+    let (a, b) = (chunks(&case_key(a)), chunks(&case_key(b)));
+    for ((a_digit, a), (b_digit, b)) in a.iter().zip(&b) {
+        let order = if *a_digit && *b_digit {
+            let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+        } else {
+            a.cmp(b)
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn priority_rank(priority: &str) -> u8 {
+    match priority {
+        "High" => 0,
+        "Low" => 2,
+        _ => 1,
+    }
+}
+
+fn case_by_number(conn: &Connection, case_number: &str) -> AppResult<Option<CaseInfo>> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM cases c
+                 WHERE trim(c.case_number) = trim(?1) COLLATE NOCASE
+                   AND COALESCE(c.is_deleted, 0) = 0
+                 ORDER BY c.case_id LIMIT 1",
+                CaseInfo::COLUMNS
+            ),
+            params![case_number],
+            |row| CaseInfo::from_row(row, 0),
+        )
+        .optional()?)
+}
+
+/// Linked offenders first, then the names typed on the case for any role
+/// no offender is linked in
+fn parties(conn: &Connection, case: &CaseInfo) -> AppResult<Vec<Party>> {
+    let mut parties: Vec<Party> = offender_cases::list_for_case(conn, case.case_id)?
+        .into_iter()
+        .map(|link| Party {
+            name: link.offender_name.unwrap_or_default(),
+            role: link.role,
+            offender_id: Some(link.offender_id),
+        })
+        .collect();
+    for (name, role) in [
+        (&case.complainant_name, CaseRole::Complainant),
+        (&case.accused_name, CaseRole::Accused),
+    ] {
+        let name = name.as_deref().map(str::trim).unwrap_or_default();
+        if !name.is_empty() && !parties.iter().any(|p| p.role == role) {
+            parties.push(Party {
+                name: name.to_string(),
+                role,
+                offender_id: None,
+            });
+        }
+    }
+    let rank = |role: CaseRole| match role {
+        CaseRole::Complainant => 0,
+        CaseRole::Accused => 1,
+        CaseRole::Witness => 2,
+    };
+    parties.sort_by_key(|p| rank(p.role));
+    Ok(parties)
+}
+
+fn file_not_returned(
+    conn: &Connection,
+    case_number: &str,
+    date: &str,
+) -> AppResult<Option<FileOut>> {
+    conn.query_row(
+        "SELECT file_id, required_on, current_location FROM files
+             WHERE trim(case_number) = trim(?1) COLLATE NOCASE
+               AND COALESCE(date_returned, '') = ''
+               AND DATE(required_on) < ?2
+               AND COALESCE(deleted, 0) = 0 AND COALESCE(is_deleted, 0) = 0
+             ORDER BY required_on DESC LIMIT 1",
+        params![case_number, date],
+        |row| {
+            Ok(FileOut {
+                file_id: row.get(0)?,
+                required_on: row.get(1)?,
+                current_location: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+    .context("Failed to check file returns")
+}
+
+/// The cause list for `date` (YYYY-MM-DD). With `courtroom`, only hearings
+/// in that courtroom are listed; files carry no courtroom, so files without
+/// a hearing only appear on the list for the whole court.
+pub fn generate(conn: &Connection, date: &str, courtroom: Option<&str>) -> AppResult<CauseList> {
+    let date = parse_date("date", date)?;
+    let courtroom = courtroom.map(str::trim).filter(|c| !c.is_empty());
+    let mut matters: Vec<(Matter, Option<CaseInfo>)> = Vec::new();
+    let mut by_case: HashMap<String, usize> = HashMap::new();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT h.hearing_id, h.purpose, h.hearing_time, h.courtroom, h.presiding_officer, {}
+             FROM hearings h
+             JOIN cases c ON c.case_id = h.case_id
+             WHERE h.hearing_date = ?1 AND COALESCE(c.is_deleted, 0) = 0
+               AND (?2 IS NULL OR h.courtroom = ?2 COLLATE NOCASE)
+             ORDER BY h.hearing_time IS NULL, h.hearing_time, h.hearing_id",
+            CaseInfo::COLUMNS
+        ))
+        .context("Failed to prepare statement")?;
+    let hearings = stmt
+        .query_map(params![date, courtroom], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                CaseInfo::from_row(row, 5)?,
+            ))
+        })
+        .context("Failed to fetch hearings")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read hearings")?;
+    for (hearing_id, purpose, time, room, presiding_officer, case) in hearings {
+        let case_number = case.case_number.clone().unwrap_or_default();
+        by_case
+            .entry(case_key(&case_number))
+            .or_insert(matters.len());
+        matters.push((
+            Matter {
+                number: 0,
+                case_id: Some(case.case_id),
+                case_number,
+                title: case.title.clone(),
+                case_type: case.case_type.clone(),
+                priority: case.priority.clone(),
+                time,
+                courtroom: room,
+                presiding_officer,
+                hearing_id: Some(hearing_id),
+                file_id: None,
+                parties: Vec::new(),
+                file_not_returned: None,
+                purpose,
+            },
+            Some(case),
+        ));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT file_id, case_number, case_type, purpose FROM files
+             WHERE DATE(required_on) = ?1
+               AND COALESCE(deleted, 0) = 0 AND COALESCE(is_deleted, 0) = 0
+             ORDER BY file_id",
+        )
+        .context("Failed to prepare statement")?;
+    let files = stmt
+        .query_map(params![date], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .context("Failed to fetch files")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read files")?;
+    for (file_id, case_number, case_type, purpose) in files {
+        let key = case_key(&case_number);
+        if let Some(&index) = by_case.get(&key) {
+            let matter = &mut matters[index].0;
+            matter.file_id.get_or_insert(file_id);
+            continue;
+        }
+        if courtroom.is_some() {
+            continue;
+        }
+        let case = case_by_number(conn, &case_number)?;
+        by_case.insert(key, matters.len());
+        matters.push((
+            Matter {
+                number: 0,
+                case_id: case.as_ref().map(|c| c.case_id),
+                case_number: case_number.trim().to_string(),
+                title: case.as_ref().and_then(|c| c.title.clone()),
+                case_type: case_type.or_else(|| case.as_ref().and_then(|c| c.case_type.clone())),
+                priority: case
+                    .as_ref()
+                    .map(|c| c.priority.clone())
+                    .unwrap_or_else(|| "Medium".to_string()),
+                time: None,
+                courtroom: None,
+                presiding_officer: None,
+                hearing_id: None,
+                file_id: Some(file_id),
+                parties: Vec::new(),
+                file_not_returned: None,
+                purpose,
+            },
+            case,
+        ));
+    }
+
+    let mut groups: Vec<CauseListGroup> = Vec::new();
+    let mut number = 0;
+    let mut files_out = 0;
+    for (purpose, title) in PURPOSES {
+        let mut group: Vec<(Matter, Option<CaseInfo>)> = Vec::new();
+        let mut rest = Vec::new();
+        for entry in matters {
+            let known = PURPOSES.iter().any(|(p, _)| *p == entry.0.purpose);
+            if entry.0.purpose == purpose || (purpose == "Other" && !known) {
+                group.push(entry);
+            } else {
+                rest.push(entry);
+            }
+        }
+        matters = rest;
+        if group.is_empty() {
+            continue;
+        }
+        group.sort_by(|(a, _), (b, _)| {
+            priority_rank(&a.priority)
+                .cmp(&priority_rank(&b.priority))
+                .then_with(|| natural_cmp(&a.case_number, &b.case_number))
+        });
+
+        let mut listed = Vec::with_capacity(group.len());
+        for (mut matter, case) in group {
+            number += 1;
+            matter.number = number;
+            if let Some(case) = &case {
+                matter.parties = parties(conn, case)?;
+            }
+            matter.file_not_returned = file_not_returned(conn, &matter.case_number, &date)?;
+            if matter.file_not_returned.is_some() {
+                files_out += 1;
+            }
+            listed.push(matter);
+        }
+        groups.push(CauseListGroup {
+            purpose: purpose.to_string(),
+            title: title.to_string(),
+            matters: listed,
+        });
+    }
+
+    let mut officers = groups
+        .iter()
+        .flat_map(|g| &g.matters)
+        .filter_map(|m| m.presiding_officer.as_deref());
+    let presiding_officer = officers
+        .next()
+        .filter(|first| officers.all(|other| other == *first))
+        .map(str::to_string);
+
+    Ok(CauseList {
+        date,
+        courtroom: courtroom.map(str::to_string),
+        presiding_officer,
+        generated_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        groups,
+        total: number,
+        files_not_returned: files_out,
+    })
+}
+
+// ----------------------------------------------------------------------------
+// Printable forms
+// ----------------------------------------------------------------------------
+
+fn long_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%A, %-d %B %Y").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// "Complainant v Accused", with any witnesses after
+fn parties_line(matter: &Matter) -> String {
+    let names = |role: CaseRole| {
+        matter
+            .parties
+            .iter()
+            .filter(|p| p.role == role)
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let (complainants, accused) = (names(CaseRole::Complainant), names(CaseRole::Accused));
+    let mut line = match (complainants.is_empty(), accused.is_empty()) {
+        (false, false) => format!("{} v {}", complainants, accused),
+        (false, true) => complainants,
+        (true, false) => accused,
+        (true, true) => matter.title.clone().unwrap_or_default(),
+    };
+    let witnesses = names(CaseRole::Witness);
+    if !witnesses.is_empty() {
+        line.push_str(&format!("\nWitnesses: {}", witnesses));
+    }
+    line
+}
+
+fn time_line(matter: &Matter, list: &CauseList) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if let Some(time) = &matter.time {
+        parts.push(time);
+    }
+    if list.courtroom.is_none() {
+        if let Some(room) = &matter.courtroom {
+            parts.push(room);
+        }
+    }
+    parts.join("\n")
+}
+
+fn remarks(matter: &Matter) -> String {
+    match &matter.file_not_returned {
+        Some(out) => format!(
+            "File not returned (out since {}{})",
+            out.required_on.get(..10).unwrap_or(&out.required_on),
+            out.current_location
+                .as_deref()
+                .map(|l| format!(", at {}", l))
+                .unwrap_or_default()
+        ),
+        None => String::new(),
+    }
+}
+
+fn subtitle(list: &CauseList) -> String {
+    let mut parts = vec![long_date(&list.date)];
+    if let Some(room) = &list.courtroom {
+        parts.push(room.clone());
+    }
+    if let Some(officer) = &list.presiding_officer {
+        parts.push(format!("Before {}", officer));
+    }
+    parts.join(" \u{2014} ")
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' => out.push_str("<br>"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; font-size: 11pt; margin: 2em; color: #000; }
+h1 { font-size: 16pt; margin: 0; }
+p.subtitle { margin: 0.3em 0 1.2em; }
+h2 { font-size: 12pt; margin: 1.4em 0 0.4em; border-bottom: 1px solid #000; }
+table { width: 100%; border-collapse: collapse; }
+th, td { text-align: left; vertical-align: top; padding: 0.3em 0.5em 0.3em 0; }
+th { font-size: 9pt; text-transform: uppercase; border-bottom: 1px solid #999; }
+td.no { width: 2.5em; }
+tr.file-out td.remarks { font-weight: bold; }
+p.footer { margin-top: 2em; font-size: 9pt; color: #555; }
+@media print { body { margin: 0; } tr { page-break-inside: avoid; } }
+";
+
+/// The list as a stand-alone HTML page, ready to print
+pub fn to_html(list: &CauseList) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Cause list {}</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>Cause list</h1>\n<p class=\"subtitle\">{}</p>\n",
+        escape(&list.date),
+        HTML_STYLE,
+        escape(&subtitle(list))
+    );
+    if list.groups.is_empty() {
+        html.push_str("<p>No matters are listed for this day.</p>\n");
+    }
+    for group in &list.groups {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<thead><tr><th>No.</th><th>Case</th><th>Parties</th>\
+             <th>Time</th><th>Remarks</th></tr></thead>\n<tbody>\n",
+            escape(&group.title)
+        ));
+        for matter in &group.matters {
+            let case = match &matter.case_type {
+                Some(kind) => format!("{}\n{}", matter.case_number, kind),
+                None => matter.case_number.clone(),
+            };
+            html.push_str(&format!(
+                "<tr{}><td class=\"no\">{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td class=\"remarks\">{}</td></tr>\n",
+                if matter.file_not_returned.is_some() {
+                    " class=\"file-out\""
+                } else {
+                    ""
+                },
+                matter.number,
+                escape(&case),
+                escape(&parties_line(matter)),
+                escape(&time_line(matter, list)),
+                escape(&remarks(matter))
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+    html.push_str(&format!(
+        "<p class=\"footer\">{} matters; {} with files not returned. Generated {}.</p>\n\
+         </body>\n</html>\n",
+        list.total,
+        list.files_not_returned,
+        escape(&list.generated_at)
+    ));
+    html
+}
+
+const MARGIN: f32 = 36.0;
+const TEXT_SIZE: f32 = 9.0;
+const LINE_HEIGHT: f32 = 11.0;
+/// Column left edges and widths: No., Case, Parties, Time, Remarks
+const COLUMNS: [(f32, f32); 5] = [
+    (MARGIN, 24.0),
+    (MARGIN + 28.0, 104.0),
+    (MARGIN + 136.0, 220.0),
+    (MARGIN + 360.0, 56.0),
+    (MARGIN + 420.0, pdf::PAGE_WIDTH - MARGIN * 2.0 - 420.0),
+];
+const HEADINGS: [&str; 5] = ["No.", "Case", "Parties", "Time", "Remarks"];
+
+/// Lays the list out page by page, starting a new page when the next block
+/// would run past the bottom margin
+struct PdfLayout<'a> {
+    pdf: PdfWriter,
+    list: &'a CauseList,
+    y: f32,
+}
+
+impl PdfLayout<'_> {
+    fn new_page(&mut self) {
+        self.pdf.add_page();
+        let page = self.pdf.page_count();
+        self.y = MARGIN + 14.0;
+        self.pdf
+            .text(MARGIN, self.y, 14.0, Font::Bold, "Cause list");
+        let number = format!("Page {}", page);
+        let width = pdf::text_width(&number, TEXT_SIZE, Font::Regular);
+        self.pdf.text(
+            pdf::PAGE_WIDTH - MARGIN - width,
+            self.y,
+            TEXT_SIZE,
+            Font::Regular,
+            &number,
+        );
+        self.y += 16.0;
+        self.pdf
+            .text(MARGIN, self.y, 10.0, Font::Regular, &subtitle(self.list));
+        self.y += 10.0;
+    }
+
+    /// Make room for `height` more points, turning the page if needed
+    fn reserve(&mut self, height: f32) {
+        if self.pdf.page_count() == 0 || self.y + height > pdf::PAGE_HEIGHT - MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn group_heading(&mut self, title: &str) {
+        self.reserve(LINE_HEIGHT * 4.0);
+        self.y += 18.0;
+        self.pdf.text(MARGIN, self.y, 11.0, Font::Bold, title);
+        self.y += 4.0;
+        self.pdf
+            .line(MARGIN, self.y, pdf::PAGE_WIDTH - MARGIN, self.y, 0.8);
+        self.y += LINE_HEIGHT;
+        for ((x, _), heading) in COLUMNS.iter().zip(HEADINGS) {
+            self.pdf.text(*x, self.y, 8.0, Font::Bold, heading);
+        }
+        self.y += 4.0;
+    }
+
+    fn row(&mut self, cells: [String; 5], emphasise_last: bool) {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(COLUMNS)
+            .enumerate()
+            .map(|(i, (text, (_, width)))| {
+                let font = if i == 4 && emphasise_last {
+                    Font::Bold
+                } else {
+                    Font::Regular
+                };
+                pdf::wrap(text, TEXT_SIZE, font, width)
+            })
+            .collect();
+        let lines = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        self.reserve(lines as f32 * LINE_HEIGHT + 4.0);
+        self.y += LINE_HEIGHT;
+        for (i, (cell, (x, _))) in wrapped.iter().zip(COLUMNS).enumerate() {
+            let font = if i == 4 && emphasise_last {
+                Font::Bold
+            } else {
+                Font::Regular
+            };
+            for (n, line) in cell.iter().enumerate() {
+                self.pdf
+                    .text(x, self.y + n as f32 * LINE_HEIGHT, TEXT_SIZE, font, line);
+            }
+        }
+        self.y += (lines - 1) as f32 * LINE_HEIGHT + 4.0;
+        self.pdf
+            .line(MARGIN, self.y, pdf::PAGE_WIDTH - MARGIN, self.y, 0.3);
+    }
+}
+
+/// The list as a printable A4 PDF
+pub fn to_pdf(list: &CauseList) -> Vec<u8> {
+    let mut layout = PdfLayout {
+        pdf: PdfWriter::new(),
+        list,
+        y: 0.0,
+    };
+    layout.reserve(0.0);
+    if list.groups.is_empty() {
+        layout.y += 24.0;
+        layout.pdf.text(
+            MARGIN,
+            layout.y,
+            TEXT_SIZE,
+            Font::Regular,
+            "No matters are listed for this day.",
+        );
+    }
+    for group in &list.groups {
+        layout.group_heading(&group.title);
+        for matter in &group.matters {
+            let case = match &matter.case_type {
+                Some(kind) => format!("{}\n{}", matter.case_number, kind),
+                None => matter.case_number.clone(),
+            };
+            layout.row(
+                [
+                    matter.number.to_string(),
+                    case,
+                    parties_line(matter),
+                    time_line(matter, list),
+                    remarks(matter),
+                ],
+                matter.file_not_returned.is_some(),
+            );
+        }
+    }
+    layout.reserve(LINE_HEIGHT * 3.0);
+    layout.y += LINE_HEIGHT * 2.0;
+    layout.pdf.text(
+        MARGIN,
+        layout.y,
+        8.0,
+        Font::Regular,
+        &format!(
+            "{} matters; {} with files not returned. Generated {}.",
+            list.total, list.files_not_returned, list.generated_at
+        ),
+    );
+    layout.pdf.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    const DAY: &str = "2026-03-02";
+
+    fn case(conn: &Connection, number: &str, priority: &str, accused: Option<&str>) -> i64 {
+        conn.execute(
+            "INSERT INTO cases (title, case_number, priority, complainant_name, accused_name)
+             VALUES (?1, ?2, ?3, 'Republic', ?4)",
+            params![format!("R v {}", number), number, priority, accused],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn hearing(conn: &Connection, case_id: i64, purpose: &str, time: Option<&str>, room: &str) {
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date, hearing_time, courtroom,
+                 presiding_officer, purpose)
+             VALUES (?1, ?2, ?3, ?4, 'Hon. Wanjiru', ?5)",
+            params![case_id, DAY, time, room, purpose],
+        )
+        .unwrap();
+    }
+
+    fn file(conn: &Connection, number: &str, purpose: &str, required_on: &str, returned: bool) {
+        conn.execute(
+            "INSERT INTO files (case_number, case_type, purpose, current_location, notes,
+                 required_on, date_returned)
+             VALUES (?1, 'Criminal', ?2, 'Registry', '', ?3, ?4)",
+            params![
+                number,
+                purpose,
+                required_on,
+                returned.then_some("2026-02-01 10:00:00")
+            ],
+        )
+        .unwrap();
+    }
+
+    fn numbers(group: &CauseListGroup) -> Vec<&str> {
+        group
+            .matters
+            .iter()
+            .map(|m| m.case_number.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn matters_are_grouped_merged_and_ordered() {
+        let conn = db::open_in_memory().unwrap();
+        let nine = case(&conn, "CR 9/2026", "Medium", Some("Otieno"));
+        let ten = case(&conn, "CR 10/2026", "Medium", None);
+        let urgent = case(&conn, "CR 11/2026", "High", None);
+        hearing(&conn, ten, "Hearing", Some("10:00"), "Court 1");
+        hearing(&conn, nine, "Hearing", Some("09:00"), "Court 1");
+        hearing(&conn, urgent, "Hearing", None, "Court 2");
+        hearing(&conn, nine, "Mention", None, "Court 1");
+        // The file for a heard case joins that matter; one for a case the
+        // registry typed differently or never opened stands alone
+        file(
+            &conn,
+            " cr 9/2026",
+            "Hearing",
+            &format!("{} 08:00:00", DAY),
+            false,
+        );
+        file(&conn, "MISC 4/2026", "Other", DAY, false);
+        conn.execute(
+            "INSERT INTO offenders (full_name) VALUES ('Mary Atieno')",
+            [],
+        )
+        .unwrap();
+        let witness = conn.last_insert_rowid();
+        offender_cases::link(&conn, witness, ten, CaseRole::Witness, None, None).unwrap();
+
+        let list = generate(&conn, DAY, None).unwrap();
+        let titles: Vec<_> = list.groups.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["Mentions", "Hearings", "Other matters"]);
+        assert_eq!(
+            numbers(&list.groups[1]),
+            vec!["CR 11/2026", "CR 9/2026", "CR 10/2026"]
+        );
+        assert_eq!(list.total, 5);
+        let numbered: Vec<_> = list
+            .groups
+            .iter()
+            .flat_map(|g| &g.matters)
+            .map(|m| m.number)
+            .collect();
+        assert_eq!(numbered, vec![1, 2, 3, 4, 5]);
+
+        // The file joins the case's earliest hearing, not its mention too
+        assert!(list.groups[1].matters[1].file_id.is_some());
+        assert!(list.groups[0].matters[0].file_id.is_none());
+        let misc = &list.groups[2].matters[0];
+        assert_eq!((misc.case_id, misc.priority.as_str()), (None, "Medium"));
+
+        let parties = |m: &Matter| {
+            m.parties
+                .iter()
+                .map(|p| (p.name.clone(), p.role, p.offender_id.is_some()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            parties(&list.groups[1].matters[1]),
+            vec![
+                ("Republic".to_string(), CaseRole::Complainant, false),
+                ("Otieno".to_string(), CaseRole::Accused, false)
+            ]
+        );
+        assert_eq!(
+            parties(&list.groups[1].matters[2]),
+            vec![
+                ("Republic".to_string(), CaseRole::Complainant, false),
+                ("Mary Atieno".to_string(), CaseRole::Witness, true)
+            ]
+        );
+        assert_eq!(list.presiding_officer.as_deref(), Some("Hon. Wanjiru"));
+    }
+
+    #[test]
+    fn a_courtroom_list_leaves_out_other_rooms_and_loose_files() {
+        let conn = db::open_in_memory().unwrap();
+        let one = case(&conn, "CR 1/2026", "Medium", None);
+        let two = case(&conn, "CR 2/2026", "Medium", None);
+        hearing(&conn, one, "Hearing", None, "Court 1");
+        hearing(&conn, two, "Hearing", None, "Court 2");
+        file(&conn, "CR 3/2026", "Mention", DAY, false);
+
+        let list = generate(&conn, DAY, Some(" court 1 ")).unwrap();
+        assert_eq!(list.courtroom.as_deref(), Some("court 1"));
+        assert_eq!(list.total, 1);
+        assert_eq!(numbers(&list.groups[0]), vec!["CR 1/2026"]);
+        assert!(matches!(
+            generate(&conn, "2 March", None),
+            Err(crate::error::AppError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn files_still_out_are_flagged_on_every_form() {
+        let conn = db::open_in_memory().unwrap();
+        let id = case(&conn, "CR 5/2026", "Medium", Some("<Kip> & Sons"));
+        hearing(&conn, id, "Ruling", Some("11:30"), "Court 1");
+        file(&conn, "CR 5/2026", "Hearing", "2026-01-10 09:00:00", false);
+        file(&conn, "CR 5/2026", "Hearing", "2026-02-10 09:00:00", true);
+
+        let list = generate(&conn, DAY, None).unwrap();
+        assert_eq!(list.files_not_returned, 1);
+        let out = list.groups[0].matters[0]
+            .file_not_returned
+            .as_ref()
+            .unwrap();
+        assert_eq!(out.required_on, "2026-01-10 09:00:00");
+
+        let html = to_html(&list);
+        assert!(html.contains("Monday, 2 March 2026"));
+        assert!(html.contains("Republic v &lt;Kip&gt; &amp; Sons"));
+        assert!(html.contains("<tr class=\"file-out\">"));
+        assert!(html.contains("File not returned (out since 2026-01-10, at Registry)"));
+
+        let pdf = to_pdf(&list);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("(CR 5/2026) Tj"));
+        assert!(text.contains("/F2 9.0 Tf") && text.contains("File not returned"));
+    }
+
+    #[test]
+    fn long_lists_run_onto_more_pages() {
+        let conn = db::open_in_memory().unwrap();
+        for n in 1..=80 {
+            let id = case(&conn, &format!("CR {}/2026", n), "Medium", None);
+            hearing(&conn, id, "Mention", None, "Court 1");
+        }
+        let pdf =
+            String::from_utf8_lossy(&to_pdf(&generate(&conn, DAY, None).unwrap())).into_owned();
+        assert!(pdf.contains("(Page 2) Tj"));
+
+        let empty = generate(&conn, "2026-03-03", None).unwrap();
+        assert!(to_html(&empty).contains("No matters are listed for this day."));
+        assert!(String::from_utf8_lossy(&to_pdf(&empty)).contains("/Count 1"));
+    }
+}
@@ -0,0 +1,700 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+use crate::services::{offence_catalogue, sentences};
+
+// ============================================================================
+// Charges
+//
+// The counts of a case (migration 0013), each with its offence, statute and
+// section, the plea and the verdict. A count is against one accused, or
+// against every accused when no offender is given, and may point at its
+// offence in the catalogue (migration 0014). Sentences are passed on a count
+// through `sentences.charge_id`. `cases.verdict` is rewritten from
+// the verdicts on every change, the same way `cases.sentence` follows the
+// sentences, so older clients and sync keep reading one line.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Plea {
+    Guilty,
+    #[serde(rename = "Not Guilty")]
+    NotGuilty,
+}
+
+impl Plea {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plea::Guilty => "Guilty",
+            Plea::NotGuilty => "Not Guilty",
+        }
+    }
+
+    pub fn parse(plea: &str) -> Option<Plea> {
+        match plea {
+            "Guilty" => Some(Plea::Guilty),
+            "Not Guilty" => Some(Plea::NotGuilty),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    Convicted,
+    Acquitted,
+    Withdrawn,
+    Dismissed,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Convicted => "Convicted",
+            Verdict::Acquitted => "Acquitted",
+            Verdict::Withdrawn => "Withdrawn",
+            Verdict::Dismissed => "Dismissed",
+        }
+    }
+
+    pub fn parse(verdict: &str) -> Option<Verdict> {
+        match verdict {
+            "Convicted" => Some(Verdict::Convicted),
+            "Acquitted" => Some(Verdict::Acquitted),
+            "Withdrawn" => Some(Verdict::Withdrawn),
+            "Dismissed" => Some(Verdict::Dismissed),
+            _ => None,
+        }
+    }
+
+    /// Whether the verdict follows a plea; a count can be withdrawn or
+    /// dismissed before the accused pleads
+    fn needs_plea(&self) -> bool {
+        matches!(self, Verdict::Convicted | Verdict::Acquitted)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Charge {
+    pub charge_id: i64,
+    pub case_id: i64,
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub count_number: i64,
+    pub catalogue_id: Option<i64>,
+    pub offence: String,
+    pub statute: Option<String>,
+    pub section: Option<String>,
+    pub particulars: Option<String>,
+    pub plea: Option<Plea>,
+    pub plea_date: Option<String>,
+    pub verdict: Option<Verdict>,
+    pub verdict_date: Option<String>,
+    /// The sentences passed on this count, in words
+    pub sentence: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const CHARGE_SELECT: &str = "SELECT ch.charge_id, ch.case_id, ch.offender_id, o.full_name,
+        ch.count_number, ch.offence, ch.statute, ch.section, ch.particulars, ch.plea,
+        ch.plea_date, ch.verdict, ch.verdict_date,
+        (SELECT GROUP_CONCAT(summary, '; ') FROM
+            (SELECT summary FROM sentences s WHERE s.charge_id = ch.charge_id
+             ORDER BY s.sentence_id)),
+        ch.notes, ch.created_by, ch.created_at, ch.catalogue_id
+    FROM charges ch
+    LEFT JOIN offenders o ON o.offender_id = ch.offender_id";
+
+impl Charge {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let plea: Option<String> = row.get(9)?;
+        let verdict: Option<String> = row.get(11)?;
+        Ok(Charge {
+            charge_id: row.get(0)?,
+            case_id: row.get(1)?,
+            offender_id: row.get(2)?,
+            offender_name: row.get(3)?,
+            count_number: row.get(4)?,
+            catalogue_id: row.get(17)?,
+            offence: row.get(5)?,
+            statute: row.get(6)?,
+            section: row.get(7)?,
+            particulars: row.get(8)?,
+            plea: plea
+                .map(|p| {
+                    Plea::parse(&p).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            9,
+                            rusqlite::types::Type::Text,
+                            format!("unknown plea {}", p).into(),
+                        )
+                    })
+                })
+                .transpose()?,
+            plea_date: row.get(10)?,
+            verdict: verdict
+                .map(|v| {
+                    Verdict::parse(&v).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            11,
+                            rusqlite::types::Type::Text,
+                            format!("unknown verdict {}", v).into(),
+                        )
+                    })
+                })
+                .transpose()?,
+            verdict_date: row.get(12)?,
+            sentence: row.get(13)?,
+            notes: row.get(14)?,
+            created_by: row.get(15)?,
+            created_at: row.get(16)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCharge {
+    pub case_id: i64,
+    /// The accused charged on this count; every accused if not given
+    pub offender_id: Option<i64>,
+    /// The next free count if not given
+    pub count_number: Option<i64>,
+    /// The offence in the catalogue; it fills in the offence, statute and
+    /// section left blank
+    pub catalogue_id: Option<i64>,
+    pub offence: Option<String>,
+    pub statute: Option<String>,
+    pub section: Option<String>,
+    pub particulars: Option<String>,
+    pub notes: Option<String>,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+pub fn get(conn: &Connection, charge_id: i64) -> AppResult<Charge> {
+    conn.query_row(
+        &format!("{} WHERE ch.charge_id = ?1", CHARGE_SELECT),
+        params![charge_id],
+        Charge::from_row,
+    )
+    .optional()
+    .context("Failed to fetch charge")?
+    .ok_or_else(|| AppError::not_found("Charge not found"))
+}
+
+/// Every count of a case, in count order
+pub fn list_for_case(conn: &Connection, case_id: i64) -> AppResult<Vec<Charge>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE ch.case_id = ?1 ORDER BY ch.count_number",
+            CHARGE_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let charges = stmt
+        .query_map(params![case_id], Charge::from_row)
+        .context("Failed to fetch charges")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read charges")?;
+    Ok(charges)
+}
+
+/// What `cases.verdict` should read, or None while no count has a verdict:
+/// the verdict alone for a single count, "Convicted on all 3 counts" when
+/// every count went the same way, and count by count otherwise
+fn verdict_text(conn: &Connection, case_id: i64) -> AppResult<Option<String>> {
+    let charges = list_for_case(conn, case_id)?;
+    let decided: Vec<&Charge> = charges.iter().filter(|c| c.verdict.is_some()).collect();
+    let Some(first) = decided.first() else {
+        return Ok(None);
+    };
+    let named = decided.iter().any(|c| c.offender_name.is_some());
+    if decided.len() == charges.len()
+        && !named
+        && decided.iter().all(|c| c.verdict == first.verdict)
+    {
+        let verdict = first.verdict.map(|v| v.as_str()).unwrap_or_default();
+        return Ok(Some(match charges.len() {
+            1 => verdict.to_string(),
+            n => format!("{} on all {} counts", verdict, n),
+        }));
+    }
+    let parts: Vec<String> = decided
+        .iter()
+        .map(|c| {
+            let verdict = c.verdict.map(|v| v.as_str()).unwrap_or_default();
+            match &c.offender_name {
+                Some(name) => format!("Count {} ({}): {}", c.count_number, name, verdict),
+                None => format!("Count {}: {}", c.count_number, verdict),
+            }
+        })
+        .collect();
+    Ok(Some(parts.join("; ")))
+}
+
+/// Bring `cases.verdict` in line with the counts. As with sentences, the
+/// text is only cleared if it still reads what the counts rendered.
+fn write_verdict(conn: &Connection, case_id: i64, before: Option<String>) -> AppResult<()> {
+    let after = verdict_text(conn, case_id)?;
+    if after == before {
+        return Ok(());
+    }
+    let expected = if after.is_none() { before } else { None };
+    conn.execute(
+        "UPDATE cases SET verdict = ?2, sync_status = 'pending', has_changes = 1,
+         last_modified = CURRENT_TIMESTAMP
+         WHERE case_id = ?1 AND (?3 IS NULL OR verdict IS ?3)",
+        params![case_id, after, expected],
+    )
+    .context("Failed to update case verdict")?;
+    Ok(())
+}
+
+/// The case's rendered verdict and sentence before a change
+struct CaseText {
+    case_id: i64,
+    verdict: Option<String>,
+    sentence: Option<String>,
+}
+
+impl CaseText {
+    fn read(conn: &Connection, case_id: i64) -> AppResult<CaseText> {
+        Ok(CaseText {
+            case_id,
+            verdict: verdict_text(conn, case_id)?,
+            sentence: sentences::case_text(conn, case_id)?,
+        })
+    }
+
+    fn rewrite(self, conn: &Connection) -> AppResult<()> {
+        write_verdict(conn, self.case_id, self.verdict)?;
+        sentences::rewrite_case_text(conn, self.case_id, self.sentence)
+    }
+}
+
+/// The offence, statute and section a count is written with
+struct Wording {
+    offence: String,
+    statute: Option<String>,
+    section: Option<String>,
+}
+
+/// Check the charge and work out its wording
+fn validate(conn: &Connection, charge: &NewCharge, charge_id: Option<i64>) -> AppResult<Wording> {
+    let catalogued = offence_catalogue::resolve(conn, charge.catalogue_id)?;
+    let wording = Wording {
+        offence: optional_text(charge.offence.clone())
+            .or_else(|| catalogued.as_ref().map(|c| c.offence.clone()))
+            .ok_or_else(|| AppError::field("offence", "Enter the offence"))?,
+        statute: optional_text(charge.statute.clone())
+            .or_else(|| catalogued.as_ref().map(|c| c.act_title.clone())),
+        section: optional_text(charge.section.clone())
+            .or_else(|| catalogued.as_ref().map(|c| c.section.clone())),
+    };
+    let case: Option<bool> = conn
+        .query_row(
+            "SELECT COALESCE(is_deleted, 0) = 0 FROM cases WHERE case_id = ?1",
+            params![charge.case_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch case")?;
+    if case != Some(true) {
+        return Err(AppError::not_found("Case not found"));
+    }
+    if let Some(offender_id) = charge.offender_id {
+        let accused: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM offender_cases
+                 WHERE offender_id = ?1 AND case_id = ?2 AND role = 'accused'
+                   AND COALESCE(is_deleted, 0) = 0",
+                params![offender_id, charge.case_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to check the accused")?;
+        if accused.is_none() {
+            return Err(AppError::field(
+                "offender_id",
+                "The offender is not an accused in this case",
+            ));
+        }
+    }
+    if let Some(count) = charge.count_number {
+        if count < 1 {
+            return Err(AppError::field(
+                "count_number",
+                "Counts are numbered from 1",
+            ));
+        }
+        let taken: Option<i64> = conn
+            .query_row(
+                "SELECT charge_id FROM charges
+                 WHERE case_id = ?1 AND count_number = ?2 AND charge_id IS NOT ?3",
+                params![charge.case_id, count, charge_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to check count number")?;
+        if taken.is_some() {
+            return Err(AppError::Conflict(format!(
+                "The case already has a count {}.",
+                count
+            )));
+        }
+    }
+    Ok(wording)
+}
+
+/// Add a count to a case
+pub fn add(
+    conn: &mut Connection,
+    charge: NewCharge,
+    created_by: Option<&str>,
+) -> AppResult<Charge> {
+    let tx = conn.transaction()?;
+    let wording = validate(&tx, &charge, None)?;
+    let count_number = match charge.count_number {
+        Some(count) => count,
+        None => tx
+            .query_row(
+                "SELECT COALESCE(MAX(count_number), 0) + 1 FROM charges WHERE case_id = ?1",
+                params![charge.case_id],
+                |row| row.get(0),
+            )
+            .context("Failed to number count")?,
+    };
+    let text = CaseText::read(&tx, charge.case_id)?;
+    tx.execute(
+        "INSERT INTO charges (case_id, offender_id, count_number, offence, statute, section,
+             particulars, notes, created_by, catalogue_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            charge.case_id,
+            charge.offender_id,
+            count_number,
+            wording.offence,
+            wording.statute,
+            wording.section,
+            optional_text(charge.particulars),
+            optional_text(charge.notes),
+            created_by,
+            charge.catalogue_id
+        ],
+    )
+    .context("Failed to add charge")?;
+    let id = tx.last_insert_rowid();
+    text.rewrite(&tx)?;
+    let added = get(&tx, id)?;
+    tx.commit()?;
+    Ok(added)
+}
+
+/// Correct a count's details. The plea and verdict are recorded separately.
+pub fn update(conn: &mut Connection, charge_id: i64, charge: NewCharge) -> AppResult<Charge> {
+    let tx = conn.transaction()?;
+    let current = get(&tx, charge_id)?;
+    if charge.case_id != current.case_id {
+        return Err(AppError::field(
+            "case_id",
+            "A count cannot move to another case",
+        ));
+    }
+    let wording = validate(&tx, &charge, Some(charge_id))?;
+    if charge.offender_id != current.offender_id {
+        let sentenced: Option<i64> = tx
+            .query_row(
+                "SELECT sentence_id FROM sentences WHERE charge_id = ?1
+                   AND offender_id IS NOT ?2 LIMIT 1",
+                params![charge_id, charge.offender_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to check sentences")?;
+        if charge.offender_id.is_some() && sentenced.is_some() {
+            return Err(AppError::Conflict(
+                "Another accused was sentenced on this count.".to_string(),
+            ));
+        }
+    }
+    let text = CaseText::read(&tx, current.case_id)?;
+    tx.execute(
+        "UPDATE charges SET offender_id = ?2, count_number = ?3, offence = ?4, statute = ?5,
+             section = ?6, particulars = ?7, notes = ?8, catalogue_id = ?9,
+             last_modified = CURRENT_TIMESTAMP
+         WHERE charge_id = ?1",
+        params![
+            charge_id,
+            charge.offender_id,
+            charge.count_number.unwrap_or(current.count_number),
+            wording.offence,
+            wording.statute,
+            wording.section,
+            optional_text(charge.particulars),
+            optional_text(charge.notes),
+            charge.catalogue_id
+        ],
+    )
+    .context("Failed to update charge")?;
+    text.rewrite(&tx)?;
+    let updated = get(&tx, charge_id)?;
+    tx.commit()?;
+    Ok(updated)
+}
+
+/// Remove a count entered by mistake. A count withdrawn in court keeps its
+/// record with a Withdrawn verdict instead.
+pub fn delete(conn: &mut Connection, charge_id: i64) -> AppResult<()> {
+    let tx = conn.transaction()?;
+    let charge = get(&tx, charge_id)?;
+    if charge.sentence.is_some() {
+        return Err(AppError::Conflict(
+            "A sentence was passed on this count; remove it first.".to_string(),
+        ));
+    }
+    let text = CaseText::read(&tx, charge.case_id)?;
+    tx.execute(
+        "DELETE FROM charges WHERE charge_id = ?1",
+        params![charge_id],
+    )
+    .context("Failed to delete charge")?;
+    text.rewrite(&tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Record the plea on a count, or clear one entered by mistake
+pub fn record_plea(
+    conn: &Connection,
+    charge_id: i64,
+    plea: Option<Plea>,
+    plea_date: Option<&str>,
+) -> AppResult<Charge> {
+    let charge = get(conn, charge_id)?;
+    let plea_date = match plea {
+        Some(_) => Some(optional_date("plea_date", plea_date)?.unwrap_or_else(today)),
+        None => None,
+    };
+    if plea.is_none() && charge.verdict.is_some_and(|v| v.needs_plea()) {
+        return Err(AppError::Conflict(
+            "The count has a verdict; clear it before the plea.".to_string(),
+        ));
+    }
+    if let (Some(plea_date), Some(verdict_date)) = (&plea_date, &charge.verdict_date) {
+        if plea_date > verdict_date {
+            return Err(AppError::field(
+                "plea_date",
+                "The plea cannot come after the verdict",
+            ));
+        }
+    }
+    conn.execute(
+        "UPDATE charges SET plea = ?2, plea_date = ?3, last_modified = CURRENT_TIMESTAMP
+         WHERE charge_id = ?1",
+        params![charge_id, plea.map(|p| p.as_str()), plea_date],
+    )
+    .context("Failed to record plea")?;
+    get(conn, charge_id)
+}
+
+/// Record the verdict on a count, or clear one entered by mistake, and
+/// rewrite the case verdict
+pub fn record_verdict(
+    conn: &mut Connection,
+    charge_id: i64,
+    verdict: Option<Verdict>,
+    verdict_date: Option<&str>,
+) -> AppResult<Charge> {
+    let verdict_date = match verdict {
+        Some(_) => Some(optional_date("verdict_date", verdict_date)?.unwrap_or_else(today)),
+        None => None,
+    };
+    let tx = conn.transaction()?;
+    let charge = get(&tx, charge_id)?;
+    if let Some(verdict) = verdict {
+        if verdict.needs_plea() && charge.plea.is_none() {
+            return Err(AppError::validation(format!(
+                "Record the plea on count {} first.",
+                charge.count_number
+            )));
+        }
+    }
+    if let (Some(verdict_date), Some(plea_date)) = (&verdict_date, &charge.plea_date) {
+        if verdict_date < plea_date {
+            return Err(AppError::field(
+                "verdict_date",
+                "The verdict cannot come before the plea",
+            ));
+        }
+    }
+    let text = CaseText::read(&tx, charge.case_id)?;
+    tx.execute(
+        "UPDATE charges SET verdict = ?2, verdict_date = ?3, last_modified = CURRENT_TIMESTAMP
+         WHERE charge_id = ?1",
+        params![charge_id, verdict.map(|v| v.as_str()), verdict_date],
+    )
+    .context("Failed to record verdict")?;
+    text.rewrite(&tx)?;
+    let recorded = get(&tx, charge_id)?;
+    tx.commit()?;
+    Ok(recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn case(conn: &Connection) -> i64 {
+        conn.execute("INSERT INTO cases (title) VALUES ('R v Doe')", [])
+            .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn accused(conn: &Connection, case_id: i64, full_name: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO offenders (full_name) VALUES (?1)",
+            params![full_name],
+        )
+        .unwrap();
+        let offender_id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO offender_cases (offender_id, case_id, role) VALUES (?1, ?2, 'accused')",
+            params![offender_id, case_id],
+        )
+        .unwrap();
+        offender_id
+    }
+
+    fn count(conn: &mut Connection, case_id: i64, offender_id: Option<i64>) -> i64 {
+        add(
+            conn,
+            NewCharge {
+                case_id,
+                offender_id,
+                count_number: None,
+                catalogue_id: None,
+                offence: Some("Stealing".to_string()),
+                statute: Some("Penal Code".to_string()),
+                section: Some("268".to_string()),
+                particulars: None,
+                notes: None,
+            },
+            None,
+        )
+        .unwrap()
+        .charge_id
+    }
+
+    fn decide(conn: &mut Connection, charge_id: i64, verdict: Option<Verdict>) {
+        record_plea(conn, charge_id, Some(Plea::NotGuilty), Some("2026-02-01")).unwrap();
+        record_verdict(conn, charge_id, verdict, Some("2026-03-01")).unwrap();
+    }
+
+    fn case_verdict(conn: &Connection, case_id: i64) -> Option<String> {
+        conn.query_row(
+            "SELECT verdict FROM cases WHERE case_id = ?1",
+            params![case_id],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn single_count_reads_as_the_verdict() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+        let charge_id = count(&mut conn, case_id, None);
+        assert_eq!(verdict_text(&conn, case_id).unwrap(), None);
+
+        decide(&mut conn, charge_id, Some(Verdict::Convicted));
+        assert_eq!(case_verdict(&conn, case_id).as_deref(), Some("Convicted"));
+    }
+
+    #[test]
+    fn counts_that_went_the_same_way_are_summed_up() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+        let charges: Vec<i64> = (0..3).map(|_| count(&mut conn, case_id, None)).collect();
+
+        decide(&mut conn, charges[0], Some(Verdict::Acquitted));
+        decide(&mut conn, charges[1], Some(Verdict::Acquitted));
+        // Until every count is decided the verdicts are listed
+        assert_eq!(
+            case_verdict(&conn, case_id).as_deref(),
+            Some("Count 1: Acquitted; Count 2: Acquitted")
+        );
+
+        decide(&mut conn, charges[2], Some(Verdict::Acquitted));
+        assert_eq!(
+            case_verdict(&conn, case_id).as_deref(),
+            Some("Acquitted on all 3 counts")
+        );
+
+        decide(&mut conn, charges[1], Some(Verdict::Convicted));
+        assert_eq!(
+            case_verdict(&conn, case_id).as_deref(),
+            Some("Count 1: Acquitted; Count 2: Convicted; Count 3: Acquitted")
+        );
+    }
+
+    #[test]
+    fn counts_against_one_accused_name_them() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+        let doe = accused(&conn, case_id, "John Doe");
+        let roe = accused(&conn, case_id, "Richard Roe");
+        let first = count(&mut conn, case_id, Some(doe));
+        let second = count(&mut conn, case_id, Some(roe));
+
+        decide(&mut conn, first, Some(Verdict::Convicted));
+        decide(&mut conn, second, Some(Verdict::Convicted));
+        assert_eq!(
+            case_verdict(&conn, case_id).as_deref(),
+            Some("Count 1 (John Doe): Convicted; Count 2 (Richard Roe): Convicted")
+        );
+    }
+
+    #[test]
+    fn cleared_verdicts_keep_a_hand_written_case_verdict() {
+        let mut conn = db::open_in_memory().unwrap();
+        let case_id = case(&conn);
+        let charge_id = count(&mut conn, case_id, None);
+
+        decide(&mut conn, charge_id, Some(Verdict::Convicted));
+        record_verdict(&mut conn, charge_id, None, None).unwrap();
+        assert_eq!(case_verdict(&conn, case_id), None);
+
+        decide(&mut conn, charge_id, Some(Verdict::Convicted));
+        conn.execute(
+            "UPDATE cases SET verdict = 'Convicted and fined' WHERE case_id = ?1",
+            params![case_id],
+        )
+        .unwrap();
+        record_verdict(&mut conn, charge_id, None, None).unwrap();
+        assert_eq!(
+            case_verdict(&conn, case_id).as_deref(),
+            Some("Convicted and fined")
+        );
+    }
+}
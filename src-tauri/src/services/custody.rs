@@ -0,0 +1,854 @@
+use chrono::{Days, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+
+// ============================================================================
+// Custody register
+//
+// Who is held, where, on what warrant and until when (migration 0009). Each
+// spell in custody is a row, open until release; a person has at most one
+// open spell. The offender's `facility` and `status` follow the open spell
+// so synced devices see where the person is. The name is kept on the row, so
+// the register still reads right after the offender record is deleted.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CustodyType {
+    #[default]
+    Remand,
+    Sentenced,
+}
+
+impl CustodyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CustodyType::Remand => "Remand",
+            CustodyType::Sentenced => "Sentenced",
+        }
+    }
+
+    pub fn parse(custody_type: &str) -> Option<CustodyType> {
+        match custody_type {
+            "Remand" => Some(CustodyType::Remand),
+            "Sentenced" => Some(CustodyType::Sentenced),
+            _ => None,
+        }
+    }
+}
+
+/// Offender status while a custody spell is open
+pub(crate) const IN_CUSTODY: &str = "In Custody";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Custody {
+    pub custody_id: i64,
+    /// None once the offender record has been deleted
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub custody_type: CustodyType,
+    pub facility: String,
+    pub committed_on: String,
+    pub warrant_number: Option<String>,
+    pub warrant_issued_on: Option<String>,
+    pub warrant_expires_on: Option<String>,
+    pub next_production_date: Option<String>,
+    pub last_produced_on: Option<String>,
+    pub released_on: Option<String>,
+    pub release_reason: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const CUSTODY_SELECT: &str = "SELECT k.custody_id, k.offender_id,
+        COALESCE(o.full_name, k.offender_name), k.case_id,
+        c.case_number, k.custody_type, k.facility, k.committed_on, k.warrant_number,
+        k.warrant_issued_on, k.warrant_expires_on, k.next_production_date,
+        k.last_produced_on, k.released_on, k.release_reason, k.notes, k.created_by,
+        k.created_at
+    FROM custody_records k
+    LEFT JOIN offenders o ON o.offender_id = k.offender_id
+    LEFT JOIN cases c ON c.case_id = k.case_id";
+
+impl Custody {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let custody_type: String = row.get(5)?;
+        Ok(Custody {
+            custody_id: row.get(0)?,
+            offender_id: row.get(1)?,
+            offender_name: row.get(2)?,
+            case_id: row.get(3)?,
+            case_number: row.get(4)?,
+            custody_type: CustodyType::parse(&custody_type).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    5,
+                    rusqlite::types::Type::Text,
+                    format!("unknown custody type {}", custody_type).into(),
+                )
+            })?,
+            facility: row.get(6)?,
+            committed_on: row.get(7)?,
+            warrant_number: row.get(8)?,
+            warrant_issued_on: row.get(9)?,
+            warrant_expires_on: row.get(10)?,
+            next_production_date: row.get(11)?,
+            last_produced_on: row.get(12)?,
+            released_on: row.get(13)?,
+            release_reason: row.get(14)?,
+            notes: row.get(15)?,
+            created_by: row.get(16)?,
+            created_at: row.get(17)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCustody {
+    pub offender_id: i64,
+    pub case_id: Option<i64>,
+    #[serde(default)]
+    pub custody_type: CustodyType,
+    pub facility: String,
+    /// YYYY-MM-DD, today if not given
+    pub committed_on: Option<String>,
+    pub warrant_number: Option<String>,
+    pub warrant_issued_on: Option<String>,
+    pub warrant_expires_on: Option<String>,
+    pub next_production_date: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A production in court, and what the court ordered next
+#[derive(Debug, Clone, Deserialize)]
+pub struct Production {
+    /// YYYY-MM-DD, today if not given
+    pub produced_on: Option<String>,
+    pub next_production_date: Option<String>,
+    /// A fresh warrant's expiry, if one was issued
+    pub warrant_expires_on: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    /// YYYY-MM-DD, today if not given
+    pub released_on: Option<String>,
+    /// e.g. bail granted, acquitted, sentence served
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CustodyFilter {
+    /// Leave out people already released
+    #[serde(default)]
+    pub in_custody_only: bool,
+    pub facility: Option<String>,
+    pub offender_id: Option<i64>,
+    pub case_id: Option<i64>,
+}
+
+/// Someone to be brought to court on a date
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductionDue {
+    pub production_date: String,
+    pub custody_id: i64,
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub facility: String,
+    pub warrant_number: Option<String>,
+    pub warrant_expires_on: Option<String>,
+    /// The hearing they are wanted for, when the date comes from the calendar
+    pub hearing_id: Option<i64>,
+    pub courtroom: Option<String>,
+    pub purpose: Option<String>,
+    /// The production date has passed and was never updated
+    pub overdue: bool,
+    /// The warrant runs out before the production date
+    pub warrant_lapsed: bool,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+pub fn get(conn: &Connection, custody_id: i64) -> AppResult<Custody> {
+    conn.query_row(
+        &format!("{} WHERE k.custody_id = ?1", CUSTODY_SELECT),
+        params![custody_id],
+        Custody::from_row,
+    )
+    .optional()
+    .context("Failed to fetch custody record")?
+    .ok_or_else(|| AppError::not_found("Custody record not found"))
+}
+
+fn get_open(conn: &Connection, custody_id: i64) -> AppResult<Custody> {
+    let custody = get(conn, custody_id)?;
+    if let Some(released_on) = &custody.released_on {
+        return Err(AppError::Conflict(format!(
+            "This person was released on {}.",
+            released_on
+        )));
+    }
+    Ok(custody)
+}
+
+/// Point the offender's facility and status at where they are held. Nothing
+/// to do once the offender record is gone.
+fn mark_offender(
+    conn: &Connection,
+    offender_id: Option<i64>,
+    facility: Option<&str>,
+) -> AppResult<()> {
+    let Some(offender_id) = offender_id else {
+        return Ok(());
+    };
+    let sql = match facility {
+        Some(_) => {
+            "UPDATE offenders SET facility = ?2, status = ?3, sync_status = 'pending',
+             has_changes = 1, last_modified = CURRENT_TIMESTAMP
+             WHERE offender_id = ?1"
+        }
+        None => {
+            "UPDATE offenders SET facility = ?2,
+             status = CASE WHEN status = ?3 THEN 'Active' ELSE status END,
+             sync_status = 'pending', has_changes = 1, last_modified = CURRENT_TIMESTAMP
+             WHERE offender_id = ?1"
+        }
+    };
+    conn.execute(sql, params![offender_id, facility, IN_CUSTODY])
+        .context("Failed to update offender")?;
+    Ok(())
+}
+
+/// Open a custody spell for an offender not already held
+pub fn admit(
+    conn: &mut Connection,
+    custody: NewCustody,
+    created_by: Option<&str>,
+) -> AppResult<Custody> {
+    let facility = custody.facility.trim().to_string();
+    if facility.is_empty() {
+        return Err(AppError::field(
+            "facility",
+            "Enter where the person is held",
+        ));
+    }
+    let committed_on =
+        optional_date("committed_on", custody.committed_on.as_deref())?.unwrap_or_else(today);
+    let warrant_issued_on =
+        optional_date("warrant_issued_on", custody.warrant_issued_on.as_deref())?;
+    let warrant_expires_on =
+        optional_date("warrant_expires_on", custody.warrant_expires_on.as_deref())?;
+    let next_production_date = optional_date(
+        "next_production_date",
+        custody.next_production_date.as_deref(),
+    )?;
+    if let (Some(issued), Some(expires)) = (&warrant_issued_on, &warrant_expires_on) {
+        if expires < issued {
+            return Err(AppError::field(
+                "warrant_expires_on",
+                "The warrant cannot expire before it was issued",
+            ));
+        }
+    }
+    if next_production_date
+        .as_ref()
+        .is_some_and(|next| *next < committed_on)
+    {
+        return Err(AppError::field(
+            "next_production_date",
+            "The production date cannot be before committal",
+        ));
+    }
+
+    let tx = conn.transaction()?;
+    let offender: Option<i64> = tx
+        .query_row(
+            "SELECT offender_id FROM offenders WHERE offender_id = ?1",
+            params![custody.offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch offender")?;
+    if offender.is_none() {
+        return Err(AppError::not_found("Offender not found"));
+    }
+    if let Some(case_id) = custody.case_id {
+        let case: Option<i64> = tx
+            .query_row(
+                "SELECT case_id FROM cases WHERE case_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+                params![case_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch case")?;
+        if case.is_none() {
+            return Err(AppError::not_found("Case not found"));
+        }
+    }
+    let held: Option<String> = tx
+        .query_row(
+            "SELECT facility FROM custody_records
+             WHERE offender_id = ?1 AND released_on IS NULL",
+            params![custody.offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check custody")?;
+    if let Some(held) = held {
+        return Err(AppError::Conflict(format!(
+            "This person is already in custody at {}.",
+            held
+        )));
+    }
+
+    tx.execute(
+        "INSERT INTO custody_records (offender_id, offender_name, case_id, custody_type,
+             facility, committed_on, warrant_number, warrant_issued_on, warrant_expires_on,
+             next_production_date, notes, created_by)
+         VALUES (?1, (SELECT full_name FROM offenders WHERE offender_id = ?1), ?2, ?3, ?4,
+             ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            custody.offender_id,
+            custody.case_id,
+            custody.custody_type.as_str(),
+            facility,
+            committed_on,
+            optional_text(custody.warrant_number),
+            warrant_issued_on,
+            warrant_expires_on,
+            next_production_date,
+            optional_text(custody.notes),
+            created_by
+        ],
+    )
+    .context("Failed to record custody")?;
+    let id = tx.last_insert_rowid();
+    mark_offender(&tx, Some(custody.offender_id), Some(&facility))?;
+    let admitted = get(&tx, id)?;
+    tx.commit()?;
+    Ok(admitted)
+}
+
+/// Record that the person was brought to court, with the next date and any
+/// fresh warrant
+pub fn record_production(
+    conn: &Connection,
+    custody_id: i64,
+    production: Production,
+) -> AppResult<Custody> {
+    let custody = get_open(conn, custody_id)?;
+    let produced_on =
+        optional_date("produced_on", production.produced_on.as_deref())?.unwrap_or_else(today);
+    let next_production_date = optional_date(
+        "next_production_date",
+        production.next_production_date.as_deref(),
+    )?;
+    let warrant_expires_on = optional_date(
+        "warrant_expires_on",
+        production.warrant_expires_on.as_deref(),
+    )?;
+    if produced_on < custody.committed_on {
+        return Err(AppError::field(
+            "produced_on",
+            "The production date cannot be before committal",
+        ));
+    }
+    if next_production_date
+        .as_ref()
+        .is_some_and(|next| *next <= produced_on)
+    {
+        return Err(AppError::field(
+            "next_production_date",
+            "The next production date must be after this one",
+        ));
+    }
+    conn.execute(
+        "UPDATE custody_records SET last_produced_on = ?2, next_production_date = ?3,
+             warrant_expires_on = COALESCE(?4, warrant_expires_on),
+             notes = COALESCE(?5, notes), last_modified = CURRENT_TIMESTAMP
+         WHERE custody_id = ?1",
+        params![
+            custody_id,
+            produced_on,
+            next_production_date,
+            warrant_expires_on,
+            optional_text(production.notes)
+        ],
+    )
+    .context("Failed to record production")?;
+    get(conn, custody_id)
+}
+
+/// Move the person to another facility
+pub fn transfer(conn: &mut Connection, custody_id: i64, facility: &str) -> AppResult<Custody> {
+    let facility = facility.trim();
+    if facility.is_empty() {
+        return Err(AppError::field(
+            "facility",
+            "Enter where the person is held",
+        ));
+    }
+    let tx = conn.transaction()?;
+    let custody = get_open(&tx, custody_id)?;
+    tx.execute(
+        "UPDATE custody_records SET facility = ?2, last_modified = CURRENT_TIMESTAMP
+         WHERE custody_id = ?1",
+        params![custody_id, facility],
+    )
+    .context("Failed to transfer custody")?;
+    mark_offender(&tx, custody.offender_id, Some(facility))?;
+    let moved = get(&tx, custody_id)?;
+    tx.commit()?;
+    Ok(moved)
+}
+
+/// Close the custody spell
+pub fn release(conn: &mut Connection, custody_id: i64, release: Release) -> AppResult<Custody> {
+    let reason = release.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::field("reason", "Give the reason for release"));
+    }
+    let released_on =
+        optional_date("released_on", release.released_on.as_deref())?.unwrap_or_else(today);
+    let tx = conn.transaction()?;
+    let custody = get_open(&tx, custody_id)?;
+    close_spell(&tx, &custody, &released_on, reason)?;
+    let released = get(&tx, custody_id)?;
+    tx.commit()?;
+    Ok(released)
+}
+
+fn close_spell(
+    conn: &Connection,
+    custody: &Custody,
+    released_on: &str,
+    reason: &str,
+) -> AppResult<()> {
+    if released_on < custody.committed_on.as_str() {
+        return Err(AppError::field(
+            "released_on",
+            "The release date cannot be before committal",
+        ));
+    }
+    conn.execute(
+        "UPDATE custody_records SET released_on = ?2, release_reason = ?3,
+             last_modified = CURRENT_TIMESTAMP
+         WHERE custody_id = ?1",
+        params![custody.custody_id, released_on, reason],
+    )
+    .context("Failed to record release")?;
+    mark_offender(conn, custody.offender_id, None)
+}
+
+/// Close the offender's open spell, if they have one, as part of a wider
+/// change such as bail being posted. The caller holds the transaction.
+pub(crate) fn release_offender(
+    conn: &Connection,
+    offender_id: i64,
+    released_on: &str,
+    reason: &str,
+) -> AppResult<Option<i64>> {
+    let open: Option<i64> = conn
+        .query_row(
+            "SELECT custody_id FROM custody_records
+             WHERE offender_id = ?1 AND released_on IS NULL",
+            params![offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check custody")?;
+    let Some(custody_id) = open else {
+        return Ok(None);
+    };
+    close_spell(conn, &get(conn, custody_id)?, released_on, reason)?;
+    Ok(Some(custody_id))
+}
+
+pub fn list(conn: &Connection, filter: &CustodyFilter) -> AppResult<Vec<Custody>> {
+    let facility = filter
+        .facility
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty());
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 = 0 OR k.released_on IS NULL)
+                 AND (?2 IS NULL OR k.facility = ?2 COLLATE NOCASE)
+                 AND (?3 IS NULL OR k.offender_id = ?3)
+                 AND (?4 IS NULL OR k.case_id = ?4)
+             ORDER BY k.released_on IS NOT NULL, k.committed_on DESC, k.custody_id DESC",
+            CUSTODY_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let records = stmt
+        .query_map(
+            params![
+                filter.in_custody_only,
+                facility,
+                filter.offender_id,
+                filter.case_id
+            ],
+            Custody::from_row,
+        )
+        .context("Failed to fetch custody records")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read custody records")?;
+    Ok(records)
+}
+
+/// Everyone held who must be brought to court from `from` to `days` days
+/// later: on their next production date, or for a scheduled hearing of
+/// their case. Production dates already past come first, marked overdue.
+pub fn due_for_production(
+    conn: &Connection,
+    from: &str,
+    days: u32,
+) -> AppResult<Vec<ProductionDue>> {
+    let from = parse_date("from", from)?;
+    let to = NaiveDate::parse_from_str(&from, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.checked_add_days(Days::new(days.into())))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| AppError::field("days", "Too many days"))?;
+
+    let mut stmt = conn
+        .prepare(
+            "WITH due(custody_id, day, hearing_id) AS (
+                 SELECT custody_id, next_production_date, NULL FROM custody_records
+                 WHERE released_on IS NULL AND next_production_date <= ?2
+                 UNION
+                 SELECT k.custody_id, h.hearing_date, h.hearing_id
+                 FROM custody_records k
+                 JOIN hearings h ON h.case_id = k.case_id
+                 WHERE k.released_on IS NULL AND h.status = 'Scheduled'
+                   AND h.hearing_date BETWEEN ?1 AND ?2
+             )
+             SELECT d.day, k.custody_id, k.offender_id, COALESCE(o.full_name, k.offender_name),
+                    k.case_id, c.case_number,
+                    k.facility, k.warrant_number, k.warrant_expires_on, d.hearing_id,
+                    h.courtroom, h.purpose, d.day < ?1,
+                    k.warrant_expires_on IS NOT NULL AND k.warrant_expires_on < d.day
+             FROM due d
+             JOIN custody_records k ON k.custody_id = d.custody_id
+             LEFT JOIN offenders o ON o.offender_id = k.offender_id
+             LEFT JOIN cases c ON c.case_id = k.case_id
+             LEFT JOIN hearings h ON h.hearing_id = d.hearing_id
+             -- A production date that matches a hearing is listed once, as the hearing
+             WHERE d.hearing_id IS NOT NULL OR NOT EXISTS (
+                 SELECT 1 FROM due other
+                 WHERE other.custody_id = d.custody_id AND other.day = d.day
+                   AND other.hearing_id IS NOT NULL)
+             ORDER BY d.day, k.facility, COALESCE(o.full_name, k.offender_name), h.hearing_time",
+        )
+        .context("Failed to prepare statement")?;
+    let due = stmt
+        .query_map(params![from, to], |row| {
+            Ok(ProductionDue {
+                production_date: row.get(0)?,
+                custody_id: row.get(1)?,
+                offender_id: row.get(2)?,
+                offender_name: row.get(3)?,
+                case_id: row.get(4)?,
+                case_number: row.get(5)?,
+                facility: row.get(6)?,
+                warrant_number: row.get(7)?,
+                warrant_expires_on: row.get(8)?,
+                hearing_id: row.get(9)?,
+                courtroom: row.get(10)?,
+                purpose: row.get(11)?,
+                overdue: row.get(12)?,
+                warrant_lapsed: row.get(13)?,
+            })
+        })
+        .context("Failed to fetch production list")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read production list")?;
+    Ok(due)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn committal(offender_id: i64) -> NewCustody {
+        NewCustody {
+            offender_id,
+            case_id: None,
+            custody_type: CustodyType::default(),
+            facility: "Machakos GK Prison".to_string(),
+            committed_on: Some("2026-03-02".to_string()),
+            warrant_number: None,
+            warrant_issued_on: None,
+            warrant_expires_on: None,
+            next_production_date: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn register_outlives_the_offender_record() {
+        let mut conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO offenders (offender_id, full_name) VALUES (1, 'Wanjiru Kamau')",
+            [],
+        )
+        .unwrap();
+        let custody = admit(&mut conn, committal(1), None).unwrap();
+
+        conn.execute("DELETE FROM offenders WHERE offender_id = 1", [])
+            .unwrap();
+
+        let kept = get(&conn, custody.custody_id).unwrap();
+        assert_eq!(kept.offender_id, None);
+        assert_eq!(kept.offender_name.as_deref(), Some("Wanjiru Kamau"));
+        let released = release(
+            &mut conn,
+            custody.custody_id,
+            Release {
+                released_on: Some("2026-03-09".to_string()),
+                reason: "Bail granted".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(released.released_on.as_deref(), Some("2026-03-09"));
+    }
+
+    fn offender(conn: &Connection, id: i64, name: &str) {
+        conn.execute(
+            "INSERT INTO offenders (offender_id, full_name) VALUES (?1, ?2)",
+            params![id, name],
+        )
+        .unwrap();
+    }
+
+    fn offender_state(conn: &Connection, id: i64) -> (Option<String>, Option<String>) {
+        conn.query_row(
+            "SELECT facility, status FROM offenders WHERE offender_id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn admission_is_checked_and_follows_the_offender() {
+        let mut conn = db::open_in_memory().unwrap();
+        offender(&conn, 1, "Wanjiru Kamau");
+
+        let blank = NewCustody {
+            facility: "  ".to_string(),
+            ..committal(1)
+        };
+        assert!(matches!(
+            admit(&mut conn, blank, None),
+            Err(AppError::Validation { .. })
+        ));
+        let backwards = NewCustody {
+            warrant_issued_on: Some("2026-03-02".to_string()),
+            warrant_expires_on: Some("2026-03-01".to_string()),
+            ..committal(1)
+        };
+        assert!(matches!(
+            admit(&mut conn, backwards, None),
+            Err(AppError::Validation { .. })
+        ));
+        assert!(matches!(
+            admit(&mut conn, committal(2), None),
+            Err(AppError::NotFound(_))
+        ));
+
+        let custody = admit(&mut conn, committal(1), None).unwrap();
+        assert_eq!(
+            offender_state(&conn, 1),
+            (
+                Some("Machakos GK Prison".to_string()),
+                Some(IN_CUSTODY.to_string())
+            )
+        );
+        assert!(matches!(
+            admit(&mut conn, committal(1), None),
+            Err(AppError::Conflict(_))
+        ));
+
+        transfer(&mut conn, custody.custody_id, "Kitui Remand").unwrap();
+        assert_eq!(offender_state(&conn, 1).0.as_deref(), Some("Kitui Remand"));
+        let release_on = |day: &str| Release {
+            released_on: Some(day.to_string()),
+            reason: "Acquitted".to_string(),
+        };
+        assert!(matches!(
+            release(&mut conn, custody.custody_id, release_on("2026-03-01")),
+            Err(AppError::Validation { .. })
+        ));
+        release(&mut conn, custody.custody_id, release_on("2026-03-20")).unwrap();
+        assert_eq!(offender_state(&conn, 1), (None, Some("Active".to_string())));
+        assert!(matches!(
+            transfer(&mut conn, custody.custody_id, "Kitui Remand"),
+            Err(AppError::Conflict(_))
+        ));
+        let held = CustodyFilter {
+            in_custody_only: true,
+            ..Default::default()
+        };
+        assert!(list(&conn, &held).unwrap().is_empty());
+        assert_eq!(list(&conn, &CustodyFilter::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn production_list_flags_overdue_dates_and_lapsing_warrants() {
+        let mut conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO cases (case_id, title, case_number) VALUES (1, 'R v Otieno', 'CR 7/2026')",
+            [],
+        )
+        .unwrap();
+        for (id, name) in [(1, "Otieno"), (2, "Akinyi"), (3, "Mutua"), (4, "Njeri")] {
+            offender(&conn, id, name);
+        }
+        // Produced for a hearing that falls on his production date
+        admit(
+            &mut conn,
+            NewCustody {
+                case_id: Some(1),
+                next_production_date: Some("2026-03-10".to_string()),
+                warrant_expires_on: Some("2026-03-09".to_string()),
+                ..committal(1)
+            },
+            None,
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date, courtroom, purpose)
+             VALUES (1, '2026-03-10', 'Court 1', 'Mention'),
+                    (1, '2026-03-30', 'Court 1', 'Hearing')",
+            [],
+        )
+        .unwrap();
+        // Should have been produced last week
+        admit(
+            &mut conn,
+            NewCustody {
+                next_production_date: Some("2026-03-04".to_string()),
+                ..committal(2)
+            },
+            None,
+        )
+        .unwrap();
+        // Due, but released first
+        let released = admit(
+            &mut conn,
+            NewCustody {
+                next_production_date: Some("2026-03-12".to_string()),
+                ..committal(3)
+            },
+            None,
+        )
+        .unwrap();
+        release(
+            &mut conn,
+            released.custody_id,
+            Release {
+                released_on: Some("2026-03-05".to_string()),
+                reason: "Bail granted".to_string(),
+            },
+        )
+        .unwrap();
+        // Not due within the window
+        admit(
+            &mut conn,
+            NewCustody {
+                next_production_date: Some("2026-04-01".to_string()),
+                ..committal(4)
+            },
+            None,
+        )
+        .unwrap();
+
+        let due = due_for_production(&conn, "2026-03-09", 7).unwrap();
+        let summary: Vec<_> = due
+            .iter()
+            .map(|d| {
+                (
+                    d.production_date.as_str(),
+                    d.offender_name.as_deref().unwrap_or_default(),
+                    d.hearing_id.is_some(),
+                    d.overdue,
+                    d.warrant_lapsed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2026-03-04", "Akinyi", false, true, false),
+                ("2026-03-10", "Otieno", true, false, true),
+            ]
+        );
+        assert_eq!(due[1].purpose.as_deref(), Some("Mention"));
+        assert_eq!(due[1].case_number.as_deref(), Some("CR 7/2026"));
+        assert!(matches!(
+            due_for_production(&conn, "next week", 7),
+            Err(AppError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn a_production_moves_the_next_date_on() {
+        let mut conn = db::open_in_memory().unwrap();
+        offender(&conn, 1, "Wanjiru Kamau");
+        let custody = admit(
+            &mut conn,
+            NewCustody {
+                next_production_date: Some("2026-03-10".to_string()),
+                warrant_expires_on: Some("2026-03-16".to_string()),
+                ..committal(1)
+            },
+            None,
+        )
+        .unwrap();
+        let produced = |next: &str| Production {
+            produced_on: Some("2026-03-10".to_string()),
+            next_production_date: Some(next.to_string()),
+            warrant_expires_on: None,
+            notes: None,
+        };
+        assert!(matches!(
+            record_production(&conn, custody.custody_id, produced("2026-03-10")),
+            Err(AppError::Validation { .. })
+        ));
+
+        let updated = record_production(&conn, custody.custody_id, produced("2026-03-24")).unwrap();
+        assert_eq!(updated.last_produced_on.as_deref(), Some("2026-03-10"));
+        assert_eq!(updated.warrant_expires_on.as_deref(), Some("2026-03-16"));
+        let due = due_for_production(&conn, "2026-03-20", 7).unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(due[0].warrant_lapsed);
+        assert!(due_for_production(&conn, "2026-03-11", 7)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,983 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::names;
+use crate::services::offender_cases;
+use crate::services::offenders::{self, split_aliases, Offender};
+
+// ============================================================================
+// Duplicate offenders
+//
+// The same person ends up in the registry twice when a clerk does not find
+// the existing record, or when the mobile app creates them again under a new
+// `firestore_id`. Candidates are paired by national id, date of birth and
+// sound-alike names, then scored. A merge folds one record into the other,
+// moves its history across and tombstones it, so the deletion syncs.
+// ============================================================================
+
+pub const DEFAULT_DUPLICATE_SCORE: f64 = 0.7;
+
+/// Offender columns that a merge may take from either record. Name, photo,
+/// notes and aliases are handled separately.
+const MERGE_FIELDS: &[&str] = &[
+    "national_id",
+    "date_of_birth",
+    "gender",
+    "file_id",
+    "penalty",
+    "penalty_notes",
+    "nationality",
+    "marital_status",
+    "occupation",
+    "address",
+    "first_offender",
+    "criminal_history",
+    "known_associates",
+    "arresting_officer",
+    "place_of_arrest",
+    "arrest_date",
+    "case_number",
+    "eye_color",
+    "hair_color",
+    "phone_number",
+    "emergency_contact_name",
+    "emergency_contact_phone",
+    "emergency_contact_relationship",
+    "legal_representation",
+    "medical_conditions",
+    "risk_level",
+    "distinguishing_marks",
+    "type",
+    "status",
+    "facility",
+    "offense_type",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    SameNationalId,
+    SameDateOfBirth,
+    SimilarName,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCandidate {
+    pub offender_id: i64,
+    pub full_name: String,
+    pub duplicate_id: i64,
+    pub duplicate_name: String,
+    pub score: f64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// The identifying parts of an offender, normalized for comparison
+struct Identity {
+    offender_id: i64,
+    full_name: String,
+    national_id: Option<String>,
+    date_of_birth: Option<String>,
+    /// Full name first, then each alias
+    names: Vec<Vec<String>>,
+}
+
+fn normalize_national_id(id: &str) -> Option<String> {
+    let id: String = id
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    (!id.is_empty()).then_some(id)
+}
+
+fn normalize_date(date: &str) -> Option<String> {
+    let date: String = date.trim().chars().take(10).collect();
+    (!date.is_empty()).then_some(date)
+}
+
+fn identities(conn: &Connection) -> AppResult<Vec<Identity>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT offender_id, full_name, national_id, date_of_birth, alias
+             FROM offenders WHERE COALESCE(is_deleted, 0) = 0",
+        )
+        .context("Failed to read offenders")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(offender_id, full_name, national_id, date_of_birth, alias)| {
+                let names = std::iter::once(full_name.as_str())
+                    .chain(alias.as_deref().into_iter().flat_map(split_aliases))
+                    .map(names::normalize)
+                    .filter(|words| !words.is_empty())
+                    .collect();
+                Identity {
+                    offender_id,
+                    national_id: national_id.as_deref().and_then(normalize_national_id),
+                    date_of_birth: date_of_birth.as_deref().and_then(normalize_date),
+                    full_name,
+                    names,
+                }
+            },
+        )
+        .collect())
+}
+
+/// Best match between any name of `a` and any name of `b`, in both
+/// directions, so "Wanjiku" alone does not count as "Wanjiku Kamau"
+fn name_similarity(a: &Identity, b: &Identity) -> f64 {
+    a.names
+        .iter()
+        .flat_map(|x| {
+            b.names
+                .iter()
+                .map(move |y| (names::similarity(x, y) + names::similarity(y, x)) / 2.0)
+        })
+        .fold(0.0, f64::max)
+}
+
+fn score_pair(a: &Identity, b: &Identity) -> Option<(f64, Vec<DuplicateReason>)> {
+    let name = name_similarity(a, b);
+    let mut reasons = Vec::new();
+
+    let same_id = match (&a.national_id, &b.national_id) {
+        (Some(x), Some(y)) if x == y => true,
+        // Two different ids are two different people
+        (Some(_), Some(_)) => return None,
+        _ => false,
+    };
+    let same_dob = match (&a.date_of_birth, &b.date_of_birth) {
+        (Some(x), Some(y)) if x == y => true,
+        (Some(_), Some(_)) if !same_id => return None,
+        _ => false,
+    };
+
+    let score = if same_id {
+        0.9 + 0.1 * name
+    } else if same_dob {
+        0.3 + 0.7 * name
+    } else {
+        0.85 * name
+    };
+    if same_id {
+        reasons.push(DuplicateReason::SameNationalId);
+    }
+    if same_dob {
+        reasons.push(DuplicateReason::SameDateOfBirth);
+    }
+    if name >= offenders::DEFAULT_NAME_MATCH_SCORE {
+        reasons.push(DuplicateReason::SimilarName);
+    }
+    Some((score, reasons))
+}
+
+/// Keys that two records of the same person are likely to share. Only
+/// records sharing a key are compared, instead of every pair.
+fn blocking_keys(identity: &Identity) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    if let Some(id) = &identity.national_id {
+        keys.insert(format!("id:{}", id));
+    }
+    if let Some(dob) = &identity.date_of_birth {
+        keys.insert(format!("dob:{}", dob));
+    }
+    for word in identity.names.iter().flatten() {
+        if word.len() >= 3 {
+            keys.insert(format!("name:{}", names::phonetic_key(word)));
+        }
+    }
+    keys
+}
+
+/// Likely duplicate pairs scoring at least `min_score`, best first. With
+/// `offender_id`, only pairs that include that offender.
+pub fn find(
+    conn: &Connection,
+    offender_id: Option<i64>,
+    min_score: f64,
+) -> AppResult<Vec<DuplicateCandidate>> {
+    let people = identities(conn)?;
+    if let Some(id) = offender_id {
+        if !people.iter().any(|p| p.offender_id == id) {
+            return Err(AppError::not_found("Offender not found"));
+        }
+    }
+
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, person) in people.iter().enumerate() {
+        for key in blocking_keys(person) {
+            groups.entry(key).or_default().push(index);
+        }
+    }
+    let mut pairs = BTreeSet::new();
+    for members in groups.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                pairs.insert((i.min(j), i.max(j)));
+            }
+        }
+    }
+
+    let mut candidates: Vec<DuplicateCandidate> = pairs
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let (mut a, mut b) = (&people[i], &people[j]);
+            if offender_id.is_some_and(|id| a.offender_id != id && b.offender_id != id) {
+                return None;
+            }
+            if Some(b.offender_id) == offender_id {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let (score, reasons) = score_pair(a, b)?;
+            (score >= min_score).then(|| DuplicateCandidate {
+                offender_id: a.offender_id,
+                full_name: a.full_name.clone(),
+                duplicate_id: b.offender_id,
+                duplicate_name: b.full_name.clone(),
+                score,
+                reasons,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(candidates)
+}
+
+// ----------------------------------------------------------------------------
+// Merge
+// ----------------------------------------------------------------------------
+
+/// Which record a merged field comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeSide {
+    Survivor,
+    Merged,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Keep the survivor's values and fill its blanks from the merged record
+    #[default]
+    FillBlanks,
+    /// Where both records have a value, take the more recently changed one
+    PreferNewest,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    pub strategy: MergeStrategy,
+    /// Per-field choices that win over `strategy`. Accepts the fields in
+    /// `MERGE_FIELDS` plus `full_name` and `photo`.
+    pub overrides: BTreeMap<String, MergeSide>,
+}
+
+/// Where a survivor's new field value came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    /// Taken from the merged record
+    Merged,
+    /// Built from both records, as for aliases and notes
+    Combined,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+    pub source: ChangeSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeOutcome {
+    /// Row id in `offender_merges`
+    pub merge_id: i64,
+    pub survivor: Offender,
+    pub merged_id: i64,
+    pub history_moved: usize,
+    /// Case links moved across, leaving out ones the survivor already had
+    pub links_moved: usize,
+    pub field_changes: Vec<FieldChange>,
+    pub photo_source: Option<MergeSide>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeRecord {
+    pub id: i64,
+    pub survivor_id: i64,
+    pub merged_id: i64,
+    pub merged_firestore_id: Option<String>,
+    pub merged_snapshot: serde_json::Value,
+    pub field_changes: serde_json::Value,
+    pub history_moved: i64,
+    pub photo_source: Option<String>,
+    pub merged_by: Option<String>,
+    pub merged_at: Option<String>,
+}
+
+/// One offender row as column name to value, for every column
+type RawRecord = BTreeMap<String, Value>;
+
+fn raw_record(conn: &Connection, offender_id: i64) -> AppResult<RawRecord> {
+    let mut stmt = conn.prepare("SELECT * FROM offenders WHERE offender_id = ?1")?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    stmt.query_row([offender_id], |row| {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Ok((name.clone(), row.get::<_, Value>(i)?)))
+            .collect()
+    })
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("Offender {} not found", offender_id)))
+}
+
+fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(i) => (*i).into(),
+        Value::Real(f) => (*f).into(),
+        Value::Text(s) => s.clone().into(),
+        Value::Blob(b) => format!("<{} bytes>", b.len()).into(),
+    }
+}
+
+fn is_blank(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::Text(s)) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+fn text(record: &RawRecord, column: &str) -> Option<String> {
+    match record.get(column) {
+        Some(Value::Text(s)) if !s.trim().is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn is_deleted(record: &RawRecord) -> bool {
+    matches!(record.get("is_deleted"), Some(Value::Integer(i)) if *i != 0)
+}
+
+/// Pick the side a field comes from when no override names it
+fn choose(
+    strategy: MergeStrategy,
+    survivor: Option<&Value>,
+    merged: Option<&Value>,
+    merged_is_newer: bool,
+) -> MergeSide {
+    let prefer_merged =
+        is_blank(survivor) || (strategy == MergeStrategy::PreferNewest && merged_is_newer);
+    if prefer_merged && !is_blank(merged) {
+        MergeSide::Merged
+    } else {
+        MergeSide::Survivor
+    }
+}
+
+fn photo_size(record: &RawRecord) -> Option<u64> {
+    text(record, "photo_path").and_then(|path| fs::metadata(path).ok().map(|m| m.len()))
+}
+
+/// Fold `merged_id` into `survivor_id` and tombstone it. `merged_by` is
+/// recorded in the audit trail.
+pub fn merge(
+    conn: &mut Connection,
+    survivor_id: i64,
+    merged_id: i64,
+    options: &MergeOptions,
+    merged_by: Option<&str>,
+) -> AppResult<MergeOutcome> {
+    if survivor_id == merged_id {
+        return Err(AppError::validation(
+            "An offender cannot be merged into itself",
+        ));
+    }
+    for field in options.overrides.keys() {
+        if !MERGE_FIELDS.contains(&field.as_str()) && field != "full_name" && field != "photo" {
+            return Err(AppError::field(
+                "overrides",
+                format!("{} cannot be chosen in a merge", field),
+            ));
+        }
+    }
+
+    let tx = conn.transaction()?;
+    let survivor = raw_record(&tx, survivor_id)?;
+    let merged = raw_record(&tx, merged_id)?;
+    if is_deleted(&survivor) || is_deleted(&merged) {
+        return Err(AppError::Conflict(
+            "A deleted offender cannot be merged.".to_string(),
+        ));
+    }
+    // Only one open custody spell per person, so one has to end first
+    let both_in_custody: bool = tx
+        .query_row(
+            "SELECT COUNT(DISTINCT offender_id) = 2 FROM custody_records
+             WHERE offender_id IN (?1, ?2) AND released_on IS NULL",
+            params![survivor_id, merged_id],
+            |row| row.get(0),
+        )
+        .context("Failed to check custody records")?;
+    if both_in_custody {
+        return Err(AppError::Conflict(
+            "Both offenders are in custody; release one of them before merging.".to_string(),
+        ));
+    }
+    // Likewise one live bond per person per case
+    let shared_bond_case: Option<i64> = tx
+        .query_row(
+            "SELECT case_id FROM bonds
+             WHERE offender_id IN (?1, ?2) AND status IN ('Granted', 'Posted')
+             GROUP BY case_id HAVING COUNT(DISTINCT offender_id) = 2
+             LIMIT 1",
+            params![survivor_id, merged_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to check bonds")?;
+    if let Some(case_id) = shared_bond_case {
+        return Err(AppError::Conflict(format!(
+            "Both offenders have a live bond in case {}; close one of them before merging.",
+            case_id
+        )));
+    }
+    let merged_is_newer = text(&merged, "last_modified") > text(&survivor, "last_modified");
+
+    let mut updates: Vec<(String, Value)> = Vec::new();
+    let mut field_changes = Vec::new();
+    let mut set = |field: &str, value: Value, source: ChangeSource| {
+        updates.retain(|(f, _)| f != field);
+        field_changes.retain(|c: &FieldChange| c.field != field);
+        let before = survivor.get(field).cloned().unwrap_or(Value::Null);
+        if before != value {
+            field_changes.push(FieldChange {
+                field: field.to_string(),
+                before: to_json(&before),
+                after: to_json(&value),
+                source,
+            });
+            updates.push((field.to_string(), value));
+        }
+    };
+
+    for &field in MERGE_FIELDS.iter().chain(std::iter::once(&"full_name")) {
+        let side = options.overrides.get(field).copied().unwrap_or_else(|| {
+            if field == "full_name" {
+                MergeSide::Survivor
+            } else {
+                choose(
+                    options.strategy,
+                    survivor.get(field),
+                    merged.get(field),
+                    merged_is_newer,
+                )
+            }
+        });
+        if side == MergeSide::Merged {
+            let value = merged.get(field).cloned().unwrap_or(Value::Null);
+            set(field, value, ChangeSource::Merged);
+        }
+    }
+
+    // A first offender no longer, if either record says so
+    if matches!(merged.get("first_offender"), Some(Value::Integer(0)))
+        && !options.overrides.contains_key("first_offender")
+    {
+        set("first_offender", Value::Integer(0), ChangeSource::Combined);
+    }
+
+    // Every name either record went by stays searchable as an alias
+    let kept_name = match options.overrides.get("full_name") {
+        Some(MergeSide::Merged) => text(&merged, "full_name"),
+        _ => text(&survivor, "full_name"),
+    }
+    .unwrap_or_default();
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in [
+        text(&survivor, "alias"),
+        text(&merged, "alias"),
+        text(&survivor, "full_name"),
+        text(&merged, "full_name"),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|a| split_aliases(&a).map(str::to_string).collect::<Vec<_>>())
+    {
+        let words = names::normalize(&alias);
+        if words != names::normalize(&kept_name)
+            && !aliases.iter().any(|a| names::normalize(a) == words)
+        {
+            aliases.push(alias);
+        }
+    }
+    let alias = if aliases.is_empty() {
+        Value::Null
+    } else {
+        Value::Text(aliases.join(", "))
+    };
+    set("alias", alias, ChangeSource::Combined);
+
+    let notes = match (text(&survivor, "notes"), text(&merged, "notes")) {
+        (Some(a), Some(b)) if a.trim() != b.trim() => Value::Text(format!("{}\n\n{}", a, b)),
+        (Some(a), _) => Value::Text(a),
+        (None, Some(b)) => Value::Text(b),
+        (None, None) => Value::Null,
+    };
+    set("notes", notes, ChangeSource::Combined);
+
+    // Keep the photo that exists on disk, the larger one if both do
+    let photo_source = match options.overrides.get("photo") {
+        Some(side) => Some(*side),
+        None => match (photo_size(&survivor), photo_size(&merged)) {
+            (Some(a), Some(b)) if b > a => Some(MergeSide::Merged),
+            (Some(_), _) => Some(MergeSide::Survivor),
+            (None, Some(_)) => Some(MergeSide::Merged),
+            (None, None) => None,
+        },
+    };
+    let (kept_photo, other_photo) = match photo_source {
+        Some(MergeSide::Merged) => (text(&merged, "photo_path"), text(&survivor, "photo_path")),
+        _ => (text(&survivor, "photo_path"), text(&merged, "photo_path")),
+    };
+    if photo_source == Some(MergeSide::Merged) {
+        set(
+            "photo_path",
+            kept_photo.map(Value::Text).unwrap_or(Value::Null),
+            ChangeSource::Merged,
+        );
+    }
+
+    if !updates.is_empty() {
+        let assignments: Vec<String> = updates
+            .iter()
+            .enumerate()
+            .map(|(i, (column, _))| format!("{} = ?{}", column, i + 2))
+            .collect();
+        let mut args: Vec<Value> = vec![Value::Integer(survivor_id)];
+        args.extend(updates.into_iter().map(|(_, value)| value));
+        tx.execute(
+            &format!(
+                "UPDATE offenders SET {}, sync_status = 'pending', has_changes = 1,
+                 last_modified = CURRENT_TIMESTAMP WHERE offender_id = ?1",
+                assignments.join(", ")
+            ),
+            params_from_iter(args),
+        )
+        .context("Failed to update surviving offender")?;
+    }
+
+    let history_moved = tx
+        .execute(
+            "UPDATE offender_history SET offender_id = ?1 WHERE offender_id = ?2",
+            params![survivor_id, merged_id],
+        )
+        .context("Failed to move offender history")?;
+    tx.execute(
+        "UPDATE sentences SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move sentences")?;
+    tx.execute(
+        "UPDATE ledger_assessments SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move ledger assessments")?;
+    tx.execute(
+        "UPDATE custody_records SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move custody records")?;
+    tx.execute(
+        "UPDATE bonds SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move bonds")?;
+    // Both records marked at the same hearing: absent if either was
+    tx.execute(
+        "UPDATE hearing_attendance SET attended = 0
+         WHERE offender_id = ?1 AND hearing_id IN (
+             SELECT hearing_id FROM hearing_attendance WHERE offender_id = ?2 AND attended = 0)",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to merge attendance")?;
+    tx.execute(
+        "DELETE FROM hearing_attendance
+         WHERE offender_id = ?2
+           AND hearing_id IN (SELECT hearing_id FROM hearing_attendance WHERE offender_id = ?1)",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to merge attendance")?;
+    tx.execute(
+        "UPDATE hearing_attendance SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move attendance")?;
+    tx.execute(
+        "UPDATE warrants SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move warrants")?;
+    tx.execute(
+        "UPDATE exhibits SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move exhibits")?;
+    tx.execute(
+        "UPDATE charges SET offender_id = ?1 WHERE offender_id = ?2",
+        params![survivor_id, merged_id],
+    )
+    .context("Failed to move charges")?;
+    let links_moved = offender_cases::reassign(&tx, merged_id, survivor_id)?;
+
+    // A record that never reached Firestore has nothing to tombstone there
+    let merged_firestore_id = text(&merged, "firestore_id");
+    let mut orphaned_photo = None;
+    if merged_firestore_id.is_some() {
+        tx.execute(
+            "UPDATE offenders SET is_deleted = 1, sync_status = 'pending', has_changes = 1,
+             photo_path = ?2, last_modified = CURRENT_TIMESTAMP WHERE offender_id = ?1",
+            params![merged_id, other_photo],
+        )
+        .context("Failed to tombstone merged offender")?;
+    } else {
+        tx.execute(
+            "DELETE FROM offenders WHERE offender_id = ?1",
+            params![merged_id],
+        )
+        .context("Failed to remove merged offender")?;
+        orphaned_photo = other_photo;
+    }
+
+    let snapshot: serde_json::Map<String, serde_json::Value> = merged
+        .iter()
+        .map(|(column, value)| (column.clone(), to_json(value)))
+        .collect();
+    tx.execute(
+        "INSERT INTO offender_merges (survivor_id, merged_id, merged_firestore_id, merged_snapshot,
+             field_changes, history_moved, photo_source, merged_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            survivor_id,
+            merged_id,
+            merged_firestore_id,
+            serde_json::Value::Object(snapshot).to_string(),
+            serde_json::to_string(&field_changes)?,
+            history_moved as i64,
+            photo_source.map(|side| match side {
+                MergeSide::Survivor => "survivor",
+                MergeSide::Merged => "merged",
+            }),
+            merged_by,
+        ],
+    )
+    .context("Failed to record merge")?;
+    let merge_id = tx.last_insert_rowid();
+    tx.commit()?;
+
+    if let Some(path) = orphaned_photo {
+        offenders::discard_photo(conn, &path)?;
+    }
+
+    Ok(MergeOutcome {
+        merge_id,
+        survivor: offenders::get(conn, survivor_id)?,
+        merged_id,
+        history_moved,
+        links_moved,
+        field_changes,
+        photo_source,
+    })
+}
+
+/// Past merges, newest first; with `offender_id`, those it took part in
+pub fn list_merges(conn: &Connection, offender_id: Option<i64>) -> AppResult<Vec<MergeRecord>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, survivor_id, merged_id, merged_firestore_id, merged_snapshot, field_changes,
+                    history_moved, photo_source, merged_by, merged_at
+             FROM offender_merges
+             WHERE ?1 IS NULL OR survivor_id = ?1 OR merged_id = ?1
+             ORDER BY id DESC",
+        )
+        .context("Failed to read merges")?;
+    let merges = stmt
+        .query_map([offender_id], |row| {
+            let json = |i: usize| -> rusqlite::Result<serde_json::Value> {
+                let text: String = row.get(i)?;
+                Ok(serde_json::from_str(&text).unwrap_or(serde_json::Value::Null))
+            };
+            Ok(MergeRecord {
+                id: row.get(0)?,
+                survivor_id: row.get(1)?,
+                merged_id: row.get(2)?,
+                merged_firestore_id: row.get(3)?,
+                merged_snapshot: json(4)?,
+                field_changes: json(5)?,
+                history_moved: row.get(6)?,
+                photo_source: row.get(7)?,
+                merged_by: row.get(8)?,
+                merged_at: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(merges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn offender(conn: &Connection, full_name: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO offenders (full_name) VALUES (?1)",
+            params![full_name],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn custody(conn: &Connection, offender_id: i64, released_on: Option<&str>) -> i64 {
+        conn.execute(
+            "INSERT INTO custody_records (offender_id, facility, committed_on, released_on)
+             VALUES (?1, 'Machakos GK Prison', '2026-01-05', ?2)",
+            params![offender_id, released_on],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn custody_of(conn: &Connection, custody_id: i64) -> Option<i64> {
+        conn.query_row(
+            "SELECT offender_id FROM custody_records WHERE custody_id = ?1",
+            params![custody_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    fn case(conn: &Connection, title: &str) -> i64 {
+        conn.execute("INSERT INTO cases (title) VALUES (?1)", params![title])
+            .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn bond(conn: &Connection, case_id: i64, offender_id: i64, status: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO bonds (case_id, offender_id, amount_cents, granted_on, status)
+             VALUES (?1, ?2, 5000000, '2026-01-05', ?3)",
+            params![case_id, offender_id, status],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn bond_of(conn: &Connection, bond_id: i64) -> Option<i64> {
+        conn.query_row(
+            "SELECT offender_id FROM bonds WHERE bond_id = ?1",
+            params![bond_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    fn person(
+        conn: &Connection,
+        full_name: &str,
+        national_id: Option<&str>,
+        dob: Option<&str>,
+    ) -> i64 {
+        conn.execute(
+            "INSERT INTO offenders (full_name, national_id, date_of_birth) VALUES (?1, ?2, ?3)",
+            params![full_name, national_id, dob],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn pairs(conn: &Connection, offender_id: Option<i64>) -> Vec<(i64, i64, Vec<DuplicateReason>)> {
+        find(conn, offender_id, 0.5)
+            .unwrap()
+            .into_iter()
+            .map(|c| {
+                (
+                    c.offender_id.min(c.duplicate_id),
+                    c.offender_id.max(c.duplicate_id),
+                    c.reasons,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_national_id_pairs_records_whatever_the_formatting() {
+        let conn = db::open_in_memory().unwrap();
+        let a = person(&conn, "John Kamau", Some("12 345-678"), None);
+        let b = person(&conn, "Kamau Njoroge", Some("12345678"), Some("1990-04-01"));
+
+        let found = find(&conn, None, DEFAULT_DUPLICATE_SCORE).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].score >= 0.9);
+        assert_eq!(found[0].reasons, vec![DuplicateReason::SameNationalId]);
+        assert_eq!(pairs(&conn, Some(b))[0].0, a);
+    }
+
+    #[test]
+    fn different_ids_or_birth_dates_are_different_people() {
+        let conn = db::open_in_memory().unwrap();
+        person(&conn, "Wanjiku Kamau", Some("11111111"), None);
+        person(&conn, "Wanjiku Kamau", Some("22222222"), None);
+        person(&conn, "Otieno Odhiambo", None, Some("1985-01-01"));
+        person(&conn, "Otieno Odhiambo", None, Some("1986-01-01T00:00:00"));
+        assert!(pairs(&conn, None).is_empty());
+    }
+
+    #[test]
+    fn birth_date_and_sound_alike_names_pair_records() {
+        let conn = db::open_in_memory().unwrap();
+        let a = person(&conn, "Wanjiku Mũthoni", None, Some("1990-04-01"));
+        let b = person(&conn, "Muthoni Wanjigu", None, Some("1990-04-01 00:00:00"));
+        let c = person(&conn, "Wanjiku Muthoni", None, None);
+        let gone = person(&conn, "Wanjiku Muthoni", None, None);
+        conn.execute(
+            "UPDATE offenders SET is_deleted = 1 WHERE offender_id = ?1",
+            params![gone],
+        )
+        .unwrap();
+
+        let found = pairs(&conn, None);
+        assert!(found.contains(&(
+            a,
+            b,
+            vec![
+                DuplicateReason::SameDateOfBirth,
+                DuplicateReason::SimilarName
+            ]
+        )));
+        assert!(found.contains(&(a, c, vec![DuplicateReason::SimilarName])));
+        assert!(found.iter().all(|(x, y, _)| *x != gone && *y != gone));
+
+        // Date of birth plus name outranks the name alone
+        let scored = find(&conn, Some(a), 0.5).unwrap();
+        assert_eq!(scored[0].duplicate_id, b);
+        assert!(scored.iter().all(|p| p.offender_id == a));
+        assert!(matches!(
+            find(&conn, Some(gone), 0.5),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn merge_refuses_two_open_custody_spells() {
+        let mut conn = db::open_in_memory().unwrap();
+        let survivor = offender(&conn, "John Mwangi Kamau");
+        let merged = offender(&conn, "John Kamau");
+        let kept = custody(&conn, survivor, None);
+        let other = custody(&conn, merged, None);
+
+        let err = merge(&mut conn, survivor, merged, &MergeOptions::default(), None).unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!(custody_of(&conn, kept), Some(survivor));
+        assert_eq!(custody_of(&conn, other), Some(merged));
+    }
+
+    #[test]
+    fn merge_moves_every_custody_spell_to_the_survivor() {
+        let mut conn = db::open_in_memory().unwrap();
+        let survivor = offender(&conn, "John Mwangi Kamau");
+        let merged = offender(&conn, "John Kamau");
+        let open = custody(&conn, survivor, None);
+        let past = custody(&conn, merged, Some("2026-02-01"));
+
+        merge(&mut conn, survivor, merged, &MergeOptions::default(), None).unwrap();
+        assert_eq!(custody_of(&conn, open), Some(survivor));
+        assert_eq!(custody_of(&conn, past), Some(survivor));
+    }
+
+    #[test]
+    fn merge_refuses_two_live_bonds_in_one_case() {
+        let mut conn = db::open_in_memory().unwrap();
+        let survivor = offender(&conn, "John Mwangi Kamau");
+        let merged = offender(&conn, "John Kamau");
+        let theft = case(&conn, "Republic v Kamau");
+        let kept = bond(&conn, theft, survivor, "Posted");
+        let other = bond(&conn, theft, merged, "Granted");
+
+        let err = merge(&mut conn, survivor, merged, &MergeOptions::default(), None).unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!(bond_of(&conn, kept), Some(survivor));
+        assert_eq!(bond_of(&conn, other), Some(merged));
+    }
+
+    #[test]
+    fn merge_moves_live_bonds_in_other_cases() {
+        let mut conn = db::open_in_memory().unwrap();
+        let survivor = offender(&conn, "John Mwangi Kamau");
+        let merged = offender(&conn, "John Kamau");
+        let theft = case(&conn, "Republic v Kamau");
+        let assault = case(&conn, "Republic v Kamau and another");
+        let kept = bond(&conn, theft, survivor, "Posted");
+        let ended = bond(&conn, theft, merged, "Discharged");
+        let elsewhere = bond(&conn, assault, merged, "Posted");
+
+        merge(&mut conn, survivor, merged, &MergeOptions::default(), None).unwrap();
+        assert_eq!(bond_of(&conn, kept), Some(survivor));
+        assert_eq!(bond_of(&conn, ended), Some(survivor));
+        assert_eq!(bond_of(&conn, elsewhere), Some(survivor));
+    }
+
+    #[test]
+    fn merge_keeps_attendance_and_any_absence() {
+        let mut conn = db::open_in_memory().unwrap();
+        let survivor = offender(&conn, "John Mwangi Kamau");
+        let merged = offender(&conn, "John Kamau");
+        let theft = case(&conn, "Republic v Kamau");
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date) VALUES (?1, '2026-02-03'), (?1, '2026-03-03')",
+            params![theft],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO hearing_attendance (hearing_id, offender_id, attended)
+             VALUES (1, ?1, 1), (1, ?2, 0), (2, ?2, 1)",
+            params![survivor, merged],
+        )
+        .unwrap();
+
+        merge(&mut conn, survivor, merged, &MergeOptions::default(), None).unwrap();
+        let attendance: Vec<(i64, i64, bool)> = conn
+            .prepare("SELECT hearing_id, offender_id, attended FROM hearing_attendance ORDER BY 1")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(attendance, vec![(1, survivor, false), (2, survivor, true)]);
+    }
+}
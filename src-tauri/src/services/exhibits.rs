@@ -0,0 +1,1112 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::cause_list::escape;
+use crate::services::hearings::parse_date;
+
+// ============================================================================
+// Exhibit register
+//
+// Physical exhibits held for a case (migration 0012), each under a unique
+// exhibit number with where it is kept. Every movement in and out of the
+// store, starting with the exhibit being received, is appended to
+// `exhibit_movements` with the names and signatures of the person handing
+// it over and the person taking it; the database refuses to change or
+// delete a movement. Disposal needs a court order reference.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExhibitCategory {
+    Weapon,
+    Drugs,
+    Document,
+    Money,
+    Electronic,
+    Vehicle,
+    #[default]
+    Other,
+}
+
+impl ExhibitCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExhibitCategory::Weapon => "Weapon",
+            ExhibitCategory::Drugs => "Drugs",
+            ExhibitCategory::Document => "Document",
+            ExhibitCategory::Money => "Money",
+            ExhibitCategory::Electronic => "Electronic",
+            ExhibitCategory::Vehicle => "Vehicle",
+            ExhibitCategory::Other => "Other",
+        }
+    }
+
+    pub fn parse(category: &str) -> Option<ExhibitCategory> {
+        match category {
+            "Weapon" => Some(ExhibitCategory::Weapon),
+            "Drugs" => Some(ExhibitCategory::Drugs),
+            "Document" => Some(ExhibitCategory::Document),
+            "Money" => Some(ExhibitCategory::Money),
+            "Electronic" => Some(ExhibitCategory::Electronic),
+            "Vehicle" => Some(ExhibitCategory::Vehicle),
+            "Other" => Some(ExhibitCategory::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExhibitStatus {
+    #[serde(rename = "In Store")]
+    InStore,
+    Out,
+    Disposed,
+}
+
+impl ExhibitStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExhibitStatus::InStore => "In Store",
+            ExhibitStatus::Out => "Out",
+            ExhibitStatus::Disposed => "Disposed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<ExhibitStatus> {
+        match status {
+            "In Store" => Some(ExhibitStatus::InStore),
+            "Out" => Some(ExhibitStatus::Out),
+            "Disposed" => Some(ExhibitStatus::Disposed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementKind {
+    /// Booked into the store; recorded when the exhibit is registered
+    Received,
+    /// Taken out, e.g. to court or for analysis
+    Out,
+    /// Brought back into the store
+    Returned,
+    /// Moved to another storage location
+    Transferred,
+    Disposed,
+}
+
+impl MovementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementKind::Received => "Received",
+            MovementKind::Out => "Out",
+            MovementKind::Returned => "Returned",
+            MovementKind::Transferred => "Transferred",
+            MovementKind::Disposed => "Disposed",
+        }
+    }
+
+    pub fn parse(movement: &str) -> Option<MovementKind> {
+        match movement {
+            "Received" => Some(MovementKind::Received),
+            "Out" => Some(MovementKind::Out),
+            "Returned" => Some(MovementKind::Returned),
+            "Transferred" => Some(MovementKind::Transferred),
+            "Disposed" => Some(MovementKind::Disposed),
+            _ => None,
+        }
+    }
+
+    /// The status an exhibit must have for this movement, and the one it has
+    /// after
+    fn transition(&self) -> (ExhibitStatus, ExhibitStatus) {
+        match self {
+            MovementKind::Received => (ExhibitStatus::InStore, ExhibitStatus::InStore),
+            MovementKind::Out => (ExhibitStatus::InStore, ExhibitStatus::Out),
+            MovementKind::Returned => (ExhibitStatus::Out, ExhibitStatus::InStore),
+            MovementKind::Transferred => (ExhibitStatus::InStore, ExhibitStatus::InStore),
+            MovementKind::Disposed => (ExhibitStatus::InStore, ExhibitStatus::Disposed),
+        }
+    }
+}
+
+/// Longest signature accepted: a drawn signature as a PNG or JPEG data URL
+const MAX_SIGNATURE_LEN: usize = 256 * 1024;
+const SIGNATURE_IMAGE_PREFIXES: &[&str] = &["data:image/png;base64,", "data:image/jpeg;base64,"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Exhibit {
+    pub exhibit_id: i64,
+    pub exhibit_number: String,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub category: ExhibitCategory,
+    pub description: String,
+    pub quantity: Option<String>,
+    pub received_on: String,
+    pub received_by: String,
+    pub produced_by: String,
+    pub storage_location: String,
+    pub status: ExhibitStatus,
+    pub disposed_on: Option<String>,
+    pub disposal_method: Option<String>,
+    pub disposal_order: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const EXHIBIT_SELECT: &str = "SELECT e.exhibit_id, e.exhibit_number, e.case_id, c.case_number,
+        e.offender_id, o.full_name, e.category, e.description, e.quantity, e.received_on,
+        e.received_by, e.produced_by, e.storage_location, e.status, e.disposed_on,
+        e.disposal_method, e.disposal_order, e.notes, e.created_by, e.created_at
+    FROM exhibits e
+    LEFT JOIN cases c ON c.case_id = e.case_id
+    LEFT JOIN offenders o ON o.offender_id = e.offender_id";
+
+impl Exhibit {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let category: String = row.get(6)?;
+        let status: String = row.get(13)?;
+        Ok(Exhibit {
+            exhibit_id: row.get(0)?,
+            exhibit_number: row.get(1)?,
+            case_id: row.get(2)?,
+            case_number: row.get(3)?,
+            offender_id: row.get(4)?,
+            offender_name: row.get(5)?,
+            category: ExhibitCategory::parse(&category).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    6,
+                    rusqlite::types::Type::Text,
+                    format!("unknown exhibit category {}", category).into(),
+                )
+            })?,
+            description: row.get(7)?,
+            quantity: row.get(8)?,
+            received_on: row.get(9)?,
+            received_by: row.get(10)?,
+            produced_by: row.get(11)?,
+            storage_location: row.get(12)?,
+            status: ExhibitStatus::parse(&status).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    13,
+                    rusqlite::types::Type::Text,
+                    format!("unknown exhibit status {}", status).into(),
+                )
+            })?,
+            disposed_on: row.get(14)?,
+            disposal_method: row.get(15)?,
+            disposal_order: row.get(16)?,
+            notes: row.get(17)?,
+            created_by: row.get(18)?,
+            created_at: row.get(19)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Movement {
+    pub movement_id: i64,
+    pub exhibit_id: i64,
+    pub movement: MovementKind,
+    pub moved_on: String,
+    pub purpose: Option<String>,
+    pub released_by: String,
+    pub released_by_signature: String,
+    pub received_by: String,
+    pub received_by_signature: String,
+    pub storage_location: Option<String>,
+    pub court_order: Option<String>,
+    pub notes: Option<String>,
+    pub recorded_by: Option<String>,
+    pub recorded_at: Option<String>,
+}
+
+const MOVEMENT_SELECT: &str = "SELECT movement_id, exhibit_id, movement, moved_on, purpose,
+        released_by, released_by_signature, received_by, received_by_signature,
+        storage_location, court_order, notes, recorded_by, recorded_at
+    FROM exhibit_movements";
+
+impl Movement {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let movement: String = row.get(2)?;
+        Ok(Movement {
+            movement_id: row.get(0)?,
+            exhibit_id: row.get(1)?,
+            movement: MovementKind::parse(&movement).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    format!("unknown exhibit movement {}", movement).into(),
+                )
+            })?,
+            moved_on: row.get(3)?,
+            purpose: row.get(4)?,
+            released_by: row.get(5)?,
+            released_by_signature: row.get(6)?,
+            received_by: row.get(7)?,
+            received_by_signature: row.get(8)?,
+            storage_location: row.get(9)?,
+            court_order: row.get(10)?,
+            notes: row.get(11)?,
+            recorded_by: row.get(12)?,
+            recorded_at: row.get(13)?,
+        })
+    }
+}
+
+/// An exhibit with its chain of custody, oldest movement first
+#[derive(Debug, Clone, Serialize)]
+pub struct ExhibitDetail {
+    pub exhibit: Exhibit,
+    pub movements: Vec<Movement>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewExhibit {
+    /// The next `EXH-<year>-<number>` if not given
+    pub exhibit_number: Option<String>,
+    pub case_id: i64,
+    pub offender_id: Option<i64>,
+    #[serde(default)]
+    pub category: ExhibitCategory,
+    pub description: String,
+    pub quantity: Option<String>,
+    /// YYYY-MM-DD, today if not given
+    pub received_on: Option<String>,
+    /// Who brought the exhibit in, and their signature
+    pub produced_by: String,
+    pub produced_by_signature: String,
+    /// The store officer who took it, and their signature
+    pub received_by: String,
+    pub received_by_signature: String,
+    pub storage_location: String,
+    pub notes: Option<String>,
+}
+
+/// A movement after the exhibit was received
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewMovement {
+    pub movement: MovementKind,
+    /// YYYY-MM-DD, today if not given
+    pub moved_on: Option<String>,
+    /// Why it is going out; needed for `Out`
+    pub purpose: Option<String>,
+    pub released_by: String,
+    pub released_by_signature: String,
+    pub received_by: String,
+    pub received_by_signature: String,
+    /// Where it is kept after a return or transfer; needed for a transfer
+    pub storage_location: Option<String>,
+    /// The court order reference; needed for disposal
+    pub court_order: Option<String>,
+    /// e.g. destroyed, returned to owner, forfeited; needed for disposal
+    pub disposal_method: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExhibitFilter {
+    pub case_id: Option<i64>,
+    pub status: Option<ExhibitStatus>,
+    pub category: Option<ExhibitCategory>,
+    pub storage_location: Option<String>,
+}
+
+/// Every exhibit of a case with its chain of custody
+#[derive(Debug, Clone, Serialize)]
+pub struct ExhibitReport {
+    pub case_id: i64,
+    pub case_number: String,
+    pub title: Option<String>,
+    pub exhibits: Vec<ExhibitDetail>,
+    pub in_store: usize,
+    pub out: usize,
+    pub disposed: usize,
+    pub generated_at: String,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+fn required<'a>(field: &str, value: &'a str, message: &str) -> AppResult<&'a str> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(AppError::field(field, message));
+    }
+    Ok(value)
+}
+
+/// A signature is either drawn, as a PNG or JPEG data URL, or typed
+fn signature<'a>(field: &str, value: &'a str) -> AppResult<&'a str> {
+    let value = required(field, value, "A signature is required")?;
+    if value.len() > MAX_SIGNATURE_LEN {
+        return Err(AppError::field(field, "The signature is too large"));
+    }
+    if value.starts_with("data:")
+        && !SIGNATURE_IMAGE_PREFIXES
+            .iter()
+            .any(|prefix| value.starts_with(prefix))
+    {
+        return Err(AppError::field(
+            field,
+            "A drawn signature must be a PNG or JPEG image",
+        ));
+    }
+    Ok(value)
+}
+
+pub fn get(conn: &Connection, exhibit_id: i64) -> AppResult<Exhibit> {
+    conn.query_row(
+        &format!("{} WHERE e.exhibit_id = ?1", EXHIBIT_SELECT),
+        params![exhibit_id],
+        Exhibit::from_row,
+    )
+    .optional()
+    .context("Failed to fetch exhibit")?
+    .ok_or_else(|| AppError::not_found("Exhibit not found"))
+}
+
+fn movements(conn: &Connection, exhibit_id: i64) -> AppResult<Vec<Movement>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE exhibit_id = ?1 ORDER BY moved_on, movement_id",
+            MOVEMENT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let movements = stmt
+        .query_map(params![exhibit_id], Movement::from_row)
+        .context("Failed to fetch exhibit movements")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read exhibit movements")?;
+    Ok(movements)
+}
+
+pub fn detail(conn: &Connection, exhibit_id: i64) -> AppResult<ExhibitDetail> {
+    Ok(ExhibitDetail {
+        exhibit: get(conn, exhibit_id)?,
+        movements: movements(conn, exhibit_id)?,
+    })
+}
+
+/// The exhibit number after the last one registered in `year`
+fn next_exhibit_number(conn: &Connection, year: &str) -> AppResult<String> {
+    let prefix = format!("EXH-{}-", year);
+    let last: Option<i64> = conn
+        .query_row(
+            "SELECT MAX(CAST(substr(exhibit_number, ?2) AS INTEGER)) FROM exhibits
+             WHERE exhibit_number LIKE ?1 || '%'",
+            params![prefix, prefix.len() as i64 + 1],
+            |row| row.get(0),
+        )
+        .context("Failed to number exhibit")?;
+    Ok(format!("{}{:06}", prefix, last.unwrap_or(0) + 1))
+}
+
+/// Enter an exhibit in the register and record it being received into the
+/// store
+pub fn register(
+    conn: &mut Connection,
+    exhibit: NewExhibit,
+    created_by: Option<&str>,
+) -> AppResult<ExhibitDetail> {
+    let description = required("description", &exhibit.description, "Describe the exhibit")?;
+    let produced_by = required(
+        "produced_by",
+        &exhibit.produced_by,
+        "Enter who brought the exhibit in",
+    )?;
+    let received_by = required(
+        "received_by",
+        &exhibit.received_by,
+        "Enter the officer who received the exhibit",
+    )?;
+    let storage_location = required(
+        "storage_location",
+        &exhibit.storage_location,
+        "Enter where the exhibit is kept",
+    )?;
+    let produced_by_signature = signature("produced_by_signature", &exhibit.produced_by_signature)?;
+    let received_by_signature = signature("received_by_signature", &exhibit.received_by_signature)?;
+    let received_on =
+        optional_date("received_on", exhibit.received_on.as_deref())?.unwrap_or_else(today);
+    if received_on > today() {
+        return Err(AppError::field(
+            "received_on",
+            "The date cannot be in the future",
+        ));
+    }
+
+    let tx = conn.transaction()?;
+    let case: Option<bool> = tx
+        .query_row(
+            "SELECT COALESCE(is_deleted, 0) = 0 FROM cases WHERE case_id = ?1",
+            params![exhibit.case_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch case")?;
+    if case != Some(true) {
+        return Err(AppError::not_found("Case not found"));
+    }
+    if let Some(offender_id) = exhibit.offender_id {
+        let offender: Option<i64> = tx
+            .query_row(
+                "SELECT offender_id FROM offenders WHERE offender_id = ?1",
+                params![offender_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch offender")?;
+        if offender.is_none() {
+            return Err(AppError::not_found("Offender not found"));
+        }
+    }
+    let exhibit_number = match optional_text(exhibit.exhibit_number) {
+        Some(number) => {
+            let taken: Option<i64> = tx
+                .query_row(
+                    "SELECT exhibit_id FROM exhibits WHERE exhibit_number = ?1",
+                    params![number],
+                    |row| row.get(0),
+                )
+                .optional()
+                .context("Failed to check exhibit number")?;
+            if taken.is_some() {
+                return Err(AppError::Conflict(format!(
+                    "Exhibit {} is already in the register.",
+                    number
+                )));
+            }
+            number
+        }
+        None => next_exhibit_number(&tx, &received_on[..4])?,
+    };
+
+    tx.execute(
+        "INSERT INTO exhibits (exhibit_number, case_id, offender_id, category, description,
+             quantity, received_on, received_by, produced_by, storage_location, notes,
+             created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            exhibit_number,
+            exhibit.case_id,
+            exhibit.offender_id,
+            exhibit.category.as_str(),
+            description,
+            optional_text(exhibit.quantity),
+            received_on,
+            received_by,
+            produced_by,
+            storage_location,
+            optional_text(exhibit.notes),
+            created_by
+        ],
+    )
+    .context("Failed to register exhibit")?;
+    let id = tx.last_insert_rowid();
+    tx.execute(
+        "INSERT INTO exhibit_movements (exhibit_id, movement, moved_on, released_by,
+             released_by_signature, received_by, received_by_signature, storage_location,
+             recorded_by)
+         VALUES (?1, 'Received', ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            id,
+            received_on,
+            produced_by,
+            produced_by_signature,
+            received_by,
+            received_by_signature,
+            storage_location,
+            created_by
+        ],
+    )
+    .context("Failed to record exhibit movement")?;
+    let registered = detail(&tx, id)?;
+    tx.commit()?;
+    Ok(registered)
+}
+
+/// Append a movement to the exhibit's chain of custody and bring its status
+/// and location up to date
+pub fn record_movement(
+    conn: &mut Connection,
+    exhibit_id: i64,
+    movement: NewMovement,
+    recorded_by: Option<&str>,
+) -> AppResult<ExhibitDetail> {
+    if movement.movement == MovementKind::Received {
+        return Err(AppError::field(
+            "movement",
+            "An exhibit is received when it is registered",
+        ));
+    }
+    let released_by = required(
+        "released_by",
+        &movement.released_by,
+        "Enter who handed the exhibit over",
+    )?;
+    let received_by = required(
+        "received_by",
+        &movement.received_by,
+        "Enter who took the exhibit",
+    )?;
+    let released_by_signature =
+        signature("released_by_signature", &movement.released_by_signature)?;
+    let received_by_signature =
+        signature("received_by_signature", &movement.received_by_signature)?;
+    let moved_on = optional_date("moved_on", movement.moved_on.as_deref())?.unwrap_or_else(today);
+    if moved_on > today() {
+        return Err(AppError::field(
+            "moved_on",
+            "The date cannot be in the future",
+        ));
+    }
+    let mut purpose = optional_text(movement.purpose);
+    let mut storage_location = optional_text(movement.storage_location);
+    let court_order = optional_text(movement.court_order);
+    let disposal_method = optional_text(movement.disposal_method);
+
+    let tx = conn.transaction()?;
+    let exhibit = get(&tx, exhibit_id)?;
+    let (from, to) = movement.movement.transition();
+    if exhibit.status != from {
+        return Err(AppError::Conflict(format!(
+            "Exhibit {} is {}; it cannot be marked {}.",
+            exhibit.exhibit_number,
+            exhibit.status.as_str().to_lowercase(),
+            movement.movement.as_str().to_lowercase()
+        )));
+    }
+    let last_moved: Option<String> = tx
+        .query_row(
+            "SELECT MAX(moved_on) FROM exhibit_movements WHERE exhibit_id = ?1",
+            params![exhibit_id],
+            |row| row.get(0),
+        )
+        .context("Failed to fetch exhibit movements")?;
+    if let Some(last_moved) = last_moved.filter(|last| moved_on < *last) {
+        return Err(AppError::field(
+            "moved_on",
+            format!("The exhibit last moved on {}", last_moved),
+        ));
+    }
+    match movement.movement {
+        MovementKind::Out if purpose.is_none() => {
+            return Err(AppError::field(
+                "purpose",
+                "Give the reason the exhibit is going out",
+            ));
+        }
+        MovementKind::Returned => {
+            storage_location = storage_location.or(Some(exhibit.storage_location.clone()));
+        }
+        MovementKind::Transferred => match &storage_location {
+            None => {
+                return Err(AppError::field(
+                    "storage_location",
+                    "Enter where the exhibit is moved to",
+                ));
+            }
+            Some(location) if location.eq_ignore_ascii_case(&exhibit.storage_location) => {
+                return Err(AppError::field(
+                    "storage_location",
+                    "The exhibit is already kept there",
+                ));
+            }
+            Some(_) => {}
+        },
+        MovementKind::Disposed => {
+            if court_order.is_none() {
+                return Err(AppError::field(
+                    "court_order",
+                    "Enter the court order authorising disposal",
+                ));
+            }
+            if disposal_method.is_none() {
+                return Err(AppError::field(
+                    "disposal_method",
+                    "Enter how the exhibit is disposed of",
+                ));
+            }
+            purpose = disposal_method.clone();
+            storage_location = None;
+        }
+        _ => {}
+    }
+    if movement.movement == MovementKind::Out {
+        storage_location = None;
+    }
+
+    tx.execute(
+        "INSERT INTO exhibit_movements (exhibit_id, movement, moved_on, purpose, released_by,
+             released_by_signature, received_by, received_by_signature, storage_location,
+             court_order, notes, recorded_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            exhibit_id,
+            movement.movement.as_str(),
+            moved_on,
+            purpose,
+            released_by,
+            released_by_signature,
+            received_by,
+            received_by_signature,
+            storage_location,
+            court_order,
+            optional_text(movement.notes),
+            recorded_by
+        ],
+    )
+    .context("Failed to record exhibit movement")?;
+    let disposed = movement.movement == MovementKind::Disposed;
+    tx.execute(
+        "UPDATE exhibits SET status = ?2, storage_location = COALESCE(?3, storage_location),
+             disposed_on = ?4, disposal_method = ?5, disposal_order = ?6,
+             last_modified = CURRENT_TIMESTAMP
+         WHERE exhibit_id = ?1",
+        params![
+            exhibit_id,
+            to.as_str(),
+            storage_location,
+            disposed.then_some(&moved_on),
+            disposal_method.filter(|_| disposed),
+            court_order.filter(|_| disposed)
+        ],
+    )
+    .context("Failed to update exhibit")?;
+    let moved = detail(&tx, exhibit_id)?;
+    tx.commit()?;
+    Ok(moved)
+}
+
+pub fn list(conn: &Connection, filter: &ExhibitFilter) -> AppResult<Vec<Exhibit>> {
+    let location = filter
+        .storage_location
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR e.case_id = ?1)
+                 AND (?2 IS NULL OR e.status = ?2)
+                 AND (?3 IS NULL OR e.category = ?3)
+                 AND (?4 IS NULL OR e.storage_location = ?4 COLLATE NOCASE)
+             ORDER BY e.received_on DESC, e.exhibit_id DESC",
+            EXHIBIT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let exhibits = stmt
+        .query_map(
+            params![
+                filter.case_id,
+                filter.status.map(|s| s.as_str()),
+                filter.category.map(|c| c.as_str()),
+                location
+            ],
+            Exhibit::from_row,
+        )
+        .context("Failed to fetch exhibits")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read exhibits")?;
+    Ok(exhibits)
+}
+
+/// Every exhibit of a case, in the order received, with its movements
+pub fn case_report(conn: &Connection, case_id: i64) -> AppResult<ExhibitReport> {
+    let (case_number, title): (String, Option<String>) = conn
+        .query_row(
+            "SELECT case_number, title FROM cases WHERE case_id = ?1",
+            params![case_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("Failed to fetch case")?
+        .ok_or_else(|| AppError::not_found("Case not found"))?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE e.case_id = ?1 ORDER BY e.received_on, e.exhibit_id",
+            EXHIBIT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let list: Vec<Exhibit> = stmt
+        .query_map(params![case_id], Exhibit::from_row)
+        .context("Failed to fetch exhibits")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read exhibits")?;
+
+    let count = |status| list.iter().filter(|e| e.status == status).count();
+    let (in_store, out, disposed) = (
+        count(ExhibitStatus::InStore),
+        count(ExhibitStatus::Out),
+        count(ExhibitStatus::Disposed),
+    );
+    let mut exhibits = Vec::with_capacity(list.len());
+    for exhibit in list {
+        let movements = movements(conn, exhibit.exhibit_id)?;
+        exhibits.push(ExhibitDetail { exhibit, movements });
+    }
+
+    Ok(ExhibitReport {
+        case_id,
+        case_number,
+        title,
+        exhibits,
+        in_store,
+        out,
+        disposed,
+        generated_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+    })
+}
+
+fn signature_html(signature: &str) -> String {
+    if SIGNATURE_IMAGE_PREFIXES
+        .iter()
+        .any(|prefix| signature.starts_with(prefix))
+    {
+        format!(
+            "<img class=\"signature\" src=\"{}\" alt=\"signature\">",
+            escape(signature)
+        )
+    } else {
+        format!("<span class=\"signature\">{}</span>", escape(signature))
+    }
+}
+
+const HTML_STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; font-size: 10pt; margin: 2em; color: #000; }
+h1 { font-size: 16pt; margin: 0; }
+p.subtitle { margin: 0.3em 0 1.2em; }
+h2 { font-size: 12pt; margin: 1.6em 0 0.2em; border-bottom: 1px solid #000; }
+p.exhibit { margin: 0.2em 0 0.6em; }
+table { width: 100%; border-collapse: collapse; }
+th, td { text-align: left; vertical-align: top; padding: 0.3em 0.5em 0.3em 0; }
+th { font-size: 8pt; text-transform: uppercase; border-bottom: 1px solid #999; }
+img.signature { max-height: 2.5em; max-width: 10em; }
+span.signature { font-style: italic; }
+p.footer { margin-top: 2em; font-size: 9pt; color: #555; }
+@media print { body { margin: 0; } tr { page-break-inside: avoid; } }
+";
+
+/// The report as a stand-alone HTML page, ready to print
+pub fn report_html(report: &ExhibitReport) -> String {
+    let subtitle = match &report.title {
+        Some(title) => format!("{} \u{2014} {}", report.case_number, title),
+        None => report.case_number.clone(),
+    };
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Exhibits {}</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>Exhibit register</h1>\n<p class=\"subtitle\">{}</p>\n",
+        escape(&report.case_number),
+        HTML_STYLE,
+        escape(&subtitle)
+    );
+    if report.exhibits.is_empty() {
+        html.push_str("<p>No exhibits are registered for this case.</p>\n");
+    }
+    for ExhibitDetail { exhibit, movements } in &report.exhibits {
+        let mut facts = vec![
+            exhibit.category.as_str().to_string(),
+            exhibit.status.as_str().to_string(),
+        ];
+        if let Some(quantity) = &exhibit.quantity {
+            facts.push(quantity.clone());
+        }
+        match exhibit.status {
+            ExhibitStatus::Disposed => facts.push(format!(
+                "{} on {} under {}",
+                exhibit.disposal_method.as_deref().unwrap_or("Disposed of"),
+                exhibit.disposed_on.as_deref().unwrap_or("an unknown date"),
+                exhibit
+                    .disposal_order
+                    .as_deref()
+                    .unwrap_or("no recorded order")
+            )),
+            _ => facts.push(format!("kept at {}", exhibit.storage_location)),
+        }
+        html.push_str(&format!(
+            "<h2>{} \u{2014} {}</h2>\n<p class=\"exhibit\">{}</p>\n<table>\n<thead><tr>\
+             <th>Date</th><th>Movement</th><th>Details</th><th>Handed over by</th>\
+             <th>Taken by</th></tr></thead>\n<tbody>\n",
+            escape(&exhibit.exhibit_number),
+            escape(&exhibit.description),
+            escape(&facts.join("; "))
+        ));
+        for movement in movements {
+            let details: Vec<&str> = [
+                movement.purpose.as_deref(),
+                movement.storage_location.as_deref(),
+                movement.court_order.as_deref(),
+                movement.notes.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}<br>{}</td><td>{}<br>{}</td></tr>\n",
+                escape(&movement.moved_on),
+                movement.movement.as_str(),
+                escape(&details.join("\n")),
+                escape(&movement.released_by),
+                signature_html(&movement.released_by_signature),
+                escape(&movement.received_by),
+                signature_html(&movement.received_by_signature)
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+    html.push_str(&format!(
+        "<p class=\"footer\">{} exhibits: {} in store, {} out, {} disposed of. Generated {}.</p>\n\
+         </body>\n</html>\n",
+        report.exhibits.len(),
+        report.in_store,
+        report.out,
+        report.disposed,
+        escape(&report.generated_at)
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    const DRAWN: &str = "data:image/png;base64,iVBORw0KGgo=";
+
+    fn setup() -> Connection {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO cases (case_id, title, case_number) VALUES (1, 'R v Mutua', 'CR 3/2026')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn knife() -> NewExhibit {
+        NewExhibit {
+            exhibit_number: None,
+            case_id: 1,
+            offender_id: None,
+            category: ExhibitCategory::Weapon,
+            description: "Kitchen knife, black handle".to_string(),
+            quantity: Some("1 piece".to_string()),
+            received_on: Some("2026-03-02".to_string()),
+            produced_by: "PC Kiprop".to_string(),
+            produced_by_signature: DRAWN.to_string(),
+            received_by: "Sgt. Wambua".to_string(),
+            received_by_signature: "S. Wambua".to_string(),
+            storage_location: "Store A, shelf 2".to_string(),
+            notes: None,
+        }
+    }
+
+    fn moved(movement: MovementKind, moved_on: &str) -> NewMovement {
+        NewMovement {
+            movement,
+            moved_on: Some(moved_on.to_string()),
+            purpose: None,
+            released_by: "Sgt. Wambua".to_string(),
+            released_by_signature: "S. Wambua".to_string(),
+            received_by: "PC Kiprop".to_string(),
+            received_by_signature: DRAWN.to_string(),
+            storage_location: None,
+            court_order: None,
+            disposal_method: None,
+            notes: None,
+        }
+    }
+
+    fn field_error(result: AppResult<ExhibitDetail>) -> String {
+        match result {
+            Err(AppError::Validation { field_errors, .. }) => field_errors[0].field.clone(),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn chain_of_custody_follows_the_exhibit_in_and_out() {
+        let mut conn = setup();
+        let registered = register(&mut conn, knife(), None).unwrap();
+        let id = registered.exhibit.exhibit_id;
+        assert_eq!(registered.exhibit.exhibit_number, "EXH-2026-000001");
+        assert_eq!(registered.movements[0].movement, MovementKind::Received);
+        assert_eq!(registered.movements[0].released_by, "PC Kiprop");
+
+        assert_eq!(
+            field_error(record_movement(
+                &mut conn,
+                id,
+                moved(MovementKind::Out, "2026-03-05"),
+                None
+            )),
+            "purpose"
+        );
+        let to_court = NewMovement {
+            purpose: Some("Production in court".to_string()),
+            ..moved(MovementKind::Out, "2026-03-05")
+        };
+        let out = record_movement(&mut conn, id, to_court, None).unwrap();
+        assert_eq!(out.exhibit.status, ExhibitStatus::Out);
+        assert!(matches!(
+            record_movement(&mut conn, id, moved(MovementKind::Out, "2026-03-05"), None),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(
+            field_error(record_movement(
+                &mut conn,
+                id,
+                moved(MovementKind::Returned, "2026-03-04"),
+                None
+            )),
+            "moved_on"
+        );
+        let back = record_movement(
+            &mut conn,
+            id,
+            moved(MovementKind::Returned, "2026-03-05"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(back.exhibit.storage_location, "Store A, shelf 2");
+        assert_eq!(
+            back.movements[2].storage_location.as_deref(),
+            Some("Store A, shelf 2")
+        );
+
+        let same_place = NewMovement {
+            storage_location: Some("store a, SHELF 2".to_string()),
+            ..moved(MovementKind::Transferred, "2026-03-06")
+        };
+        assert_eq!(
+            field_error(record_movement(&mut conn, id, same_place, None)),
+            "storage_location"
+        );
+        let strongroom = NewMovement {
+            storage_location: Some("Strongroom".to_string()),
+            ..moved(MovementKind::Transferred, "2026-03-06")
+        };
+        let moved_in = record_movement(&mut conn, id, strongroom, None).unwrap();
+        assert_eq!(moved_in.exhibit.storage_location, "Strongroom");
+
+        let unordered = NewMovement {
+            disposal_method: Some("Destroyed".to_string()),
+            ..moved(MovementKind::Disposed, "2026-03-09")
+        };
+        assert_eq!(
+            field_error(record_movement(&mut conn, id, unordered, None)),
+            "court_order"
+        );
+        let destroyed = NewMovement {
+            court_order: Some("Order of 9 March 2026".to_string()),
+            disposal_method: Some("Destroyed".to_string()),
+            ..moved(MovementKind::Disposed, "2026-03-09")
+        };
+        let gone = record_movement(&mut conn, id, destroyed, None).unwrap();
+        assert_eq!(gone.exhibit.status, ExhibitStatus::Disposed);
+        assert_eq!(gone.exhibit.disposed_on.as_deref(), Some("2026-03-09"));
+        assert_eq!(gone.movements.len(), 5);
+        assert!(matches!(
+            record_movement(
+                &mut conn,
+                id,
+                moved(MovementKind::Returned, "2026-03-10"),
+                None
+            ),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(
+            field_error(record_movement(
+                &mut conn,
+                id,
+                moved(MovementKind::Received, "2026-03-10"),
+                None
+            )),
+            "movement"
+        );
+    }
+
+    #[test]
+    fn the_register_cannot_be_rewritten() {
+        let mut conn = setup();
+        let id = register(&mut conn, knife(), None)
+            .unwrap()
+            .exhibit
+            .exhibit_id;
+
+        for sql in [
+            "UPDATE exhibit_movements SET received_by = 'Someone else'",
+            "DELETE FROM exhibit_movements",
+            "DELETE FROM exhibits",
+        ] {
+            assert!(conn.execute(sql, []).is_err(), "{} was allowed", sql);
+        }
+        let kept = detail(&conn, id).unwrap();
+        assert_eq!(kept.movements.len(), 1);
+        assert_eq!(kept.movements[0].received_by, "Sgt. Wambua");
+
+        let taken = NewExhibit {
+            exhibit_number: Some("EXH-2026-000001".to_string()),
+            ..knife()
+        };
+        assert!(matches!(
+            register(&mut conn, taken, None),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn signatures_are_drawn_images_or_typed_names() {
+        let mut conn = setup();
+        let svg = NewExhibit {
+            produced_by_signature: "data:image/svg+xml;base64,PHN2Zz4=".to_string(),
+            ..knife()
+        };
+        assert_eq!(
+            field_error(register(&mut conn, svg, None)),
+            "produced_by_signature"
+        );
+        let unsigned = NewExhibit {
+            received_by_signature: " ".to_string(),
+            ..knife()
+        };
+        assert_eq!(
+            field_error(register(&mut conn, unsigned, None)),
+            "received_by_signature"
+        );
+        let scripted = NewExhibit {
+            description: "<script>alert(1)</script>".to_string(),
+            ..knife()
+        };
+        register(&mut conn, scripted, None).unwrap();
+
+        let report = case_report(&conn, 1).unwrap();
+        assert_eq!((report.in_store, report.out, report.disposed), (1, 0, 0));
+        let html = report_html(&report);
+        assert!(html.contains(&format!("<img class=\"signature\" src=\"{}\"", DRAWN)));
+        assert!(html.contains("<span class=\"signature\">S. Wambua</span>"));
+        assert!(html.contains("&lt;script&gt;") && !html.contains("<script>"));
+    }
+}
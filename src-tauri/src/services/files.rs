@@ -0,0 +1,258 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+#[serde(default)]
+pub struct File {
+    pub file_id: i32,
+    pub case_number: String,
+    pub case_type: String,
+    pub purpose: String,
+    pub uploaded_by: i32,
+    pub current_location: String,
+    pub notes: Option<String>,
+    pub date_recieved: Option<String>,
+    pub required_on: Option<String>,
+    pub required_on_signature: Option<String>,
+    pub date_returned: Option<String>,
+    pub date_returned_signature: Option<String>,
+    pub deleted: Option<i32>, // ensure type matches Supabase: 0
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_deleted: Option<bool>,
+}
+
+const FILE_COLUMNS: &str =
+    "file_id, case_number, case_type, purpose, uploaded_by, current_location, notes,
+     date_recieved, required_on, required_on_signature, date_returned,
+     date_returned_signature, deleted";
+
+impl File {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(File {
+            file_id: row.get(0)?,
+            case_number: row.get(1)?,
+            case_type: row.get(2)?,
+            purpose: row.get(3)?,
+            uploaded_by: row.get(4)?,
+            current_location: row.get(5)?,
+            notes: row.get(6)?,
+            date_recieved: row.get(7)?,
+            required_on: row.get(8)?,
+            required_on_signature: row.get(9)?,
+            date_returned: row.get(10)?,
+            date_returned_signature: row.get(11)?,
+            deleted: row.get(12)?,
+            is_deleted: None,
+        })
+    }
+}
+
+/// The editable part of a file record
+pub struct FileDetails {
+    pub case_number: String,
+    pub case_type: String,
+    pub purpose: String,
+    pub current_location: String,
+    pub notes: String,
+    pub required_on: String,
+}
+
+/// Date columns that `update_date` may set
+const DATE_COLUMNS: [&str; 3] = ["required_on", "date_returned", "date_recieved"];
+
+fn query(conn: &Connection, filter: &str, args: impl rusqlite::Params) -> AppResult<Vec<File>> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM files {}", FILE_COLUMNS, filter))
+        .context("Failed to prepare statement")?;
+
+    let files = stmt
+        .query_map(args, File::from_row)
+        .context("Failed to fetch files")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read files")?;
+    Ok(files)
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<File>> {
+    query(conn, "", [])
+}
+
+pub fn get(conn: &Connection, file_id: i32) -> AppResult<Option<File>> {
+    conn.query_row(
+        &format!("SELECT {} FROM files WHERE file_id = ?1", FILE_COLUMNS),
+        params![file_id],
+        File::from_row,
+    )
+    .optional()
+    .context("Failed to fetch file")
+}
+
+pub fn search_by_case_number(conn: &Connection, case_number: &str) -> AppResult<Vec<File>> {
+    query(
+        conn,
+        "WHERE case_number = ?1 AND deleted = 0",
+        params![case_number],
+    )
+}
+
+pub fn filter_by_user(conn: &Connection, user_id: i32) -> AppResult<Vec<File>> {
+    query(
+        conn,
+        "WHERE uploaded_by = ?1 AND deleted = 0",
+        params![user_id],
+    )
+}
+
+pub fn filter_by_purpose(conn: &Connection, purpose: &str) -> AppResult<Vec<File>> {
+    query(conn, "WHERE purpose = ?1 AND deleted = 0", params![purpose])
+}
+
+/// Register a new file, pending sync, and return its id
+pub fn create(conn: &Connection, uploaded_by: i32, file: &FileDetails) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO files (
+            case_number, case_type, purpose, uploaded_by, current_location, notes,
+            required_on, sync_status
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            file.case_number,
+            file.case_type,
+            file.purpose,
+            uploaded_by,
+            file.current_location,
+            file.notes,
+            file.required_on,
+            "pending"
+        ],
+    )
+    .context("Failed to upload file")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update(conn: &Connection, file_id: i64, file: &FileDetails) -> AppResult<()> {
+    let rows_updated = conn
+        .execute(
+            "UPDATE files
+             SET case_number = ?1,
+                    case_type = ?2,
+                    purpose = ?3,
+                    current_location = ?4,
+                    notes = ?5,
+                    required_on = ?6,
+                    sync_status = 'pending'
+
+             WHERE file_id = ?7",
+            params![
+                file.case_number,
+                file.case_type,
+                file.purpose,
+                file.current_location,
+                file.notes,
+                file.required_on,
+                file_id
+            ],
+        )
+        .context("Failed to update file")?;
+    if rows_updated == 0 {
+        return Err(AppError::not_found("No file found with the provided ID."));
+    }
+    Ok(())
+}
+
+/// Set one of the file's date columns (see `DATE_COLUMNS`)
+pub fn update_date(
+    conn: &Connection,
+    file_id: i32,
+    date_type: &str,
+    new_date: &str,
+) -> AppResult<()> {
+    // The column name is interpolated, so only the known date columns are allowed
+    if !DATE_COLUMNS.contains(&date_type) {
+        return Err(AppError::field(
+            "date_type",
+            format!("Unknown date type '{}'", date_type),
+        ));
+    }
+    let query = format!("UPDATE files SET {} = ?1 WHERE file_id = ?2", date_type);
+    conn.execute(&query, params![new_date, file_id])
+        .context(&format!("Failed to update {}", date_type))?;
+    Ok(())
+}
+
+pub fn update_notes(conn: &Connection, file_id: i32, new_notes: &str) -> AppResult<()> {
+    conn.execute(
+        "UPDATE files SET notes = ?1 WHERE file_id = ?2",
+        params![new_notes, file_id],
+    )
+    .context("Failed to update notes")?;
+    Ok(())
+}
+
+pub fn mark_returned(
+    conn: &Connection,
+    file_id: i32,
+    return_date: &str,
+    return_signature: &str,
+) -> AppResult<()> {
+    conn.execute(
+        "UPDATE files SET date_returned = ?1, date_returned_signature = ?2 WHERE file_id = ?3",
+        params![return_date, return_signature, file_id],
+    )
+    .context("Failed to update returned info")?;
+    Ok(())
+}
+
+/// Soft delete; the row stays and is synced as deleted
+pub fn delete(conn: &Connection, file_id: i32) -> AppResult<()> {
+    conn.execute(
+        "UPDATE files SET deleted = 1, is_deleted = 1, sync_status='pending' WHERE file_id = ?1",
+        params![file_id],
+    )
+    .context("Failed to delete file")?;
+    Ok(())
+}
+
+pub fn restore(conn: &Connection, file_id: i32) -> AppResult<()> {
+    conn.execute(
+        "UPDATE files SET deleted = 0, is_deleted = 0, sync_status='pending' WHERE file_id = ?1",
+        params![file_id],
+    )
+    .context("Failed to restore file")?;
+    Ok(())
+}
+
+/// (required_on, note) pairs, oldest first
+pub fn required_on_history(conn: &Connection, file_id: i32) -> AppResult<Vec<(String, String)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT required_on, note FROM history_required_on_in_files WHERE file_id = ?1 ORDER BY required_on ASC",
+        )
+        .context("Failed to prepare statement")?;
+
+    let rows = stmt
+        .query_map(params![file_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .context("Failed to fetch history")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read history")?;
+    Ok(rows)
+}
+
+/// (note, created_by) pairs, oldest first
+pub fn notes_history(conn: &Connection, file_id: i32) -> AppResult<Vec<(String, i32)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT note, created_by FROM history_notes_in_files WHERE file_id = ?1 ORDER BY created_at ASC",
+        )
+        .context("Failed to prepare statement")?;
+
+    let rows = stmt
+        .query_map(params![file_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .context("Failed to fetch history")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read history")?;
+    Ok(rows)
+}
@@ -0,0 +1,496 @@
+use chrono::{Local, NaiveDate, NaiveTime};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Hearings calendar
+//
+// Each sitting of a case is a row in `hearings` (migration 0005). A hearing
+// is scheduled, then either concluded with an outcome or adjourned with a
+// reason, which schedules the next sitting as a new hearing. Whether each
+// accused attended is kept in `hearing_attendance` (migration 0015). Dates
+// are plain local `YYYY-MM-DD`, times `HH:MM`.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HearingPurpose {
+    Mention,
+    Hearing,
+    Ruling,
+    Judgement,
+}
+
+impl HearingPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HearingPurpose::Mention => "Mention",
+            HearingPurpose::Hearing => "Hearing",
+            HearingPurpose::Ruling => "Ruling",
+            HearingPurpose::Judgement => "Judgement",
+        }
+    }
+
+    pub fn parse(purpose: &str) -> Option<HearingPurpose> {
+        match purpose {
+            "Mention" => Some(HearingPurpose::Mention),
+            "Hearing" => Some(HearingPurpose::Hearing),
+            "Ruling" => Some(HearingPurpose::Ruling),
+            "Judgement" => Some(HearingPurpose::Judgement),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HearingStatus {
+    Scheduled,
+    Adjourned,
+    Concluded,
+}
+
+impl HearingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HearingStatus::Scheduled => "Scheduled",
+            HearingStatus::Adjourned => "Adjourned",
+            HearingStatus::Concluded => "Concluded",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<HearingStatus> {
+        match status {
+            "Scheduled" => Some(HearingStatus::Scheduled),
+            "Adjourned" => Some(HearingStatus::Adjourned),
+            "Concluded" => Some(HearingStatus::Concluded),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Hearing {
+    pub hearing_id: i64,
+    pub case_id: i64,
+    pub case_number: Option<String>,
+    pub case_title: Option<String>,
+    pub hearing_date: String,
+    pub hearing_time: Option<String>,
+    pub courtroom: Option<String>,
+    pub presiding_officer: Option<String>,
+    pub purpose: HearingPurpose,
+    pub status: HearingStatus,
+    pub outcome: Option<String>,
+    pub adjournment_reason: Option<String>,
+    pub next_date: Option<String>,
+    /// The hearing this one was adjourned to
+    pub adjourned_to: Option<i64>,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const HEARING_SELECT: &str = "SELECT h.hearing_id, h.case_id, c.case_number, c.title,
+        h.hearing_date, h.hearing_time, h.courtroom, h.presiding_officer, h.purpose, h.status,
+        h.outcome, h.adjournment_reason, h.next_date, h.adjourned_to, h.notes, h.created_by,
+        h.created_at
+    FROM hearings h
+    LEFT JOIN cases c ON c.case_id = h.case_id";
+
+const HEARING_ORDER: &str =
+    "ORDER BY h.hearing_date, h.hearing_time IS NULL, h.hearing_time, h.courtroom, h.hearing_id";
+
+fn unknown(column: usize, value: &str) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        column,
+        rusqlite::types::Type::Text,
+        format!("unexpected value {}", value).into(),
+    )
+}
+
+impl Hearing {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let purpose: String = row.get(8)?;
+        let status: String = row.get(9)?;
+        Ok(Hearing {
+            hearing_id: row.get(0)?,
+            case_id: row.get(1)?,
+            case_number: row.get(2)?,
+            case_title: row.get(3)?,
+            hearing_date: row.get(4)?,
+            hearing_time: row.get(5)?,
+            courtroom: row.get(6)?,
+            presiding_officer: row.get(7)?,
+            purpose: HearingPurpose::parse(&purpose).ok_or_else(|| unknown(8, &purpose))?,
+            status: HearingStatus::parse(&status).ok_or_else(|| unknown(9, &status))?,
+            outcome: row.get(10)?,
+            adjournment_reason: row.get(11)?,
+            next_date: row.get(12)?,
+            adjourned_to: row.get(13)?,
+            notes: row.get(14)?,
+            created_by: row.get(15)?,
+            created_at: row.get(16)?,
+        })
+    }
+}
+
+/// A hearing to put in the calendar
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewHearing {
+    pub case_id: i64,
+    pub hearing_date: String,
+    pub hearing_time: Option<String>,
+    pub courtroom: Option<String>,
+    pub presiding_officer: Option<String>,
+    /// Defaults to `Hearing`
+    pub purpose: Option<HearingPurpose>,
+    pub notes: Option<String>,
+}
+
+/// Why a hearing did not go ahead and when the case comes back. Courtroom,
+/// presiding officer and purpose default to those of the adjourned hearing.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Adjournment {
+    pub reason: String,
+    pub next_date: String,
+    pub next_time: Option<String>,
+    pub courtroom: Option<String>,
+    pub presiding_officer: Option<String>,
+    pub purpose: Option<HearingPurpose>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Adjourned {
+    pub adjourned: Hearing,
+    pub next: Hearing,
+}
+
+/// Whether an accused was at a hearing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attendance {
+    pub offender_id: i64,
+    pub attended: bool,
+}
+
+/// Which hearings to list; every field narrows the list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HearingFilter {
+    /// First day, inclusive
+    pub from: Option<String>,
+    /// Last day, inclusive
+    pub to: Option<String>,
+    pub courtroom: Option<String>,
+    pub status: Option<HearingStatus>,
+}
+
+pub(crate) fn parse_date(field: &str, value: &str) -> AppResult<String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .map_err(|_| AppError::field(field, "Enter a date as YYYY-MM-DD"))
+}
+
+fn parse_time(field: &str, value: &str) -> AppResult<String> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map(|time| time.format("%H:%M").to_string())
+        .map_err(|_| AppError::field(field, "Enter a time as HH:MM"))
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+pub fn get(conn: &Connection, hearing_id: i64) -> AppResult<Hearing> {
+    conn.query_row(
+        &format!("{} WHERE h.hearing_id = ?1", HEARING_SELECT),
+        params![hearing_id],
+        Hearing::from_row,
+    )
+    .optional()
+    .context("Failed to fetch hearing")?
+    .ok_or_else(|| AppError::not_found("Hearing not found"))
+}
+
+/// Put a hearing of a case in the calendar
+pub fn schedule(
+    conn: &Connection,
+    hearing: NewHearing,
+    created_by: Option<&str>,
+) -> AppResult<Hearing> {
+    let case_open: Option<bool> = conn
+        .query_row(
+            "SELECT COALESCE(is_deleted, 0) = 0 FROM cases WHERE case_id = ?1",
+            params![hearing.case_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if case_open != Some(true) {
+        return Err(AppError::not_found("Case not found"));
+    }
+    let date = parse_date("hearing_date", &hearing.hearing_date)?;
+    let time = hearing
+        .hearing_time
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+        .map(|t| parse_time("hearing_time", t))
+        .transpose()?;
+
+    conn.execute(
+        "INSERT INTO hearings (case_id, hearing_date, hearing_time, courtroom, presiding_officer,
+                               purpose, notes, created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            hearing.case_id,
+            date,
+            time,
+            optional_text(hearing.courtroom),
+            optional_text(hearing.presiding_officer),
+            hearing.purpose.unwrap_or(HearingPurpose::Hearing).as_str(),
+            optional_text(hearing.notes),
+            created_by,
+        ],
+    )
+    .context("Failed to schedule hearing")?;
+    get(conn, conn.last_insert_rowid())
+}
+
+/// Adjourn a scheduled hearing and schedule the next sitting
+pub fn adjourn(
+    conn: &mut Connection,
+    hearing_id: i64,
+    adjournment: Adjournment,
+    adjourned_by: Option<&str>,
+) -> AppResult<Adjourned> {
+    let reason = adjournment.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::field(
+            "reason",
+            "Give a reason for the adjournment",
+        ));
+    }
+    let next_date = parse_date("next_date", &adjournment.next_date)?;
+
+    let tx = conn.transaction()?;
+    let hearing = get(&tx, hearing_id)?;
+    if hearing.status != HearingStatus::Scheduled {
+        return Err(AppError::Conflict(format!(
+            "Only a scheduled hearing can be adjourned; this one is {}.",
+            hearing.status.as_str().to_lowercase()
+        )));
+    }
+    if next_date < hearing.hearing_date {
+        return Err(AppError::field(
+            "next_date",
+            "The next date cannot be before the adjourned hearing",
+        ));
+    }
+
+    let next = schedule(
+        &tx,
+        NewHearing {
+            case_id: hearing.case_id,
+            hearing_date: next_date.clone(),
+            hearing_time: adjournment.next_time,
+            courtroom: optional_text(adjournment.courtroom).or(hearing.courtroom),
+            presiding_officer: optional_text(adjournment.presiding_officer)
+                .or(hearing.presiding_officer),
+            purpose: Some(adjournment.purpose.unwrap_or(hearing.purpose)),
+            notes: None,
+        },
+        adjourned_by,
+    )?;
+    tx.execute(
+        "UPDATE hearings SET status = 'Adjourned', adjournment_reason = ?2, next_date = ?3,
+         adjourned_to = ?4, last_modified = CURRENT_TIMESTAMP
+         WHERE hearing_id = ?1",
+        params![hearing_id, reason, next_date, next.hearing_id],
+    )
+    .context("Failed to adjourn hearing")?;
+    let adjourned = get(&tx, hearing_id)?;
+    tx.commit()?;
+
+    Ok(Adjourned { adjourned, next })
+}
+
+/// Record what happened at a hearing, which concludes it, with who of the
+/// accused attended. An outcome can be corrected later; an adjourned hearing
+/// has none.
+pub fn record_outcome(
+    conn: &mut Connection,
+    hearing_id: i64,
+    outcome: &str,
+    attendance: &[Attendance],
+    recorded_by: Option<&str>,
+) -> AppResult<Hearing> {
+    let outcome = outcome.trim();
+    if outcome.is_empty() {
+        return Err(AppError::field("outcome", "Describe the outcome"));
+    }
+    let tx = conn.transaction()?;
+    let hearing = get(&tx, hearing_id)?;
+    if hearing.status == HearingStatus::Adjourned {
+        return Err(AppError::Conflict(
+            "The hearing was adjourned; record the outcome on the next one.".to_string(),
+        ));
+    }
+    tx.execute(
+        "UPDATE hearings SET status = 'Concluded', outcome = ?2, last_modified = CURRENT_TIMESTAMP
+         WHERE hearing_id = ?1",
+        params![hearing_id, outcome],
+    )
+    .context("Failed to record hearing outcome")?;
+    write_attendance(&tx, &hearing, attendance, recorded_by)?;
+    let concluded = get(&tx, hearing_id)?;
+    tx.commit()?;
+    Ok(concluded)
+}
+
+/// Record who of the accused attended a hearing that has taken place,
+/// such as one that was adjourned. Replaces what was recorded before for
+/// the same people.
+pub fn record_attendance(
+    conn: &mut Connection,
+    hearing_id: i64,
+    attendance: &[Attendance],
+    recorded_by: Option<&str>,
+) -> AppResult<Vec<Attendance>> {
+    let tx = conn.transaction()?;
+    let hearing = get(&tx, hearing_id)?;
+    write_attendance(&tx, &hearing, attendance, recorded_by)?;
+    let recorded = list_attendance(&tx, hearing_id)?;
+    tx.commit()?;
+    Ok(recorded)
+}
+
+fn write_attendance(
+    conn: &Connection,
+    hearing: &Hearing,
+    attendance: &[Attendance],
+    recorded_by: Option<&str>,
+) -> AppResult<()> {
+    if attendance.is_empty() {
+        return Ok(());
+    }
+    if hearing.hearing_date > today() {
+        return Err(AppError::validation("The hearing has not taken place yet."));
+    }
+    for entry in attendance {
+        let known: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM offenders
+                    WHERE offender_id = ?1 AND COALESCE(is_deleted, 0) = 0)",
+                params![entry.offender_id],
+                |row| row.get(0),
+            )
+            .context("Failed to check offender")?;
+        if !known {
+            return Err(AppError::field(
+                "attendance",
+                format!("Offender {} not found", entry.offender_id),
+            ));
+        }
+        conn.execute(
+            "INSERT INTO hearing_attendance (hearing_id, offender_id, offender_name, attended,
+                 recorded_by)
+             VALUES (?1, ?2, (SELECT full_name FROM offenders WHERE offender_id = ?2), ?3, ?4)
+             ON CONFLICT(hearing_id, offender_id) DO UPDATE SET
+                 attended = excluded.attended,
+                 recorded_by = excluded.recorded_by,
+                 recorded_at = CURRENT_TIMESTAMP",
+            params![
+                hearing.hearing_id,
+                entry.offender_id,
+                entry.attended,
+                recorded_by
+            ],
+        )
+        .context("Failed to record attendance")?;
+    }
+    Ok(())
+}
+
+/// Who of the accused was recorded at a hearing
+pub fn list_attendance(conn: &Connection, hearing_id: i64) -> AppResult<Vec<Attendance>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT offender_id, attended FROM hearing_attendance
+             WHERE hearing_id = ?1 AND offender_id IS NOT NULL ORDER BY offender_id",
+        )
+        .context("Failed to prepare statement")?;
+    let attendance = stmt
+        .query_map(params![hearing_id], |row| {
+            Ok(Attendance {
+                offender_id: row.get(0)?,
+                attended: row.get(1)?,
+            })
+        })
+        .context("Failed to fetch attendance")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read attendance")?;
+    Ok(attendance)
+}
+
+/// Hearings in date and time order, leaving out those of deleted cases
+pub fn list(conn: &Connection, filter: &HearingFilter) -> AppResult<Vec<Hearing>> {
+    let from = filter
+        .from
+        .as_deref()
+        .map(|d| parse_date("from", d))
+        .transpose()?;
+    let to = filter
+        .to
+        .as_deref()
+        .map(|d| parse_date("to", d))
+        .transpose()?;
+    let courtroom = filter
+        .courtroom
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE COALESCE(c.is_deleted, 0) = 0
+                AND (?1 IS NULL OR h.hearing_date >= ?1)
+                AND (?2 IS NULL OR h.hearing_date <= ?2)
+                AND (?3 IS NULL OR h.courtroom = ?3 COLLATE NOCASE)
+                AND (?4 IS NULL OR h.status = ?4)
+             {}",
+            HEARING_SELECT, HEARING_ORDER
+        ))
+        .context("Failed to prepare statement")?;
+    let hearings = stmt
+        .query_map(
+            params![from, to, courtroom, filter.status.map(|s| s.as_str())],
+            Hearing::from_row,
+        )
+        .context("Failed to fetch hearings")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read hearings")?;
+    Ok(hearings)
+}
+
+/// Every hearing of a case, adjourned ones included
+pub fn list_for_case(conn: &Connection, case_id: i64) -> AppResult<Vec<Hearing>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE h.case_id = ?1 {}",
+            HEARING_SELECT, HEARING_ORDER
+        ))
+        .context("Failed to prepare statement")?;
+    let hearings = stmt
+        .query_map(params![case_id], Hearing::from_row)
+        .context("Failed to fetch hearings")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read hearings")?;
+    Ok(hearings)
+}
@@ -0,0 +1,1000 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+use crate::services::sentences::{self, format_cents, PenaltyKind};
+
+// ============================================================================
+// Fines and fees ledger
+//
+// Assessments are amounts owed on a case, offender or file; payments with
+// receipt numbers pay them down (migration 0008). Payments are never changed
+// or removed: a mistaken one is reversed by a negative entry on the day of
+// the reversal, so past days' totals stay as they were. Balances and
+// statuses are always worked out from the entries. Amounts are integer
+// cents throughout.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssessmentKind {
+    Fine,
+    #[serde(rename = "Filing Fee")]
+    FilingFee,
+    #[serde(rename = "Court Fee")]
+    CourtFee,
+    Other,
+}
+
+impl AssessmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssessmentKind::Fine => "Fine",
+            AssessmentKind::FilingFee => "Filing Fee",
+            AssessmentKind::CourtFee => "Court Fee",
+            AssessmentKind::Other => "Other",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<AssessmentKind> {
+        match kind {
+            "Fine" => Some(AssessmentKind::Fine),
+            "Filing Fee" => Some(AssessmentKind::FilingFee),
+            "Court Fee" => Some(AssessmentKind::CourtFee),
+            "Other" => Some(AssessmentKind::Other),
+            _ => None,
+        }
+    }
+}
+
+/// How an assessment was settled other than by payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Closure {
+    /// A fine served as the default term instead
+    Converted,
+    Waived,
+}
+
+impl Closure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Closure::Converted => "Converted",
+            Closure::Waived => "Waived",
+        }
+    }
+
+    pub fn parse(closure: &str) -> Option<Closure> {
+        match closure {
+            "Converted" => Some(Closure::Converted),
+            "Waived" => Some(Closure::Waived),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentMethod {
+    Cash,
+    #[serde(rename = "M-Pesa")]
+    MPesa,
+    Bank,
+    Cheque,
+    Other,
+}
+
+impl PaymentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "Cash",
+            PaymentMethod::MPesa => "M-Pesa",
+            PaymentMethod::Bank => "Bank",
+            PaymentMethod::Cheque => "Cheque",
+            PaymentMethod::Other => "Other",
+        }
+    }
+
+    pub fn parse(method: &str) -> Option<PaymentMethod> {
+        match method {
+            "Cash" => Some(PaymentMethod::Cash),
+            "M-Pesa" => Some(PaymentMethod::MPesa),
+            "Bank" => Some(PaymentMethod::Bank),
+            "Cheque" => Some(PaymentMethod::Cheque),
+            "Other" => Some(PaymentMethod::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Where an assessment stands, worked out from its payments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AssessmentStatus {
+    Unpaid,
+    #[serde(rename = "Partly Paid")]
+    PartlyPaid,
+    Paid,
+    Converted,
+    Waived,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewAssessment {
+    pub kind: AssessmentKind,
+    pub description: Option<String>,
+    pub case_id: Option<i64>,
+    pub offender_id: Option<i64>,
+    pub file_id: Option<i64>,
+    /// A sentence with a fine; the amount, case and offender default from it
+    pub sentence_id: Option<i64>,
+    pub amount_cents: Option<i64>,
+    /// YYYY-MM-DD, today if not given
+    pub assessed_on: Option<String>,
+    pub due_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Assessment {
+    pub assessment_id: i64,
+    pub kind: AssessmentKind,
+    pub description: Option<String>,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub file_id: Option<i64>,
+    pub sentence_id: Option<i64>,
+    /// The case or file number when assessed
+    pub reference: Option<String>,
+    pub amount_cents: i64,
+    /// Payments less reversals
+    pub paid_cents: i64,
+    pub balance_cents: i64,
+    pub status: AssessmentStatus,
+    pub assessed_on: String,
+    pub due_date: Option<String>,
+    pub closure_reason: Option<String>,
+    pub closed_on: Option<String>,
+    pub closed_by: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const ASSESSMENT_SELECT: &str = "SELECT a.assessment_id, a.kind, a.description, a.case_id,
+        c.case_number, a.offender_id, o.full_name, a.file_id, a.sentence_id, a.reference,
+        a.amount_cents, COALESCE(p.paid, 0), a.assessed_on, a.due_date, a.closure,
+        a.closure_reason, a.closed_on, a.closed_by, a.created_by, a.created_at
+    FROM ledger_assessments a
+    LEFT JOIN (SELECT assessment_id, SUM(amount_cents) AS paid
+               FROM ledger_payments GROUP BY assessment_id) p
+        ON p.assessment_id = a.assessment_id
+    LEFT JOIN cases c ON c.case_id = a.case_id
+    LEFT JOIN offenders o ON o.offender_id = a.offender_id";
+
+fn conversion_error(column: usize, message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, message.into())
+}
+
+impl Assessment {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let kind: String = row.get(1)?;
+        let closure: Option<String> = row.get(14)?;
+        let amount_cents: i64 = row.get(10)?;
+        let paid_cents: i64 = row.get(11)?;
+        let closure = closure
+            .map(|c| {
+                Closure::parse(&c)
+                    .ok_or_else(|| conversion_error(14, format!("unknown closure {}", c)))
+            })
+            .transpose()?;
+        let status = match closure {
+            Some(Closure::Converted) => AssessmentStatus::Converted,
+            Some(Closure::Waived) => AssessmentStatus::Waived,
+            None if paid_cents >= amount_cents => AssessmentStatus::Paid,
+            None if paid_cents > 0 => AssessmentStatus::PartlyPaid,
+            None => AssessmentStatus::Unpaid,
+        };
+        Ok(Assessment {
+            assessment_id: row.get(0)?,
+            kind: AssessmentKind::parse(&kind)
+                .ok_or_else(|| conversion_error(1, format!("unknown assessment kind {}", kind)))?,
+            description: row.get(2)?,
+            case_id: row.get(3)?,
+            case_number: row.get(4)?,
+            offender_id: row.get(5)?,
+            offender_name: row.get(6)?,
+            file_id: row.get(7)?,
+            sentence_id: row.get(8)?,
+            reference: row.get(9)?,
+            amount_cents,
+            paid_cents,
+            balance_cents: amount_cents - paid_cents,
+            status,
+            assessed_on: row.get(12)?,
+            due_date: row.get(13)?,
+            closure_reason: row.get(15)?,
+            closed_on: row.get(16)?,
+            closed_by: row.get(17)?,
+            created_by: row.get(18)?,
+            created_at: row.get(19)?,
+        })
+    }
+
+    fn status_word(&self) -> &'static str {
+        match self.status {
+            AssessmentStatus::Converted => "converted to the default term",
+            AssessmentStatus::Waived => "waived",
+            AssessmentStatus::Paid => "paid",
+            AssessmentStatus::PartlyPaid => "partly paid",
+            AssessmentStatus::Unpaid => "unpaid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewPayment {
+    pub assessment_id: i64,
+    pub amount_cents: i64,
+    /// From the receipt book; the next `RCP-<year>-<number>` if not given
+    pub receipt_number: Option<String>,
+    pub method: PaymentMethod,
+    /// M-Pesa code, cheque number and the like
+    pub reference: Option<String>,
+    /// YYYY-MM-DD, today if not given
+    pub paid_on: Option<String>,
+}
+
+/// A payment, or the reversal of one
+#[derive(Debug, Clone, Serialize)]
+pub struct Payment {
+    pub payment_id: i64,
+    pub assessment_id: i64,
+    /// Negative for a reversal
+    pub amount_cents: i64,
+    pub receipt_number: Option<String>,
+    pub method: PaymentMethod,
+    pub reference: Option<String>,
+    pub paid_on: String,
+    pub reversal_of: Option<i64>,
+    /// The reversal that cancelled this payment, if any
+    pub reversed_by: Option<i64>,
+    pub reason: Option<String>,
+    pub recorded_by: Option<String>,
+    pub recorded_at: Option<String>,
+}
+
+const PAYMENT_SELECT: &str = "SELECT p.payment_id, p.assessment_id, p.amount_cents,
+        p.receipt_number, p.method, p.reference, p.paid_on, p.reversal_of, r.payment_id,
+        p.reason, p.recorded_by, p.recorded_at
+    FROM ledger_payments p
+    LEFT JOIN ledger_payments r ON r.reversal_of = p.payment_id";
+
+impl Payment {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let method: String = row.get(4)?;
+        Ok(Payment {
+            payment_id: row.get(0)?,
+            assessment_id: row.get(1)?,
+            amount_cents: row.get(2)?,
+            receipt_number: row.get(3)?,
+            method: PaymentMethod::parse(&method)
+                .ok_or_else(|| conversion_error(4, format!("unknown payment method {}", method)))?,
+            reference: row.get(5)?,
+            paid_on: row.get(6)?,
+            reversal_of: row.get(7)?,
+            reversed_by: row.get(8)?,
+            reason: row.get(9)?,
+            recorded_by: row.get(10)?,
+            recorded_at: row.get(11)?,
+        })
+    }
+}
+
+/// An assessment with every payment and reversal against it
+#[derive(Debug, Clone, Serialize)]
+pub struct AssessmentDetail {
+    pub assessment: Assessment,
+    pub payments: Vec<Payment>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AssessmentFilter {
+    pub case_id: Option<i64>,
+    pub offender_id: Option<i64>,
+    pub file_id: Option<i64>,
+    pub kind: Option<AssessmentKind>,
+    /// Only open assessments with something left to pay
+    #[serde(default)]
+    pub outstanding_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Outstanding {
+    pub assessments: Vec<Assessment>,
+    pub total_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Subtotal {
+    pub name: String,
+    pub net_cents: i64,
+}
+
+/// Money taken on one day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyTotal {
+    pub date: String,
+    pub payments: i64,
+    pub collected_cents: i64,
+    pub reversed_cents: i64,
+    pub net_cents: i64,
+    pub by_method: Vec<Subtotal>,
+    pub by_kind: Vec<Subtotal>,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn exists(conn: &Connection, table: &str, id_column: &str, id: i64) -> AppResult<bool> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT 1 FROM {} WHERE {} = ?1", table, id_column),
+            params![id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+pub fn get_assessment(conn: &Connection, assessment_id: i64) -> AppResult<Assessment> {
+    conn.query_row(
+        &format!("{} WHERE a.assessment_id = ?1", ASSESSMENT_SELECT),
+        params![assessment_id],
+        Assessment::from_row,
+    )
+    .optional()
+    .context("Failed to fetch assessment")?
+    .ok_or_else(|| AppError::not_found("Assessment not found"))
+}
+
+fn get_payment(conn: &Connection, payment_id: i64) -> AppResult<Payment> {
+    conn.query_row(
+        &format!("{} WHERE p.payment_id = ?1", PAYMENT_SELECT),
+        params![payment_id],
+        Payment::from_row,
+    )
+    .optional()
+    .context("Failed to fetch payment")?
+    .ok_or_else(|| AppError::not_found("Payment not found"))
+}
+
+pub fn detail(conn: &Connection, assessment_id: i64) -> AppResult<AssessmentDetail> {
+    let assessment = get_assessment(conn, assessment_id)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE p.assessment_id = ?1 ORDER BY p.paid_on, p.payment_id",
+            PAYMENT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let payments = stmt
+        .query_map(params![assessment_id], Payment::from_row)
+        .context("Failed to fetch payments")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read payments")?;
+    Ok(AssessmentDetail {
+        assessment,
+        payments,
+    })
+}
+
+/// Record an amount owed
+pub fn assess(
+    conn: &Connection,
+    mut assessment: NewAssessment,
+    created_by: Option<&str>,
+) -> AppResult<Assessment> {
+    if let Some(sentence_id) = assessment.sentence_id {
+        let sentence = sentences::get(conn, sentence_id)?;
+        let fine = sentence
+            .fine_amount_cents
+            .filter(|_| sentence.penalty == PenaltyKind::Fine)
+            .ok_or_else(|| AppError::field("sentence_id", "The sentence has no fine"))?;
+        if assessment.kind != AssessmentKind::Fine {
+            return Err(AppError::field(
+                "kind",
+                "An assessment from a sentence is a fine",
+            ));
+        }
+        assessment.amount_cents = assessment.amount_cents.or(Some(fine));
+        assessment.case_id = assessment.case_id.or(sentence.case_id);
+        assessment.offender_id = assessment.offender_id.or(sentence.offender_id);
+    }
+    let amount_cents = assessment
+        .amount_cents
+        .ok_or_else(|| AppError::field("amount_cents", "Enter the amount"))?;
+    if amount_cents <= 0 {
+        return Err(AppError::field(
+            "amount_cents",
+            "The amount must be above zero",
+        ));
+    }
+    if assessment.case_id.is_none()
+        && assessment.offender_id.is_none()
+        && assessment.file_id.is_none()
+    {
+        return Err(AppError::validation(
+            "An assessment must be against a case, an offender or a file",
+        ));
+    }
+    if let Some(id) = assessment.case_id {
+        if !exists(conn, "cases", "case_id", id)? {
+            return Err(AppError::not_found("Case not found"));
+        }
+    }
+    if let Some(id) = assessment.offender_id {
+        if !exists(conn, "offenders", "offender_id", id)? {
+            return Err(AppError::not_found("Offender not found"));
+        }
+    }
+    if let Some(id) = assessment.file_id {
+        if !exists(conn, "files", "file_id", id)? {
+            return Err(AppError::not_found("File not found"));
+        }
+    }
+    let assessed_on = match assessment.assessed_on.as_deref() {
+        Some(date) => parse_date("assessed_on", date)?,
+        None => today(),
+    };
+    let due_date = assessment
+        .due_date
+        .as_deref()
+        .map(|date| parse_date("due_date", date))
+        .transpose()?;
+
+    let reference: Option<String> = conn
+        .query_row(
+            "SELECT COALESCE(
+                 (SELECT case_number FROM cases WHERE case_id = ?1),
+                 (SELECT case_number FROM files WHERE file_id = ?2))",
+            params![assessment.case_id, assessment.file_id],
+            |row| row.get(0),
+        )
+        .context("Failed to look up case number")?;
+
+    conn.execute(
+        "INSERT INTO ledger_assessments (kind, description, case_id, offender_id, file_id,
+             sentence_id, reference, amount_cents, assessed_on, due_date, created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            assessment.kind.as_str(),
+            optional_text(assessment.description),
+            assessment.case_id,
+            assessment.offender_id,
+            assessment.file_id,
+            assessment.sentence_id,
+            reference,
+            amount_cents,
+            assessed_on,
+            due_date,
+            created_by
+        ],
+    )
+    .context("Failed to record assessment")?;
+    get_assessment(conn, conn.last_insert_rowid())
+}
+
+/// The receipt number after the last one issued in `year`
+fn next_receipt_number(conn: &Connection, year: &str) -> AppResult<String> {
+    let prefix = format!("RCP-{}-", year);
+    let last: Option<i64> = conn
+        .query_row(
+            "SELECT MAX(CAST(substr(receipt_number, ?2) AS INTEGER)) FROM ledger_payments
+             WHERE receipt_number LIKE ?1 || '%'",
+            params![prefix, prefix.len() as i64 + 1],
+            |row| row.get(0),
+        )
+        .context("Failed to number receipt")?;
+    Ok(format!("{}{:06}", prefix, last.unwrap_or(0) + 1))
+}
+
+/// Take a payment against an assessment. A payment cannot be more than the
+/// balance.
+pub fn pay(
+    conn: &mut Connection,
+    payment: NewPayment,
+    recorded_by: Option<&str>,
+) -> AppResult<Payment> {
+    if payment.amount_cents <= 0 {
+        return Err(AppError::field(
+            "amount_cents",
+            "The amount must be above zero",
+        ));
+    }
+    let paid_on = match payment.paid_on.as_deref() {
+        Some(date) => parse_date("paid_on", date)?,
+        None => today(),
+    };
+
+    let tx = conn.transaction()?;
+    let assessment = get_assessment(&tx, payment.assessment_id)?;
+    if matches!(
+        assessment.status,
+        AssessmentStatus::Converted | AssessmentStatus::Waived
+    ) {
+        return Err(AppError::Conflict(format!(
+            "This {} was {} and takes no more payments.",
+            assessment.kind.as_str().to_lowercase(),
+            assessment.status_word()
+        )));
+    }
+    if payment.amount_cents > assessment.balance_cents {
+        return Err(AppError::field(
+            "amount_cents",
+            format!(
+                "The balance is only KSh {}",
+                format_cents(assessment.balance_cents.max(0))
+            ),
+        ));
+    }
+    let receipt_number = match optional_text(payment.receipt_number) {
+        Some(number) => number,
+        None => next_receipt_number(&tx, &paid_on[..4])?,
+    };
+    let taken: Option<i64> = tx
+        .query_row(
+            "SELECT payment_id FROM ledger_payments WHERE receipt_number = ?1",
+            params![receipt_number],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to look up receipt")?;
+    if taken.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Receipt {} has already been used.",
+            receipt_number
+        )));
+    }
+
+    tx.execute(
+        "INSERT INTO ledger_payments (assessment_id, amount_cents, receipt_number, method,
+             reference, paid_on, recorded_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            payment.assessment_id,
+            payment.amount_cents,
+            receipt_number,
+            payment.method.as_str(),
+            optional_text(payment.reference),
+            paid_on,
+            recorded_by
+        ],
+    )
+    .context("Failed to record payment")?;
+    let recorded = get_payment(&tx, tx.last_insert_rowid())?;
+    tx.commit()?;
+    Ok(recorded)
+}
+
+/// Cancel a payment with a negative entry dated today
+pub fn reverse(
+    conn: &mut Connection,
+    payment_id: i64,
+    reason: &str,
+    recorded_by: Option<&str>,
+) -> AppResult<Payment> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::field("reason", "Give a reason for the reversal"));
+    }
+    let tx = conn.transaction()?;
+    let payment = get_payment(&tx, payment_id)?;
+    if payment.reversal_of.is_some() {
+        return Err(AppError::Conflict(
+            "A reversal cannot itself be reversed.".to_string(),
+        ));
+    }
+    if payment.reversed_by.is_some() {
+        return Err(AppError::Conflict(
+            "This payment has already been reversed.".to_string(),
+        ));
+    }
+    tx.execute(
+        "INSERT INTO ledger_payments (assessment_id, amount_cents, method, reference, paid_on,
+             reversal_of, reason, recorded_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            payment.assessment_id,
+            -payment.amount_cents,
+            payment.method.as_str(),
+            payment.receipt_number,
+            today(),
+            payment_id,
+            reason,
+            recorded_by
+        ],
+    )
+    .context("Failed to reverse payment")?;
+    let reversal = get_payment(&tx, tx.last_insert_rowid())?;
+    tx.commit()?;
+    Ok(reversal)
+}
+
+/// Settle what is left of an assessment without payment: a fine converted
+/// to its default term, or a fee waived
+pub fn close(
+    conn: &Connection,
+    assessment_id: i64,
+    closure: Closure,
+    reason: &str,
+    closed_by: Option<&str>,
+) -> AppResult<Assessment> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::field("reason", "Give a reason"));
+    }
+    let assessment = get_assessment(conn, assessment_id)?;
+    match assessment.status {
+        AssessmentStatus::Converted | AssessmentStatus::Waived | AssessmentStatus::Paid => {
+            return Err(AppError::Conflict(format!(
+                "This {} is already {}.",
+                assessment.kind.as_str().to_lowercase(),
+                assessment.status_word()
+            )))
+        }
+        AssessmentStatus::Unpaid | AssessmentStatus::PartlyPaid => {}
+    }
+    if closure == Closure::Converted && assessment.kind != AssessmentKind::Fine {
+        return Err(AppError::field(
+            "closure",
+            "Only a fine can be converted to a default term",
+        ));
+    }
+    conn.execute(
+        "UPDATE ledger_assessments SET closure = ?2, closure_reason = ?3, closed_on = ?4,
+             closed_by = ?5
+         WHERE assessment_id = ?1",
+        params![assessment_id, closure.as_str(), reason, today(), closed_by],
+    )
+    .context("Failed to close assessment")?;
+    get_assessment(conn, assessment_id)
+}
+
+pub fn list(conn: &Connection, filter: &AssessmentFilter) -> AppResult<Vec<Assessment>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR a.case_id = ?1)
+                 AND (?2 IS NULL OR a.offender_id = ?2)
+                 AND (?3 IS NULL OR a.file_id = ?3)
+                 AND (?4 IS NULL OR a.kind = ?4)
+                 AND (?5 = 0 OR (a.closure IS NULL AND COALESCE(p.paid, 0) < a.amount_cents))
+             ORDER BY a.assessed_on, a.assessment_id",
+            ASSESSMENT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let assessments = stmt
+        .query_map(
+            params![
+                filter.case_id,
+                filter.offender_id,
+                filter.file_id,
+                filter.kind.map(|k| k.as_str()),
+                filter.outstanding_only
+            ],
+            Assessment::from_row,
+        )
+        .context("Failed to fetch assessments")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read assessments")?;
+    Ok(assessments)
+}
+
+/// Open assessments with money still owed, and what is owed in all
+pub fn outstanding(conn: &Connection, filter: AssessmentFilter) -> AppResult<Outstanding> {
+    let assessments = list(
+        conn,
+        &AssessmentFilter {
+            outstanding_only: true,
+            ..filter
+        },
+    )?;
+    let total_cents = assessments.iter().map(|a| a.balance_cents).sum();
+    Ok(Outstanding {
+        assessments,
+        total_cents,
+    })
+}
+
+fn add_to(subtotals: &mut Vec<Subtotal>, name: &str, cents: i64) {
+    match subtotals.iter_mut().find(|s| s.name == name) {
+        Some(subtotal) => subtotal.net_cents += cents,
+        None => subtotals.push(Subtotal {
+            name: name.to_string(),
+            net_cents: cents,
+        }),
+    }
+}
+
+/// Takings for each day from `from` to `to`, both YYYY-MM-DD and inclusive.
+/// Days without payments are left out.
+pub fn daily_totals(conn: &Connection, from: &str, to: &str) -> AppResult<Vec<DailyTotal>> {
+    let from = parse_date("from", from)?;
+    let to = parse_date("to", to)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.paid_on, p.method, a.kind,
+                    SUM(CASE WHEN p.amount_cents > 0 THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN p.amount_cents > 0 THEN p.amount_cents ELSE 0 END),
+                    SUM(CASE WHEN p.amount_cents < 0 THEN -p.amount_cents ELSE 0 END)
+             FROM ledger_payments p
+             JOIN ledger_assessments a ON a.assessment_id = p.assessment_id
+             WHERE p.paid_on BETWEEN ?1 AND ?2
+             GROUP BY p.paid_on, p.method, a.kind
+             ORDER BY p.paid_on, p.method, a.kind",
+        )
+        .context("Failed to prepare statement")?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })
+        .context("Failed to fetch collections")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read collections")?;
+
+    let mut days: Vec<DailyTotal> = Vec::new();
+    for (date, method, kind, payments, collected, reversed) in rows {
+        if days.last().map(|d| d.date != date).unwrap_or(true) {
+            days.push(DailyTotal {
+                date: date.clone(),
+                payments: 0,
+                collected_cents: 0,
+                reversed_cents: 0,
+                net_cents: 0,
+                by_method: Vec::new(),
+                by_kind: Vec::new(),
+            });
+        }
+        let day = days.last_mut().expect("a day was just pushed");
+        day.payments += payments;
+        day.collected_cents += collected;
+        day.reversed_cents += reversed;
+        day.net_cents += collected - reversed;
+        add_to(&mut day.by_method, &method, collected - reversed);
+        add_to(&mut day.by_kind, &kind, collected - reversed);
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn fine(conn: &Connection, amount_cents: i64) -> Assessment {
+        conn.execute(
+            "INSERT INTO cases (title, case_number) VALUES ('R v Doe', 'MCCR/12/2026')",
+            [],
+        )
+        .unwrap();
+        let case_id = conn.last_insert_rowid();
+        assess(
+            conn,
+            NewAssessment {
+                kind: AssessmentKind::Fine,
+                description: None,
+                case_id: Some(case_id),
+                offender_id: None,
+                file_id: None,
+                sentence_id: None,
+                amount_cents: Some(amount_cents),
+                assessed_on: Some("2026-03-01".to_string()),
+                due_date: None,
+            },
+            None,
+        )
+        .unwrap()
+    }
+
+    fn pay_on(
+        conn: &mut Connection,
+        assessment_id: i64,
+        amount_cents: i64,
+        method: PaymentMethod,
+        paid_on: &str,
+    ) -> AppResult<Payment> {
+        pay(
+            conn,
+            NewPayment {
+                assessment_id,
+                amount_cents,
+                receipt_number: None,
+                method,
+                reference: None,
+                paid_on: Some(paid_on.to_string()),
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn payments_pay_the_balance_down() {
+        let mut conn = db::open_in_memory().unwrap();
+        let fine = fine(&conn, 1_000_000);
+        assert_eq!(fine.status, AssessmentStatus::Unpaid);
+        assert_eq!(fine.reference.as_deref(), Some("MCCR/12/2026"));
+
+        let first = pay_on(
+            &mut conn,
+            fine.assessment_id,
+            400_000,
+            PaymentMethod::Cash,
+            "2026-03-02",
+        )
+        .unwrap();
+        assert_eq!(first.receipt_number.as_deref(), Some("RCP-2026-000001"));
+        let partly = get_assessment(&conn, fine.assessment_id).unwrap();
+        assert_eq!(partly.status, AssessmentStatus::PartlyPaid);
+        assert_eq!(partly.balance_cents, 600_000);
+
+        assert!(matches!(
+            pay_on(
+                &mut conn,
+                fine.assessment_id,
+                600_001,
+                PaymentMethod::Cash,
+                "2026-03-02"
+            ),
+            Err(AppError::Validation { .. })
+        ));
+
+        let second = pay_on(
+            &mut conn,
+            fine.assessment_id,
+            600_000,
+            PaymentMethod::MPesa,
+            "2026-03-03",
+        )
+        .unwrap();
+        assert_eq!(second.receipt_number.as_deref(), Some("RCP-2026-000002"));
+        let paid = get_assessment(&conn, fine.assessment_id).unwrap();
+        assert_eq!(paid.status, AssessmentStatus::Paid);
+        assert_eq!(paid.balance_cents, 0);
+    }
+
+    #[test]
+    fn reversal_restores_the_balance_once() {
+        let mut conn = db::open_in_memory().unwrap();
+        let fine = fine(&conn, 1_000_000);
+        let first = pay_on(
+            &mut conn,
+            fine.assessment_id,
+            400_000,
+            PaymentMethod::Cash,
+            "2026-03-02",
+        )
+        .unwrap();
+        pay_on(
+            &mut conn,
+            fine.assessment_id,
+            600_000,
+            PaymentMethod::MPesa,
+            "2026-03-03",
+        )
+        .unwrap();
+
+        let reversal = reverse(&mut conn, first.payment_id, "Bounced", None).unwrap();
+        assert_eq!(reversal.amount_cents, -400_000);
+        assert_eq!(reversal.reversal_of, Some(first.payment_id));
+        assert_eq!(reversal.paid_on, today());
+
+        let after = get_assessment(&conn, fine.assessment_id).unwrap();
+        assert_eq!(after.paid_cents, 600_000);
+        assert_eq!(after.balance_cents, 400_000);
+        assert_eq!(after.status, AssessmentStatus::PartlyPaid);
+        assert_eq!(
+            detail(&conn, fine.assessment_id).unwrap().payments[0].reversed_by,
+            Some(reversal.payment_id)
+        );
+
+        assert!(matches!(
+            reverse(&mut conn, first.payment_id, "Again", None),
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            reverse(&mut conn, reversal.payment_id, "Undo", None),
+            Err(AppError::Conflict(_))
+        ));
+
+        let outstanding = outstanding(&conn, AssessmentFilter::default()).unwrap();
+        assert_eq!(outstanding.total_cents, 400_000);
+    }
+
+    #[test]
+    fn reversal_leaves_past_days_alone() {
+        let mut conn = db::open_in_memory().unwrap();
+        let fine = fine(&conn, 1_000_000);
+        let first = pay_on(
+            &mut conn,
+            fine.assessment_id,
+            400_000,
+            PaymentMethod::Cash,
+            "2026-03-02",
+        )
+        .unwrap();
+        pay_on(
+            &mut conn,
+            fine.assessment_id,
+            100_000,
+            PaymentMethod::MPesa,
+            "2026-03-02",
+        )
+        .unwrap();
+        reverse(&mut conn, first.payment_id, "Bounced", None).unwrap();
+
+        let march = daily_totals(&conn, "2026-03-01", "2026-03-31").unwrap();
+        assert_eq!(march.len(), 1);
+        assert_eq!(march[0].payments, 2);
+        assert_eq!(march[0].net_cents, 500_000);
+        assert_eq!(march[0].by_method.len(), 2);
+
+        let today = today();
+        let reversed = daily_totals(&conn, &today, &today).unwrap();
+        assert_eq!(reversed[0].payments, 0);
+        assert_eq!(reversed[0].reversed_cents, 400_000);
+        assert_eq!(reversed[0].net_cents, -400_000);
+    }
+
+    #[test]
+    fn closed_assessment_takes_no_payments() {
+        let mut conn = db::open_in_memory().unwrap();
+        let fine = fine(&conn, 1_000_000);
+        pay_on(
+            &mut conn,
+            fine.assessment_id,
+            250_000,
+            PaymentMethod::Cash,
+            "2026-03-02",
+        )
+        .unwrap();
+
+        let converted = close(
+            &conn,
+            fine.assessment_id,
+            Closure::Converted,
+            "Served default",
+            None,
+        )
+        .unwrap();
+        assert_eq!(converted.status, AssessmentStatus::Converted);
+        assert_eq!(converted.balance_cents, 750_000);
+
+        assert!(matches!(
+            pay_on(
+                &mut conn,
+                fine.assessment_id,
+                100,
+                PaymentMethod::Cash,
+                "2026-03-04"
+            ),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(
+            outstanding(&conn, AssessmentFilter::default())
+                .unwrap()
+                .total_cents,
+            0
+        );
+    }
+}
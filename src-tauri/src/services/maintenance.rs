@@ -0,0 +1,530 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::{AppResult, ResultExt};
+use crate::services::photos::{self, PhotoStore};
+
+// ============================================================================
+// Integrity check and repair
+//
+// Power cuts have left databases with damaged pages, and databases written
+// before foreign keys were enforced on every connection can hold child rows
+// whose parent is gone. `PRAGMA foreign_key_check` is the source for those;
+// the app rules only cover the references SQLite knows nothing about, such as
+// `files.uploaded_by` and the photo files on disk. Every repair is one SQLite
+// itself would have made, had the constraint been enforced, or a file move
+// that can be undone by hand.
+// Append-only rows (ledger payments, exhibit movements, catalogue offences)
+// are never touched: their triggers would abort the repair, so their issues
+// are reported for a restore or manual work instead.
+// ============================================================================
+
+/// Orphan photos are moved here, inside the photo directory, not deleted
+pub const QUARANTINE_DIR: &str = "orphaned";
+
+/// Cascading deletes can orphan grandchildren, so repairs run in passes
+const MAX_REPAIR_PASSES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A row whose foreign key points at a missing parent row
+    ForeignKey,
+    /// `files.uploaded_by` names a user that no longer exists
+    MissingUploader,
+    /// `offenders.photo_path` names a file that is not on disk
+    MissingPhotoFile,
+    /// A file in the photo directory that no offender refers to
+    OrphanPhotoFile,
+}
+
+/// What `repair` would do about an issue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Repair {
+    DeleteRow,
+    ClearColumn {
+        column: String,
+    },
+    /// The photo was found in the photo directory under the same name
+    RelinkPhoto {
+        path: String,
+    },
+    QuarantineFile,
+    /// A trigger refuses the change; restore a backup or fix it by hand
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub table: Option<String>,
+    pub row_id: Option<i64>,
+    pub path: Option<String>,
+    pub detail: String,
+    pub repair: Repair,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: String,
+    /// Messages from `PRAGMA integrity_check`; empty when it reports "ok"
+    pub integrity_errors: Vec<String>,
+    /// True when every integrity error is a damaged index, which `REINDEX`
+    /// rebuilds from the table data
+    pub reindex_repairs: bool,
+    /// Rows from `PRAGMA foreign_key_check`, one per violation
+    pub foreign_key_errors: Vec<String>,
+    pub issues: Vec<Issue>,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_errors.is_empty()
+            && self.issues.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairReport {
+    pub reindexed: bool,
+    pub repaired: Vec<Issue>,
+    /// The state after repair; anything left needs a restore or manual work
+    pub after: IntegrityReport,
+}
+
+/// Run every check. Only reads the database and the photo directory.
+pub fn check(conn: &Connection, photos: &PhotoStore) -> AppResult<IntegrityReport> {
+    let integrity_errors = integrity_errors(conn)?;
+    let reindex_repairs =
+        !integrity_errors.is_empty() && integrity_errors.iter().all(|e| is_index_error(e));
+
+    let violations = foreign_key_violations(conn)?;
+    let foreign_key_errors = violations.iter().map(Violation::to_string).collect();
+    let mut issues = foreign_key_issues(conn, violations)?;
+    issues.extend(missing_uploaders(conn)?);
+    issues.extend(photo_issues(conn, photos.dir())?);
+
+    Ok(IntegrityReport {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        integrity_errors,
+        reindex_repairs,
+        foreign_key_errors,
+        issues,
+    })
+}
+
+/// Apply every safe repair and check again. The caller takes a backup first.
+pub fn repair(conn: &mut Connection, photos: &PhotoStore) -> AppResult<RepairReport> {
+    let reindexed = {
+        let errors = integrity_errors(conn)?;
+        if !errors.is_empty() && errors.iter().all(|e| is_index_error(e)) {
+            conn.execute_batch("REINDEX")
+                .context("Failed to rebuild indexes")?;
+            true
+        } else {
+            false
+        }
+    };
+
+    let mut repaired = Vec::new();
+    let tx = conn.transaction()?;
+    for _ in 0..MAX_REPAIR_PASSES {
+        let mut issues = foreign_key_issues(&tx, foreign_key_violations(&tx)?)?;
+        issues.extend(missing_uploaders(&tx)?);
+        issues.extend(
+            photo_issues(&tx, photos.dir())?
+                .into_iter()
+                .filter(|i| i.kind == IssueKind::MissingPhotoFile),
+        );
+        issues.retain(|i| i.repair != Repair::Manual);
+        if issues.is_empty() {
+            break;
+        }
+        for issue in &issues {
+            apply_row_repair(&tx, issue)?;
+        }
+        repaired.extend(issues);
+    }
+    tx.commit()?;
+
+    // Files last, once the rows that might still refer to them are settled
+    for issue in photo_issues(conn, photos.dir())? {
+        if issue.kind == IssueKind::OrphanPhotoFile {
+            quarantine(photos.dir(), &issue)?;
+            repaired.push(issue);
+        }
+    }
+
+    Ok(RepairReport {
+        reindexed,
+        repaired,
+        after: check(conn, photos)?,
+    })
+}
+
+fn integrity_errors(conn: &Connection) -> AppResult<Vec<String>> {
+    let mut stmt = conn
+        .prepare("PRAGMA integrity_check")
+        .context("Failed to run integrity check")?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(messages.into_iter().filter(|m| m != "ok").collect())
+}
+
+fn is_index_error(message: &str) -> bool {
+    // e.g. "row 12 missing from index idx_files_purpose",
+    // "wrong # of entries in index idx_files_purpose"
+    message.contains(" index ")
+}
+
+/// One foreign key of a table, as listed by `PRAGMA foreign_key_list`
+struct ForeignKey {
+    id: i64,
+    column: String,
+    on_delete: String,
+}
+
+fn foreign_keys(conn: &Connection, table: &str) -> AppResult<Vec<ForeignKey>> {
+    let mut stmt = conn
+        .prepare("SELECT id, \"from\", on_delete FROM pragma_foreign_key_list(?1)")
+        .context("Failed to read foreign keys")?;
+    let keys = stmt
+        .query_map([table], |row| {
+            Ok(ForeignKey {
+                id: row.get(0)?,
+                column: row.get(1)?,
+                on_delete: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(keys)
+}
+
+/// One row of `PRAGMA foreign_key_check`
+struct Violation {
+    table: String,
+    row_id: Option<i64>,
+    parent: String,
+    fk_id: i64,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.row_id {
+            Some(row_id) => write!(f, "{} row {}", self.table, row_id)?,
+            None => write!(f, "{}", self.table)?,
+        }
+        write!(
+            f,
+            " has no matching {} row (foreign key {})",
+            self.parent, self.fk_id
+        )
+    }
+}
+
+fn foreign_key_violations(conn: &Connection) -> AppResult<Vec<Violation>> {
+    let mut stmt = conn
+        .prepare("PRAGMA foreign_key_check")
+        .context("Failed to run foreign key check")?;
+    let violations = stmt
+        .query_map([], |row| {
+            Ok(Violation {
+                table: row.get(0)?,
+                row_id: row.get(1)?,
+                parent: row.get(2)?,
+                fk_id: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(violations)
+}
+
+fn foreign_key_issues(conn: &Connection, violations: Vec<Violation>) -> AppResult<Vec<Issue>> {
+    let mut issues = Vec::new();
+    for Violation {
+        table,
+        row_id,
+        parent,
+        fk_id,
+    } in violations
+    {
+        let Some(key) = foreign_keys(conn, &table)?
+            .into_iter()
+            .find(|k| k.id == fk_id)
+        else {
+            continue;
+        };
+        // Mirror the declared ON DELETE action. Without one, clearing the
+        // reference loses less than deleting the row, where the column allows it.
+        let repair = if key.on_delete != "CASCADE" && !is_not_null(conn, &table, &key.column) {
+            Repair::ClearColumn {
+                column: key.column.clone(),
+            }
+        } else {
+            Repair::DeleteRow
+        };
+        let refused = match &repair {
+            Repair::DeleteRow => trigger_refuses(conn, &table, "DELETE")?,
+            _ => trigger_refuses(conn, &table, "UPDATE")?,
+        };
+        let repair = if refused { Repair::Manual } else { repair };
+        issues.push(Issue {
+            kind: IssueKind::ForeignKey,
+            detail: format!(
+                "{}.{} refers to a missing {} row",
+                table, key.column, parent
+            ),
+            table: Some(table),
+            row_id,
+            path: None,
+            repair,
+        });
+    }
+    Ok(issues)
+}
+
+fn is_not_null(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.query_row(
+        "SELECT \"notnull\" FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Whether a `BEFORE DELETE` / `BEFORE UPDATE` trigger on `table` raises,
+/// as on the append-only tables
+fn trigger_refuses(conn: &Connection, table: &str, operation: &str) -> AppResult<bool> {
+    let refuses = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master
+                WHERE type = 'trigger' AND tbl_name = ?1
+                  AND sql LIKE ?2 AND sql LIKE '%RAISE(%')",
+            params![table, format!("%BEFORE {}%", operation)],
+            |row| row.get(0),
+        )
+        .context("Failed to read triggers")?;
+    Ok(refuses)
+}
+
+fn missing_uploaders(conn: &Connection) -> AppResult<Vec<Issue>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.file_id, f.uploaded_by FROM files f
+             WHERE f.uploaded_by IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM users u WHERE u.user_id = f.uploaded_by)",
+        )
+        .context("Failed to check file uploaders")?;
+    let issues = stmt
+        .query_map([], |row| {
+            let file_id: i64 = row.get(0)?;
+            let user_id: i64 = row.get(1)?;
+            Ok(Issue {
+                kind: IssueKind::MissingUploader,
+                table: Some("files".to_string()),
+                row_id: Some(file_id),
+                path: None,
+                detail: format!("files.uploaded_by refers to missing user {}", user_id),
+                repair: Repair::ClearColumn {
+                    column: "uploaded_by".to_string(),
+                },
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(issues)
+}
+
+fn photo_issues(conn: &Connection, photo_dir: &Path) -> AppResult<Vec<Issue>> {
+    let mut stmt = conn
+        .prepare("SELECT offender_id, photo_path FROM offenders WHERE photo_path IS NOT NULL AND photo_path <> ''")
+        .context("Failed to read photo paths")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut issues = Vec::new();
+    let mut referenced = HashSet::new();
+    for (offender_id, photo_path) in rows {
+        let path = Path::new(&photo_path);
+        let in_store = path.file_name().map(|name| photo_dir.join(name));
+        if let Some(name) = path.file_name() {
+            referenced.insert(name.to_os_string());
+        }
+        if path.is_file() {
+            continue;
+        }
+        let repair = match in_store.filter(|p| p.is_file()) {
+            Some(found) => Repair::RelinkPhoto {
+                path: found.to_string_lossy().to_string(),
+            },
+            None => Repair::ClearColumn {
+                column: "photo_path".to_string(),
+            },
+        };
+        issues.push(Issue {
+            kind: IssueKind::MissingPhotoFile,
+            table: Some("offenders".to_string()),
+            row_id: Some(offender_id),
+            path: Some(photo_path.clone()),
+            detail: format!("Photo file {} is missing", photo_path),
+            repair,
+        });
+    }
+
+    if photo_dir.is_dir() {
+        for entry in
+            fs::read_dir(photo_dir).context(&format!("Failed to read {}", photo_dir.display()))?
+        {
+            let path = entry?.path();
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            // Leftovers of an interrupted write or encryption are not photos
+            let name_text = name.to_string_lossy();
+            let skip = name_text.starts_with('.')
+                || name_text.ends_with(".encrypting")
+                || name_text.ends_with(".partial");
+            if !path.is_file() || skip || referenced.contains(name) {
+                continue;
+            }
+            issues.push(Issue {
+                kind: IssueKind::OrphanPhotoFile,
+                table: None,
+                row_id: None,
+                path: Some(path.to_string_lossy().to_string()),
+                detail: format!("{} is not used by any offender", name.to_string_lossy()),
+                repair: Repair::QuarantineFile,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+fn apply_row_repair(conn: &Connection, issue: &Issue) -> AppResult<()> {
+    let (Some(table), Some(row_id)) = (&issue.table, issue.row_id) else {
+        return Ok(());
+    };
+    // Table and column names come from SQLite's own catalogue, never from
+    // the caller
+    let sql = match &issue.repair {
+        Repair::DeleteRow => format!("DELETE FROM \"{}\" WHERE rowid = ?1", table),
+        Repair::ClearColumn { column } => {
+            format!(
+                "UPDATE \"{}\" SET \"{}\" = NULL WHERE rowid = ?1",
+                table, column
+            )
+        }
+        Repair::RelinkPhoto { path } => {
+            conn.execute(
+                "UPDATE offenders SET photo_path = ?1 WHERE offender_id = ?2",
+                params![path, row_id],
+            )
+            .context("Failed to relink photo")?;
+            return Ok(());
+        }
+        Repair::QuarantineFile | Repair::Manual => return Ok(()),
+    };
+    conn.execute(&sql, [row_id])
+        .context(&format!("Failed to repair {} row {}", table, row_id))?;
+    Ok(())
+}
+
+fn quarantine(photo_dir: &Path, issue: &Issue) -> AppResult<()> {
+    let Some(path) = issue.path.as_deref().map(PathBuf::from) else {
+        return Ok(());
+    };
+    let Some(name) = path.file_name() else {
+        return Ok(());
+    };
+    let target_dir = photo_dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&target_dir)
+        .context(&format!("Failed to create {}", target_dir.display()))?;
+    fs::rename(&path, target_dir.join(name))
+        .context(&format!("Failed to move {}", path.display()))?;
+    let _ = fs::remove_file(photos::thumbnail_path(&path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[test]
+    fn repair_leaves_append_only_rows_for_manual_work() {
+        let mut conn = db::open_in_memory().unwrap();
+        // As after a power cut on a connection without foreign keys
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             INSERT INTO ledger_payments (assessment_id, amount_cents, receipt_number, method, paid_on)
+             VALUES (404, 50000, 'R-0001', 'Cash', '2026-03-02');
+             INSERT INTO custody_records (offender_id, facility, committed_on)
+             VALUES (404, 'Machakos GK Prison', '2026-03-02');",
+        )
+        .unwrap();
+        let photos = PhotoStore::new(std::env::temp_dir().join("rat-no-photos"), None);
+
+        let before = check(&conn, &photos).unwrap();
+        assert_eq!(before.foreign_key_errors.len(), 2);
+        let repair_of = |table: &str| {
+            before
+                .issues
+                .iter()
+                .find(|i| i.table.as_deref() == Some(table))
+                .map(|i| i.repair.clone())
+        };
+        assert_eq!(repair_of("ledger_payments"), Some(Repair::Manual));
+        assert_eq!(
+            repair_of("custody_records"),
+            Some(Repair::ClearColumn {
+                column: "offender_id".into()
+            })
+        );
+
+        let report = repair(&mut conn, &photos).unwrap();
+        assert_eq!(report.repaired.len(), 1);
+        assert_eq!(report.repaired[0].table.as_deref(), Some("custody_records"));
+        assert_eq!(report.after.foreign_key_errors.len(), 1);
+        assert_eq!(report.after.issues.len(), 1);
+        assert_eq!(report.after.issues[0].repair, Repair::Manual);
+        let payments: i64 = conn
+            .query_row("SELECT COUNT(*) FROM ledger_payments", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(payments, 1);
+        let orphaned: Option<i64> = conn
+            .query_row("SELECT offender_id FROM custody_records", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(orphaned, None);
+    }
+
+    #[test]
+    fn append_only_tables_are_recognised() {
+        let conn = db::open_in_memory().unwrap();
+        for table in ["ledger_payments", "exhibit_movements"] {
+            assert!(
+                trigger_refuses(&conn, table, "DELETE").unwrap(),
+                "{}",
+                table
+            );
+            assert!(
+                trigger_refuses(&conn, table, "UPDATE").unwrap(),
+                "{}",
+                table
+            );
+        }
+        assert!(trigger_refuses(&conn, "catalogue_offences", "DELETE").unwrap());
+        assert!(!trigger_refuses(&conn, "custody_records", "DELETE").unwrap());
+        assert!(!trigger_refuses(&conn, "ledger_assessments", "UPDATE").unwrap());
+    }
+}
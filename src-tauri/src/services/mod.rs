@@ -0,0 +1,35 @@
+// ============================================================================
+// Records services
+//
+// The records logic behind the Tauri commands, written against a plain
+// `rusqlite::Connection` so it can be driven from commands, a CLI or tests
+// alike. Nothing in here knows about Tauri, `AppState` or the pool; the
+// commands in `methods::*` pick a connection and call in.
+// ============================================================================
+
+pub mod attendance;
+pub mod bail;
+pub mod case_status;
+pub mod cases;
+pub mod cause_list;
+pub mod charges;
+pub mod custody;
+pub mod duplicates;
+pub mod exhibits;
+pub mod files;
+pub mod hearings;
+pub mod ledger;
+pub mod maintenance;
+pub mod names;
+pub mod notifications;
+pub mod offence_catalogue;
+pub mod offender_cases;
+pub mod offenders;
+pub mod pdf;
+pub mod photos;
+pub mod search;
+pub mod sentences;
+pub mod staff;
+pub mod two_factor;
+pub mod users;
+pub mod warrants;
@@ -0,0 +1,220 @@
+// ============================================================================
+// Person name matching
+//
+// Names in the registry are typed by many hands: "Ochieng'" and "Ochieng",
+// "Mũthoni" and "Muthoni", "Wanjiku" and "Wanjiru", given and family names
+// in either order. Names are folded to plain lowercase ASCII words, and each
+// word is compared both as spelled and by a phonetic key tuned for Kenyan
+// spellings. Scores run from 0 (nothing alike) to 1 (same name).
+// ============================================================================
+
+/// The ASCII form of a Latin letter with diacritics, if it has one
+pub(crate) fn fold_char(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// Lowercase ASCII words of a name. Apostrophes are dropped ("Ochieng'",
+/// "Ng'ang'a"), other punctuation splits words.
+pub fn normalize(name: &str) -> Vec<String> {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            '\'' | '\u{2018}' | '\u{2019}' | '\u{02BC}' | '`' | '\u{00B4}' => {}
+            c if c.is_ascii_alphanumeric() => folded.push(c),
+            c => match fold_char(c) {
+                Some(ascii) => folded.push_str(ascii),
+                None => folded.push(' '),
+            },
+        }
+    }
+    folded.split_whitespace().map(str::to_string).collect()
+}
+
+/// A sound-alike key for one normalized word. Digraphs collapse to one
+/// letter, letters that are often swapped in local spellings share a code
+/// (l/r, k/c/q/g, b/p/v/f, d/t, s/z/x), vowels after the first letter are
+/// dropped and repeats are merged, so "Wanjiku" and "Wanjigu" share a key.
+pub fn phonetic_key(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    let mut codes = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        let (code, width) = match (chars[i], next) {
+            ('c', Some('h')) | ('s', Some('h')) => ('S', 2),
+            ('p', Some('h')) => ('B', 2),
+            ('t', Some('h')) | ('d', Some('h')) => ('T', 2),
+            ('n', Some('g')) | ('n', Some('y')) => ('N', 2),
+            ('c', Some('k')) => ('K', 2),
+            (c, _) => (
+                match c {
+                    'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'h' | 'w' => '_',
+                    'l' | 'r' => 'L',
+                    'k' | 'c' | 'q' | 'g' => 'K',
+                    'b' | 'p' | 'v' | 'f' => 'B',
+                    'd' | 't' => 'T',
+                    's' | 'z' | 'x' => 'S',
+                    'm' | 'n' => 'N',
+                    'j' => 'J',
+                    c => c.to_ascii_uppercase(),
+                },
+                1,
+            ),
+        };
+        codes.push(code);
+        i += width;
+    }
+
+    let mut key = String::new();
+    let mut previous = None;
+    for (index, code) in codes.into_iter().enumerate() {
+        if index == 0 {
+            // Keep the opening sound even when it is a vowel
+            key.push(match word.chars().next() {
+                Some(c) if code == '_' => c.to_ascii_uppercase(),
+                _ => code,
+            });
+        } else if code != '_' && Some(code) != previous {
+            key.push(code);
+        }
+        previous = Some(code);
+    }
+    key
+}
+
+/// Edit distance counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn ratio(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// How alike two normalized words are
+fn word_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let spelled = ratio(a, b);
+    let (key_a, key_b) = (phonetic_key(a), phonetic_key(b));
+    let sounded = if key_a == key_b {
+        0.9
+    } else {
+        0.85 * ratio(&key_a, &key_b)
+    };
+    // A partly typed word, e.g. "Wanj" for "Wanjiku"
+    let prefix = if a.len() >= 3 && b.starts_with(a) {
+        0.8
+    } else {
+        0.0
+    };
+    spelled.max(sounded).max(prefix)
+}
+
+/// How well `candidate` matches what was searched for. Each searched word is
+/// paired with its closest word in the candidate, in any order, so a search
+/// for one name still finds the person by their full name.
+pub fn similarity(query: &[String], candidate: &[String]) -> f64 {
+    if query.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = query
+        .iter()
+        .map(|q| {
+            candidate
+                .iter()
+                .map(|c| word_similarity(q, c))
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    total / query.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(a: &str, b: &str) -> f64 {
+        similarity(&normalize(a), &normalize(b))
+    }
+
+    #[test]
+    fn names_fold_to_plain_words() {
+        assert_eq!(
+            normalize("  Ng'ang'a  MŨTHONI-Kamau "),
+            vec!["nganga", "muthoni", "kamau"]
+        );
+        assert_eq!(normalize("Ochieng\u{2019}"), vec!["ochieng"]);
+        assert!(normalize("-- ..").is_empty());
+    }
+
+    #[test]
+    fn local_spellings_share_a_phonetic_key() {
+        assert_eq!(phonetic_key("wanjiku"), phonetic_key("wanjigu"));
+        assert_eq!(phonetic_key("kiprotich"), phonetic_key("kiplotich"));
+        assert_eq!(phonetic_key("otieno"), "OTN");
+        assert_ne!(phonetic_key("otieno"), phonetic_key("atieno"));
+    }
+
+    #[test]
+    fn levenshtein_counts_characters() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("wanjiku", "wanjiru"), 1);
+        assert_eq!(levenshtein("mũthoni", "muthoni"), 1);
+        assert_eq!(levenshtein("kamau", "kamau"), 0);
+    }
+
+    #[test]
+    fn similarity_ignores_word_order_and_spelling_slips() {
+        assert_eq!(score("Kamau John", "John Kamau"), 1.0);
+        assert_eq!(score("Mũthoni", "Muthoni"), 1.0);
+        assert!(score("Wanjiku", "Wanjigu") >= 0.85);
+        assert!(score("Wanj", "Wanjiku Kamau") >= 0.8);
+        assert!(score("Otieno", "Wanjiku Kamau") < 0.5);
+        // Every searched word counts, so a wrong surname pulls the score down
+        assert!(score("John Kamau", "John Otieno") < score("John Kamau", "John Kamau"));
+        assert_eq!(score("", "John"), 0.0);
+    }
+}
@@ -0,0 +1,92 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppResult, ResultExt};
+
+#[derive(Serialize, Deserialize)]
+pub struct Notification {
+    pub notification_id: i32,
+    pub message: String,
+    pub notification_type: String, // "Info", "Warning", "Error", "Success"
+    pub date_created: String,
+    pub read_status: bool,
+    pub user_id: Option<i32>,
+}
+
+const NOTIFICATION_COLUMNS: &str =
+    "notification_id, message, type, date_created, read_status, user_id";
+
+impl Notification {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Notification {
+            notification_id: row.get(0)?,
+            message: row.get(1)?,
+            notification_type: row.get(2)?,
+            date_created: row.get(3)?,
+            read_status: row.get(4)?,
+            user_id: row.get(5)?,
+        })
+    }
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<Notification>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM notifications ORDER BY date_created DESC",
+            NOTIFICATION_COLUMNS
+        ))
+        .context("Failed to prepare statement")?;
+
+    let notifications = stmt
+        .query_map([], Notification::from_row)
+        .context("Failed to fetch notifications")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read notifications")?;
+    Ok(notifications)
+}
+
+pub fn get(conn: &Connection, notification_id: i32) -> AppResult<Option<Notification>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM notifications WHERE notification_id = ?1",
+            NOTIFICATION_COLUMNS
+        ),
+        params![notification_id],
+        Notification::from_row,
+    )
+    .optional()
+    .context("Failed to fetch notification")
+}
+
+/// Record a notification and return its id
+pub fn create(
+    conn: &Connection,
+    message: &str,
+    notification_type: &str,
+    user_id: Option<i32>,
+) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO notifications (message, type, user_id) VALUES (?1, ?2, ?3)",
+        params![message, notification_type, user_id],
+    )
+    .context("Failed to create notification")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn mark_as_read(conn: &Connection, notification_id: i32) -> AppResult<()> {
+    conn.execute(
+        "UPDATE notifications SET read_status = 1 WHERE notification_id = ?1",
+        params![notification_id],
+    )
+    .context("Failed to mark notification as read")?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, notification_id: i32) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM notifications WHERE notification_id = ?1",
+        params![notification_id],
+    )
+    .context("Failed to delete notification")?;
+    Ok(())
+}
@@ -0,0 +1,907 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+use crate::services::sentences::format_cents;
+
+// ============================================================================
+// Offence catalogue
+//
+// The Acts, sections and offences charges and offender history are recorded
+// against (migration 0014), with the prescribed penalty ranges. A catalogue
+// edition is a JSON file (see `CatalogueFile`); the app bundles one and
+// installs it when it is newer than the database's, and later editions are
+// imported by an administrator. Installing upserts by code and never
+// removes anything.
+// ============================================================================
+
+/// The edition shipped with the app
+const BUNDLED: &str = include_str!("../../catalogue/offences.json");
+
+/// The most results a search returns unless asked for fewer
+const SEARCH_LIMIT: usize = 50;
+
+/// A maximum penalty that is not a term of months
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaxPenalty {
+    #[serde(rename = "Life imprisonment")]
+    LifeImprisonment,
+    Death,
+}
+
+impl MaxPenalty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaxPenalty::LifeImprisonment => "Life imprisonment",
+            MaxPenalty::Death => "Death",
+        }
+    }
+
+    pub fn parse(penalty: &str) -> Option<MaxPenalty> {
+        match penalty {
+            "Life imprisonment" => Some(MaxPenalty::LifeImprisonment),
+            "Death" => Some(MaxPenalty::Death),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CatalogueSource {
+    Bundled,
+    Imported,
+}
+
+impl CatalogueSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogueSource::Bundled => "Bundled",
+            CatalogueSource::Imported => "Imported",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueAct {
+    pub act_id: i64,
+    pub code: String,
+    pub title: String,
+    pub chapter: Option<String>,
+    pub is_repealed: bool,
+    pub offence_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueOffence {
+    pub catalogue_id: i64,
+    pub code: String,
+    pub act_id: i64,
+    pub act_code: String,
+    pub act_title: String,
+    pub chapter: Option<String>,
+    pub section: String,
+    pub offence: String,
+    pub min_fine_cents: Option<i64>,
+    pub max_fine_cents: Option<i64>,
+    pub min_custody_months: Option<i64>,
+    pub max_custody_months: Option<i64>,
+    pub max_penalty: Option<MaxPenalty>,
+    /// The range in words, e.g. "Imprisonment for up to 3 years"
+    pub penalty: Option<String>,
+    pub penalty_notes: Option<String>,
+    pub is_repealed: bool,
+}
+
+const OFFENCE_SELECT: &str = "SELECT co.catalogue_id, co.code, co.act_id, a.code, a.title,
+        a.chapter, co.section, co.offence, co.min_fine_cents, co.max_fine_cents,
+        co.min_custody_months, co.max_custody_months, co.max_penalty, co.penalty_notes,
+        co.is_repealed OR a.is_repealed
+    FROM catalogue_offences co
+    JOIN catalogue_acts a ON a.act_id = co.act_id";
+
+impl CatalogueOffence {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let max_penalty: Option<String> = row.get(12)?;
+        let mut offence = CatalogueOffence {
+            catalogue_id: row.get(0)?,
+            code: row.get(1)?,
+            act_id: row.get(2)?,
+            act_code: row.get(3)?,
+            act_title: row.get(4)?,
+            chapter: row.get(5)?,
+            section: row.get(6)?,
+            offence: row.get(7)?,
+            min_fine_cents: row.get(8)?,
+            max_fine_cents: row.get(9)?,
+            min_custody_months: row.get(10)?,
+            max_custody_months: row.get(11)?,
+            max_penalty: max_penalty
+                .map(|p| {
+                    MaxPenalty::parse(&p).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            12,
+                            rusqlite::types::Type::Text,
+                            format!("unknown maximum penalty {}", p).into(),
+                        )
+                    })
+                })
+                .transpose()?,
+            penalty: None,
+            penalty_notes: row.get(13)?,
+            is_repealed: row.get(14)?,
+        };
+        offence.penalty = describe_penalty(&offence);
+        Ok(offence)
+    }
+}
+
+fn months(months: i64) -> String {
+    match (months % 12, months / 12) {
+        (0, 1) => "1 year".to_string(),
+        (0, years) => format!("{} years", years),
+        _ if months == 1 => "1 month".to_string(),
+        _ => format!("{} months", months),
+    }
+}
+
+/// The prescribed range in words, e.g. "Imprisonment for at least 10 years,
+/// up to life" or "Imprisonment for up to 2 years or a fine of up to
+/// KSh 100,000"
+fn describe_penalty(offence: &CatalogueOffence) -> Option<String> {
+    let custody = match (
+        offence.min_custody_months,
+        offence.max_custody_months,
+        offence.max_penalty,
+    ) {
+        (_, _, Some(MaxPenalty::Death)) => Some("Death".to_string()),
+        (None, _, Some(MaxPenalty::LifeImprisonment)) => Some("Imprisonment for life".to_string()),
+        (Some(min), _, Some(MaxPenalty::LifeImprisonment)) => Some(format!(
+            "Imprisonment for at least {}, up to life",
+            months(min)
+        )),
+        (Some(min), Some(max), None) => Some(format!(
+            "Imprisonment for {} to {}",
+            months(min),
+            months(max)
+        )),
+        (Some(min), None, None) => Some(format!("Imprisonment for at least {}", months(min))),
+        (None, Some(max), None) => Some(format!("Imprisonment for up to {}", months(max))),
+        (None, None, None) => None,
+    };
+    let fine = match (offence.min_fine_cents, offence.max_fine_cents) {
+        (Some(min), Some(max)) => Some(format!(
+            "a fine of KSh {} to KSh {}",
+            format_cents(min),
+            format_cents(max)
+        )),
+        (Some(min), None) => Some(format!("a fine of at least KSh {}", format_cents(min))),
+        (None, Some(max)) => Some(format!("a fine of up to KSh {}", format_cents(max))),
+        (None, None) => None,
+    };
+    match (custody, fine) {
+        (Some(custody), Some(fine)) => Some(format!("{} or {}", custody, fine)),
+        (Some(custody), None) => Some(custody),
+        (None, Some(fine)) => {
+            let mut fine = fine;
+            fine.replace_range(..1, "A");
+            Some(fine)
+        }
+        (None, None) => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueVersion {
+    pub version: i64,
+    pub name: Option<String>,
+    pub published_on: Option<String>,
+    pub source: String,
+    pub acts_added: i64,
+    pub acts_updated: i64,
+    pub offences_added: i64,
+    pub offences_updated: i64,
+    pub installed_by: Option<String>,
+    pub installed_at: Option<String>,
+}
+
+/// The installed edition, the one bundled with the app, and every edition
+/// installed so far, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueInfo {
+    pub version: Option<i64>,
+    pub bundled_version: i64,
+    pub acts: i64,
+    pub offences: i64,
+    pub history: Vec<CatalogueVersion>,
+}
+
+/// A catalogue edition as published. Entries are matched to the installed
+/// ones by code; anything the file leaves out stays as it is.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogueFile {
+    /// Must be higher than the installed edition
+    pub version: i64,
+    pub name: Option<String>,
+    /// YYYY-MM-DD
+    pub published_on: Option<String>,
+    #[serde(default)]
+    pub acts: Vec<ActEntry>,
+    #[serde(default)]
+    pub offences: Vec<OffenceEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActEntry {
+    pub code: String,
+    pub title: String,
+    pub chapter: Option<String>,
+    #[serde(default)]
+    pub repealed: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OffenceEntry {
+    pub code: String,
+    /// The code of the Act, in this file or already installed
+    pub act: String,
+    pub section: String,
+    pub offence: String,
+    pub min_fine_cents: Option<i64>,
+    pub max_fine_cents: Option<i64>,
+    pub min_custody_months: Option<i64>,
+    pub max_custody_months: Option<i64>,
+    pub max_penalty: Option<MaxPenalty>,
+    pub penalty_notes: Option<String>,
+    #[serde(default)]
+    pub repealed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub version: i64,
+    pub acts_added: i64,
+    pub acts_updated: i64,
+    pub offences_added: i64,
+    pub offences_updated: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CatalogueFilter {
+    /// Words to find in the offence, its code, its section or the Act
+    pub query: Option<String>,
+    /// Act code
+    pub act: Option<String>,
+    #[serde(default)]
+    pub include_repealed: bool,
+    pub limit: Option<usize>,
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+/// The highest edition installed, if any
+pub fn installed_version(conn: &Connection) -> AppResult<Option<i64>> {
+    conn.query_row("SELECT MAX(version) FROM catalogue_versions", [], |row| {
+        row.get(0)
+    })
+    .context("Failed to read catalogue version")
+}
+
+fn parse(json: &str) -> AppResult<CatalogueFile> {
+    serde_json::from_str(json)
+        .map_err(|e| AppError::validation(format!("Not a valid catalogue file: {}", e)))
+}
+
+/// Check a catalogue file before anything is written
+fn validate(conn: &Connection, file: &CatalogueFile) -> AppResult<()> {
+    if file.version < 1 {
+        return Err(AppError::field("version", "Catalogue versions start at 1"));
+    }
+    if let Some(installed) = installed_version(conn)? {
+        if file.version <= installed {
+            return Err(AppError::Conflict(format!(
+                "Catalogue version {} is installed; this file is version {}.",
+                installed, file.version
+            )));
+        }
+    }
+    optional_date("published_on", file.published_on.as_deref())?;
+
+    let mut act_codes = HashSet::new();
+    for act in &file.acts {
+        let code = act.code.trim();
+        if code.is_empty() || act.title.trim().is_empty() {
+            return Err(AppError::validation("Every Act needs a code and a title."));
+        }
+        if !act_codes.insert(code.to_string()) {
+            return Err(AppError::validation(format!(
+                "Act {} appears more than once.",
+                code
+            )));
+        }
+    }
+
+    let mut offence_codes = HashSet::new();
+    for offence in &file.offences {
+        let code = offence.code.trim();
+        if code.is_empty() {
+            return Err(AppError::validation("Every offence needs a code."));
+        }
+        if !offence_codes.insert(code.to_string()) {
+            return Err(AppError::validation(format!(
+                "Offence {} appears more than once.",
+                code
+            )));
+        }
+        if offence.section.trim().is_empty() || offence.offence.trim().is_empty() {
+            return Err(AppError::validation(format!(
+                "Offence {} needs a section and a name.",
+                code
+            )));
+        }
+        let act = offence.act.trim();
+        if !act_codes.contains(act) && act_id(conn, act)?.is_none() {
+            return Err(AppError::validation(format!(
+                "Offence {} is under Act {}, which is not in the catalogue.",
+                code, act
+            )));
+        }
+        let amounts = [
+            offence.min_fine_cents,
+            offence.max_fine_cents,
+            offence.min_custody_months,
+            offence.max_custody_months,
+        ];
+        if amounts.iter().flatten().any(|v| *v < 0) {
+            return Err(AppError::validation(format!(
+                "Offence {} has a negative penalty.",
+                code
+            )));
+        }
+        let inverted = |min: Option<i64>, max: Option<i64>| matches!((min, max), (Some(min), Some(max)) if min > max);
+        if inverted(offence.min_fine_cents, offence.max_fine_cents)
+            || inverted(offence.min_custody_months, offence.max_custody_months)
+        {
+            return Err(AppError::validation(format!(
+                "Offence {} has a minimum penalty above its maximum.",
+                code
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn act_id(conn: &Connection, code: &str) -> AppResult<Option<i64>> {
+    conn.query_row(
+        "SELECT act_id FROM catalogue_acts WHERE code = ?1",
+        params![code],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("Failed to fetch Act")
+}
+
+/// Apply a catalogue edition in one transaction
+fn install(
+    conn: &mut Connection,
+    file: CatalogueFile,
+    source: CatalogueSource,
+    installed_by: Option<&str>,
+) -> AppResult<ImportReport> {
+    let tx = conn.transaction()?;
+    validate(&tx, &file)?;
+    let mut report = ImportReport {
+        version: file.version,
+        acts_added: 0,
+        acts_updated: 0,
+        offences_added: 0,
+        offences_updated: 0,
+    };
+
+    for act in file.acts {
+        let code = act.code.trim();
+        let title = act.title.trim();
+        let chapter = optional_text(act.chapter);
+        match act_id(&tx, code)? {
+            Some(id) => {
+                let changed = tx
+                    .execute(
+                        "UPDATE catalogue_acts SET title = ?2, chapter = ?3, is_repealed = ?4,
+                             updated_at = CURRENT_TIMESTAMP
+                         WHERE act_id = ?1
+                           AND (title IS NOT ?2 OR chapter IS NOT ?3 OR is_repealed IS NOT ?4)",
+                        params![id, title, chapter, act.repealed],
+                    )
+                    .context("Failed to update Act")?;
+                report.acts_updated += changed as i64;
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO catalogue_acts (code, title, chapter, is_repealed)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![code, title, chapter, act.repealed],
+                )
+                .context("Failed to add Act")?;
+                report.acts_added += 1;
+            }
+        }
+    }
+
+    for offence in file.offences {
+        let code = offence.code.trim();
+        let act = act_id(&tx, offence.act.trim())?
+            .ok_or_else(|| AppError::internal("Act vanished during import"))?;
+        let existing: Option<i64> = tx
+            .query_row(
+                "SELECT catalogue_id FROM catalogue_offences WHERE code = ?1",
+                params![code],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch catalogue offence")?;
+        let values = params![
+            code,
+            act,
+            offence.section.trim(),
+            offence.offence.trim(),
+            offence.min_fine_cents,
+            offence.max_fine_cents,
+            offence.min_custody_months,
+            offence.max_custody_months,
+            offence.max_penalty.map(|p| p.as_str()),
+            optional_text(offence.penalty_notes),
+            offence.repealed
+        ];
+        match existing {
+            Some(_) => {
+                let changed = tx
+                    .execute(
+                        "UPDATE catalogue_offences SET act_id = ?2, section = ?3, offence = ?4,
+                             min_fine_cents = ?5, max_fine_cents = ?6, min_custody_months = ?7,
+                             max_custody_months = ?8, max_penalty = ?9, penalty_notes = ?10,
+                             is_repealed = ?11, updated_at = CURRENT_TIMESTAMP
+                         WHERE code = ?1
+                           AND (act_id IS NOT ?2 OR section IS NOT ?3 OR offence IS NOT ?4
+                                OR min_fine_cents IS NOT ?5 OR max_fine_cents IS NOT ?6
+                                OR min_custody_months IS NOT ?7 OR max_custody_months IS NOT ?8
+                                OR max_penalty IS NOT ?9 OR penalty_notes IS NOT ?10
+                                OR is_repealed IS NOT ?11)",
+                        values,
+                    )
+                    .context("Failed to update catalogue offence")?;
+                report.offences_updated += changed as i64;
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO catalogue_offences (code, act_id, section, offence,
+                         min_fine_cents, max_fine_cents, min_custody_months, max_custody_months,
+                         max_penalty, penalty_notes, is_repealed)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    values,
+                )
+                .context("Failed to add catalogue offence")?;
+                report.offences_added += 1;
+            }
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO catalogue_versions (version, name, published_on, source, acts_added,
+             acts_updated, offences_added, offences_updated, installed_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            report.version,
+            optional_text(file.name),
+            optional_date("published_on", file.published_on.as_deref())?,
+            source.as_str(),
+            report.acts_added,
+            report.acts_updated,
+            report.offences_added,
+            report.offences_updated,
+            installed_by
+        ],
+    )
+    .context("Failed to record catalogue version")?;
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Install the bundled edition if the database has an older one or none.
+/// Run at startup, after the migrations.
+pub fn install_bundled(conn: &mut Connection) -> AppResult<Option<ImportReport>> {
+    let file = parse(BUNDLED)?;
+    if installed_version(conn)?.is_some_and(|installed| installed >= file.version) {
+        return Ok(None);
+    }
+    install(conn, file, CatalogueSource::Bundled, None).map(Some)
+}
+
+/// Install a catalogue edition from a JSON file
+pub fn import_file(
+    conn: &mut Connection,
+    path: &Path,
+    installed_by: Option<&str>,
+) -> AppResult<ImportReport> {
+    let json = fs::read_to_string(path).context(&format!("Failed to read {}", path.display()))?;
+    install(conn, parse(&json)?, CatalogueSource::Imported, installed_by)
+}
+
+pub fn get(conn: &Connection, catalogue_id: i64) -> AppResult<CatalogueOffence> {
+    conn.query_row(
+        &format!("{} WHERE co.catalogue_id = ?1", OFFENCE_SELECT),
+        params![catalogue_id],
+        CatalogueOffence::from_row,
+    )
+    .optional()
+    .context("Failed to fetch catalogue offence")?
+    .ok_or_else(|| AppError::not_found("Offence not found in the catalogue"))
+}
+
+/// The catalogue entry a charge or history record points at, if any
+pub(crate) fn resolve(
+    conn: &Connection,
+    catalogue_id: Option<i64>,
+) -> AppResult<Option<CatalogueOffence>> {
+    catalogue_id
+        .map(|id| {
+            get(conn, id).map_err(|e| match e {
+                AppError::NotFound(_) => {
+                    AppError::field("catalogue_id", "No such offence in the catalogue")
+                }
+                e => e,
+            })
+        })
+        .transpose()
+}
+
+/// The offences under a section of an Act, given by code or title. "268"
+/// finds "268(1) as read with 275", "296" both 296(1) and 296(2), and "275"
+/// the sections read with it.
+pub fn lookup(conn: &Connection, act: &str, section: &str) -> AppResult<Vec<CatalogueOffence>> {
+    let act = act.trim();
+    let section = section.trim();
+    let section = ["section ", "sec. ", "s. ", "s."]
+        .iter()
+        .find_map(|prefix| {
+            section
+                .get(..prefix.len())
+                .filter(|p| p.eq_ignore_ascii_case(prefix))
+                .map(|_| section[prefix.len()..].trim())
+        })
+        .unwrap_or(section);
+    if act.is_empty() {
+        return Err(AppError::field("act", "Enter the Act"));
+    }
+    if section.is_empty() {
+        return Err(AppError::field("section", "Enter the section"));
+    }
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (a.code = ?1 COLLATE NOCASE OR a.title = ?1 COLLATE NOCASE)
+                 AND (co.section = ?2 OR co.section LIKE ?2 || '(%'
+                      OR co.section LIKE ?2 || ' %' OR co.section LIKE '% with ' || ?2
+                      OR co.section LIKE '% with ' || ?2 || '(%')
+             ORDER BY co.section <> ?2, co.section, co.code",
+            OFFENCE_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let offences = stmt
+        .query_map(params![act, section], CatalogueOffence::from_row)
+        .context("Failed to look up offences")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read offences")?;
+    Ok(offences)
+}
+
+/// Offences matching every word of the query, by Act and section
+pub fn search(conn: &Connection, filter: &CatalogueFilter) -> AppResult<Vec<CatalogueOffence>> {
+    let act = filter
+        .act
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty());
+    let words: Vec<String> = filter
+        .query
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR a.code = ?1 COLLATE NOCASE)
+                 AND (?2 OR (co.is_repealed = 0 AND a.is_repealed = 0))
+             ORDER BY a.title, CAST(co.section AS INTEGER), co.section, co.code",
+            OFFENCE_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let offences = stmt
+        .query_map(
+            params![act, filter.include_repealed],
+            CatalogueOffence::from_row,
+        )
+        .context("Failed to search the catalogue")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read offences")?;
+    Ok(offences
+        .into_iter()
+        .filter(|o| {
+            let text = format!(
+                "{} {} {} {} {}",
+                o.offence, o.code, o.section, o.act_code, o.act_title
+            )
+            .to_lowercase();
+            words.iter().all(|w| text.contains(w.as_str()))
+        })
+        .take(filter.limit.unwrap_or(SEARCH_LIMIT))
+        .collect())
+}
+
+/// Every Act in the catalogue with the number of offences under it
+pub fn list_acts(conn: &Connection) -> AppResult<Vec<CatalogueAct>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.act_id, a.code, a.title, a.chapter, a.is_repealed,
+                 (SELECT COUNT(*) FROM catalogue_offences co WHERE co.act_id = a.act_id)
+             FROM catalogue_acts a ORDER BY a.title",
+        )
+        .context("Failed to prepare statement")?;
+    let acts = stmt
+        .query_map([], |row| {
+            Ok(CatalogueAct {
+                act_id: row.get(0)?,
+                code: row.get(1)?,
+                title: row.get(2)?,
+                chapter: row.get(3)?,
+                is_repealed: row.get(4)?,
+                offence_count: row.get(5)?,
+            })
+        })
+        .context("Failed to fetch Acts")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read Acts")?;
+    Ok(acts)
+}
+
+pub fn info(conn: &Connection) -> AppResult<CatalogueInfo> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT version, name, published_on, source, acts_added, acts_updated,
+                 offences_added, offences_updated, installed_by, installed_at
+             FROM catalogue_versions ORDER BY version DESC",
+        )
+        .context("Failed to prepare statement")?;
+    let history: Vec<CatalogueVersion> = stmt
+        .query_map([], |row| {
+            Ok(CatalogueVersion {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                published_on: row.get(2)?,
+                source: row.get(3)?,
+                acts_added: row.get(4)?,
+                acts_updated: row.get(5)?,
+                offences_added: row.get(6)?,
+                offences_updated: row.get(7)?,
+                installed_by: row.get(8)?,
+                installed_at: row.get(9)?,
+            })
+        })
+        .context("Failed to fetch catalogue versions")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read catalogue versions")?;
+    let (acts, offences) = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM catalogue_acts),
+                    (SELECT COUNT(*) FROM catalogue_offences)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("Failed to count catalogue entries")?;
+    Ok(CatalogueInfo {
+        version: history.first().map(|v| v.version),
+        bundled_version: parse(BUNDLED)?.version,
+        acts,
+        offences,
+        history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    /// Write `json` to a scratch file and import it
+    fn import(conn: &mut Connection, json: &str) -> AppResult<ImportReport> {
+        let path =
+            std::env::temp_dir().join(format!("rat-catalogue-{}.json", uuid::Uuid::new_v4()));
+        fs::write(&path, json).unwrap();
+        let imported = import_file(conn, &path, Some("registrar"));
+        let _ = fs::remove_file(&path);
+        imported
+    }
+
+    fn one(conn: &Connection, act: &str, section: &str) -> CatalogueOffence {
+        let found = lookup(conn, act, section).unwrap();
+        assert_eq!(found.len(), 1, "{} s. {}", act, section);
+        found.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn the_bundled_edition_is_installed_once() {
+        let mut conn = db::open_in_memory().unwrap();
+        let bundled = parse(BUNDLED).unwrap();
+        assert_eq!(installed_version(&conn).unwrap(), Some(bundled.version));
+        assert!(install_bundled(&mut conn).unwrap().is_none());
+
+        let info = info(&conn).unwrap();
+        assert_eq!(info.history.len(), 1);
+        assert_eq!(info.history[0].source, "Bundled");
+        assert_eq!(info.offences, bundled.offences.len() as i64);
+        assert_eq!(info.history[0].offences_added, info.offences);
+    }
+
+    #[test]
+    fn a_newer_edition_updates_by_code_and_keeps_the_rest() {
+        let mut conn = db::open_in_memory().unwrap();
+        let stealing = one(&conn, "PC", "268").catalogue_id;
+        let report = import(
+            &mut conn,
+            r#"{
+                "version": 2,
+                "published_on": "2026-11-01",
+                "acts": [
+                    {"code": "PC", "title": "Penal Code", "chapter": "Cap. 63"},
+                    {"code": "NACADA", "title": "Narcotic Drugs and Psychotropic Substances (Control) Act"}
+                ],
+                "offences": [
+                    {"code": "PC-268", "act": "PC", "section": "268(1) as read with 275",
+                     "offence": "Stealing", "max_custody_months": 36},
+                    {"code": "PC-250", "act": "PC", "section": "250",
+                     "offence": "Common assault", "max_custody_months": 12,
+                     "max_fine_cents": 5000000},
+                    {"code": "NACADA-3", "act": "NACADA", "section": "3(1)",
+                     "offence": "Possession of narcotic drugs", "repealed": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            (
+                report.acts_added,
+                report.acts_updated,
+                report.offences_added,
+                report.offences_updated
+            ),
+            (1, 0, 1, 1)
+        );
+        // Entries keep their ids, so charges pointing at them still resolve
+        assert_eq!(one(&conn, "PC", "268").catalogue_id, stealing);
+        assert_eq!(
+            one(&conn, "Penal Code", "s. 250").penalty.as_deref(),
+            Some("Imprisonment for up to 1 year or a fine of up to KSh 50,000")
+        );
+        assert!(!lookup(&conn, "PC", "203").unwrap().is_empty());
+
+        let drugs = CatalogueFilter {
+            query: Some("narcotic".to_string()),
+            ..Default::default()
+        };
+        assert!(search(&conn, &drugs).unwrap().is_empty());
+        let with_repealed = CatalogueFilter {
+            include_repealed: true,
+            ..drugs
+        };
+        assert_eq!(search(&conn, &with_repealed).unwrap().len(), 1);
+        let history = info(&conn).unwrap().history;
+        assert_eq!(
+            (history[0].version, history[0].installed_by.as_deref()),
+            (2, Some("registrar"))
+        );
+    }
+
+    #[test]
+    fn a_bad_file_changes_nothing() {
+        let mut conn = db::open_in_memory().unwrap();
+        let before = info(&conn).unwrap();
+        let rejected = |conn: &mut Connection, json: &str| match import(conn, json) {
+            Err(AppError::Validation { message, .. }) => message,
+            other => panic!("expected a validation error, got {:?}", other),
+        };
+
+        assert!(rejected(&mut conn, "{\"version\": ").starts_with("Not a valid catalogue file"));
+        assert_eq!(
+            rejected(
+                &mut conn,
+                r#"{"version": 2, "offences": [
+                    {"code": "X-1", "act": "PC", "section": "1", "offence": "A"},
+                    {"code": "X-2", "act": "NOPE", "section": "1", "offence": "B"}
+                ]}"#
+            ),
+            "Offence X-2 is under Act NOPE, which is not in the catalogue."
+        );
+        assert_eq!(
+            rejected(
+                &mut conn,
+                r#"{"version": 2, "offences": [
+                    {"code": "X-1", "act": "PC", "section": "1", "offence": "A",
+                     "min_custody_months": 24, "max_custody_months": 12}
+                ]}"#
+            ),
+            "Offence X-1 has a minimum penalty above its maximum."
+        );
+        assert_eq!(
+            rejected(
+                &mut conn,
+                r#"{"version": 2, "acts": [
+                    {"code": "PC", "title": "Penal Code"}, {"code": "PC", "title": "Again"}
+                ]}"#
+            ),
+            "Act PC appears more than once."
+        );
+        assert!(matches!(
+            import(&mut conn, r#"{"version": 1, "offences": []}"#),
+            Err(AppError::Conflict(_))
+        ));
+
+        let after = info(&conn).unwrap();
+        assert_eq!(after.version, before.version);
+        assert_eq!(after.offences, before.offences);
+        assert_eq!(after.acts, before.acts);
+    }
+
+    #[test]
+    fn sections_are_found_however_they_are_written() {
+        let conn = db::open_in_memory().unwrap();
+        assert_eq!(one(&conn, "pc", "Section 268").code, "PC-268");
+        // Read-with sections find the offences they penalise
+        assert_eq!(one(&conn, "PC", "275").code, "PC-268");
+        let robbery: Vec<_> = lookup(&conn, "PC", "296")
+            .unwrap()
+            .into_iter()
+            .map(|o| o.code)
+            .collect();
+        assert_eq!(robbery, vec!["PC-296-1", "PC-296-2"]);
+        assert!(lookup(&conn, "PC", "29").unwrap().is_empty());
+        assert!(matches!(
+            lookup(&conn, "PC", "s. "),
+            Err(AppError::Validation { .. })
+        ));
+
+        assert_eq!(
+            one(&conn, "SOA", "3").penalty.as_deref(),
+            Some("Imprisonment for at least 10 years, up to life")
+        );
+        assert_eq!(
+            one(&conn, "SOA", "8(3)").penalty.as_deref(),
+            Some("Imprisonment for at least 20 years")
+        );
+        assert_eq!(one(&conn, "TA", "49").penalty, None);
+        let drink_driving = CatalogueFilter {
+            query: Some("influence TRAFFIC".to_string()),
+            ..Default::default()
+        };
+        let found = search(&conn, &drink_driving).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].penalty.as_deref(),
+            Some("Imprisonment for up to 2 years or a fine of up to KSh 100,000")
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Offender–case links
+//
+// Who is party to which case, and as what (migration 0004). An offender can
+// sit in many cases and a case can name many offenders, in more than one
+// role each. Unlinking a synced link leaves a tombstone so the deletion
+// reaches Firestore; triggers do the same when an offender or case goes.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseRole {
+    Accused,
+    Complainant,
+    Witness,
+}
+
+impl CaseRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseRole::Accused => "accused",
+            CaseRole::Complainant => "complainant",
+            CaseRole::Witness => "witness",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<CaseRole> {
+        match role {
+            "accused" => Some(CaseRole::Accused),
+            "complainant" => Some(CaseRole::Complainant),
+            "witness" => Some(CaseRole::Witness),
+            _ => None,
+        }
+    }
+}
+
+/// A link, with enough of either side to show it without another lookup
+#[derive(Debug, Clone, Serialize)]
+pub struct OffenderCase {
+    pub id: i64,
+    pub offender_id: i64,
+    pub offender_name: Option<String>,
+    pub case_id: i64,
+    pub case_number: Option<String>,
+    pub case_title: Option<String>,
+    pub case_status: Option<String>,
+    pub role: CaseRole,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const LINK_SELECT: &str = "SELECT l.id, l.offender_id, o.full_name, l.case_id, c.case_number,
+        c.title, c.status, l.role, l.notes, l.created_by, l.created_at
+    FROM offender_cases l
+    LEFT JOIN offenders o ON o.offender_id = l.offender_id
+    LEFT JOIN cases c ON c.case_id = l.case_id";
+
+impl OffenderCase {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let role: String = row.get(7)?;
+        Ok(OffenderCase {
+            id: row.get(0)?,
+            offender_id: row.get(1)?,
+            offender_name: row.get(2)?,
+            case_id: row.get(3)?,
+            case_number: row.get(4)?,
+            case_title: row.get(5)?,
+            case_status: row.get(6)?,
+            role: CaseRole::parse(&role).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    7,
+                    rusqlite::types::Type::Text,
+                    format!("unknown case role {}", role).into(),
+                )
+            })?,
+            notes: row.get(8)?,
+            created_by: row.get(9)?,
+            created_at: row.get(10)?,
+        })
+    }
+}
+
+fn get(conn: &Connection, id: i64) -> AppResult<OffenderCase> {
+    conn.query_row(
+        &format!("{} WHERE l.id = ?1", LINK_SELECT),
+        params![id],
+        OffenderCase::from_row,
+    )
+    .optional()
+    .context("Failed to fetch offender case link")?
+    .ok_or_else(|| AppError::not_found("Link not found"))
+}
+
+fn live(conn: &Connection, table: &str, id_column: &str, id: i64) -> AppResult<bool> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT COALESCE(is_deleted, 0) = 0 FROM {} WHERE {} = ?1",
+                table, id_column
+            ),
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(false))
+}
+
+/// Record `offender_id` as party to `case_id` in `role`. Relinking after an
+/// unlink that has not synced yet revives the tombstone rather than adding
+/// a second row.
+pub fn link(
+    conn: &Connection,
+    offender_id: i64,
+    case_id: i64,
+    role: CaseRole,
+    notes: Option<&str>,
+    created_by: Option<&str>,
+) -> AppResult<OffenderCase> {
+    if !live(conn, "offenders", "offender_id", offender_id)? {
+        return Err(AppError::not_found("Offender not found"));
+    }
+    if !live(conn, "cases", "case_id", case_id)? {
+        return Err(AppError::not_found("Case not found"));
+    }
+    let notes = notes.map(str::trim).filter(|n| !n.is_empty());
+
+    let existing: Option<(i64, bool)> = conn
+        .query_row(
+            "SELECT id, COALESCE(is_deleted, 0) FROM offender_cases
+             WHERE offender_id = ?1 AND case_id = ?2 AND role = ?3
+             ORDER BY is_deleted, id DESC LIMIT 1",
+            params![offender_id, case_id, role.as_str()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("Failed to look up offender case link")?;
+
+    let id = match existing {
+        Some((_, false)) => {
+            return Err(AppError::Conflict(format!(
+                "The offender is already linked to this case as {}.",
+                role.as_str()
+            )))
+        }
+        Some((id, true)) => {
+            conn.execute(
+                "UPDATE offender_cases SET is_deleted = 0, notes = ?2, created_by = ?3,
+                 created_at = CURRENT_TIMESTAMP, last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+                 sync_status = 'pending'
+                 WHERE id = ?1",
+                params![id, notes, created_by],
+            )
+            .context("Failed to link offender to case")?;
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO offender_cases (offender_id, case_id, role, notes, created_by)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![offender_id, case_id, role.as_str(), notes, created_by],
+            )
+            .context("Failed to link offender to case")?;
+            conn.last_insert_rowid()
+        }
+    };
+    get(conn, id)
+}
+
+/// Remove links between `offender_id` and `case_id`: the one in `role`, or
+/// all of them. Returns how many were removed.
+pub fn unlink(
+    conn: &Connection,
+    offender_id: i64,
+    case_id: i64,
+    role: Option<CaseRole>,
+) -> AppResult<usize> {
+    let role = role.map(|r| r.as_str());
+    let filter = "offender_id = ?1 AND case_id = ?2 AND (?3 IS NULL OR role = ?3)
+                  AND COALESCE(is_deleted, 0) = 0";
+    let dropped = conn
+        .execute(
+            &format!(
+                "DELETE FROM offender_cases WHERE {} AND firestore_id IS NULL",
+                filter
+            ),
+            params![offender_id, case_id, role],
+        )
+        .context("Failed to unlink offender from case")?;
+    let tombstoned = conn
+        .execute(
+            &format!(
+                "UPDATE offender_cases SET is_deleted = 1, sync_status = 'pending',
+                 last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE {}",
+                filter
+            ),
+            params![offender_id, case_id, role],
+        )
+        .context("Failed to unlink offender from case")?;
+    if dropped + tombstoned == 0 {
+        return Err(AppError::not_found(
+            "The offender is not linked to this case",
+        ));
+    }
+    Ok(dropped + tombstoned)
+}
+
+fn list(conn: &Connection, filter: &str, id: i64) -> AppResult<Vec<OffenderCase>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE {} = ?1 AND COALESCE(l.is_deleted, 0) = 0 ORDER BY l.id",
+            LINK_SELECT, filter
+        ))
+        .context("Failed to prepare statement")?;
+    let links = stmt
+        .query_map(params![id], OffenderCase::from_row)
+        .context("Failed to fetch offender case links")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read offender case links")?;
+    Ok(links)
+}
+
+/// The cases an offender is party to
+pub fn list_for_offender(conn: &Connection, offender_id: i64) -> AppResult<Vec<OffenderCase>> {
+    list(conn, "l.offender_id", offender_id)
+}
+
+/// The offenders party to a case
+pub fn list_for_case(conn: &Connection, case_id: i64) -> AppResult<Vec<OffenderCase>> {
+    list(conn, "l.case_id", case_id)
+}
+
+/// Move every link of `from` to `to`, for a merge. A link `to` already has
+/// is not duplicated. Returns how many links moved.
+pub(crate) fn reassign(conn: &Connection, from: i64, to: i64) -> AppResult<usize> {
+    let mut stmt = conn.prepare(
+        "SELECT case_id, role, notes, created_by FROM offender_cases
+         WHERE offender_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+    )?;
+    let links = stmt
+        .query_map(params![from], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut moved = 0;
+    for (case_id, role, notes, created_by) in links {
+        let role = CaseRole::parse(&role)
+            .ok_or_else(|| AppError::internal(format!("Unknown case role {}", role)))?;
+        // The link's Firestore id is derived from both sides, so moving it
+        // means ending the old link and starting a new one
+        unlink(conn, from, case_id, Some(role))?;
+        match link(
+            conn,
+            to,
+            case_id,
+            role,
+            notes.as_deref(),
+            created_by.as_deref(),
+        ) {
+            Ok(_) => moved += 1,
+            Err(AppError::Conflict(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn setup() -> (Connection, i64, i64) {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO offenders (full_name) VALUES ('John Kamau')",
+            [],
+        )
+        .unwrap();
+        let offender_id = conn.last_insert_rowid();
+        conn.execute("INSERT INTO cases (title) VALUES ('R v Kamau')", [])
+            .unwrap();
+        let case_id = conn.last_insert_rowid();
+        (conn, offender_id, case_id)
+    }
+
+    /// (is_deleted, sync_status) of every row for the pair, live or not
+    fn rows(conn: &Connection, offender_id: i64, case_id: i64) -> Vec<(bool, String)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT COALESCE(is_deleted, 0), sync_status FROM offender_cases
+                 WHERE offender_id = ?1 AND case_id = ?2 ORDER BY id",
+            )
+            .unwrap();
+        stmt.query_map(params![offender_id, case_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+    }
+
+    fn mark_synced(conn: &Connection) {
+        conn.execute(
+            "UPDATE offender_cases SET firestore_id = 'fs-' || id, sync_status = 'synced'",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn an_unpushed_link_is_simply_deleted() {
+        let (conn, offender, case) = setup();
+        link(&conn, offender, case, CaseRole::Accused, Some("  "), None).unwrap();
+        assert!(matches!(
+            link(&conn, offender, case, CaseRole::Accused, None, None),
+            Err(AppError::Conflict(_))
+        ));
+
+        assert_eq!(unlink(&conn, offender, case, None).unwrap(), 1);
+        assert!(rows(&conn, offender, case).is_empty());
+        assert!(matches!(
+            unlink(&conn, offender, case, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn a_synced_link_leaves_a_tombstone_that_relinking_revives() {
+        let (conn, offender, case) = setup();
+        link(&conn, offender, case, CaseRole::Accused, None, None).unwrap();
+        link(&conn, offender, case, CaseRole::Witness, None, None).unwrap();
+        mark_synced(&conn);
+
+        assert_eq!(
+            unlink(&conn, offender, case, Some(CaseRole::Witness)).unwrap(),
+            1
+        );
+        assert_eq!(
+            rows(&conn, offender, case),
+            vec![(false, "synced".to_string()), (true, "pending".to_string())]
+        );
+        let live = list_for_case(&conn, case).unwrap();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].role, CaseRole::Accused);
+
+        let revived = link(
+            &conn,
+            offender,
+            case,
+            CaseRole::Witness,
+            Some("Saw it"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(revived.notes.as_deref(), Some("Saw it"));
+        assert_eq!(rows(&conn, offender, case).len(), 2);
+        assert_eq!(list_for_offender(&conn, offender).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn removing_either_side_removes_its_links() {
+        let (conn, offender, case) = setup();
+        conn.execute("INSERT INTO cases (title) VALUES ('R v Kamau (2)')", [])
+            .unwrap();
+        let unpushed_case = conn.last_insert_rowid();
+        link(&conn, offender, case, CaseRole::Accused, None, None).unwrap();
+        mark_synced(&conn);
+        link(
+            &conn,
+            offender,
+            unpushed_case,
+            CaseRole::Accused,
+            None,
+            None,
+        )
+        .unwrap();
+
+        conn.execute(
+            "UPDATE offenders SET is_deleted = 1 WHERE offender_id = ?1",
+            params![offender],
+        )
+        .unwrap();
+        assert_eq!(
+            rows(&conn, offender, case),
+            vec![(true, "pending".to_string())]
+        );
+        assert!(rows(&conn, offender, unpushed_case).is_empty());
+        assert!(matches!(
+            link(&conn, offender, case, CaseRole::Accused, None, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reassign_moves_links_without_duplicating_them() {
+        let (conn, from, case) = setup();
+        conn.execute("INSERT INTO offenders (full_name) VALUES ('J. Kamau')", [])
+            .unwrap();
+        let to = conn.last_insert_rowid();
+        link(&conn, from, case, CaseRole::Accused, None, None).unwrap();
+        link(&conn, from, case, CaseRole::Witness, None, None).unwrap();
+        link(&conn, to, case, CaseRole::Accused, None, None).unwrap();
+        mark_synced(&conn);
+
+        assert_eq!(reassign(&conn, from, to).unwrap(), 1);
+        assert!(list_for_offender(&conn, from).unwrap().is_empty());
+        let roles: Vec<_> = list_for_offender(&conn, to)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.role)
+            .collect();
+        assert_eq!(roles, vec![CaseRole::Accused, CaseRole::Witness]);
+    }
+}
@@ -0,0 +1,474 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::services::names;
+use crate::services::offence_catalogue;
+use crate::services::photos::{self, PhotoStore};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Offender {
+    pub offender_id: Option<i64>,
+    pub full_name: String,
+    pub national_id: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<String>,
+    pub photo_path: Option<String>,
+    pub notes: Option<String>,
+    pub date_created: Option<String>,
+    pub file_id: Option<i64>, // Foreign key to files
+    pub penalty: Option<String>,
+    pub penalty_notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OffenderHistory {
+    pub id: Option<i64>,
+    pub offender_id: i64,
+    pub file_id: Option<i64>,
+    pub case_id: Option<i64>,
+    pub offense_date: Option<String>,
+    pub penalty: Option<String>,
+    pub penalty_notes: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: Option<String>,
+    /// The offence in the catalogue, if picked from it
+    pub catalogue_id: Option<i64>,
+}
+
+const OFFENDER_COLUMNS: &str = "offender_id, full_name, national_id, date_of_birth, gender, photo_path, notes, date_created, file_id, penalty, penalty_notes";
+
+const HISTORY_COLUMNS: &str =
+    "id, offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes, created_at, catalogue_id";
+
+impl Offender {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Offender {
+            offender_id: row.get(0)?,
+            full_name: row.get(1)?,
+            national_id: row.get(2)?,
+            date_of_birth: row.get(3)?,
+            gender: row.get(4)?,
+            photo_path: row.get(5)?,
+            notes: row.get(6)?,
+            date_created: row.get(7)?,
+            file_id: row.get(8)?,
+            penalty: row.get(9)?,
+            penalty_notes: row.get(10)?,
+        })
+    }
+}
+
+impl OffenderHistory {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(OffenderHistory {
+            id: row.get(0)?,
+            offender_id: row.get(1)?,
+            file_id: row.get(2)?,
+            case_id: row.get(3)?,
+            offense_date: row.get(4)?,
+            penalty: row.get(5)?,
+            penalty_notes: row.get(6)?,
+            notes: row.get(7)?,
+            created_at: row.get(8)?,
+            catalogue_id: row.get(9)?,
+        })
+    }
+}
+
+/// Offender fields. On update, `None` leaves the stored value unchanged.
+#[derive(Debug, Default, Clone)]
+pub struct OffenderDetails {
+    pub full_name: Option<String>,
+    pub national_id: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<String>,
+    pub notes: Option<String>,
+    pub file_id: Option<i64>,
+    pub penalty: Option<String>,
+    pub penalty_notes: Option<String>,
+}
+
+/// History fields. On update, `None` leaves the stored value unchanged.
+#[derive(Debug, Default, Clone)]
+pub struct HistoryDetails {
+    pub offender_id: Option<i64>,
+    pub file_id: Option<i64>,
+    pub case_id: Option<i64>,
+    pub offense_date: Option<String>,
+    pub penalty: Option<String>,
+    pub penalty_notes: Option<String>,
+    pub notes: Option<String>,
+    pub catalogue_id: Option<i64>,
+}
+
+/// An uploaded photo and its original file name
+pub struct Photo {
+    pub bytes: Vec<u8>,
+    pub filename: String,
+}
+
+fn photo_path(conn: &Connection, offender_id: i64) -> AppResult<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT photo_path FROM offenders WHERE offender_id = ?1",
+            params![offender_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten())
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<Offender>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM offenders", OFFENDER_COLUMNS))?;
+    let offenders = stmt
+        .query_map([], Offender::from_row)?
+        .collect::<Result<_, _>>()?;
+    Ok(offenders)
+}
+
+pub fn get(conn: &Connection, offender_id: i64) -> AppResult<Offender> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM offenders WHERE offender_id = ?1",
+            OFFENDER_COLUMNS
+        ),
+        params![offender_id],
+        Offender::from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Offender not found"))
+}
+
+/// An offender whose name or an alias is close to a searched name
+#[derive(Debug, Serialize, Clone)]
+pub struct NameMatch {
+    pub offender: Offender,
+    pub alias: Option<String>,
+    /// The full name or alias that matched best
+    pub matched_name: String,
+    pub matched_alias: bool,
+    /// 1.0 for the same name after normalization
+    pub score: f64,
+}
+
+pub const DEFAULT_NAME_MATCH_SCORE: f64 = 0.75;
+
+/// Aliases are typed into one column, several to a line
+pub(crate) fn split_aliases(alias: &str) -> impl Iterator<Item = &str> {
+    alias
+        .split([',', ';', '/', '|', '\n'])
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+}
+
+/// Offenders (not deleted) whose full name or an alias scores at least
+/// `min_score` against `name`, best first
+pub fn search_by_name(
+    conn: &Connection,
+    name: &str,
+    min_score: f64,
+    limit: usize,
+) -> AppResult<Vec<NameMatch>> {
+    let query = names::normalize(name);
+    if query.is_empty() {
+        return Err(AppError::field("name", "Enter a name to search for"));
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, alias FROM offenders WHERE COALESCE(is_deleted, 0) = 0",
+        OFFENDER_COLUMNS
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((Offender::from_row(row)?, row.get::<_, Option<String>>(11)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut matches: Vec<NameMatch> = rows
+        .into_iter()
+        .filter_map(|(offender, alias)| {
+            let full_name = offender.full_name.clone();
+            let candidates = std::iter::once((full_name.as_str(), false)).chain(
+                alias
+                    .as_deref()
+                    .into_iter()
+                    .flat_map(split_aliases)
+                    .map(|a| (a, true)),
+            );
+            let (matched_name, matched_alias, score) = candidates
+                .map(|(candidate, is_alias)| {
+                    let score = names::similarity(&query, &names::normalize(candidate));
+                    (candidate.to_string(), is_alias, score)
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2))?;
+            (score >= min_score).then_some(NameMatch {
+                offender,
+                alias,
+                matched_name,
+                matched_alias,
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Create an offender, storing `photo` in `photos` if given
+pub fn create(
+    conn: &Connection,
+    photos: &PhotoStore,
+    offender: OffenderDetails,
+    photo: Option<Photo>,
+) -> AppResult<Offender> {
+    let full_name = offender
+        .full_name
+        .ok_or_else(|| AppError::field("full_name", "Full name is required"))?;
+    let photo_path = photo
+        .map(|photo| photos.save(&photo.filename, &photo.bytes))
+        .transpose()?;
+    conn.execute(
+        "INSERT INTO offenders (full_name, national_id, date_of_birth, gender, photo_path, notes, file_id, penalty, penalty_notes) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            full_name,
+            offender.national_id,
+            offender.date_of_birth,
+            offender.gender,
+            photo_path,
+            offender.notes,
+            offender.file_id,
+            offender.penalty,
+            offender.penalty_notes
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    Ok(Offender {
+        offender_id: Some(id),
+        full_name,
+        national_id: offender.national_id,
+        date_of_birth: offender.date_of_birth,
+        gender: offender.gender,
+        photo_path,
+        notes: offender.notes,
+        date_created: None,
+        file_id: offender.file_id,
+        penalty: offender.penalty,
+        penalty_notes: offender.penalty_notes,
+    })
+}
+
+/// Apply `changes` to an offender. A new `photo` replaces (and removes) the old one.
+pub fn update(
+    conn: &Connection,
+    photos: &PhotoStore,
+    offender_id: i64,
+    changes: OffenderDetails,
+    photo: Option<Photo>,
+) -> AppResult<Offender> {
+    let old_photo_path = photo_path(conn, offender_id)?;
+    let new_photo_path = photo
+        .map(|photo| photos.save(&photo.filename, &photo.bytes))
+        .transpose()?;
+    conn.execute(
+        "UPDATE offenders SET full_name = COALESCE(?2, full_name), national_id = COALESCE(?3, national_id), date_of_birth = COALESCE(?4, date_of_birth), gender = COALESCE(?5, gender), notes = COALESCE(?6, notes), photo_path = COALESCE(?7, photo_path), file_id = COALESCE(?8, file_id), penalty = COALESCE(?9, penalty), penalty_notes = COALESCE(?10, penalty_notes) WHERE offender_id = ?1",
+        params![
+            offender_id,
+            changes.full_name,
+            changes.national_id,
+            changes.date_of_birth,
+            changes.gender,
+            changes.notes,
+            new_photo_path,
+            changes.file_id,
+            changes.penalty,
+            changes.penalty_notes
+        ],
+    )?;
+    if let Some(old) = old_photo_path.filter(|old| new_photo_path.is_some_and(|new| new != *old)) {
+        discard_photo(conn, &old)?;
+    }
+    get(conn, offender_id)
+}
+
+/// Delete an offender and their photo file
+pub fn delete(conn: &Connection, offender_id: i64) -> AppResult<()> {
+    let path = photo_path(conn, offender_id)?;
+    conn.execute(
+        "DELETE FROM offenders WHERE offender_id = ?1",
+        params![offender_id],
+    )?;
+    if let Some(path) = path {
+        discard_photo(conn, &path)?;
+    }
+    Ok(())
+}
+
+/// Remove a photo file no offender uses any more. Identical photos share
+/// one file, so it may still belong to someone else.
+pub(crate) fn discard_photo(conn: &Connection, path: &str) -> AppResult<()> {
+    let in_use: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM offenders WHERE photo_path = ?1)",
+        params![path],
+        |row| row.get(0),
+    )?;
+    if !in_use {
+        photos::remove(Path::new(path));
+    }
+    Ok(())
+}
+
+pub fn photo(conn: &Connection, photos: &PhotoStore, offender_id: i64) -> AppResult<Vec<u8>> {
+    match photo_path(conn, offender_id)? {
+        Some(path) => photos.read(Path::new(&path)),
+        None => Err(AppError::not_found("No photo found")),
+    }
+}
+
+pub fn photo_thumbnail(
+    conn: &Connection,
+    photos: &PhotoStore,
+    offender_id: i64,
+) -> AppResult<Vec<u8>> {
+    match photo_path(conn, offender_id)? {
+        Some(path) => photos.thumbnail(Path::new(&path)),
+        None => Err(AppError::not_found("No photo found")),
+    }
+}
+
+fn query_history(
+    conn: &Connection,
+    filter: &str,
+    args: impl rusqlite::Params,
+) -> AppResult<Vec<OffenderHistory>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM offender_history {} ORDER BY offense_date DESC, created_at DESC",
+        HISTORY_COLUMNS, filter
+    ))?;
+    let history = stmt
+        .query_map(args, OffenderHistory::from_row)?
+        .collect::<Result<_, _>>()?;
+    Ok(history)
+}
+
+pub fn list_all_history(conn: &Connection) -> AppResult<Vec<OffenderHistory>> {
+    query_history(conn, "", [])
+}
+
+pub fn list_history(conn: &Connection, offender_id: i64) -> AppResult<Vec<OffenderHistory>> {
+    query_history(conn, "WHERE offender_id = ?1", params![offender_id])
+}
+
+pub fn get_history(conn: &Connection, id: i64) -> AppResult<OffenderHistory> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM offender_history WHERE id = ?1",
+            HISTORY_COLUMNS
+        ),
+        params![id],
+        OffenderHistory::from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found("Offender history record not found"))
+}
+
+pub fn add_history(conn: &Connection, entry: HistoryDetails) -> AppResult<OffenderHistory> {
+    let offender_id = entry
+        .offender_id
+        .ok_or_else(|| AppError::field("offender_id", "Offender is required"))?;
+    offence_catalogue::resolve(conn, entry.catalogue_id)?;
+    conn.execute(
+        "INSERT INTO offender_history (offender_id, file_id, case_id, offense_date, penalty, penalty_notes, notes, catalogue_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            offender_id,
+            entry.file_id,
+            entry.case_id,
+            entry.offense_date,
+            entry.penalty,
+            entry.penalty_notes,
+            entry.notes,
+            entry.catalogue_id
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    Ok(OffenderHistory {
+        id: Some(id),
+        offender_id,
+        file_id: entry.file_id,
+        case_id: entry.case_id,
+        offense_date: entry.offense_date,
+        penalty: entry.penalty,
+        penalty_notes: entry.penalty_notes,
+        notes: entry.notes,
+        created_at: None,
+        catalogue_id: entry.catalogue_id,
+    })
+}
+
+pub fn update_history(
+    conn: &Connection,
+    id: i64,
+    changes: HistoryDetails,
+) -> AppResult<OffenderHistory> {
+    offence_catalogue::resolve(conn, changes.catalogue_id)?;
+    conn.execute(
+        "UPDATE offender_history SET offender_id = COALESCE(?2, offender_id), file_id = COALESCE(?3, file_id), case_id = COALESCE(?4, case_id), offense_date = COALESCE(?5, offense_date), penalty = COALESCE(?6, penalty), penalty_notes = COALESCE(?7, penalty_notes), notes = COALESCE(?8, notes), catalogue_id = COALESCE(?9, catalogue_id) WHERE id = ?1",
+        params![
+            id,
+            changes.offender_id,
+            changes.file_id,
+            changes.case_id,
+            changes.offense_date,
+            changes.penalty,
+            changes.penalty_notes,
+            changes.notes,
+            changes.catalogue_id
+        ],
+    )?;
+    get_history(conn, id)
+}
+
+pub fn delete_history(conn: &Connection, id: i64) -> AppResult<()> {
+    conn.execute("DELETE FROM offender_history WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[test]
+    fn search_by_name_matches_aliases_and_skips_deleted_offenders() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute_batch(
+            "INSERT INTO offenders (offender_id, full_name, alias)
+             VALUES (1, 'John Kamau', 'Johnny K; Kamau wa Njeri');
+             INSERT INTO offenders (offender_id, full_name) VALUES (2, 'Wanjigu Muthoni');
+             INSERT INTO offenders (offender_id, full_name, is_deleted)
+             VALUES (3, 'Wanjiku Muthoni', 1);",
+        )
+        .unwrap();
+
+        let found = search_by_name(&conn, "Mũthoni Wanjiku", 0.75, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].offender.offender_id, Some(2));
+        assert!(found[0].score < 1.0);
+
+        let found = search_by_name(&conn, "njeri kamau", 0.75, 10).unwrap();
+        assert_eq!(found[0].offender.offender_id, Some(1));
+        assert!(found[0].matched_alias);
+        assert_eq!(found[0].matched_name, "Kamau wa Njeri");
+
+        assert!(search_by_name(&conn, "Otieno", 0.75, 10)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            search_by_name(&conn, " '' ", 0.75, 10),
+            Err(AppError::Validation { .. })
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::services::names;
+
+// ============================================================================
+// Minimal PDF writer
+//
+// Enough PDF for printable text reports: A4 pages, lines of text in
+// Helvetica and Helvetica-Bold, and ruled lines. Both fonts are among the
+// standard fonts every PDF reader has, so nothing is embedded. Text is
+// encoded as WinAnsi (Latin-1 plus typographic quotes and dashes); letters
+// outside it are folded to their plain form, e.g. "Mũthoni" to "Muthoni".
+// ============================================================================
+
+/// A4 in points
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Helvetica advance widths in 1/1000 em for ' ' to '~'
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// The WinAnsi byte for `c`, folding what the encoding lacks
+fn win_ansi(c: char) -> Vec<u8> {
+    match c {
+        ' '..='~' => vec![c as u8],
+        '\u{2018}' => vec![0x91],
+        '\u{2019}' => vec![0x92],
+        '\u{201C}' => vec![0x93],
+        '\u{201D}' => vec![0x94],
+        '\u{2022}' => vec![0x95],
+        '\u{2013}' => vec![0x96],
+        '\u{2014}' => vec![0x97],
+        '\u{2026}' => vec![0x85],
+        '\u{00A0}'..='\u{00FF}' => vec![c as u8],
+        c => {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            match names::fold_char(lower) {
+                Some(plain) if c.is_uppercase() => plain.to_uppercase().into_bytes(),
+                Some(plain) => plain.as_bytes().to_vec(),
+                None => vec![b'?'],
+            }
+        }
+    }
+}
+
+fn encode(text: &str) -> Vec<u8> {
+    text.chars().flat_map(win_ansi).collect()
+}
+
+/// Width of `text` in points
+pub fn text_width(text: &str, size: f32, font: Font) -> f32 {
+    let units: u32 = encode(text)
+        .into_iter()
+        .map(|b| match b {
+            b' '..=b'~' => HELVETICA_WIDTHS[(b - b' ') as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    // Bold runs a little wider; close enough for laying out lines
+    let scale = if font == Font::Bold { 1.06 } else { 1.0 };
+    units as f32 * size / 1000.0 * scale
+}
+
+/// Break `text` into lines no wider than `width`, at spaces where possible
+pub fn wrap(text: &str, size: f32, font: Font, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, size, font) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // A word too long for a line of its own is split
+            for c in word.chars() {
+                line.push(c);
+                if text_width(&line, size, font) > width && line.chars().count() > 1 {
+                    let last = line.pop().unwrap_or(c);
+                    lines.push(std::mem::replace(&mut line, last.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Pages of drawing operations, turned into a PDF file by `finish`.
+/// Coordinates are in points from the top-left corner.
+#[derive(Debug, Default)]
+pub struct PdfWriter {
+    pages: Vec<Vec<u8>>,
+}
+
+impl PdfWriter {
+    pub fn new() -> PdfWriter {
+        PdfWriter::default()
+    }
+
+    pub fn add_page(&mut self) {
+        self.pages.push(Vec::new());
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn page(&mut self) -> &mut Vec<u8> {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        self.pages.last_mut().expect("a page was just added")
+    }
+
+    /// Write one line of text with its baseline at `y`
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let mut literal = Vec::new();
+        for byte in encode(text) {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                literal.push(b'\\');
+            }
+            literal.push(byte);
+        }
+        let page = self.page();
+        page.extend_from_slice(
+            format!(
+                "BT /{} {:.1} Tf {:.2} {:.2} Td (",
+                font.resource(),
+                size,
+                x,
+                PAGE_HEIGHT - y
+            )
+            .as_bytes(),
+        );
+        page.extend_from_slice(&literal);
+        page.extend_from_slice(b") Tj ET\n");
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        let op = format!(
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S\n",
+            width,
+            x1,
+            PAGE_HEIGHT - y1,
+            x2,
+            PAGE_HEIGHT - y2
+        );
+        self.page().extend_from_slice(op.as_bytes());
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and its
+        // content stream for every page
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| 5 + i * 2).collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                page_ids.len()
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+        for (page, content) in page_ids.iter().zip(&self.pages) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    page + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
@@ -0,0 +1,468 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use data_encoding::HEXLOWER;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::security::encryption::{self, Keys};
+
+// ============================================================================
+// Offender photo store
+//
+// Uploads are decoded, turned upright from their EXIF orientation, scaled
+// down and re-encoded as JPEG, which leaves all metadata (GPS position,
+// camera serials) behind. Each photo is named by the SHA-256 of the result,
+// so the caller's filename never reaches the file system and identical
+// photos are stored once. A small thumbnail sits beside it in `thumbnails/`.
+// ============================================================================
+
+/// Largest upload accepted, before processing
+pub const MAX_UPLOAD_BYTES: usize = 15 * 1024 * 1024;
+/// Longest edge of a stored photo
+const MAX_DIMENSION: u32 = 2048;
+/// Longest edge of a thumbnail
+const THUMBNAIL_DIMENSION: u32 = 256;
+const PHOTO_QUALITY: u8 = 88;
+const THUMBNAIL_QUALITY: u8 = 80;
+/// Refuse to decode anything larger, so a small file cannot claim gigabytes
+const MAX_DECODE_PIXELS: u32 = 12_000;
+const MAX_DECODE_ALLOC: u64 = 512 * 1024 * 1024;
+
+pub const THUMBNAIL_DIR: &str = "thumbnails";
+
+/// The offender photo directory. With encryption at rest on, photos are
+/// written encrypted; reads accept both forms so photos from before
+/// encryption was switched on (or from an old backup) still open.
+#[derive(Debug, Clone)]
+pub struct PhotoStore {
+    dir: PathBuf,
+    keys: Option<Keys>,
+}
+
+/// Where the thumbnail of the photo at `photo` lives
+pub fn thumbnail_path(photo: &Path) -> PathBuf {
+    let stem = photo.file_stem().unwrap_or_default().to_string_lossy();
+    photo
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(THUMBNAIL_DIR)
+        .join(format!("{}.jpg", stem))
+}
+
+/// Remove a photo file and its thumbnail. The caller checks that no other
+/// record still uses it.
+pub fn remove(photo: &Path) {
+    let _ = fs::remove_file(photo);
+    let _ = fs::remove_file(thumbnail_path(photo));
+}
+
+fn invalid_photo(filename: &str, reason: impl std::fmt::Display) -> AppError {
+    AppError::field(
+        "photo",
+        format!(
+            "{} is not a usable photo: {}",
+            filename,
+            reason.to_string().trim()
+        ),
+    )
+}
+
+/// Decode an upload and bring it upright, failing on anything that is not
+/// a JPEG, PNG or WebP image
+fn decode(filename: &str, bytes: &[u8]) -> AppResult<DynamicImage> {
+    if bytes.is_empty() {
+        return Err(invalid_photo(filename, "the file is empty"));
+    }
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        return Err(invalid_photo(
+            filename,
+            format!("larger than {} MB", MAX_UPLOAD_BYTES / (1024 * 1024)),
+        ));
+    }
+    let format = image::guess_format(bytes)
+        .ok()
+        .filter(|f| matches!(f, ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP))
+        .ok_or_else(|| invalid_photo(filename, "only JPEG, PNG and WebP images are accepted"))?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODE_PIXELS);
+    limits.max_image_height = Some(MAX_DECODE_PIXELS);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|e| invalid_photo(filename, e))?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| invalid_photo(filename, e))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Scale down to fit `max` and encode as a metadata-free JPEG
+fn encode_jpeg(image: &DynamicImage, max: u32, quality: u8) -> AppResult<Vec<u8>> {
+    let rgb = if image.width() > max || image.height() > max {
+        image.resize(max, max, FilterType::Lanczos3).to_rgb8()
+    } else {
+        image.to_rgb8()
+    };
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, quality)
+        .encode_image(&rgb)
+        .map_err(|e| AppError::internal(format!("Failed to encode photo: {}", e)))?;
+    Ok(out)
+}
+
+impl PhotoStore {
+    pub fn new(dir: PathBuf, keys: Option<Keys>) -> PhotoStore {
+        PhotoStore { dir, keys }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write beside `path` and rename over it, encrypting when a key is set,
+    /// so a reader never sees half a file
+    fn write(&self, path: &Path, bytes: &[u8]) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .context(&format!("Failed to create {}", parent.display()))?;
+        }
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        match &self.keys {
+            Some(keys) => fs::write(&partial, keys.encrypt_photo(bytes)?)?,
+            None => fs::write(&partial, bytes)?,
+        }
+        fs::rename(&partial, path).context(&format!("Failed to write {}", path.display()))
+    }
+
+    /// Validate and clean an uploaded photo, store it with its thumbnail and
+    /// return its path. `filename` is only used in error messages.
+    pub fn save(&self, filename: &str, bytes: &[u8]) -> AppResult<String> {
+        let image = decode(filename, bytes)?;
+        let photo = encode_jpeg(&image, MAX_DIMENSION, PHOTO_QUALITY)?;
+        let path = self
+            .dir
+            .join(format!("{}.jpg", HEXLOWER.encode(&Sha256::digest(&photo))));
+
+        // Same content, same name: an existing file is already this photo
+        if !path.is_file() {
+            self.write(&path, &photo)?;
+        }
+        let thumbnail = thumbnail_path(&path);
+        if !thumbnail.is_file() {
+            self.write(
+                &thumbnail,
+                &encode_jpeg(&image, THUMBNAIL_DIMENSION, THUMBNAIL_QUALITY)?,
+            )?;
+        }
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    pub fn read(&self, path: &Path) -> AppResult<Vec<u8>> {
+        let data = fs::read(path)?;
+        match &self.keys {
+            Some(keys) if encryption::is_encrypted_photo(&data) => keys.decrypt_photo(&data),
+            _ => Ok(data),
+        }
+    }
+
+    /// The thumbnail of the photo at `path`, made on first use for photos
+    /// stored before thumbnails existed
+    pub fn thumbnail(&self, path: &Path) -> AppResult<Vec<u8>> {
+        let thumbnail = thumbnail_path(path);
+        if thumbnail.is_file() {
+            return self.read(&thumbnail);
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let image = decode(&name, &self.read(path)?)?;
+        let bytes = encode_jpeg(&image, THUMBNAIL_DIMENSION, THUMBNAIL_QUALITY)?;
+        self.write(&thumbnail, &bytes)?;
+        Ok(bytes)
+    }
+
+    /// Move every photo and thumbnail to `to` and point `offenders.photo_path`
+    /// at the new copies. The old files are removed only once the paths are
+    /// updated, so a failure part way leaves the old directory in use.
+    /// Returns how many photos moved.
+    pub fn relocate(&self, conn: &mut Connection, to: &Path) -> AppResult<usize> {
+        if to == self.dir {
+            return Ok(0);
+        }
+        if to.starts_with(&self.dir) || self.dir.starts_with(to) {
+            return Err(AppError::field(
+                "photos.directory",
+                "must not be inside the current photo directory or contain it",
+            ));
+        }
+
+        let mut moved = Vec::new();
+        for sub in [Path::new(""), Path::new(THUMBNAIL_DIR)] {
+            let (from_dir, to_dir) = (self.dir.join(sub), to.join(sub));
+            if !from_dir.is_dir() {
+                continue;
+            }
+            fs::create_dir_all(&to_dir)
+                .context(&format!("Failed to create {}", to_dir.display()))?;
+            for entry in fs::read_dir(&from_dir)
+                .context(&format!("Failed to read {}", from_dir.display()))?
+            {
+                let path = entry?.path();
+                let Some(name) = path.file_name().filter(|_| path.is_file()) else {
+                    continue;
+                };
+                let target = to_dir.join(name);
+                if !target.exists() {
+                    fs::copy(&path, &target)
+                        .context(&format!("Failed to copy {}", path.display()))?;
+                } else if fs::read(&target)? != fs::read(&path)? {
+                    return Err(AppError::Conflict(format!(
+                        "{} already holds a different {}",
+                        to_dir.display(),
+                        name.to_string_lossy()
+                    )));
+                }
+                moved.push(path);
+            }
+        }
+
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT offender_id, photo_path FROM offenders WHERE photo_path IS NOT NULL",
+                )
+                .context("Failed to read photo paths")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (offender_id, old_path) in rows {
+                let old_path = Path::new(&old_path);
+                let (Some(parent), Some(name)) = (old_path.parent(), old_path.file_name()) else {
+                    continue;
+                };
+                if parent == self.dir {
+                    tx.execute(
+                        "UPDATE offenders SET photo_path = ?1 WHERE offender_id = ?2",
+                        params![to.join(name).to_string_lossy(), offender_id],
+                    )
+                    .context("Failed to update photo path")?;
+                }
+            }
+        }
+        tx.commit().context("Failed to update photo paths")?;
+
+        for path in &moved {
+            let _ = fs::remove_file(path);
+        }
+        Ok(moved
+            .iter()
+            .filter(|path| path.parent() == Some(self.dir.as_path()))
+            .count())
+    }
+
+    /// Encrypt any plaintext photos and thumbnails left in the directory.
+    /// Returns how many were converted; a no-op when encryption is off.
+    pub fn encrypt_existing(&self) -> AppResult<usize> {
+        let Some(keys) = &self.keys else {
+            return Ok(0);
+        };
+
+        let mut converted = 0;
+        for dir in [self.dir.clone(), self.dir.join(THUMBNAIL_DIR)] {
+            if !dir.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir).context(&format!("Failed to read {}", dir.display()))? {
+                let path = entry?.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let data = fs::read(&path)?;
+                if encryption::is_encrypted_photo(&data) {
+                    continue;
+                }
+                // Write beside the original and rename over it, so a crash
+                // never leaves a half-encrypted photo
+                let mut partial = path.clone().into_os_string();
+                partial.push(".encrypting");
+                fs::write(&partial, keys.encrypt_photo(&data)?)?;
+                fs::rename(&partial, &path)
+                    .context(&format!("Failed to replace {}", path.display()))?;
+                converted += 1;
+            }
+        }
+        Ok(converted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rat-{}-{}", name, uuid::Uuid::new_v4()))
+    }
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::new_rgb8(width, height);
+        let mut out = Vec::new();
+        JpegEncoder::new(&mut out).encode_image(&image).unwrap();
+        out
+    }
+
+    /// `jpeg` with an EXIF block saying "rotate 90° clockwise" and naming
+    /// the camera, as a phone would write it
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let mut tiff = b"MM\0\x2a\0\0\0\x08\0\x02".to_vec();
+        // Orientation (0x0112), SHORT, 1 value: 6
+        tiff.extend_from_slice(b"\x01\x12\0\x03\0\0\0\x01\0\x06\0\0");
+        // Model (0x0110), ASCII, 4 bytes inline: "Cam"
+        tiff.extend_from_slice(b"\x01\x10\0\x02\0\0\0\x04Cam\0");
+        tiff.extend_from_slice(&[0, 0, 0, 0]);
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&tiff);
+
+        let plain = jpeg(width, height);
+        let mut out = plain[..2].to_vec();
+        out.extend_from_slice(&[0xff, 0xe1]);
+        out.extend_from_slice(&(app1.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&plain[2..]);
+        out
+    }
+
+    #[test]
+    fn uploads_are_turned_upright_and_stripped_of_metadata() {
+        let dir = scratch("photos");
+        let store = PhotoStore::new(dir.clone(), None);
+        let upload = jpeg_with_exif(40, 20);
+        assert!(upload.windows(4).any(|w| w == b"Exif"));
+
+        let path = store.save("phone.jpg", &upload).unwrap();
+        let stored = store.read(Path::new(&path)).unwrap();
+        assert!(!stored.windows(4).any(|w| w == b"Exif"));
+        assert!(!stored.windows(3).any(|w| w == b"Cam"));
+        let image = image::load_from_memory(&stored).unwrap();
+        assert_eq!((image.width(), image.height()), (20, 40));
+
+        // Named by content: the same upload is stored once
+        assert_eq!(store.save("again.jpg", &upload).unwrap(), path);
+        let thumbnail =
+            image::load_from_memory(&store.thumbnail(Path::new(&path)).unwrap()).unwrap();
+        assert!(
+            thumbnail.width() <= THUMBNAIL_DIMENSION && thumbnail.height() <= THUMBNAIL_DIMENSION
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn large_photos_are_scaled_down() {
+        let dir = scratch("photos");
+        let store = PhotoStore::new(dir.clone(), None);
+        let path = store
+            .save("big.jpg", &jpeg(MAX_DIMENSION * 2, 100))
+            .unwrap();
+        let image = image::load_from_memory(&store.read(Path::new(&path)).unwrap()).unwrap();
+        assert_eq!(image.width(), MAX_DIMENSION);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn anything_but_a_usable_image_is_refused() {
+        let dir = scratch("photos");
+        let store = PhotoStore::new(dir.clone(), None);
+        let refused = |name: &str, bytes: &[u8]| {
+            matches!(store.save(name, bytes), Err(AppError::Validation { .. }))
+        };
+
+        assert!(refused("empty.jpg", b""));
+        assert!(refused("notes.jpg", b"not an image at all"));
+        // A format the build has no decoder for
+        assert!(refused("anim.gif", b"GIF89a\x02\0\x02\0\0\0\0;"));
+        let truncated = jpeg(64, 64);
+        assert!(refused("cut.jpg", &truncated[..truncated.len() / 3]));
+
+        // A PNG header claiming 20000 x 20000 pixels, refused before decoding
+        let mut ihdr = b"IHDR".to_vec();
+        ihdr.extend_from_slice(&20_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&20_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        let mut crc = flate2::Crc::new();
+        crc.update(&ihdr);
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0d".to_vec();
+        png.extend_from_slice(&ihdr);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+        assert!(refused("huge.png", &png));
+
+        let err = store.save("C:\\evil\\name.jpg", b"").unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("C:\\evil\\name.jpg is not a usable photo"));
+        assert!(!dir.exists() || fs::read_dir(&dir).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn with_a_key_photos_are_stored_encrypted() {
+        let dir = scratch("photos");
+        let (_, keys) = crate::security::encryption::KeyFile::create(
+            crate::security::encryption::KeySource::Passphrase,
+            b"correct horse battery",
+        );
+        let plain = PhotoStore::new(dir.clone(), None);
+        let sealed = PhotoStore::new(dir.clone(), Some(keys));
+
+        let path = sealed.save("a.jpg", &jpeg(8, 8)).unwrap();
+        let on_disk = fs::read(&path).unwrap();
+        assert!(encryption::is_encrypted_photo(&on_disk));
+        assert!(image::load_from_memory(&sealed.read(Path::new(&path)).unwrap()).is_ok());
+        assert!(plain.read(Path::new(&path)).unwrap() == on_disk);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relocate_moves_photos_and_repoints_offenders() {
+        let mut conn = db::open_in_memory().unwrap();
+        let (from, to) = (scratch("photos-from"), scratch("photos-to"));
+        fs::create_dir_all(from.join(THUMBNAIL_DIR)).unwrap();
+        let photo = from.join("abc.jpg");
+        fs::write(&photo, b"photo").unwrap();
+        fs::write(thumbnail_path(&photo), b"thumb").unwrap();
+        conn.execute(
+            "INSERT INTO offenders (full_name, photo_path) VALUES ('Jane Doe', ?1)",
+            params![photo.to_string_lossy()],
+        )
+        .unwrap();
+
+        let store = PhotoStore::new(from.clone(), None);
+        assert_eq!(store.relocate(&mut conn, &to).unwrap(), 1);
+
+        let path: String = conn
+            .query_row("SELECT photo_path FROM offenders", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(Path::new(&path), to.join("abc.jpg"));
+        assert_eq!(fs::read(to.join("abc.jpg")).unwrap(), b"photo");
+        assert_eq!(
+            fs::read(thumbnail_path(&to.join("abc.jpg"))).unwrap(),
+            b"thumb"
+        );
+        assert!(!photo.exists());
+
+        assert!(store.relocate(&mut conn, &from.join("inner")).is_err());
+        let _ = fs::remove_dir_all(&from);
+        let _ = fs::remove_dir_all(&to);
+    }
+}
@@ -0,0 +1,287 @@
+use rusqlite::{params_from_iter, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+
+// ============================================================================
+// Global search
+//
+// Queries the `search_index` FTS5 table (migration 0002), which triggers keep
+// in step with files, cases and offenders; a case's entry also carries the
+// offence, statute and section of its charges (migration 0013). Hits are ranked with BM25, giving
+// names and titles more weight than case numbers and ids, and those more than
+// free-text notes.
+// ============================================================================
+
+pub const DEFAULT_LIMIT: usize = 25;
+pub const MAX_LIMIT: usize = 200;
+
+/// BM25 weights, one per `search_index` column in declaration order
+const RANK: &str = "bm25(search_index, 0.0, 0.0, 0.0, 10.0, 5.0, 1.0)";
+
+/// Stand-ins for the snippet highlight markers, swapped for `<mark>` once
+/// the rest of the snippet is HTML-escaped
+const MATCH_START: char = '\u{1}';
+const MATCH_END: char = '\u{2}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchKind {
+    File,
+    Case,
+    Offender,
+}
+
+impl SearchKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::File => "file",
+            SearchKind::Case => "case",
+            SearchKind::Offender => "offender",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<SearchKind> {
+        match kind {
+            "file" => Some(SearchKind::File),
+            "case" => Some(SearchKind::Case),
+            "offender" => Some(SearchKind::Offender),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    /// `file_id`, `case_id` or `offender_id`, depending on `kind`
+    pub id: i64,
+    pub label: String,
+    /// HTML-escaped text around the match, with matched terms in `<mark>`
+    pub snippet: String,
+    /// Higher is better; only comparable within one search
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub query: String,
+    /// Restrict to these kinds; all kinds when empty
+    pub kinds: Vec<SearchKind>,
+    pub limit: Option<usize>,
+}
+
+/// Turn what the user typed into an FTS5 query. Every word is quoted, so
+/// FTS5 operators and punctuation in case numbers are taken literally, and
+/// the last word matches as a prefix for search-as-you-type.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    let last = terms.len().checked_sub(1)?;
+    Some(
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, term)| {
+                if i == last {
+                    format!("{}*", term)
+                } else {
+                    term.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            MATCH_START => out.push_str("<mark>"),
+            MATCH_END => out.push_str("</mark>"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn search(conn: &Connection, request: &SearchQuery) -> AppResult<Vec<SearchHit>> {
+    let Some(expression) = match_expression(&request.query) else {
+        return Ok(Vec::new());
+    };
+    let limit = request.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AppError::field(
+            "limit",
+            format!("Limit must be between 1 and {}.", MAX_LIMIT),
+        ));
+    }
+
+    let mut args: Vec<Value> = vec![Value::Text(expression)];
+    let mut kind_filter = String::new();
+    if !request.kinds.is_empty() {
+        let placeholders = vec!["?"; request.kinds.len()].join(", ");
+        kind_filter = format!("AND kind IN ({})", placeholders);
+        args.extend(
+            request
+                .kinds
+                .iter()
+                .map(|k| Value::Text(k.as_str().to_string())),
+        );
+    }
+    args.push(Value::Integer(limit as i64));
+
+    let sql = format!(
+        "SELECT kind, record_id, label,
+                snippet(search_index, -1, char(1), char(2), '…', 12),
+                {rank} AS rank
+         FROM search_index
+         WHERE search_index MATCH ? {kind_filter}
+         ORDER BY rank
+         LIMIT ?",
+        rank = RANK,
+        kind_filter = kind_filter,
+    );
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare search")?;
+    let rows = stmt
+        .query_map(params_from_iter(args), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, f64>(4)?,
+            ))
+        })
+        .context("Failed to search")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read search results")?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(kind, id, label, snippet, rank)| {
+            Some(SearchHit {
+                kind: SearchKind::parse(&kind)?,
+                id,
+                label: label.unwrap_or_default(),
+                snippet: escape_html(&snippet.unwrap_or_default()),
+                score: -rank,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn find(conn: &Connection, query: &str) -> Vec<(SearchKind, i64)> {
+        let request = SearchQuery {
+            query: query.to_string(),
+            ..Default::default()
+        };
+        search(conn, &request)
+            .unwrap()
+            .into_iter()
+            .map(|hit| (hit.kind, hit.id))
+            .collect()
+    }
+
+    #[test]
+    fn match_expression_quotes_every_word_and_prefixes_the_last() {
+        assert_eq!(match_expression("   "), None);
+        assert_eq!(match_expression("jane"), Some("\"jane\"*".to_string()));
+        assert_eq!(
+            match_expression("CR 12/2026 OR -x"),
+            Some("\"CR\" \"12/2026\" \"OR\" \"-x\"*".to_string())
+        );
+        assert_eq!(
+            match_expression("say \"hi\""),
+            Some("\"say\" \"\"\"hi\"\"\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn operators_and_punctuation_are_searched_literally() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute_batch(
+            "INSERT INTO cases (case_id, title, case_number) VALUES (1, 'R v Otieno', 'CR 12/2026');
+             INSERT INTO offenders (offender_id, full_name, notes)
+             VALUES (1, 'Jane Doe', 'Seen near the NOT market');",
+        )
+        .unwrap();
+        assert_eq!(find(&conn, "12/2026"), vec![(SearchKind::Case, 1)]);
+        assert_eq!(find(&conn, "oti"), vec![(SearchKind::Case, 1)]);
+        assert_eq!(find(&conn, "NOT mark"), vec![(SearchKind::Offender, 1)]);
+        assert!(find(&conn, "\"unbalanced (").is_empty());
+        assert!(find(&conn, "jane otieno").is_empty());
+    }
+
+    #[test]
+    fn snippets_are_escaped_around_the_marks() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO offenders (full_name, notes) VALUES ('Jane Doe', '<b>Tom & Jerry</b>')",
+            [],
+        )
+        .unwrap();
+        let request = SearchQuery {
+            query: "jerry".to_string(),
+            kinds: vec![SearchKind::Offender],
+            limit: Some(5),
+        };
+        let hits = search(&conn, &request).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(
+            hits[0]
+                .snippet
+                .contains("&lt;b&gt;Tom &amp; <mark>Jerry</mark>&lt;/b&gt;"),
+            "{}",
+            hits[0].snippet
+        );
+
+        let too_many = SearchQuery {
+            limit: Some(MAX_LIMIT + 1),
+            ..request
+        };
+        assert!(matches!(
+            search(&conn, &too_many),
+            Err(AppError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn cases_are_found_by_their_charges() {
+        let conn = db::open_in_memory().unwrap();
+        conn.execute_batch(
+            "INSERT INTO cases (case_id, title, case_number) VALUES (1, 'R v Otieno', 'CR 12/2026');
+             INSERT INTO charges (case_id, count_number, offence, statute, section)
+             VALUES (1, 1, 'Stealing by servant', 'Penal Code', '281');",
+        )
+        .unwrap();
+        assert_eq!(find(&conn, "servant"), vec![(SearchKind::Case, 1)]);
+        assert_eq!(find(&conn, "penal 281"), vec![(SearchKind::Case, 1)]);
+
+        conn.execute(
+            "UPDATE charges SET offence = 'Robbery with violence', section = '296(2)'",
+            [],
+        )
+        .unwrap();
+        assert!(find(&conn, "servant").is_empty());
+        assert_eq!(find(&conn, "robbery"), vec![(SearchKind::Case, 1)]);
+
+        conn.execute("DELETE FROM charges", []).unwrap();
+        assert!(find(&conn, "robbery").is_empty());
+        assert_eq!(find(&conn, "otieno"), vec![(SearchKind::Case, 1)]);
+    }
+}
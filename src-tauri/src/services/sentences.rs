@@ -0,0 +1,1105 @@
+use chrono::{Days, Months, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension, Row, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+
+// ============================================================================
+// Sentences
+//
+// A structured sentence (migration 0007): the penalty, a fine, the custodial,
+// default and supervision terms, whether it runs concurrently or after
+// another sentence, and the start date. Release, default release and
+// supervision end dates are worked out on save. The free-text penalty and
+// sentence fields the rest of the app and sync read are rewritten from the
+// sentences on every change.
+// ============================================================================
+
+/// The penalties offered in the offender forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PenaltyKind {
+    Fine,
+    #[serde(rename = "Locked Up")]
+    LockedUp,
+    #[serde(rename = "Community Service")]
+    CommunityService,
+    Probation,
+    #[serde(rename = "Suspended Sentence")]
+    SuspendedSentence,
+    #[serde(rename = "Discharge Under Section 35 of The Penal Code")]
+    DischargeSection35,
+    #[serde(rename = "Withdrawal Under Section 204 CPC")]
+    WithdrawalSection204,
+    #[serde(rename = "Withdrawal Under Section 87a CPC")]
+    WithdrawalSection87a,
+    Other,
+}
+
+impl PenaltyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PenaltyKind::Fine => "Fine",
+            PenaltyKind::LockedUp => "Locked Up",
+            PenaltyKind::CommunityService => "Community Service",
+            PenaltyKind::Probation => "Probation",
+            PenaltyKind::SuspendedSentence => "Suspended Sentence",
+            PenaltyKind::DischargeSection35 => "Discharge Under Section 35 of The Penal Code",
+            PenaltyKind::WithdrawalSection204 => "Withdrawal Under Section 204 CPC",
+            PenaltyKind::WithdrawalSection87a => "Withdrawal Under Section 87a CPC",
+            PenaltyKind::Other => "Other",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<PenaltyKind> {
+        match kind {
+            "Fine" => Some(PenaltyKind::Fine),
+            "Locked Up" => Some(PenaltyKind::LockedUp),
+            "Community Service" => Some(PenaltyKind::CommunityService),
+            "Probation" => Some(PenaltyKind::Probation),
+            "Suspended Sentence" => Some(PenaltyKind::SuspendedSentence),
+            "Discharge Under Section 35 of The Penal Code" => Some(PenaltyKind::DischargeSection35),
+            "Withdrawal Under Section 204 CPC" => Some(PenaltyKind::WithdrawalSection204),
+            "Withdrawal Under Section 87a CPC" => Some(PenaltyKind::WithdrawalSection87a),
+            "Other" => Some(PenaltyKind::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl TermUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TermUnit::Days => "days",
+            TermUnit::Weeks => "weeks",
+            TermUnit::Months => "months",
+            TermUnit::Years => "years",
+        }
+    }
+
+    pub fn parse(unit: &str) -> Option<TermUnit> {
+        match unit {
+            "days" => Some(TermUnit::Days),
+            "weeks" => Some(TermUnit::Weeks),
+            "months" => Some(TermUnit::Months),
+            "years" => Some(TermUnit::Years),
+            _ => None,
+        }
+    }
+}
+
+/// A length of time, e.g. 3 months
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Term {
+    pub value: u32,
+    pub unit: TermUnit,
+}
+
+impl Term {
+    /// The date `self` after `date`: a 3 month term from 10 January ends
+    /// on 10 April
+    pub fn after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self.unit {
+            TermUnit::Days => date.checked_add_days(Days::new(self.value.into())),
+            TermUnit::Weeks => date.checked_add_days(Days::new(u64::from(self.value) * 7)),
+            TermUnit::Months => date.checked_add_months(Months::new(self.value)),
+            TermUnit::Years => date.checked_add_months(Months::new(self.value.saturating_mul(12))),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let unit = self.unit.as_str();
+        if self.value == 1 {
+            format!("1 {}", &unit[..unit.len() - 1])
+        } else {
+            format!("{} {}", self.value, unit)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Concurrency {
+    #[default]
+    Concurrent,
+    Consecutive,
+}
+
+impl Concurrency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Concurrency::Concurrent => "concurrent",
+            Concurrency::Consecutive => "consecutive",
+        }
+    }
+
+    pub fn parse(concurrency: &str) -> Option<Concurrency> {
+        match concurrency {
+            "concurrent" => Some(Concurrency::Concurrent),
+            "consecutive" => Some(Concurrency::Consecutive),
+            _ => None,
+        }
+    }
+}
+
+/// A sentence as entered
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSentence {
+    pub case_id: Option<i64>,
+    pub offender_id: Option<i64>,
+    /// The offender_history entry this sentence belongs to, if any
+    pub history_id: Option<i64>,
+    /// The count it is passed on, if any
+    pub charge_id: Option<i64>,
+    pub penalty: PenaltyKind,
+    pub fine_amount_cents: Option<i64>,
+    pub custodial_term: Option<Term>,
+    /// Imprisonment in default of paying the fine
+    pub default_term: Option<Term>,
+    /// Probation, community service or, for a suspended sentence, the
+    /// period it is suspended for
+    pub supervision_term: Option<Term>,
+    #[serde(default)]
+    pub concurrency: Concurrency,
+    pub consecutive_to: Option<i64>,
+    /// YYYY-MM-DD
+    pub start_date: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Sentence {
+    pub sentence_id: i64,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub history_id: Option<i64>,
+    pub charge_id: Option<i64>,
+    pub count_number: Option<i64>,
+    pub penalty: PenaltyKind,
+    pub fine_amount_cents: Option<i64>,
+    pub custodial_term: Option<Term>,
+    pub default_term: Option<Term>,
+    pub supervision_term: Option<Term>,
+    pub concurrency: Concurrency,
+    pub consecutive_to: Option<i64>,
+    pub start_date: Option<String>,
+    pub effective_start_date: Option<String>,
+    pub release_date: Option<String>,
+    pub default_release_date: Option<String>,
+    pub supervision_end_date: Option<String>,
+    pub summary: String,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const SENTENCE_SELECT: &str = "SELECT s.sentence_id, s.case_id, c.case_number, s.offender_id,
+        COALESCE(o.full_name, s.offender_name), s.history_id, s.penalty, s.fine_amount_cents, s.custodial_term,
+        s.custodial_unit, s.default_term, s.default_unit, s.supervision_term,
+        s.supervision_unit, s.concurrency, s.consecutive_to, s.start_date,
+        s.effective_start_date, s.release_date, s.default_release_date,
+        s.supervision_end_date, s.summary, s.notes, s.created_by, s.created_at, s.charge_id,
+        ch.count_number
+    FROM sentences s
+    LEFT JOIN cases c ON c.case_id = s.case_id
+    LEFT JOIN offenders o ON o.offender_id = s.offender_id
+    LEFT JOIN charges ch ON ch.charge_id = s.charge_id";
+
+fn conversion_error(column: usize, message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, message.into())
+}
+
+fn term_from_row(row: &Row, column: usize) -> rusqlite::Result<Option<Term>> {
+    let value: Option<u32> = row.get(column)?;
+    let unit: Option<String> = row.get(column + 1)?;
+    match (value, unit) {
+        (Some(value), Some(unit)) => Ok(Some(Term {
+            value,
+            unit: TermUnit::parse(&unit).ok_or_else(|| {
+                conversion_error(column + 1, format!("unknown term unit {}", unit))
+            })?,
+        })),
+        _ => Ok(None),
+    }
+}
+
+impl Sentence {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let penalty: String = row.get(6)?;
+        let concurrency: String = row.get(14)?;
+        Ok(Sentence {
+            sentence_id: row.get(0)?,
+            case_id: row.get(1)?,
+            case_number: row.get(2)?,
+            offender_id: row.get(3)?,
+            offender_name: row.get(4)?,
+            history_id: row.get(5)?,
+            charge_id: row.get(25)?,
+            count_number: row.get(26)?,
+            penalty: PenaltyKind::parse(&penalty)
+                .ok_or_else(|| conversion_error(6, format!("unknown penalty {}", penalty)))?,
+            fine_amount_cents: row.get(7)?,
+            custodial_term: term_from_row(row, 8)?,
+            default_term: term_from_row(row, 10)?,
+            supervision_term: term_from_row(row, 12)?,
+            concurrency: Concurrency::parse(&concurrency).ok_or_else(|| {
+                conversion_error(14, format!("unknown concurrency {}", concurrency))
+            })?,
+            consecutive_to: row.get(15)?,
+            start_date: row.get(16)?,
+            effective_start_date: row.get(17)?,
+            release_date: row.get(18)?,
+            default_release_date: row.get(19)?,
+            supervision_end_date: row.get(20)?,
+            summary: row.get(21)?,
+            notes: row.get(22)?,
+            created_by: row.get(23)?,
+            created_at: row.get(24)?,
+        })
+    }
+}
+
+/// `cents` as shillings with thousands separators, e.g. "20,000" or
+/// "1,250.50"
+pub(crate) fn format_cents(cents: i64) -> String {
+    let whole = (cents / 100).unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if cents < 0 { "-" } else { "" };
+    match (cents % 100).abs() {
+        0 => format!("{}{}", sign, grouped),
+        fraction => format!("{}{}.{:02}", sign, grouped, fraction),
+    }
+}
+
+/// The sentence in words, e.g. "Fine: KSh 20,000 in default 3 months"
+pub fn render(sentence: &NewSentence) -> String {
+    let mut details = Vec::new();
+    if let Some(cents) = sentence.fine_amount_cents {
+        let mut fine = format!("KSh {}", format_cents(cents));
+        if let Some(term) = sentence.default_term {
+            fine.push_str(&format!(" in default {}", term.render()));
+        }
+        details.push(fine);
+    }
+    if sentence.penalty == PenaltyKind::SuspendedSentence {
+        match (sentence.custodial_term, sentence.supervision_term) {
+            (Some(custodial), Some(period)) => details.push(format!(
+                "{} suspended for {}",
+                custodial.render(),
+                period.render()
+            )),
+            (Some(custodial), None) => details.push(format!("{} suspended", custodial.render())),
+            (None, Some(period)) => details.push(format!("suspended for {}", period.render())),
+            (None, None) => {}
+        }
+    } else {
+        if let Some(term) = sentence.custodial_term {
+            details.push(term.render());
+        }
+        if let Some(term) = sentence.supervision_term {
+            if details.is_empty() {
+                details.push(term.render());
+            } else {
+                details.push(format!("then {} supervision", term.render()));
+            }
+        }
+    }
+    if sentence.concurrency == Concurrency::Consecutive {
+        details.push("consecutive".to_string());
+    }
+    if let Some(start) = &sentence.start_date {
+        details.push(format!("from {}", start));
+    }
+
+    if details.is_empty() {
+        sentence.penalty.as_str().to_string()
+    } else {
+        format!("{}: {}", sentence.penalty.as_str(), details.join(", "))
+    }
+}
+
+/// The dates worked out from a sentence's terms
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Dates {
+    effective_start: Option<NaiveDate>,
+    release: Option<NaiveDate>,
+    default_release: Option<NaiveDate>,
+    supervision_end: Option<NaiveDate>,
+}
+
+fn to_date(value: Option<&str>) -> Option<NaiveDate> {
+    value.and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+}
+
+fn format_date(date: Option<NaiveDate>) -> Option<String> {
+    date.map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// `follows` is when the sentence this one is consecutive to ends. Custody
+/// runs from the effective start; imprisonment in default runs after any
+/// custody; supervision runs after custody, except under a suspended
+/// sentence, where it is the suspension period from the start. Remission
+/// is not taken off.
+fn compute(sentence: &NewSentence, follows: Option<NaiveDate>) -> Dates {
+    let start = to_date(sentence.start_date.as_deref());
+    let effective_start = match sentence.concurrency {
+        Concurrency::Consecutive => follows.or(start),
+        Concurrency::Concurrent => start,
+    };
+    let Some(from) = effective_start else {
+        return Dates::default();
+    };
+    let suspended = sentence.penalty == PenaltyKind::SuspendedSentence;
+    let release = sentence
+        .custodial_term
+        .filter(|_| !suspended)
+        .and_then(|term| term.after(from));
+    let after_custody = release.unwrap_or(from);
+    Dates {
+        effective_start: Some(from),
+        release,
+        default_release: sentence
+            .default_term
+            .and_then(|term| term.after(after_custody)),
+        supervision_end: sentence
+            .supervision_term
+            .and_then(|term| term.after(if suspended { from } else { after_custody })),
+    }
+}
+
+/// When a sentence ends, for one consecutive to it to start
+fn end_of(conn: &Connection, sentence_id: i64) -> AppResult<Option<NaiveDate>> {
+    let end: Option<Option<String>> = conn
+        .query_row(
+            "SELECT COALESCE(release_date, default_release_date, supervision_end_date)
+             FROM sentences WHERE sentence_id = ?1",
+            params![sentence_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch sentence")?;
+    Ok(to_date(end.flatten().as_deref()))
+}
+
+fn validate(sentence: &mut NewSentence) -> AppResult<()> {
+    for (field, term) in [
+        ("custodial_term", sentence.custodial_term),
+        ("default_term", sentence.default_term),
+        ("supervision_term", sentence.supervision_term),
+    ] {
+        if term.is_some_and(|t| t.value == 0) {
+            return Err(AppError::field(field, "A term must be at least 1"));
+        }
+    }
+    if sentence.fine_amount_cents.is_some_and(|c| c <= 0) {
+        return Err(AppError::field(
+            "fine_amount_cents",
+            "Enter a fine above zero",
+        ));
+    }
+    match sentence.penalty {
+        PenaltyKind::Fine if sentence.fine_amount_cents.is_none() => {
+            return Err(AppError::field(
+                "fine_amount_cents",
+                "Enter the amount of the fine",
+            ))
+        }
+        PenaltyKind::LockedUp | PenaltyKind::SuspendedSentence
+            if sentence.custodial_term.is_none() =>
+        {
+            return Err(AppError::field(
+                "custodial_term",
+                "Enter the term of imprisonment",
+            ))
+        }
+        PenaltyKind::Probation | PenaltyKind::CommunityService
+            if sentence.supervision_term.is_none() =>
+        {
+            return Err(AppError::field(
+                "supervision_term",
+                format!(
+                    "Enter the {} term",
+                    sentence.penalty.as_str().to_lowercase()
+                ),
+            ))
+        }
+        _ => {}
+    }
+    if sentence.default_term.is_some() && sentence.fine_amount_cents.is_none() {
+        return Err(AppError::field(
+            "default_term",
+            "A default term goes with a fine",
+        ));
+    }
+    match (sentence.concurrency, sentence.consecutive_to) {
+        (Concurrency::Consecutive, None) => {
+            return Err(AppError::field(
+                "consecutive_to",
+                "Choose the sentence this one follows",
+            ))
+        }
+        (Concurrency::Concurrent, Some(_)) => sentence.consecutive_to = None,
+        _ => {}
+    }
+    if let Some(start) = &sentence.start_date {
+        sentence.start_date = Some(parse_date("start_date", start)?);
+    }
+    sentence.notes = sentence
+        .notes
+        .take()
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    Ok(())
+}
+
+/// Fill in the offender and case from the history entry and the count, and
+/// check every reference points at something
+fn resolve(
+    conn: &Connection,
+    sentence: &mut NewSentence,
+    sentence_id: Option<i64>,
+) -> AppResult<()> {
+    if let Some(history_id) = sentence.history_id {
+        let (offender_id, case_id): (i64, Option<i64>) = conn
+            .query_row(
+                "SELECT offender_id, case_id FROM offender_history WHERE id = ?1",
+                params![history_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .context("Failed to fetch offender history")?
+            .ok_or_else(|| AppError::not_found("Offender history entry not found"))?;
+        if sentence.offender_id.is_some_and(|id| id != offender_id) {
+            return Err(AppError::field(
+                "history_id",
+                "The history entry belongs to another offender",
+            ));
+        }
+        sentence.offender_id = Some(offender_id);
+        sentence.case_id = sentence.case_id.or(case_id);
+    }
+    if let Some(charge_id) = sentence.charge_id {
+        let (case_id, offender_id): (i64, Option<i64>) = conn
+            .query_row(
+                "SELECT case_id, offender_id FROM charges WHERE charge_id = ?1",
+                params![charge_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .context("Failed to fetch charge")?
+            .ok_or_else(|| AppError::not_found("Charge not found"))?;
+        if sentence.case_id.is_some_and(|id| id != case_id) {
+            return Err(AppError::field(
+                "charge_id",
+                "The count belongs to another case",
+            ));
+        }
+        if let (Some(charged), Some(sentenced)) = (offender_id, sentence.offender_id) {
+            if charged != sentenced {
+                return Err(AppError::field(
+                    "charge_id",
+                    "The count is against another accused",
+                ));
+            }
+        }
+        sentence.case_id = Some(case_id);
+        sentence.offender_id = sentence.offender_id.or(offender_id);
+    }
+    if sentence.case_id.is_none() && sentence.offender_id.is_none() {
+        return Err(AppError::validation(
+            "A sentence must be passed on an offender or in a case",
+        ));
+    }
+    if let Some(offender_id) = sentence.offender_id {
+        let found: Option<i64> = conn
+            .query_row(
+                "SELECT offender_id FROM offenders WHERE offender_id = ?1",
+                params![offender_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch offender")?;
+        if found.is_none() {
+            return Err(AppError::not_found("Offender not found"));
+        }
+    }
+    if let Some(case_id) = sentence.case_id {
+        let found: Option<i64> = conn
+            .query_row(
+                "SELECT case_id FROM cases WHERE case_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+                params![case_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch case")?;
+        if found.is_none() {
+            return Err(AppError::not_found("Case not found"));
+        }
+    }
+    if let Some(previous) = sentence.consecutive_to {
+        let other = get(conn, previous)?;
+        if sentence.offender_id.is_some() && other.offender_id != sentence.offender_id {
+            return Err(AppError::field(
+                "consecutive_to",
+                "A sentence can only follow another sentence on the same offender",
+            ));
+        }
+        // Walk the chain to make sure it does not come back round
+        if let Some(own_id) = sentence_id {
+            let mut next = Some(previous);
+            while let Some(id) = next {
+                if id == own_id {
+                    return Err(AppError::field(
+                        "consecutive_to",
+                        "A sentence cannot end up following itself",
+                    ));
+                }
+                next = conn
+                    .query_row(
+                        "SELECT consecutive_to FROM sentences WHERE sentence_id = ?1",
+                        params![id],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .context("Failed to fetch sentence")?
+                    .flatten();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A record whose free text is rendered from sentences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Case(i64),
+    Offender(i64),
+    History(i64),
+}
+
+fn targets(case_id: Option<i64>, offender_id: Option<i64>, history_id: Option<i64>) -> Vec<Target> {
+    let mut targets = Vec::new();
+    targets.extend(case_id.map(Target::Case));
+    targets.extend(offender_id.map(Target::Offender));
+    targets.extend(history_id.map(Target::History));
+    targets
+}
+
+/// What the target's free text should read, or None without sentences
+fn rendered_text(conn: &Connection, target: Target) -> AppResult<Option<String>> {
+    let text = match target {
+        Target::Case(case_id) => {
+            let mut stmt = conn.prepare(
+                "SELECT COALESCE(o.full_name, s.offender_name), ch.count_number, s.summary
+                 FROM sentences s
+                 LEFT JOIN offenders o ON o.offender_id = s.offender_id
+                 LEFT JOIN charges ch ON ch.charge_id = s.charge_id
+                 WHERE s.case_id = ?1 ORDER BY s.sentence_id",
+            )?;
+            let rows = stmt
+                .query_map(params![case_id], |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<i64>>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            // Name the offenders when the case sentenced more than one, and
+            // the count when the sentence was passed on one
+            let named = rows.iter().any(|(name, _, _)| name != &rows[0].0);
+            let parts: Vec<String> = rows
+                .iter()
+                .map(|(name, count, summary)| match (name, count) {
+                    (Some(name), Some(count)) if named => {
+                        format!("{}, count {}: {}", name, count, summary)
+                    }
+                    (Some(name), None) if named => format!("{}: {}", name, summary),
+                    (_, Some(count)) => format!("Count {}: {}", count, summary),
+                    _ => summary.clone(),
+                })
+                .collect();
+            Some(parts.join("; ")).filter(|t| !t.is_empty())
+        }
+        // The offender's penalty is their latest sentence
+        Target::Offender(offender_id) => conn
+            .query_row(
+                "SELECT summary FROM sentences WHERE offender_id = ?1
+                 ORDER BY COALESCE(effective_start_date, start_date, '') DESC, sentence_id DESC
+                 LIMIT 1",
+                params![offender_id],
+                |row| row.get(0),
+            )
+            .optional()?,
+        Target::History(history_id) => {
+            let mut stmt = conn.prepare(
+                "SELECT summary FROM sentences WHERE history_id = ?1 ORDER BY sentence_id",
+            )?;
+            let parts = stmt
+                .query_map(params![history_id], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Some(parts.join("; ")).filter(|t| !t.is_empty())
+        }
+    };
+    Ok(text)
+}
+
+/// Bring the target's free text in line with its sentences. Once the last
+/// sentence is gone the text is cleared, but only if it still reads what
+/// the sentences rendered; text typed in by hand is left alone.
+fn write_text(conn: &Connection, target: Target, before: Option<String>) -> AppResult<()> {
+    let after = rendered_text(conn, target)?;
+    if after == before {
+        return Ok(());
+    }
+    let (sql, id) = match target {
+        Target::Case(id) => (
+            "UPDATE cases SET sentence = ?2, sync_status = 'pending', has_changes = 1,
+             last_modified = CURRENT_TIMESTAMP
+             WHERE case_id = ?1 AND (?3 IS NULL OR sentence IS ?3)",
+            id,
+        ),
+        Target::Offender(id) => (
+            "UPDATE offenders SET penalty = ?2, sync_status = 'pending', has_changes = 1,
+             last_modified = CURRENT_TIMESTAMP
+             WHERE offender_id = ?1 AND (?3 IS NULL OR penalty IS ?3)",
+            id,
+        ),
+        Target::History(id) => (
+            "UPDATE offender_history SET penalty = ?2 WHERE id = ?1 AND (?3 IS NULL OR penalty IS ?3)",
+            id,
+        ),
+    };
+    // ?3 is only set when clearing, so an existing value is replaced
+    // outright but only a rendered one is removed
+    let expected = if after.is_none() { before } else { None };
+    conn.execute(sql, params![id, after, expected])
+        .context("Failed to update sentence text")?;
+    Ok(())
+}
+
+/// What `cases.sentence` should read for the case's sentences
+pub(crate) fn case_text(conn: &Connection, case_id: i64) -> AppResult<Option<String>> {
+    rendered_text(conn, Target::Case(case_id))
+}
+
+/// Rewrite `cases.sentence` after something it is rendered from changed,
+/// e.g. a count was renumbered; `before` is what `case_text` read first
+pub(crate) fn rewrite_case_text(
+    conn: &Connection,
+    case_id: i64,
+    before: Option<String>,
+) -> AppResult<()> {
+    write_text(conn, Target::Case(case_id), before)
+}
+
+pub fn get(conn: &Connection, sentence_id: i64) -> AppResult<Sentence> {
+    conn.query_row(
+        &format!("{} WHERE s.sentence_id = ?1", SENTENCE_SELECT),
+        params![sentence_id],
+        Sentence::from_row,
+    )
+    .optional()
+    .context("Failed to fetch sentence")?
+    .ok_or_else(|| AppError::not_found("Sentence not found"))
+}
+
+fn store(
+    conn: &Connection,
+    sentence_id: Option<i64>,
+    sentence: &NewSentence,
+    created_by: Option<&str>,
+) -> AppResult<i64> {
+    let follows = match sentence.consecutive_to {
+        Some(previous) => end_of(conn, previous)?,
+        None => None,
+    };
+    let dates = compute(sentence, follows);
+    let term = |t: Option<Term>| (t.map(|t| t.value), t.map(|t| t.unit.as_str()));
+    let penalty = sentence.penalty.as_str();
+    let concurrency = sentence.concurrency.as_str();
+    let (custodial_term, custodial_unit) = term(sentence.custodial_term);
+    let (default_term, default_unit) = term(sentence.default_term);
+    let (supervision_term, supervision_unit) = term(sentence.supervision_term);
+    let summary = render(sentence);
+    let (effective_start, release, default_release, supervision_end) = (
+        format_date(dates.effective_start),
+        format_date(dates.release),
+        format_date(dates.default_release),
+        format_date(dates.supervision_end),
+    );
+    let mut values: Vec<&dyn ToSql> = vec![
+        &sentence.case_id,
+        &sentence.offender_id,
+        &sentence.history_id,
+        &penalty,
+        &sentence.fine_amount_cents,
+        &custodial_term,
+        &custodial_unit,
+        &default_term,
+        &default_unit,
+        &supervision_term,
+        &supervision_unit,
+        &concurrency,
+        &sentence.consecutive_to,
+        &sentence.start_date,
+        &effective_start,
+        &release,
+        &default_release,
+        &supervision_end,
+        &summary,
+        &sentence.notes,
+        &sentence.charge_id,
+    ];
+    match sentence_id {
+        None => {
+            values.push(&created_by);
+            conn.execute(
+                "INSERT INTO sentences (case_id, offender_id, offender_name, history_id, penalty,
+                     fine_amount_cents, custodial_term, custodial_unit, default_term,
+                     default_unit, supervision_term, supervision_unit, concurrency,
+                     consecutive_to, start_date, effective_start_date, release_date,
+                     default_release_date, supervision_end_date, summary, notes, charge_id,
+                     created_by)
+                 VALUES (?1, ?2, (SELECT full_name FROM offenders WHERE offender_id = ?2), ?3,
+                         ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                         ?19, ?20, ?21, ?22)",
+                &*values,
+            )
+            .context("Failed to record sentence")?;
+            Ok(conn.last_insert_rowid())
+        }
+        Some(id) => {
+            values.push(&id);
+            conn.execute(
+                "UPDATE sentences SET case_id = ?1, offender_id = ?2,
+                     offender_name = COALESCE(
+                         (SELECT full_name FROM offenders WHERE offender_id = ?2), offender_name),
+                     history_id = ?3,
+                     penalty = ?4, fine_amount_cents = ?5, custodial_term = ?6,
+                     custodial_unit = ?7, default_term = ?8, default_unit = ?9,
+                     supervision_term = ?10, supervision_unit = ?11, concurrency = ?12,
+                     consecutive_to = ?13, start_date = ?14, effective_start_date = ?15,
+                     release_date = ?16, default_release_date = ?17,
+                     supervision_end_date = ?18, summary = ?19, notes = ?20, charge_id = ?21,
+                     last_modified = CURRENT_TIMESTAMP
+                 WHERE sentence_id = ?22",
+                &*values,
+            )
+            .context("Failed to update sentence")?;
+            Ok(id)
+        }
+    }
+}
+
+impl From<&Sentence> for NewSentence {
+    fn from(s: &Sentence) -> NewSentence {
+        NewSentence {
+            case_id: s.case_id,
+            offender_id: s.offender_id,
+            history_id: s.history_id,
+            charge_id: s.charge_id,
+            penalty: s.penalty,
+            fine_amount_cents: s.fine_amount_cents,
+            custodial_term: s.custodial_term,
+            default_term: s.default_term,
+            supervision_term: s.supervision_term,
+            concurrency: s.concurrency,
+            consecutive_to: s.consecutive_to,
+            start_date: s.start_date.clone(),
+            notes: s.notes.clone(),
+        }
+    }
+}
+
+/// Work the dates out again for every sentence that follows `sentence_id`,
+/// and for the ones after those
+fn recompute_followers(conn: &Connection, sentence_id: i64) -> AppResult<()> {
+    let mut pending = vec![sentence_id];
+    while let Some(id) = pending.pop() {
+        let mut stmt =
+            conn.prepare("SELECT sentence_id FROM sentences WHERE consecutive_to = ?1")?;
+        let followers = stmt
+            .query_map(params![id], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for follower in followers {
+            let current = get(conn, follower)?;
+            store(conn, Some(follower), &NewSentence::from(&current), None)?;
+            pending.push(follower);
+        }
+    }
+    Ok(())
+}
+
+/// Save a new sentence, or replace `sentence_id`, and rewrite the free text
+/// of everything it touches
+fn save(
+    conn: &mut Connection,
+    sentence_id: Option<i64>,
+    mut sentence: NewSentence,
+    created_by: Option<&str>,
+) -> AppResult<Sentence> {
+    validate(&mut sentence)?;
+    let tx = conn.transaction()?;
+    resolve(&tx, &mut sentence, sentence_id)?;
+
+    let mut touched = targets(sentence.case_id, sentence.offender_id, sentence.history_id);
+    if let Some(id) = sentence_id {
+        let old = get(&tx, id)?;
+        for target in targets(old.case_id, old.offender_id, old.history_id) {
+            if !touched.contains(&target) {
+                touched.push(target);
+            }
+        }
+    }
+    let before = touched
+        .iter()
+        .map(|&target| rendered_text(&tx, target))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let id = store(&tx, sentence_id, &sentence, created_by)?;
+    recompute_followers(&tx, id)?;
+    for (target, before) in touched.into_iter().zip(before) {
+        write_text(&tx, target, before)?;
+    }
+    let saved = get(&tx, id)?;
+    tx.commit()?;
+    Ok(saved)
+}
+
+pub fn record(
+    conn: &mut Connection,
+    sentence: NewSentence,
+    created_by: Option<&str>,
+) -> AppResult<Sentence> {
+    save(conn, None, sentence, created_by)
+}
+
+pub fn update(
+    conn: &mut Connection,
+    sentence_id: i64,
+    sentence: NewSentence,
+) -> AppResult<Sentence> {
+    save(conn, Some(sentence_id), sentence, None)
+}
+
+pub fn delete(conn: &mut Connection, sentence_id: i64) -> AppResult<()> {
+    let tx = conn.transaction()?;
+    let sentence = get(&tx, sentence_id)?;
+    let followers: i64 = tx.query_row(
+        "SELECT COUNT(*) FROM sentences WHERE consecutive_to = ?1",
+        params![sentence_id],
+        |row| row.get(0),
+    )?;
+    if followers > 0 {
+        return Err(AppError::Conflict(
+            "Another sentence runs consecutively to this one; change or remove it first."
+                .to_string(),
+        ));
+    }
+    let touched = targets(sentence.case_id, sentence.offender_id, sentence.history_id);
+    let before = touched
+        .iter()
+        .map(|&target| rendered_text(&tx, target))
+        .collect::<AppResult<Vec<_>>>()?;
+    tx.execute(
+        "DELETE FROM sentences WHERE sentence_id = ?1",
+        params![sentence_id],
+    )
+    .context("Failed to delete sentence")?;
+    for (target, before) in touched.into_iter().zip(before) {
+        write_text(&tx, target, before)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn list(conn: &Connection, filter: &str, id: i64) -> AppResult<Vec<Sentence>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE {} = ?1 ORDER BY COALESCE(s.effective_start_date, s.start_date, ''), s.sentence_id",
+            SENTENCE_SELECT, filter
+        ))
+        .context("Failed to prepare statement")?;
+    let sentences = stmt
+        .query_map(params![id], Sentence::from_row)
+        .context("Failed to fetch sentences")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read sentences")?;
+    Ok(sentences)
+}
+
+pub fn list_for_case(conn: &Connection, case_id: i64) -> AppResult<Vec<Sentence>> {
+    list(conn, "s.case_id", case_id)
+}
+
+pub fn list_for_offender(conn: &Connection, offender_id: i64) -> AppResult<Vec<Sentence>> {
+    list(conn, "s.offender_id", offender_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn term(value: u32, unit: TermUnit) -> Option<Term> {
+        Some(Term { value, unit })
+    }
+
+    fn sentence(penalty: PenaltyKind, start_date: &str) -> NewSentence {
+        NewSentence {
+            case_id: None,
+            offender_id: None,
+            history_id: None,
+            charge_id: None,
+            penalty,
+            fine_amount_cents: None,
+            custodial_term: None,
+            default_term: None,
+            supervision_term: None,
+            concurrency: Concurrency::Concurrent,
+            consecutive_to: None,
+            start_date: Some(start_date.to_string()),
+            notes: None,
+        }
+    }
+
+    fn date(value: &str) -> Option<NaiveDate> {
+        to_date(Some(value))
+    }
+
+    #[test]
+    fn terms_run_to_the_same_day_or_the_end_of_a_short_month() {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        let after = |value, unit| term(value, unit).unwrap().after(start);
+
+        assert_eq!(after(1, TermUnit::Months), date("2026-02-28"));
+        assert_eq!(after(3, TermUnit::Months), date("2026-04-30"));
+        assert_eq!(after(2, TermUnit::Years), date("2028-01-31"));
+        assert_eq!(after(2, TermUnit::Weeks), date("2026-02-14"));
+        assert_eq!(after(30, TermUnit::Days), date("2026-03-02"));
+    }
+
+    #[test]
+    fn custody_then_default_then_supervision() {
+        let mut locked_up = sentence(PenaltyKind::LockedUp, "2026-01-10");
+        locked_up.custodial_term = term(6, TermUnit::Months);
+        locked_up.fine_amount_cents = Some(2_000_000);
+        locked_up.default_term = term(3, TermUnit::Months);
+        locked_up.supervision_term = term(1, TermUnit::Years);
+
+        assert_eq!(
+            compute(&locked_up, None),
+            Dates {
+                effective_start: date("2026-01-10"),
+                release: date("2026-07-10"),
+                default_release: date("2026-10-10"),
+                supervision_end: date("2027-07-10"),
+            }
+        );
+    }
+
+    #[test]
+    fn fine_default_runs_from_the_start_without_custody() {
+        let mut fine = sentence(PenaltyKind::Fine, "2026-03-01");
+        fine.fine_amount_cents = Some(500_000);
+        fine.default_term = term(2, TermUnit::Weeks);
+
+        let dates = compute(&fine, None);
+        assert_eq!(dates.release, None);
+        assert_eq!(dates.default_release, date("2026-03-15"));
+    }
+
+    #[test]
+    fn suspended_sentence_is_never_served() {
+        let mut suspended = sentence(PenaltyKind::SuspendedSentence, "2026-01-10");
+        suspended.custodial_term = term(2, TermUnit::Years);
+        suspended.supervision_term = term(1, TermUnit::Years);
+
+        let dates = compute(&suspended, None);
+        assert_eq!(dates.release, None);
+        assert_eq!(dates.supervision_end, date("2027-01-10"));
+    }
+
+    #[test]
+    fn consecutive_sentence_starts_when_the_earlier_one_ends() {
+        let mut consecutive = sentence(PenaltyKind::LockedUp, "2026-01-10");
+        consecutive.custodial_term = term(1, TermUnit::Months);
+        consecutive.concurrency = Concurrency::Consecutive;
+
+        let dates = compute(&consecutive, date("2026-07-10"));
+        assert_eq!(dates.effective_start, date("2026-07-10"));
+        assert_eq!(dates.release, date("2026-08-10"));
+
+        // Without a start date there is nothing to work from
+        consecutive.concurrency = Concurrency::Concurrent;
+        consecutive.start_date = None;
+        assert_eq!(compute(&consecutive, None), Dates::default());
+    }
+
+    #[test]
+    fn following_sentences_move_when_the_first_changes() {
+        let mut conn = db::open_in_memory().unwrap();
+        conn.execute("INSERT INTO offenders (full_name) VALUES ('John Doe')", [])
+            .unwrap();
+        let offender_id = conn.last_insert_rowid();
+
+        let mut first = sentence(PenaltyKind::LockedUp, "2026-01-10");
+        first.offender_id = Some(offender_id);
+        first.custodial_term = term(6, TermUnit::Months);
+        let first = record(&mut conn, first, None).unwrap();
+
+        let mut second = sentence(PenaltyKind::LockedUp, "2026-01-10");
+        second.offender_id = Some(offender_id);
+        second.custodial_term = term(1, TermUnit::Years);
+        second.concurrency = Concurrency::Consecutive;
+        second.consecutive_to = Some(first.sentence_id);
+        let second = record(&mut conn, second, None).unwrap();
+        assert_eq!(second.effective_start_date.as_deref(), Some("2026-07-10"));
+        assert_eq!(second.release_date.as_deref(), Some("2027-07-10"));
+
+        let mut shorter = NewSentence::from(&first);
+        shorter.custodial_term = term(3, TermUnit::Months);
+        update(&mut conn, first.sentence_id, shorter).unwrap();
+
+        let second = get(&conn, second.sentence_id).unwrap();
+        assert_eq!(second.effective_start_date.as_deref(), Some("2026-04-10"));
+        assert_eq!(second.release_date.as_deref(), Some("2027-04-10"));
+    }
+
+    #[test]
+    fn sentence_outlives_the_offender_record() {
+        let mut conn = db::open_in_memory().unwrap();
+        conn.execute("INSERT INTO offenders (full_name) VALUES ('John Doe')", [])
+            .unwrap();
+        let offender_id = conn.last_insert_rowid();
+        let mut fine = sentence(PenaltyKind::Fine, "2026-01-10");
+        fine.offender_id = Some(offender_id);
+        fine.fine_amount_cents = Some(2_000_000);
+        let fine = record(&mut conn, fine, None).unwrap();
+
+        conn.execute("DELETE FROM offenders", []).unwrap();
+
+        let kept = get(&conn, fine.sentence_id).unwrap();
+        assert_eq!(kept.offender_id, None);
+        assert_eq!(kept.offender_name.as_deref(), Some("John Doe"));
+        assert_eq!(kept.summary, fine.summary);
+    }
+}
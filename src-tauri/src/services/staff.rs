@@ -0,0 +1,80 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppResult, ResultExt};
+
+#[derive(Serialize, Deserialize)]
+pub struct Staff {
+    pub staff_id: i32,
+    pub user_id: i32,
+    pub role: String,
+    pub contact_number: Option<String>,
+    pub status: String,
+}
+
+const STAFF_COLUMNS: &str = "staff_id, user_id, role, contact_number, status";
+
+impl Staff {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Staff {
+            staff_id: row.get(0)?,
+            user_id: row.get(1)?,
+            role: row.get(2)?,
+            contact_number: row.get(3)?,
+            status: row.get(4)?,
+        })
+    }
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<Staff>> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM staff", STAFF_COLUMNS))
+        .context("Failed to prepare statement")?;
+
+    let staff = stmt
+        .query_map([], Staff::from_row)
+        .context("Failed to fetch staff members")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read staff")?;
+    Ok(staff)
+}
+
+pub fn get(conn: &Connection, staff_id: i32) -> AppResult<Option<Staff>> {
+    conn.query_row(
+        &format!("SELECT {} FROM staff WHERE staff_id = ?1", STAFF_COLUMNS),
+        params![staff_id],
+        Staff::from_row,
+    )
+    .optional()
+    .context("Failed to fetch staff member")
+}
+
+/// Add an active staff member for `user_id` and return the staff id
+pub fn create(
+    conn: &Connection,
+    user_id: i32,
+    role: &str,
+    contact_number: Option<&str>,
+) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO staff (user_id, role, contact_number, status) VALUES (?1, ?2, ?3, 'Active')",
+        params![user_id, role, contact_number],
+    )
+    .context("Failed to create staff member")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_status(conn: &Connection, staff_id: i32, new_status: &str) -> AppResult<()> {
+    conn.execute(
+        "UPDATE staff SET status = ?1 WHERE staff_id = ?2",
+        params![new_status, staff_id],
+    )
+    .context("Failed to update staff member status")?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, staff_id: i32) -> AppResult<()> {
+    conn.execute("DELETE FROM staff WHERE staff_id = ?1", params![staff_id])
+        .context("Failed to delete staff member")?;
+    Ok(())
+}
@@ -0,0 +1,325 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::security::totp;
+use crate::services::users::is_admin_role;
+
+// ============================================================================
+// Two-factor authentication
+//
+// TOTP enrolment and the check made at sign-in, kept in `user_totp` and
+// `user_recovery_codes` so it works offline. Wrong codes are counted per
+// email; after `LOCKOUT_THRESHOLD` in a row the account is locked out of
+// two-factor for a while, doubling with every further failure, so the six
+// digits cannot be guessed at the speed the app can check them. A right
+// code clears the count.
+// ============================================================================
+
+/// Wrong codes in a row before the first lockout
+pub const LOCKOUT_THRESHOLD: i64 = 5;
+const BASE_LOCKOUT_SECS: i64 = 60;
+const MAX_LOCKOUT_SECS: i64 = 60 * 60;
+
+struct TotpRecord {
+    secret: String,
+    confirmed: bool,
+    last_used_step: Option<i64>,
+    failed_attempts: i64,
+    locked_until: Option<i64>,
+}
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Roles that must present a second factor once they have enrolled
+pub fn role_requires_two_factor(role: &str) -> bool {
+    is_admin_role(role)
+}
+
+fn load_totp(conn: &Connection, email: &str) -> AppResult<Option<TotpRecord>> {
+    conn.query_row(
+        "SELECT secret, confirmed, last_used_step, failed_attempts, locked_until
+         FROM user_totp WHERE email = ?1",
+        params![normalize_email(email)],
+        |row| {
+            Ok(TotpRecord {
+                secret: row.get(0)?,
+                confirmed: row.get(1)?,
+                last_used_step: row.get(2)?,
+                failed_attempts: row.get(3)?,
+                locked_until: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .context("Failed to read two-factor settings")
+}
+
+pub fn is_enrolled(conn: &Connection, email: &str) -> AppResult<bool> {
+    Ok(load_totp(conn, email)?.is_some_and(|r| r.confirmed))
+}
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp().max(0)
+}
+
+/// How long to lock out after `failures` wrong codes in a row, if at all
+fn lockout_secs(failures: i64) -> Option<i64> {
+    let over = failures - LOCKOUT_THRESHOLD;
+    (over >= 0).then(|| {
+        BASE_LOCKOUT_SECS
+            .saturating_mul(1 << over.min(16))
+            .min(MAX_LOCKOUT_SECS)
+    })
+}
+
+/// Verify a TOTP code against the stored secret, recording the step so it
+/// cannot be replayed. Works entirely from the local database.
+fn verify_totp_code(
+    conn: &Connection,
+    email: &str,
+    record: &TotpRecord,
+    code: &str,
+    now: i64,
+) -> AppResult<bool> {
+    let last = record.last_used_step.map(|s| s.max(0) as u64);
+    match totp::verify(&record.secret, code, now as u64, last).map_err(AppError::internal)? {
+        Some(step) => {
+            conn.execute(
+                "UPDATE user_totp SET last_used_step = ?1 WHERE email = ?2",
+                params![step as i64, normalize_email(email)],
+            )
+            .context("Failed to record two-factor use")?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Consume a one-time recovery code if it matches an unused one
+fn use_recovery_code(conn: &Connection, email: &str, code: &str) -> AppResult<bool> {
+    let used = conn
+        .execute(
+            "UPDATE user_recovery_codes SET used_at = CURRENT_TIMESTAMP
+             WHERE id = (
+                 SELECT id FROM user_recovery_codes
+                 WHERE email = ?1 AND code_hash = ?2 AND used_at IS NULL
+                 LIMIT 1
+             )",
+            params![normalize_email(email), totp::hash_recovery_code(code)],
+        )
+        .context("Failed to check recovery code")?;
+    Ok(used > 0)
+}
+
+/// Accept either a current TOTP code or an unused recovery code. Fails with
+/// `Forbidden`, without looking at the code, while the email is locked out.
+pub fn verify_second_factor(conn: &Connection, email: &str, code: &str) -> AppResult<bool> {
+    verify_second_factor_at(conn, email, code, now_secs())
+}
+
+fn verify_second_factor_at(
+    conn: &Connection,
+    email: &str,
+    code: &str,
+    now: i64,
+) -> AppResult<bool> {
+    let record = match load_totp(conn, email)? {
+        Some(r) if r.confirmed => r,
+        _ => return Ok(false),
+    };
+    if let Some(until) = record.locked_until.filter(|until| *until > now) {
+        let minutes = (until - now + 59) / 60;
+        return Err(AppError::Forbidden(format!(
+            "Too many wrong two-factor codes. Try again in {} minute{}.",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        )));
+    }
+
+    let valid =
+        verify_totp_code(conn, email, &record, code, now)? || use_recovery_code(conn, email, code)?;
+    if valid {
+        conn.execute(
+            "UPDATE user_totp SET failed_attempts = 0, locked_until = NULL WHERE email = ?1",
+            params![normalize_email(email)],
+        )
+        .context("Failed to clear two-factor failures")?;
+    } else {
+        let failures = record.failed_attempts + 1;
+        conn.execute(
+            "UPDATE user_totp SET failed_attempts = ?1, locked_until = ?2 WHERE email = ?3",
+            params![
+                failures,
+                lockout_secs(failures).map(|secs| now + secs),
+                normalize_email(email)
+            ],
+        )
+        .context("Failed to record two-factor failure")?;
+    }
+    Ok(valid)
+}
+
+/// Start enrolment with a fresh secret, replacing any unconfirmed one
+pub fn begin_enrollment(conn: &Connection, email: &str) -> AppResult<String> {
+    if is_enrolled(conn, email)? {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled for this account.".to_string(),
+        ));
+    }
+
+    let secret = totp::generate_secret();
+    conn.execute(
+        "INSERT INTO user_totp (email, secret, confirmed) VALUES (?1, ?2, 0)
+         ON CONFLICT(email) DO UPDATE SET secret = excluded.secret, confirmed = 0,
+             last_used_step = NULL, failed_attempts = 0, locked_until = NULL,
+             created_at = CURRENT_TIMESTAMP, confirmed_at = NULL",
+        params![normalize_email(email), secret],
+    )
+    .context("Failed to start two-factor enrolment")?;
+    Ok(secret)
+}
+
+/// Finish enrolment with a first code. Returns the recovery codes.
+pub fn confirm_enrollment(conn: &Connection, email: &str, code: &str) -> AppResult<Vec<String>> {
+    let record = load_totp(conn, email)?
+        .ok_or_else(|| AppError::not_found("No two-factor enrolment in progress."))?;
+    if record.confirmed {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled for this account.".to_string(),
+        ));
+    }
+    if !verify_totp_code(conn, email, &record, code, now_secs())? {
+        return Err(AppError::field("code", "Invalid two-factor code."));
+    }
+
+    conn.execute(
+        "UPDATE user_totp SET confirmed = 1, confirmed_at = CURRENT_TIMESTAMP WHERE email = ?1",
+        params![normalize_email(email)],
+    )
+    .context("Failed to confirm two-factor enrolment")?;
+    replace_recovery_codes(conn, email)
+}
+
+pub fn replace_recovery_codes(conn: &Connection, email: &str) -> AppResult<Vec<String>> {
+    let email = normalize_email(email);
+    let codes = totp::generate_recovery_codes();
+
+    conn.execute(
+        "DELETE FROM user_recovery_codes WHERE email = ?1",
+        params![email],
+    )
+    .context("Failed to clear recovery codes")?;
+
+    for code in &codes {
+        conn.execute(
+            "INSERT INTO user_recovery_codes (email, code_hash) VALUES (?1, ?2)",
+            params![email, totp::hash_recovery_code(code)],
+        )
+        .context("Failed to store recovery code")?;
+    }
+
+    Ok(codes)
+}
+
+/// Turn two-factor off for `email`
+pub fn disable(conn: &Connection, email: &str) -> AppResult<()> {
+    let email = normalize_email(email);
+    conn.execute(
+        "DELETE FROM user_recovery_codes WHERE email = ?1",
+        params![email],
+    )
+    .context("Failed to remove recovery codes")?;
+    conn.execute("DELETE FROM user_totp WHERE email = ?1", params![email])
+        .context("Failed to disable two-factor")?;
+    Ok(())
+}
+
+/// Recovery codes not yet used
+pub fn recovery_codes_remaining(conn: &Connection, email: &str) -> AppResult<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM user_recovery_codes WHERE email = ?1 AND used_at IS NULL",
+        params![normalize_email(email)],
+        |row| row.get(0),
+    )
+    .context("Failed to count recovery codes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    const EMAIL: &str = "clerk.admin@judiciary.go.ke";
+    const NOW: i64 = 1_800_000_000;
+
+    fn enrolled(conn: &Connection, email: &str) -> String {
+        let secret = totp::generate_secret();
+        conn.execute(
+            "INSERT INTO user_totp (email, secret, confirmed) VALUES (?1, ?2, 1)",
+            params![email, secret],
+        )
+        .unwrap();
+        secret
+    }
+
+    fn code_at(secret: &str, now: i64) -> String {
+        totp::code_at_step(secret, totp::time_step(now as u64)).unwrap()
+    }
+
+    #[test]
+    fn wrong_codes_lock_the_email_out_with_growing_waits() {
+        let conn = db::open_in_memory().unwrap();
+        let secret = enrolled(&conn, EMAIL);
+
+        for _ in 0..LOCKOUT_THRESHOLD {
+            assert!(!verify_second_factor_at(&conn, EMAIL, "000000", NOW).unwrap());
+        }
+        // Locked: even the right code is refused until the wait is over
+        let right = code_at(&secret, NOW);
+        assert!(matches!(
+            verify_second_factor_at(&conn, EMAIL, &right, NOW + 30),
+            Err(AppError::Forbidden(_))
+        ));
+
+        let after_first = NOW + BASE_LOCKOUT_SECS;
+        assert!(!verify_second_factor_at(&conn, EMAIL, "000000", after_first).unwrap());
+        assert!(matches!(
+            verify_second_factor_at(&conn, EMAIL, "000000", after_first + BASE_LOCKOUT_SECS),
+            Err(AppError::Forbidden(_))
+        ));
+
+        let after_second = after_first + 2 * BASE_LOCKOUT_SECS;
+        let right = code_at(&secret, after_second);
+        assert!(verify_second_factor_at(&conn, EMAIL, &right, after_second).unwrap());
+        let (failures, locked_until): (i64, Option<i64>) = conn
+            .query_row(
+                "SELECT failed_attempts, locked_until FROM user_totp WHERE email = ?1",
+                params![EMAIL],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((failures, locked_until), (0, None));
+    }
+
+    #[test]
+    fn lockout_is_per_email_and_capped() {
+        let conn = db::open_in_memory().unwrap();
+        enrolled(&conn, EMAIL);
+        let other = "magistrate@judiciary.go.ke";
+        let other_secret = enrolled(&conn, other);
+        for _ in 0..LOCKOUT_THRESHOLD {
+            verify_second_factor_at(&conn, EMAIL, "000000", NOW).unwrap();
+        }
+        let right = code_at(&other_secret, NOW);
+        assert!(verify_second_factor_at(&conn, other, &right, NOW).unwrap());
+
+        assert_eq!(lockout_secs(LOCKOUT_THRESHOLD - 1), None);
+        assert_eq!(lockout_secs(LOCKOUT_THRESHOLD), Some(BASE_LOCKOUT_SECS));
+        assert_eq!(
+            lockout_secs(LOCKOUT_THRESHOLD + 100),
+            Some(MAX_LOCKOUT_SECS)
+        );
+    }
+}
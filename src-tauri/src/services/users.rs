@@ -0,0 +1,118 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppResult, ResultExt};
+
+/// Local and Firestore spell roles differently ("Court Admin", "COURT_ADMIN")
+pub fn normalize_role(role: &str) -> String {
+    role.trim().to_uppercase().replace([' ', '-'], "_")
+}
+
+/// Whether a role string (local or Firestore spelling) is an administrator role
+pub fn is_admin_role(role: &str) -> bool {
+    matches!(
+        normalize_role(role).as_str(),
+        "COURT_ADMIN" | "SUPER_ADMIN" | "ADMIN"
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+    pub user_id: i32,
+    pub name: String,
+    pub role: String,
+    pub email: String,
+    pub phone_number: Option<String>,
+    pub password_hash: String,
+    pub status: String,
+}
+
+const USER_COLUMNS: &str = "user_id, name, role, email, phone_number, password_hash, status";
+
+impl User {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(User {
+            user_id: row.get(0)?,
+            name: row.get(1)?,
+            role: row.get(2)?,
+            email: row.get(3)?,
+            phone_number: row.get(4)?,
+            password_hash: row.get(5)?,
+            status: row.get(6)?,
+        })
+    }
+}
+
+pub struct NewUser {
+    pub name: String,
+    pub role: String,
+    pub email: String,
+    pub phone_number: Option<String>,
+    pub password_hash: String,
+    pub professional_title: Option<String>,
+}
+
+pub fn list(conn: &Connection) -> AppResult<Vec<User>> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM users", USER_COLUMNS))
+        .context("Failed to prepare statement")?;
+
+    let users = stmt
+        .query_map([], User::from_row)
+        .context("Failed to fetch users")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read users")?;
+    Ok(users)
+}
+
+pub fn get(conn: &Connection, user_id: i32) -> AppResult<Option<User>> {
+    conn.query_row(
+        &format!("SELECT {} FROM users WHERE user_id = ?1", USER_COLUMNS),
+        params![user_id],
+        User::from_row,
+    )
+    .optional()
+    .context("Failed to fetch user")
+}
+
+pub fn get_by_email(conn: &Connection, email: &str) -> AppResult<Option<User>> {
+    conn.query_row(
+        &format!("SELECT {} FROM users WHERE email = ?1", USER_COLUMNS),
+        params![email],
+        User::from_row,
+    )
+    .optional()
+    .context("Failed to fetch user")
+}
+
+/// Create an active user and return its id
+pub fn create(conn: &Connection, user: &NewUser) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO users (name, role, email, phone_number, password_hash, professional_title, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'Active')",
+        params![
+            user.name,
+            user.role,
+            user.email,
+            user.phone_number,
+            user.password_hash,
+            user.professional_title
+        ],
+    )
+    .context("Failed to create user")?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_status(conn: &Connection, user_id: i32, new_status: &str) -> AppResult<()> {
+    conn.execute(
+        "UPDATE users SET status = ?1 WHERE user_id = ?2",
+        params![new_status, user_id],
+    )
+    .context("Failed to update user status")?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, user_id: i32) -> AppResult<()> {
+    conn.execute("DELETE FROM users WHERE user_id = ?1", params![user_id])
+        .context("Failed to delete user")?;
+    Ok(())
+}
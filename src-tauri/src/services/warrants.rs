@@ -0,0 +1,739 @@
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::hearings::parse_date;
+use crate::services::offenders::{self, HistoryDetails};
+
+// ============================================================================
+// Warrants of arrest
+//
+// Bench warrants and warrants of arrest (migration 0011), each issued
+// against an offender, usually in a case, for a police station to execute.
+// A warrant is outstanding until it is executed or lifted. Execution marks
+// the offender arrested, fills in their arrest details and adds an entry to
+// their history.
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WarrantType {
+    /// For an accused who failed to attend court
+    #[default]
+    #[serde(rename = "Bench Warrant")]
+    BenchWarrant,
+    #[serde(rename = "Warrant of Arrest")]
+    WarrantOfArrest,
+}
+
+impl WarrantType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarrantType::BenchWarrant => "Bench Warrant",
+            WarrantType::WarrantOfArrest => "Warrant of Arrest",
+        }
+    }
+
+    pub fn parse(warrant_type: &str) -> Option<WarrantType> {
+        match warrant_type {
+            "Bench Warrant" => Some(WarrantType::BenchWarrant),
+            "Warrant of Arrest" => Some(WarrantType::WarrantOfArrest),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarrantStatus {
+    Issued,
+    Executed,
+    Lifted,
+}
+
+impl WarrantStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarrantStatus::Issued => "Issued",
+            WarrantStatus::Executed => "Executed",
+            WarrantStatus::Lifted => "Lifted",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<WarrantStatus> {
+        match status {
+            "Issued" => Some(WarrantStatus::Issued),
+            "Executed" => Some(WarrantStatus::Executed),
+            "Lifted" => Some(WarrantStatus::Lifted),
+            _ => None,
+        }
+    }
+}
+
+/// Offender status once a warrant against them is executed
+pub(crate) const ARRESTED: &str = "Arrested";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Warrant {
+    pub warrant_id: i64,
+    pub warrant_number: String,
+    pub warrant_type: WarrantType,
+    /// None once the offender record has been deleted
+    pub offender_id: Option<i64>,
+    pub offender_name: Option<String>,
+    pub case_id: Option<i64>,
+    pub case_number: Option<String>,
+    pub issued_on: String,
+    pub issued_by: String,
+    pub executing_station: Option<String>,
+    pub reason: Option<String>,
+    pub status: WarrantStatus,
+    pub executed_on: Option<String>,
+    pub executed_by: Option<String>,
+    pub place_of_arrest: Option<String>,
+    pub lifted_on: Option<String>,
+    pub lift_reason: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const WARRANT_SELECT: &str = "SELECT w.warrant_id, w.warrant_number, w.warrant_type,
+        w.offender_id, COALESCE(o.full_name, w.offender_name), w.case_id, c.case_number,
+        w.issued_on, w.issued_by, w.executing_station, w.reason, w.status, w.executed_on, w.executed_by,
+        w.place_of_arrest, w.lifted_on, w.lift_reason, w.notes, w.created_by, w.created_at
+    FROM warrants w
+    LEFT JOIN offenders o ON o.offender_id = w.offender_id
+    LEFT JOIN cases c ON c.case_id = w.case_id";
+
+impl Warrant {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let warrant_type: String = row.get(2)?;
+        let status: String = row.get(11)?;
+        Ok(Warrant {
+            warrant_id: row.get(0)?,
+            warrant_number: row.get(1)?,
+            warrant_type: WarrantType::parse(&warrant_type).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    format!("unknown warrant type {}", warrant_type).into(),
+                )
+            })?,
+            offender_id: row.get(3)?,
+            offender_name: row.get(4)?,
+            case_id: row.get(5)?,
+            case_number: row.get(6)?,
+            issued_on: row.get(7)?,
+            issued_by: row.get(8)?,
+            executing_station: row.get(9)?,
+            reason: row.get(10)?,
+            status: WarrantStatus::parse(&status).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    11,
+                    rusqlite::types::Type::Text,
+                    format!("unknown warrant status {}", status).into(),
+                )
+            })?,
+            executed_on: row.get(12)?,
+            executed_by: row.get(13)?,
+            place_of_arrest: row.get(14)?,
+            lifted_on: row.get(15)?,
+            lift_reason: row.get(16)?,
+            notes: row.get(17)?,
+            created_by: row.get(18)?,
+            created_at: row.get(19)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewWarrant {
+    /// As written on the warrant; the next `WA-<year>-<number>` if not given
+    pub warrant_number: Option<String>,
+    #[serde(default)]
+    pub warrant_type: WarrantType,
+    pub offender_id: i64,
+    pub case_id: Option<i64>,
+    /// YYYY-MM-DD, today if not given
+    pub issued_on: Option<String>,
+    pub issued_by: String,
+    pub executing_station: Option<String>,
+    /// e.g. failed to attend the mention of 3 March
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Execution {
+    /// YYYY-MM-DD, today if not given
+    pub executed_on: Option<String>,
+    /// The arresting officer
+    pub executed_by: Option<String>,
+    pub place_of_arrest: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WarrantFilter {
+    pub status: Option<WarrantStatus>,
+    pub offender_id: Option<i64>,
+    pub case_id: Option<i64>,
+    pub executing_station: Option<String>,
+}
+
+/// A warrant not yet executed or lifted, and how long it has been out
+#[derive(Debug, Clone, Serialize)]
+pub struct OutstandingWarrant {
+    #[serde(flatten)]
+    pub warrant: Warrant,
+    pub days_outstanding: i64,
+}
+
+fn today() -> String {
+    Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+fn optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn optional_date(field: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_date(field, v))
+        .transpose()
+}
+
+pub fn get(conn: &Connection, warrant_id: i64) -> AppResult<Warrant> {
+    conn.query_row(
+        &format!("{} WHERE w.warrant_id = ?1", WARRANT_SELECT),
+        params![warrant_id],
+        Warrant::from_row,
+    )
+    .optional()
+    .context("Failed to fetch warrant")?
+    .ok_or_else(|| AppError::not_found("Warrant not found"))
+}
+
+fn get_outstanding(conn: &Connection, warrant_id: i64) -> AppResult<Warrant> {
+    let warrant = get(conn, warrant_id)?;
+    let closed_on = match warrant.status {
+        WarrantStatus::Issued => return Ok(warrant),
+        WarrantStatus::Executed => warrant.executed_on.as_deref(),
+        WarrantStatus::Lifted => warrant.lifted_on.as_deref(),
+    };
+    Err(AppError::Conflict(format!(
+        "The warrant was {} on {}.",
+        warrant.status.as_str().to_lowercase(),
+        closed_on.unwrap_or("an earlier date")
+    )))
+}
+
+/// The warrant number after the last one issued in `year`
+fn next_warrant_number(conn: &Connection, year: &str) -> AppResult<String> {
+    let prefix = format!("WA-{}-", year);
+    let last: Option<i64> = conn
+        .query_row(
+            "SELECT MAX(CAST(substr(warrant_number, ?2) AS INTEGER)) FROM warrants
+             WHERE warrant_number LIKE ?1 || '%'",
+            params![prefix, prefix.len() as i64 + 1],
+            |row| row.get(0),
+        )
+        .context("Failed to number warrant")?;
+    Ok(format!("{}{:06}", prefix, last.unwrap_or(0) + 1))
+}
+
+pub fn issue(
+    conn: &Connection,
+    warrant: NewWarrant,
+    created_by: Option<&str>,
+) -> AppResult<Warrant> {
+    let issued_by = warrant.issued_by.trim();
+    if issued_by.is_empty() {
+        return Err(AppError::field(
+            "issued_by",
+            "Enter the officer who issued the warrant",
+        ));
+    }
+    let issued_on = optional_date("issued_on", warrant.issued_on.as_deref())?.unwrap_or_else(today);
+    if issued_on > today() {
+        return Err(AppError::field(
+            "issued_on",
+            "A warrant cannot be issued in the future",
+        ));
+    }
+    let offender: Option<i64> = conn
+        .query_row(
+            "SELECT offender_id FROM offenders WHERE offender_id = ?1",
+            params![warrant.offender_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to fetch offender")?;
+    if offender.is_none() {
+        return Err(AppError::not_found("Offender not found"));
+    }
+    if let Some(case_id) = warrant.case_id {
+        let case: Option<i64> = conn
+            .query_row(
+                "SELECT case_id FROM cases WHERE case_id = ?1 AND COALESCE(is_deleted, 0) = 0",
+                params![case_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to fetch case")?;
+        if case.is_none() {
+            return Err(AppError::not_found("Case not found"));
+        }
+    }
+    let warrant_number = match optional_text(warrant.warrant_number) {
+        Some(number) => {
+            let taken: Option<i64> = conn
+                .query_row(
+                    "SELECT warrant_id FROM warrants WHERE warrant_number = ?1",
+                    params![number],
+                    |row| row.get(0),
+                )
+                .optional()
+                .context("Failed to check warrant number")?;
+            if taken.is_some() {
+                return Err(AppError::Conflict(format!(
+                    "Warrant {} is already recorded.",
+                    number
+                )));
+            }
+            number
+        }
+        None => next_warrant_number(conn, &issued_on[..4])?,
+    };
+
+    conn.execute(
+        "INSERT INTO warrants (warrant_number, warrant_type, offender_id, offender_name,
+             case_id, issued_on, issued_by, executing_station, reason, notes, created_by)
+         VALUES (?1, ?2, ?3, (SELECT full_name FROM offenders WHERE offender_id = ?3), ?4,
+             ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            warrant_number,
+            warrant.warrant_type.as_str(),
+            warrant.offender_id,
+            warrant.case_id,
+            issued_on,
+            issued_by,
+            optional_text(warrant.executing_station),
+            optional_text(warrant.reason),
+            optional_text(warrant.notes),
+            created_by
+        ],
+    )
+    .context("Failed to record warrant")?;
+    get(conn, conn.last_insert_rowid())
+}
+
+/// Record the arrest. The offender is marked arrested with the arrest
+/// details, and the arrest goes into their history.
+pub fn execute(conn: &mut Connection, warrant_id: i64, execution: Execution) -> AppResult<Warrant> {
+    let executed_on =
+        optional_date("executed_on", execution.executed_on.as_deref())?.unwrap_or_else(today);
+    let executed_by = optional_text(execution.executed_by);
+    let place_of_arrest = optional_text(execution.place_of_arrest);
+
+    let tx = conn.transaction()?;
+    let warrant = get_outstanding(&tx, warrant_id)?;
+    if executed_on < warrant.issued_on {
+        return Err(AppError::field(
+            "executed_on",
+            "A warrant cannot be executed before it was issued",
+        ));
+    }
+    tx.execute(
+        "UPDATE warrants SET status = 'Executed', executed_on = ?2, executed_by = ?3,
+             place_of_arrest = ?4, notes = COALESCE(?5, notes),
+             last_modified = CURRENT_TIMESTAMP
+         WHERE warrant_id = ?1",
+        params![
+            warrant_id,
+            executed_on,
+            executed_by,
+            place_of_arrest,
+            optional_text(execution.notes)
+        ],
+    )
+    .context("Failed to execute warrant")?;
+    // Nobody to mark arrested once the offender record is gone
+    if let Some(offender_id) = warrant.offender_id {
+        tx.execute(
+            "UPDATE offenders SET status = ?2, arrest_date = ?3,
+                 arresting_officer = COALESCE(?4, arresting_officer),
+                 place_of_arrest = COALESCE(?5, place_of_arrest),
+                 sync_status = 'pending', has_changes = 1, last_modified = CURRENT_TIMESTAMP
+             WHERE offender_id = ?1",
+            params![
+                offender_id,
+                ARRESTED,
+                executed_on,
+                executed_by,
+                place_of_arrest
+            ],
+        )
+        .context("Failed to update offender")?;
+
+        // The arrest date goes in the note; `offense_date` is when the offence was committed
+        let mut note = format!(
+            "Arrested on {} on {} {} issued on {} by {}",
+            executed_on,
+            warrant.warrant_type.as_str().to_lowercase(),
+            warrant.warrant_number,
+            warrant.issued_on,
+            warrant.issued_by
+        );
+        if let Some(officer) = &executed_by {
+            note.push_str(&format!("; executed by {}", officer));
+        }
+        if let Some(place) = &place_of_arrest {
+            note.push_str(&format!(" at {}", place));
+        }
+        offenders::add_history(
+            &tx,
+            HistoryDetails {
+                offender_id: Some(offender_id),
+                file_id: None,
+                case_id: warrant.case_id,
+                offense_date: None,
+                penalty: None,
+                penalty_notes: None,
+                notes: Some(note),
+                catalogue_id: None,
+            },
+        )?;
+    }
+    let executed = get(&tx, warrant_id)?;
+    tx.commit()?;
+    Ok(executed)
+}
+
+/// Cancel an outstanding warrant on the court's order
+pub fn lift(
+    conn: &Connection,
+    warrant_id: i64,
+    lifted_on: Option<&str>,
+    reason: &str,
+) -> AppResult<Warrant> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::field(
+            "reason",
+            "Give the reason the warrant is lifted",
+        ));
+    }
+    let lifted_on = optional_date("lifted_on", lifted_on)?.unwrap_or_else(today);
+    let warrant = get_outstanding(conn, warrant_id)?;
+    if lifted_on < warrant.issued_on {
+        return Err(AppError::field(
+            "lifted_on",
+            "A warrant cannot be lifted before it was issued",
+        ));
+    }
+    conn.execute(
+        "UPDATE warrants SET status = 'Lifted', lifted_on = ?2, lift_reason = ?3,
+             last_modified = CURRENT_TIMESTAMP
+         WHERE warrant_id = ?1",
+        params![warrant_id, lifted_on, reason],
+    )
+    .context("Failed to lift warrant")?;
+    get(conn, warrant_id)
+}
+
+pub fn list(conn: &Connection, filter: &WarrantFilter) -> AppResult<Vec<Warrant>> {
+    let station = filter
+        .executing_station
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR w.status = ?1)
+                 AND (?2 IS NULL OR w.offender_id = ?2)
+                 AND (?3 IS NULL OR w.case_id = ?3)
+                 AND (?4 IS NULL OR w.executing_station = ?4 COLLATE NOCASE)
+             ORDER BY w.issued_on DESC, w.warrant_id DESC",
+            WARRANT_SELECT
+        ))
+        .context("Failed to prepare statement")?;
+    let warrants = stmt
+        .query_map(
+            params![
+                filter.status.map(|s| s.as_str()),
+                filter.offender_id,
+                filter.case_id,
+                station
+            ],
+            Warrant::from_row,
+        )
+        .context("Failed to fetch warrants")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read warrants")?;
+    Ok(warrants)
+}
+
+/// Every warrant still to be executed, oldest first, optionally for one
+/// station
+pub fn outstanding(
+    conn: &Connection,
+    executing_station: Option<&str>,
+) -> AppResult<Vec<OutstandingWarrant>> {
+    let mut warrants = list(
+        conn,
+        &WarrantFilter {
+            status: Some(WarrantStatus::Issued),
+            executing_station: executing_station.map(str::to_string),
+            ..Default::default()
+        },
+    )?;
+    warrants.reverse();
+
+    let today = Local::now().date_naive();
+    Ok(warrants
+        .into_iter()
+        .map(|warrant| {
+            let days_outstanding = NaiveDate::parse_from_str(&warrant.issued_on, "%Y-%m-%d")
+                .map(|issued| (today - issued).num_days())
+                .unwrap_or(0);
+            OutstandingWarrant {
+                warrant,
+                days_outstanding,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn bench_warrant(offender_id: i64) -> NewWarrant {
+        NewWarrant {
+            warrant_number: None,
+            warrant_type: WarrantType::default(),
+            offender_id,
+            case_id: None,
+            issued_on: Some("2026-03-03".to_string()),
+            issued_by: "Hon. A. Otieno".to_string(),
+            executing_station: Some("Machakos Police Station".to_string()),
+            reason: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn book_outlives_the_offender_record() {
+        let mut conn = db::open_in_memory().unwrap();
+        conn.execute(
+            "INSERT INTO offenders (offender_id, full_name) VALUES (1, 'Otieno Odhiambo')",
+            [],
+        )
+        .unwrap();
+        let warrant = issue(&conn, bench_warrant(1), None).unwrap();
+
+        conn.execute("DELETE FROM offenders WHERE offender_id = 1", [])
+            .unwrap();
+
+        let kept = get(&conn, warrant.warrant_id).unwrap();
+        assert_eq!(kept.offender_id, None);
+        assert_eq!(kept.offender_name.as_deref(), Some("Otieno Odhiambo"));
+        let executed = execute(
+            &mut conn,
+            warrant.warrant_id,
+            Execution {
+                executed_on: Some("2026-03-10".to_string()),
+                executed_by: None,
+                place_of_arrest: None,
+                notes: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(executed.status, WarrantStatus::Executed);
+    }
+
+    fn offender(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO offenders (offender_id, full_name, arresting_officer)
+             VALUES (1, 'Otieno Odhiambo', 'PC Kiprop')",
+            [],
+        )
+        .unwrap();
+    }
+
+    fn arrest(day: &str) -> Execution {
+        Execution {
+            executed_on: Some(day.to_string()),
+            executed_by: Some("Cpl. Mwangi".to_string()),
+            place_of_arrest: Some("Kilungu market".to_string()),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn numbers_run_on_within_the_year_and_are_never_reused() {
+        let conn = db::open_in_memory().unwrap();
+        offender(&conn);
+        let first = issue(&conn, bench_warrant(1), None).unwrap();
+        let second = issue(&conn, bench_warrant(1), None).unwrap();
+        assert_eq!(first.warrant_number, "WA-2026-000001");
+        assert_eq!(second.warrant_number, "WA-2026-000002");
+
+        let typed = NewWarrant {
+            warrant_number: Some(" WA-2026-000041 ".to_string()),
+            ..bench_warrant(1)
+        };
+        assert_eq!(
+            issue(&conn, typed.clone(), None).unwrap().warrant_number,
+            "WA-2026-000041"
+        );
+        assert!(matches!(
+            issue(&conn, typed, None),
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(
+            issue(&conn, bench_warrant(1), None).unwrap().warrant_number,
+            "WA-2026-000042"
+        );
+        let last_year = NewWarrant {
+            issued_on: Some("2025-12-30".to_string()),
+            ..bench_warrant(1)
+        };
+        assert_eq!(
+            issue(&conn, last_year, None).unwrap().warrant_number,
+            "WA-2025-000001"
+        );
+    }
+
+    #[test]
+    fn issuing_is_checked() {
+        let conn = db::open_in_memory().unwrap();
+        offender(&conn);
+        let unsigned = NewWarrant {
+            issued_by: " ".to_string(),
+            ..bench_warrant(1)
+        };
+        assert!(matches!(
+            issue(&conn, unsigned, None),
+            Err(AppError::Validation { .. })
+        ));
+        let future = NewWarrant {
+            issued_on: Some("2999-01-01".to_string()),
+            ..bench_warrant(1)
+        };
+        assert!(matches!(
+            issue(&conn, future, None),
+            Err(AppError::Validation { .. })
+        ));
+        assert!(matches!(
+            issue(&conn, bench_warrant(2), None),
+            Err(AppError::NotFound(_))
+        ));
+        let no_case = NewWarrant {
+            case_id: Some(9),
+            ..bench_warrant(1)
+        };
+        assert!(matches!(
+            issue(&conn, no_case, None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn execution_marks_the_offender_arrested_and_adds_to_their_history() {
+        let mut conn = db::open_in_memory().unwrap();
+        offender(&conn);
+        let warrant = issue(&conn, bench_warrant(1), None).unwrap();
+        assert!(matches!(
+            execute(&mut conn, warrant.warrant_id, arrest("2026-03-02")),
+            Err(AppError::Validation { .. })
+        ));
+
+        let executed = execute(&mut conn, warrant.warrant_id, arrest("2026-03-10")).unwrap();
+        assert_eq!(executed.executed_by.as_deref(), Some("Cpl. Mwangi"));
+        let (status, arrested_on, officer): (String, String, String) = conn
+            .query_row(
+                "SELECT status, arrest_date, arresting_officer FROM offenders
+                 WHERE offender_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (status.as_str(), arrested_on.as_str(), officer.as_str()),
+            (ARRESTED, "2026-03-10", "Cpl. Mwangi")
+        );
+        let note: String = conn
+            .query_row(
+                "SELECT notes FROM offender_history WHERE offender_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            note,
+            "Arrested on 2026-03-10 on bench warrant WA-2026-000001 issued on 2026-03-03 \
+             by Hon. A. Otieno; executed by Cpl. Mwangi at Kilungu market"
+        );
+
+        let again = execute(&mut conn, warrant.warrant_id, arrest("2026-03-11")).unwrap_err();
+        assert_eq!(again.message(), "The warrant was executed on 2026-03-10.");
+        assert!(matches!(
+            lift(&conn, warrant.warrant_id, None, "Attended court"),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn only_warrants_still_out_are_outstanding() {
+        let mut conn = db::open_in_memory().unwrap();
+        offender(&conn);
+        let older = NewWarrant {
+            issued_on: Some("2026-01-05".to_string()),
+            executing_station: Some("Kilungu Police Station".to_string()),
+            ..bench_warrant(1)
+        };
+        let older = issue(&conn, older, None).unwrap();
+        let newer = issue(&conn, bench_warrant(1), None).unwrap();
+        let lifted = issue(&conn, bench_warrant(1), None).unwrap();
+        let executed = issue(&conn, bench_warrant(1), None).unwrap();
+
+        assert!(matches!(
+            lift(&conn, lifted.warrant_id, None, "  "),
+            Err(AppError::Validation { .. })
+        ));
+        lift(
+            &conn,
+            lifted.warrant_id,
+            Some("2026-03-04"),
+            "Attended court",
+        )
+        .unwrap();
+        execute(&mut conn, executed.warrant_id, arrest("2026-03-04")).unwrap();
+
+        let ids: Vec<_> = outstanding(&conn, None)
+            .unwrap()
+            .iter()
+            .map(|o| o.warrant.warrant_id)
+            .collect();
+        assert_eq!(ids, vec![older.warrant_id, newer.warrant_id]);
+        let kilungu = outstanding(&conn, Some("kilungu police station")).unwrap();
+        assert_eq!(kilungu.len(), 1);
+        assert!(kilungu[0].days_outstanding > 50);
+        let closed = list(
+            &conn,
+            &WarrantFilter {
+                status: Some(WarrantStatus::Lifted),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(closed[0].lift_reason.as_deref(), Some("Attended court"));
+    }
+}
@@ -1,45 +1,49 @@
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, RwLock};
 
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
 use crate::config::firebase::{
-    CollectionSelector, Filter, FirestoreClient,
+    CollectionSelector, Filter, FirestoreClient, FirestoreDocument,
     FirestoreValue, OrderBy, StructuredQuery, extract_document_id,
 };
+use crate::db::pool::Database;
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::services::offender_cases::CaseRole;
 
 pub struct SyncEngine {
     firestore: FirestoreClient,
-    conn: Arc<Mutex<Connection>>,
+    db: Database,
     court_id: Arc<RwLock<Option<String>>>,
 }
 
 impl SyncEngine {
     pub fn new(
         firestore: FirestoreClient,
-        conn: Arc<Mutex<Connection>>,
+        db: Database,
         court_id: Arc<RwLock<Option<String>>>,
     ) -> Self {
         SyncEngine {
             firestore,
-            conn,
+            db,
             court_id,
         }
     }
 
-    pub async fn sync_all(&self) -> Result<String, String> {
+    pub async fn sync_all(&self) -> AppResult<String> {
         let court_id = {
-            let guard = self.court_id.read().map_err(|e| e.to_string())?;
-            guard
-                .clone()
-                .ok_or_else(|| "No court_id set. Please log in first.".to_string())?
+            let guard = self.court_id.read()?;
+            guard.clone().ok_or_else(|| {
+                AppError::Unauthenticated("No court_id set. Please log in first.".to_string())
+            })?
         };
 
-        let last_sync = self.get_last_sync_time();
+        let last_sync = self.get_last_sync_time().await?;
 
         // Phase 1: Push local pending changes to Firestore
         let pushed_offenders = self.push_pending_offenders(&court_id).await?;
         let pushed_cases = self.push_pending_cases(&court_id).await?;
+        let pushed_links = self.push_pending_offender_cases(&court_id).await?;
 
         // Phase 2: Pull remote changes from Firestore
         let pulled_offenders = self
@@ -48,34 +52,48 @@ impl SyncEngine {
         let pulled_cases = self
             .pull_remote_cases(&court_id, &last_sync)
             .await?;
+        let pulled_links = self
+            .pull_remote_offender_cases(&court_id, &last_sync)
+            .await?;
 
         // Phase 3: Update last sync time
-        self.update_last_sync_time()?;
+        self.update_last_sync_time().await?;
 
         Ok(format!(
-            "Pushed: {} offenders, {} cases. Pulled: {} offenders, {} cases.",
-            pushed_offenders, pushed_cases, pulled_offenders, pulled_cases
+            "Pushed: {} offenders, {} cases, {} case links. \
+             Pulled: {} offenders, {} cases, {} case links.",
+            pushed_offenders, pushed_cases, pushed_links,
+            pulled_offenders, pulled_cases, pulled_links
         ))
     }
 
-    fn get_last_sync_time(&self) -> String {
-        let conn = self.conn.lock().unwrap();
-        conn.query_row(
-            "SELECT last_sync FROM sync_metadata WHERE id = 1",
-            [],
-            |row| row.get::<_, String>(0),
-        )
-        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+    async fn get_last_sync_time(&self) -> AppResult<String> {
+        self.db
+            .read(|conn| {
+                Ok(conn
+                    .query_row(
+                        "SELECT last_sync FROM sync_metadata WHERE id = 1",
+                        [],
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()
+                    .context("Failed to read last sync time")?
+                    .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string()))
+            })
+            .await
     }
 
-    fn update_last_sync_time(&self) -> Result<(), String> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT OR REPLACE INTO sync_metadata (id, last_sync) VALUES (1, ?)",
-            params![Utc::now().to_rfc3339()],
-        )
-        .map_err(|e| format!("Failed to update sync time: {}", e))?;
-        Ok(())
+    async fn update_last_sync_time(&self) -> AppResult<()> {
+        self.db
+            .write(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO sync_metadata (id, last_sync) VALUES (1, ?)",
+                    params![Utc::now().to_rfc3339()],
+                )
+                .context("Failed to update sync time")?;
+                Ok(())
+            })
+            .await
     }
 
     // ========================================================================
@@ -85,9 +103,8 @@ impl SyncEngine {
     async fn push_pending_offenders(
         &self,
         court_id: &str,
-    ) -> Result<usize, String> {
-        let pending = {
-            let conn = self.conn.lock().unwrap();
+    ) -> AppResult<usize> {
+        let pending = self.db.read(|conn| {
             let mut stmt = conn
                 .prepare(
                     "SELECT offender_id, firestore_id, full_name, national_id, date_of_birth,
@@ -103,7 +120,7 @@ impl SyncEngine {
                      FROM offenders
                      WHERE sync_status = 'pending' OR is_new = 1 OR has_changes = 1",
                 )
-                .map_err(|e| format!("Failed to query pending offenders: {}", e))?;
+                .context("Failed to query pending offenders")?;
 
             let rows = stmt
                 .query_map([], |row| {
@@ -153,11 +170,12 @@ impl SyncEngine {
                         court_id: row.get(42)?,
                     })
                 })
-                .map_err(|e| format!("Failed to map offender rows: {}", e))?
+                .context("Failed to map offender rows")?
                 .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| format!("Failed to collect offender rows: {}", e))?;
-            rows
-        };
+                .context("Failed to collect offender rows")?;
+            Ok(rows)
+        })
+        .await?;
 
         let count = pending.len();
 
@@ -169,12 +187,17 @@ impl SyncEngine {
                 None => {
                     let new_id = uuid::Uuid::new_v4().to_string();
                     // Update local record with generated firestore_id
-                    let conn = self.conn.lock().unwrap();
-                    conn.execute(
-                        "UPDATE offenders SET firestore_id = ? WHERE offender_id = ?",
-                        params![new_id, offender.offender_id],
-                    )
-                    .map_err(|e| format!("Failed to set firestore_id: {}", e))?;
+                    let (id, offender_id) = (new_id.clone(), offender.offender_id);
+                    self.db
+                        .write(move |conn| {
+                            conn.execute(
+                                "UPDATE offenders SET firestore_id = ? WHERE offender_id = ?",
+                                params![id, offender_id],
+                            )
+                            .context("Failed to set firestore_id")?;
+                            Ok(())
+                        })
+                        .await?;
                     new_id
                 }
             };
@@ -184,19 +207,26 @@ impl SyncEngine {
 
             if offender.is_deleted.unwrap_or(false) {
                 // Soft-deleted: delete from Firestore, then purge locally
-                match self.firestore.delete_document(&doc_path).await {
-                    Ok(_) => {
-                        let conn = self.conn.lock().unwrap();
+                self.firestore
+                    .delete_document(&doc_path)
+                    .await
+                    .map_err(|e| {
+                        e.context(&format!(
+                            "Failed to delete offender {} from Firestore",
+                            firestore_id
+                        ))
+                    })?;
+                let offender_id = offender.offender_id;
+                self.db
+                    .write(move |conn| {
                         conn.execute(
                             "DELETE FROM offenders WHERE offender_id = ?",
-                            params![offender.offender_id],
+                            params![offender_id],
                         )
-                        .map_err(|e| format!("Failed to purge deleted offender: {}", e))?;
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to delete offender from Firestore: {}", e);
-                    }
-                }
+                        .context("Failed to purge deleted offender")?;
+                        Ok(())
+                    })
+                    .await?;
             } else {
                 // Create or update
                 let result = self
@@ -212,20 +242,25 @@ impl SyncEngine {
                             .create_document(&collection, &firestore_id, &fields)
                             .await
                             .map_err(|e| {
-                                format!("Failed to push offender {}: {}", firestore_id, e)
+                                e.context(&format!("Failed to push offender {}", firestore_id))
                             })?;
                     }
                 }
 
                 // Mark as synced
-                let conn = self.conn.lock().unwrap();
-                conn.execute(
-                    "UPDATE offenders SET sync_status = 'synced', is_new = 0, has_changes = 0,
-                     last_synced_at = strftime('%s','now'), version = COALESCE(version, 0) + 1
-                     WHERE offender_id = ?",
-                    params![offender.offender_id],
-                )
-                .map_err(|e| format!("Failed to mark offender as synced: {}", e))?;
+                let offender_id = offender.offender_id;
+                self.db
+                    .write(move |conn| {
+                        conn.execute(
+                            "UPDATE offenders SET sync_status = 'synced', is_new = 0, has_changes = 0,
+                             last_synced_at = strftime('%s','now'), version = COALESCE(version, 0) + 1
+                             WHERE offender_id = ?",
+                            params![offender_id],
+                        )
+                        .context("Failed to mark offender as synced")?;
+                        Ok(())
+                    })
+                    .await?;
             }
         }
 
@@ -236,7 +271,7 @@ impl SyncEngine {
         &self,
         court_id: &str,
         last_sync: &str,
-    ) -> Result<usize, String> {
+    ) -> AppResult<usize> {
         let parent = format!("courts/{}", court_id);
 
         let query = StructuredQuery {
@@ -257,159 +292,31 @@ impl SyncEngine {
             .firestore
             .run_query(&parent, query)
             .await
-            .unwrap_or_else(|e| {
-                eprintln!("Failed to query remote offenders: {}", e);
-                vec![]
-            });
+            .context("Failed to query remote offenders")?;
 
         let count = remote_docs.len();
-        let conn = self.conn.lock().unwrap();
-
-        for doc in &remote_docs {
-            let firestore_id = extract_document_id(&doc.name);
-            if firestore_id.is_empty() {
-                continue;
-            }
-
-            let fields = &doc.fields;
-
-            // Check if exists locally
-            let local_sync_status: Option<String> = conn
-                .query_row(
-                    "SELECT sync_status FROM offenders WHERE firestore_id = ?",
-                    params![firestore_id],
-                    |row| row.get(0),
-                )
-                .ok();
-
-            // Skip if local has pending changes (conflict - local wins for now)
-            if local_sync_status.as_deref() == Some("pending") {
-                continue;
-            }
-
-            let full_name = get_str(fields, "fullName").unwrap_or_default();
-            let national_id = get_str(fields, "nationalId");
-            let date_of_birth = get_str(fields, "dateOfBirth");
-            let gender = get_str(fields, "gender");
-            let notes = get_str(fields, "notes");
-            let penalty = get_str(fields, "penalty");
-            let penalty_notes = get_str(fields, "penaltyNotes");
-            let alias = get_str(fields, "alias");
-            let nationality = get_str(fields, "nationality");
-            let marital_status = get_str(fields, "maritalStatus");
-            let occupation = get_str(fields, "occupation");
-            let address = get_str(fields, "address");
-            let first_offender = get_bool(fields, "firstOffender");
-            let criminal_history = get_str(fields, "criminalHistory");
-            let known_associates = get_str(fields, "knownAssociates");
-            let arresting_officer = get_str(fields, "arrestingOfficer");
-            let place_of_arrest = get_str(fields, "placeOfArrest");
-            let arrest_date = get_str(fields, "arrestDate");
-            let case_number = get_str(fields, "caseNumber");
-            let eye_color = get_str(fields, "eyeColor");
-            let hair_color = get_str(fields, "hairColor");
-            let phone_number = get_str(fields, "phoneNumber");
-            let emergency_contact_name = get_str(fields, "emergencyContactName");
-            let emergency_contact_phone = get_str(fields, "emergencyContactPhone");
-            let emergency_contact_relationship = get_str(fields, "emergencyContactRelationship");
-            let legal_representation = get_str(fields, "legalRepresentation");
-            let medical_conditions = get_str(fields, "medicalConditions");
-            let risk_level = get_str(fields, "riskLevel");
-            let distinguishing_marks = get_str(fields, "distinguishingMarks");
-            let offender_type = get_str(fields, "type");
-            let status = get_str(fields, "status");
-            let facility = get_str(fields, "facility");
-            let offense_type = get_str(fields, "offenseType");
-            let uploaded_by_id = get_str(fields, "uploadedById");
-            let photo_storage_url = get_str(fields, "photoStorageUrl");
-            let is_deleted = get_bool(fields, "isDeleted").unwrap_or(false);
-            let version = get_int(fields, "version").unwrap_or(1);
-
-            if is_deleted {
-                // Remote was deleted - delete locally too
-                conn.execute(
-                    "DELETE FROM offenders WHERE firestore_id = ?",
-                    params![firestore_id],
-                )
-                .ok();
-                continue;
-            }
-
-            conn.execute(
-                "INSERT INTO offenders (
-                    firestore_id, court_id, full_name, national_id, date_of_birth, gender,
-                    notes, penalty, penalty_notes, alias, nationality, marital_status,
-                    occupation, address, first_offender, criminal_history, known_associates,
-                    arresting_officer, place_of_arrest, arrest_date, case_number,
-                    eye_color, hair_color, phone_number,
-                    emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
-                    legal_representation, medical_conditions, risk_level, distinguishing_marks,
-                    type, status, facility, offense_type, uploaded_by_id, photo_storage_url,
-                    is_deleted, version, sync_status, is_new, has_changes,
-                    last_synced_at, date_created
-                ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
-                    ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32,
-                    ?33, ?34, ?35, ?36, ?37, ?38, ?39, 'synced', 0, 0, strftime('%s','now'),
-                    datetime('now')
-                )
-                ON CONFLICT(firestore_id) DO UPDATE SET
-                    full_name = excluded.full_name,
-                    national_id = excluded.national_id,
-                    date_of_birth = excluded.date_of_birth,
-                    gender = excluded.gender,
-                    notes = excluded.notes,
-                    penalty = excluded.penalty,
-                    penalty_notes = excluded.penalty_notes,
-                    alias = excluded.alias,
-                    nationality = excluded.nationality,
-                    marital_status = excluded.marital_status,
-                    occupation = excluded.occupation,
-                    address = excluded.address,
-                    first_offender = excluded.first_offender,
-                    criminal_history = excluded.criminal_history,
-                    known_associates = excluded.known_associates,
-                    arresting_officer = excluded.arresting_officer,
-                    place_of_arrest = excluded.place_of_arrest,
-                    arrest_date = excluded.arrest_date,
-                    case_number = excluded.case_number,
-                    eye_color = excluded.eye_color,
-                    hair_color = excluded.hair_color,
-                    phone_number = excluded.phone_number,
-                    emergency_contact_name = excluded.emergency_contact_name,
-                    emergency_contact_phone = excluded.emergency_contact_phone,
-                    emergency_contact_relationship = excluded.emergency_contact_relationship,
-                    legal_representation = excluded.legal_representation,
-                    medical_conditions = excluded.medical_conditions,
-                    risk_level = excluded.risk_level,
-                    distinguishing_marks = excluded.distinguishing_marks,
-                    type = excluded.type,
-                    status = excluded.status,
-                    facility = excluded.facility,
-                    offense_type = excluded.offense_type,
-                    uploaded_by_id = excluded.uploaded_by_id,
-                    photo_storage_url = excluded.photo_storage_url,
-                    version = excluded.version,
-                    sync_status = 'synced',
-                    is_new = 0,
-                    has_changes = 0,
-                    last_synced_at = strftime('%s','now')",
-                params![
-                    firestore_id, court_id, full_name, national_id, date_of_birth, gender,
-                    notes, penalty, penalty_notes, alias, nationality, marital_status,
-                    occupation, address, first_offender, criminal_history, known_associates,
-                    arresting_officer, place_of_arrest, arrest_date, case_number,
-                    eye_color, hair_color, phone_number,
-                    emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
-                    legal_representation, medical_conditions, risk_level, distinguishing_marks,
-                    offender_type, status, facility, offense_type, uploaded_by_id, photo_storage_url,
-                    is_deleted, version
-                ],
-            )
-            .map_err(|e| format!("Failed to upsert remote offender: {}", e))?;
-        }
+        let court_id = court_id.to_string();
+
+        self.db
+            .write(move |conn| {
+                let mut skipped = 0;
+                for doc in &remote_docs {
+                    // One bad document must not hold back the rest of the
+                    // pass; its partial writes are rolled back with the
+                    // savepoint
+                    let savepoint = conn.savepoint()?;
+                    match apply_remote_offender(&savepoint, doc, &court_id) {
+                        Ok(()) => savepoint.commit()?,
+                        Err(e) => {
+                            eprintln!("Skipping remote offender {}: {}", doc.name, e);
+                            skipped += 1;
+                        }
+                    }
+                }
 
-        Ok(count)
+                Ok(count - skipped)
+            })
+            .await
     }
 
     // ========================================================================
@@ -419,9 +326,8 @@ impl SyncEngine {
     async fn push_pending_cases(
         &self,
         court_id: &str,
-    ) -> Result<usize, String> {
-        let pending = {
-            let conn = self.conn.lock().unwrap();
+    ) -> AppResult<usize> {
+        let pending = self.db.read(|conn| {
             let mut stmt = conn
                 .prepare(
                     "SELECT case_id, firestore_id, title, case_number, status, priority,
@@ -435,7 +341,7 @@ impl SyncEngine {
                      FROM cases
                      WHERE sync_status = 'pending' OR is_new = 1 OR has_changes = 1",
                 )
-                .map_err(|e| format!("Failed to query pending cases: {}", e))?;
+                .context("Failed to query pending cases")?;
 
             let rows = stmt
                 .query_map([], |row| {
@@ -474,11 +380,12 @@ impl SyncEngine {
                         court_id: row.get(31)?,
                     })
                 })
-                .map_err(|e| format!("Failed to map case rows: {}", e))?
+                .context("Failed to map case rows")?
                 .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| format!("Failed to collect case rows: {}", e))?;
-            rows
-        };
+                .context("Failed to collect case rows")?;
+            Ok(rows)
+        })
+        .await?;
 
         let count = pending.len();
 
@@ -489,12 +396,17 @@ impl SyncEngine {
                 Some(id) => id.clone(),
                 None => {
                     let new_id = uuid::Uuid::new_v4().to_string();
-                    let conn = self.conn.lock().unwrap();
-                    conn.execute(
-                        "UPDATE cases SET firestore_id = ? WHERE case_id = ?",
-                        params![new_id, case.case_id],
-                    )
-                    .map_err(|e| format!("Failed to set case firestore_id: {}", e))?;
+                    let (id, case_id) = (new_id.clone(), case.case_id);
+                    self.db
+                        .write(move |conn| {
+                            conn.execute(
+                                "UPDATE cases SET firestore_id = ? WHERE case_id = ?",
+                                params![id, case_id],
+                            )
+                            .context("Failed to set case firestore_id")?;
+                            Ok(())
+                        })
+                        .await?;
                     new_id
                 }
             };
@@ -503,19 +415,30 @@ impl SyncEngine {
             let doc_path = format!("courts/{}/cases/{}", court_id, firestore_id);
 
             if case.is_deleted.unwrap_or(false) {
-                match self.firestore.delete_document(&doc_path).await {
-                    Ok(_) => {
-                        let conn = self.conn.lock().unwrap();
+                self.firestore
+                    .delete_document(&doc_path)
+                    .await
+                    .map_err(|e| {
+                        e.context(&format!(
+                            "Failed to delete case {} from Firestore",
+                            firestore_id
+                        ))
+                    })?;
+                // The tombstone stays: hearings, status history and counts
+                // hang off the case
+                let case_id = case.case_id;
+                self.db
+                    .write(move |conn| {
                         conn.execute(
-                            "DELETE FROM cases WHERE case_id = ?",
-                            params![case.case_id],
+                            "UPDATE cases SET sync_status = 'synced', is_new = 0, has_changes = 0,
+                             last_synced_at = strftime('%s','now')
+                             WHERE case_id = ?",
+                            params![case_id],
                         )
-                        .ok();
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to delete case from Firestore: {}", e);
-                    }
-                }
+                        .context("Failed to mark deleted case as synced")?;
+                        Ok(())
+                    })
+                    .await?;
             } else {
                 let result = self
                     .firestore
@@ -529,19 +452,24 @@ impl SyncEngine {
                             .create_document(&collection, &firestore_id, &fields)
                             .await
                             .map_err(|e| {
-                                format!("Failed to push case {}: {}", firestore_id, e)
+                                e.context(&format!("Failed to push case {}", firestore_id))
                             })?;
                     }
                 }
 
-                let conn = self.conn.lock().unwrap();
-                conn.execute(
-                    "UPDATE cases SET sync_status = 'synced', is_new = 0, has_changes = 0,
-                     last_synced_at = strftime('%s','now'), version = COALESCE(version, 0) + 1
-                     WHERE case_id = ?",
-                    params![case.case_id],
-                )
-                .map_err(|e| format!("Failed to mark case as synced: {}", e))?;
+                let case_id = case.case_id;
+                self.db
+                    .write(move |conn| {
+                        conn.execute(
+                            "UPDATE cases SET sync_status = 'synced', is_new = 0, has_changes = 0,
+                             last_synced_at = strftime('%s','now'), version = COALESCE(version, 0) + 1
+                             WHERE case_id = ?",
+                            params![case_id],
+                        )
+                        .context("Failed to mark case as synced")?;
+                        Ok(())
+                    })
+                    .await?;
             }
         }
 
@@ -552,7 +480,7 @@ impl SyncEngine {
         &self,
         court_id: &str,
         last_sync: &str,
-    ) -> Result<usize, String> {
+    ) -> AppResult<usize> {
         let parent = format!("courts/{}", court_id);
 
         let query = StructuredQuery {
@@ -573,135 +501,614 @@ impl SyncEngine {
             .firestore
             .run_query(&parent, query)
             .await
-            .unwrap_or_else(|e| {
-                eprintln!("Failed to query remote cases: {}", e);
-                vec![]
-            });
+            .context("Failed to query remote cases")?;
 
         let count = remote_docs.len();
-        let conn = self.conn.lock().unwrap();
+        let court_id = court_id.to_string();
+
+        self.db
+            .write(move |conn| {
+                let mut skipped = 0;
+                for doc in &remote_docs {
+                    // One bad document must not hold back the rest of the
+                    // pass; its partial writes are rolled back with the
+                    // savepoint
+                    let savepoint = conn.savepoint()?;
+                    match apply_remote_case(&savepoint, doc, &court_id) {
+                        Ok(()) => savepoint.commit()?,
+                        Err(e) => {
+                            eprintln!("Skipping remote case {}: {}", doc.name, e);
+                            skipped += 1;
+                        }
+                    }
+                }
 
-        for doc in &remote_docs {
-            let firestore_id = extract_document_id(&doc.name);
-            if firestore_id.is_empty() {
-                continue;
-            }
+                Ok(count - skipped)
+            })
+            .await
+    }
 
-            let fields = &doc.fields;
+    // ========================================================================
+    // Offender–Case Link Sync
+    // ========================================================================
 
-            let local_sync_status: Option<String> = conn
-                .query_row(
-                    "SELECT sync_status FROM cases WHERE firestore_id = ?",
-                    params![firestore_id],
-                    |row| row.get(0),
+    async fn push_pending_offender_cases(
+        &self,
+        court_id: &str,
+    ) -> AppResult<usize> {
+        // Run after offenders and cases are pushed, so both sides have their
+        // Firestore ids
+        let pending = self.db.read(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT l.id, l.firestore_id, o.firestore_id, c.firestore_id, l.role, l.notes,
+                            l.created_by, l.created_at, l.is_deleted, l.version, l.last_modified
+                     FROM offender_cases l
+                     LEFT JOIN offenders o ON o.offender_id = l.offender_id
+                     LEFT JOIN cases c ON c.case_id = l.case_id
+                     WHERE l.sync_status = 'pending'
+                     ORDER BY l.id",
                 )
-                .ok();
+                .context("Failed to query pending offender case links")?;
 
-            if local_sync_status.as_deref() == Some("pending") {
-                continue;
-            }
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(OffenderCaseRow {
+                        id: row.get(0)?,
+                        firestore_id: row.get(1)?,
+                        offender_firestore_id: row.get(2)?,
+                        case_firestore_id: row.get(3)?,
+                        role: row.get(4)?,
+                        notes: row.get(5)?,
+                        created_by: row.get(6)?,
+                        created_at: row.get(7)?,
+                        is_deleted: row.get(8)?,
+                        version: row.get(9)?,
+                        last_modified: row.get(10)?,
+                    })
+                })
+                .context("Failed to map offender case link rows")?
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to collect offender case link rows")?;
+            Ok(rows)
+        })
+        .await?;
+
+        let mut count = 0;
+
+        for link in &pending {
+            let is_deleted = link.is_deleted.unwrap_or(false);
+            let firestore_id = match (
+                &link.firestore_id,
+                &link.offender_firestore_id,
+                &link.case_firestore_id,
+            ) {
+                (Some(id), _, _) => id.clone(),
+                // One id per offender, case and role, so the same link made
+                // on two devices lands on one document
+                (None, Some(offender), Some(case)) if !is_deleted => {
+                    let new_id = format!("{}_{}_{}", offender, case, link.role);
+                    let (id, link_id) = (new_id.clone(), link.id);
+                    self.db
+                        .write(move |conn| {
+                            conn.execute(
+                                "UPDATE offender_cases SET firestore_id = ? WHERE id = ?",
+                                params![id, link_id],
+                            )
+                            .context("Failed to set firestore_id")?;
+                            Ok(())
+                        })
+                        .await?;
+                    new_id
+                }
+                // A side whose push failed: the link stays pending and is
+                // tried again next sync
+                _ => continue,
+            };
 
-            let title = get_str(fields, "title").unwrap_or_default();
-            let case_number = get_str(fields, "caseNumber");
-            let status = get_str(fields, "status").unwrap_or_else(|| "Open".to_string());
-            let priority = get_str(fields, "priority").unwrap_or_else(|| "Medium".to_string());
-            let case_type = get_str(fields, "CaseType");
-            let description = get_str(fields, "description");
-            let court_name = get_str(fields, "courtName");
-            let date_filed = get_str(fields, "dateFiled");
-            let date_of_judgment = get_str(fields, "dateOfJudgment");
-            let judge_name = get_str(fields, "judgeName");
-            let complainant_name = get_str(fields, "complainantName");
-            let accused_name = get_str(fields, "accusedName");
-            let charge_description = get_str(fields, "chargeDescription");
-            let applicable_law = get_str(fields, "applicableLaw");
-            let verdict = get_str(fields, "verdict");
-            let sentence = get_str(fields, "sentence");
-            let mitigation_notes = get_str(fields, "mitigationNotes");
-            let prosecution_counsel = get_str(fields, "prosecutionCounsel");
-            let defense_witnesses = get_str(fields, "defenseWitnesses");
-            let prosecution_witnesses = get_str(fields, "prosecutionWitnesses");
-            let evidence_summary = get_str(fields, "evidenceSummary");
-            let appeal_status = get_str(fields, "appealStatus");
-            let location_of_offence = get_str(fields, "locationOfOffence");
-            let hearing_dates = get_str(fields, "hearingDates");
-            let court_assistant = get_str(fields, "courtAssistant");
-            let uploaded_by_id = get_str(fields, "uploadedById");
-            let is_deleted = get_bool(fields, "isDeleted").unwrap_or(false);
-            let version = get_int(fields, "version").unwrap_or(1);
-
-            if is_deleted {
-                conn.execute(
-                    "DELETE FROM cases WHERE firestore_id = ?",
-                    params![firestore_id],
-                )
-                .ok();
-                continue;
+            let collection = format!("courts/{}/offenderCases", court_id);
+            let doc_path = format!("courts/{}/offenderCases/{}", court_id, firestore_id);
+            let fields = offender_case_to_firestore_fields(link, court_id);
+
+            // Deletions are written as isDeleted rather than removing the
+            // document, so other devices pull them and drop their copy
+            let result = self
+                .firestore
+                .update_document(&doc_path, &fields)
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(_) => {
+                    self.firestore
+                        .create_document(&collection, &firestore_id, &fields)
+                        .await
+                        .map_err(|e| {
+                            e.context(&format!(
+                                "Failed to push offender case link {}",
+                                firestore_id
+                            ))
+                        })?;
+                }
             }
 
+            // A link changed while it was being pushed (unlinked, relinked,
+            // moved by a merge) has a newer last_modified and stays pending
+            let (link_id, last_modified) = (link.id, link.last_modified.clone());
+            self.db
+                .write(move |conn| {
+                    if is_deleted {
+                        conn.execute(
+                            "DELETE FROM offender_cases
+                             WHERE id = ? AND is_deleted = 1 AND last_modified IS ?",
+                            params![link_id, last_modified],
+                        )
+                        .context("Failed to purge deleted offender case link")?;
+                    } else {
+                        conn.execute(
+                            "UPDATE offender_cases SET sync_status = 'synced',
+                             version = COALESCE(version, 0) + 1
+                             WHERE id = ? AND last_modified IS ?",
+                            params![link_id, last_modified],
+                        )
+                        .context("Failed to mark offender case link as synced")?;
+                    }
+                    Ok(())
+                })
+                .await?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    async fn pull_remote_offender_cases(
+        &self,
+        court_id: &str,
+        last_sync: &str,
+    ) -> AppResult<usize> {
+        let parent = format!("courts/{}", court_id);
+
+        let query = StructuredQuery {
+            from: vec![CollectionSelector {
+                collection_id: "offenderCases".to_string(),
+            }],
+            r#where: Some(Filter::field(
+                "updatedAt",
+                "GREATER_THAN",
+                FirestoreValue::timestamp(last_sync),
+            )),
+            order_by: Some(vec![OrderBy::asc("updatedAt")]),
+            limit: Some(500),
+            offset: None,
+        };
+
+        let remote_docs = self
+            .firestore
+            .run_query(&parent, query)
+            .await
+            .context("Failed to query remote offender case links")?;
+
+        let count = remote_docs.len();
+        let court_id = court_id.to_string();
+
+        self.db
+            .write(move |conn| {
+                let mut skipped = 0;
+                for doc in &remote_docs {
+                    // One bad document must not hold back the rest of the
+                    // pass; its partial writes are rolled back with the
+                    // savepoint
+                    let savepoint = conn.savepoint()?;
+                    match apply_remote_offender_case(&savepoint, doc, &court_id) {
+                        Ok(()) => savepoint.commit()?,
+                        Err(e) => {
+                            eprintln!("Skipping remote offender case link {}: {}", doc.name, e);
+                            skipped += 1;
+                        }
+                    }
+                }
+
+                Ok(count - skipped)
+            })
+            .await
+    }
+}
+
+// ============================================================================
+// Applying pulled documents
+// ============================================================================
+
+/// Apply one pulled offender document to the local database
+fn apply_remote_offender(
+    conn: &Connection,
+    doc: &FirestoreDocument,
+    court_id: &str,
+) -> AppResult<()> {
+    let firestore_id = extract_document_id(&doc.name);
+    if firestore_id.is_empty() {
+        return Ok(());
+    }
+
+    let fields = &doc.fields;
+
+    // Check if exists locally
+    let local_sync_status: Option<String> = conn
+        .query_row(
+            "SELECT sync_status FROM offenders WHERE firestore_id = ?",
+            params![firestore_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    // Skip if local has pending changes (conflict - local wins for now)
+    if local_sync_status.as_deref() == Some("pending") {
+        return Ok(());
+    }
+
+    let full_name = get_str(fields, "fullName").unwrap_or_default();
+    let national_id = get_str(fields, "nationalId");
+    let date_of_birth = get_str(fields, "dateOfBirth");
+    let gender = get_str(fields, "gender");
+    let notes = get_str(fields, "notes");
+    let penalty = get_str(fields, "penalty");
+    let penalty_notes = get_str(fields, "penaltyNotes");
+    let alias = get_str(fields, "alias");
+    let nationality = get_str(fields, "nationality");
+    let marital_status = get_str(fields, "maritalStatus");
+    let occupation = get_str(fields, "occupation");
+    let address = get_str(fields, "address");
+    let first_offender = get_bool(fields, "firstOffender");
+    let criminal_history = get_str(fields, "criminalHistory");
+    let known_associates = get_str(fields, "knownAssociates");
+    let arresting_officer = get_str(fields, "arrestingOfficer");
+    let place_of_arrest = get_str(fields, "placeOfArrest");
+    let arrest_date = get_str(fields, "arrestDate");
+    let case_number = get_str(fields, "caseNumber");
+    let eye_color = get_str(fields, "eyeColor");
+    let hair_color = get_str(fields, "hairColor");
+    let phone_number = get_str(fields, "phoneNumber");
+    let emergency_contact_name = get_str(fields, "emergencyContactName");
+    let emergency_contact_phone = get_str(fields, "emergencyContactPhone");
+    let emergency_contact_relationship = get_str(fields, "emergencyContactRelationship");
+    let legal_representation = get_str(fields, "legalRepresentation");
+    let medical_conditions = get_str(fields, "medicalConditions");
+    let risk_level = get_str(fields, "riskLevel");
+    let distinguishing_marks = get_str(fields, "distinguishingMarks");
+    let offender_type = get_str(fields, "type");
+    let status = get_str(fields, "status");
+    let facility = get_str(fields, "facility");
+    let offense_type = get_str(fields, "offenseType");
+    let uploaded_by_id = get_str(fields, "uploadedById");
+    let photo_storage_url = get_str(fields, "photoStorageUrl");
+    let is_deleted = get_bool(fields, "isDeleted").unwrap_or(false);
+    let version = get_int(fields, "version").unwrap_or(1);
+
+    if is_deleted {
+        // Remote was deleted - delete locally too
+        conn.execute(
+            "DELETE FROM offenders WHERE firestore_id = ?",
+            params![firestore_id],
+        )
+        .context("Failed to delete remotely deleted offender")?;
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO offenders (
+            firestore_id, court_id, full_name, national_id, date_of_birth, gender,
+            notes, penalty, penalty_notes, alias, nationality, marital_status,
+            occupation, address, first_offender, criminal_history, known_associates,
+            arresting_officer, place_of_arrest, arrest_date, case_number,
+            eye_color, hair_color, phone_number,
+            emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
+            legal_representation, medical_conditions, risk_level, distinguishing_marks,
+            type, status, facility, offense_type, uploaded_by_id, photo_storage_url,
+            is_deleted, version, sync_status, is_new, has_changes,
+            last_synced_at, date_created
+        ) VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+            ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32,
+            ?33, ?34, ?35, ?36, ?37, ?38, ?39, 'synced', 0, 0, strftime('%s','now'),
+            datetime('now')
+        )
+        ON CONFLICT(firestore_id) DO UPDATE SET
+            full_name = excluded.full_name,
+            national_id = excluded.national_id,
+            date_of_birth = excluded.date_of_birth,
+            gender = excluded.gender,
+            notes = excluded.notes,
+            penalty = excluded.penalty,
+            penalty_notes = excluded.penalty_notes,
+            alias = excluded.alias,
+            nationality = excluded.nationality,
+            marital_status = excluded.marital_status,
+            occupation = excluded.occupation,
+            address = excluded.address,
+            first_offender = excluded.first_offender,
+            criminal_history = excluded.criminal_history,
+            known_associates = excluded.known_associates,
+            arresting_officer = excluded.arresting_officer,
+            place_of_arrest = excluded.place_of_arrest,
+            arrest_date = excluded.arrest_date,
+            case_number = excluded.case_number,
+            eye_color = excluded.eye_color,
+            hair_color = excluded.hair_color,
+            phone_number = excluded.phone_number,
+            emergency_contact_name = excluded.emergency_contact_name,
+            emergency_contact_phone = excluded.emergency_contact_phone,
+            emergency_contact_relationship = excluded.emergency_contact_relationship,
+            legal_representation = excluded.legal_representation,
+            medical_conditions = excluded.medical_conditions,
+            risk_level = excluded.risk_level,
+            distinguishing_marks = excluded.distinguishing_marks,
+            type = excluded.type,
+            status = excluded.status,
+            facility = excluded.facility,
+            offense_type = excluded.offense_type,
+            uploaded_by_id = excluded.uploaded_by_id,
+            photo_storage_url = excluded.photo_storage_url,
+            version = excluded.version,
+            sync_status = 'synced',
+            is_new = 0,
+            has_changes = 0,
+            last_synced_at = strftime('%s','now')",
+        params![
+            firestore_id, court_id, full_name, national_id, date_of_birth, gender,
+            notes, penalty, penalty_notes, alias, nationality, marital_status,
+            occupation, address, first_offender, criminal_history, known_associates,
+            arresting_officer, place_of_arrest, arrest_date, case_number,
+            eye_color, hair_color, phone_number,
+            emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
+            legal_representation, medical_conditions, risk_level, distinguishing_marks,
+            offender_type, status, facility, offense_type, uploaded_by_id, photo_storage_url,
+            is_deleted, version
+        ],
+    )
+    .context("Failed to upsert remote offender")?;
+    Ok(())
+}
+
+/// Apply one pulled case document to the local database
+fn apply_remote_case(
+    conn: &Connection,
+    doc: &FirestoreDocument,
+    court_id: &str,
+) -> AppResult<()> {
+    let firestore_id = extract_document_id(&doc.name);
+    if firestore_id.is_empty() {
+        return Ok(());
+    }
+
+    let fields = &doc.fields;
+
+    let local_sync_status: Option<String> = conn
+        .query_row(
+            "SELECT sync_status FROM cases WHERE firestore_id = ?",
+            params![firestore_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if local_sync_status.as_deref() == Some("pending") {
+        return Ok(());
+    }
+
+    let title = get_str(fields, "title").unwrap_or_default();
+    let case_number = get_str(fields, "caseNumber");
+    let status = get_str(fields, "status").unwrap_or_else(|| "Open".to_string());
+    let priority = get_str(fields, "priority").unwrap_or_else(|| "Medium".to_string());
+    let case_type = get_str(fields, "CaseType");
+    let description = get_str(fields, "description");
+    let court_name = get_str(fields, "courtName");
+    let date_filed = get_str(fields, "dateFiled");
+    let date_of_judgment = get_str(fields, "dateOfJudgment");
+    let judge_name = get_str(fields, "judgeName");
+    let complainant_name = get_str(fields, "complainantName");
+    let accused_name = get_str(fields, "accusedName");
+    let charge_description = get_str(fields, "chargeDescription");
+    let applicable_law = get_str(fields, "applicableLaw");
+    let verdict = get_str(fields, "verdict");
+    let sentence = get_str(fields, "sentence");
+    let mitigation_notes = get_str(fields, "mitigationNotes");
+    let prosecution_counsel = get_str(fields, "prosecutionCounsel");
+    let defense_witnesses = get_str(fields, "defenseWitnesses");
+    let prosecution_witnesses = get_str(fields, "prosecutionWitnesses");
+    let evidence_summary = get_str(fields, "evidenceSummary");
+    let appeal_status = get_str(fields, "appealStatus");
+    let location_of_offence = get_str(fields, "locationOfOffence");
+    let hearing_dates = get_str(fields, "hearingDates");
+    let court_assistant = get_str(fields, "courtAssistant");
+    let uploaded_by_id = get_str(fields, "uploadedById");
+    let is_deleted = get_bool(fields, "isDeleted").unwrap_or(false);
+    let version = get_int(fields, "version").unwrap_or(1);
+
+    if is_deleted {
+        conn.execute(
+            "UPDATE cases SET is_deleted = 1, sync_status = 'synced', is_new = 0,
+             has_changes = 0, last_synced_at = strftime('%s','now')
+             WHERE firestore_id = ?",
+            params![firestore_id],
+        )
+        .context("Failed to delete remotely deleted case")?;
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO cases (
+            firestore_id, court_id, title, case_number, status, priority,
+            date_created, case_type, description, court_name, date_filed,
+            date_of_judgment, judge_name, complainant_name, accused_name,
+            charge_description, applicable_law, verdict, sentence,
+            mitigation_notes, prosecution_counsel, defense_witnesses,
+            prosecution_witnesses, evidence_summary, appeal_status,
+            location_of_offence, hearing_dates, court_assistant,
+            uploaded_by_id, is_deleted, version,
+            sync_status, is_new, has_changes, last_synced_at
+        ) VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, datetime('now'), ?7, ?8, ?9, ?10, ?11, ?12,
+            ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25,
+            ?26, ?27, ?28, ?29, ?30, 'synced', 0, 0, strftime('%s','now')
+        )
+        ON CONFLICT(firestore_id) DO UPDATE SET
+            title = excluded.title,
+            case_number = excluded.case_number,
+            status = excluded.status,
+            priority = excluded.priority,
+            case_type = excluded.case_type,
+            description = excluded.description,
+            court_name = excluded.court_name,
+            date_filed = excluded.date_filed,
+            date_of_judgment = excluded.date_of_judgment,
+            judge_name = excluded.judge_name,
+            complainant_name = excluded.complainant_name,
+            accused_name = excluded.accused_name,
+            charge_description = excluded.charge_description,
+            applicable_law = excluded.applicable_law,
+            verdict = excluded.verdict,
+            sentence = excluded.sentence,
+            mitigation_notes = excluded.mitigation_notes,
+            prosecution_counsel = excluded.prosecution_counsel,
+            defense_witnesses = excluded.defense_witnesses,
+            prosecution_witnesses = excluded.prosecution_witnesses,
+            evidence_summary = excluded.evidence_summary,
+            appeal_status = excluded.appeal_status,
+            location_of_offence = excluded.location_of_offence,
+            hearing_dates = excluded.hearing_dates,
+            court_assistant = excluded.court_assistant,
+            uploaded_by_id = excluded.uploaded_by_id,
+            version = excluded.version,
+            sync_status = 'synced',
+            is_new = 0,
+            has_changes = 0,
+            last_synced_at = strftime('%s','now')",
+        params![
+            firestore_id, court_id, title, case_number, status, priority,
+            case_type, description, court_name, date_filed, date_of_judgment,
+            judge_name, complainant_name, accused_name, charge_description,
+            applicable_law, verdict, sentence, mitigation_notes,
+            prosecution_counsel, defense_witnesses, prosecution_witnesses,
+            evidence_summary, appeal_status, location_of_offence,
+            hearing_dates, court_assistant, uploaded_by_id, is_deleted, version
+        ],
+    )
+    .context("Failed to upsert remote case")?;
+    Ok(())
+}
+
+/// Apply one pulled offender case link document to the local database
+fn apply_remote_offender_case(
+    conn: &Connection,
+    doc: &FirestoreDocument,
+    court_id: &str,
+) -> AppResult<()> {
+    let firestore_id = extract_document_id(&doc.name);
+    if firestore_id.is_empty() {
+        return Ok(());
+    }
+
+    let fields = &doc.fields;
+
+    let local: Option<(i64, Option<String>)> = conn
+        .query_row(
+            "SELECT id, sync_status FROM offender_cases WHERE firestore_id = ?",
+            params![firestore_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    // Skip if local has pending changes (conflict - local wins for now)
+    let local_status = local.as_ref().and_then(|(_, status)| status.as_deref());
+    if local_status == Some("pending") {
+        return Ok(());
+    }
+
+    let is_deleted = get_bool(fields, "isDeleted").unwrap_or(false);
+    let role = get_str(fields, "role").filter(|r| CaseRole::parse(r).is_some());
+    let local_side = |table: &str, id_column: &str, key: &str| {
+        let Some(side) = get_str(fields, key) else {
+            return Ok(None);
+        };
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM {} WHERE firestore_id = ?
+                 AND COALESCE(is_deleted, 0) = 0",
+                id_column, table
+            ),
+            params![side],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()
+        .context(&format!("Failed to resolve remote offender case link {}", key))
+    };
+    let offender_id = local_side("offenders", "offender_id", "offenderId")?;
+    let case_id = local_side("cases", "case_id", "caseId")?;
+
+    // Deleted, or either side is not (or no longer) here
+    let (Some(offender_id), Some(case_id), Some(role), false) =
+        (offender_id, case_id, role, is_deleted)
+    else {
+        conn.execute(
+            "DELETE FROM offender_cases WHERE firestore_id = ?",
+            params![firestore_id],
+        )
+        .context("Failed to drop remote offender case link")?;
+        return Ok(());
+    };
+
+    let notes = get_str(fields, "notes");
+    let created_by = get_str(fields, "createdBy");
+    let created_at = get_str(fields, "createdAt");
+    let version = get_int(fields, "version").unwrap_or(1);
+
+    // The same link made here before it was first pushed
+    let local_id = match local {
+        Some((id, _)) => Some(id),
+        None => conn
+            .query_row(
+                "SELECT id FROM offender_cases
+                 WHERE offender_id = ? AND case_id = ? AND role = ?
+                   AND is_deleted = 0 AND firestore_id IS NULL",
+                params![offender_id, case_id, role],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?,
+    };
+
+    match local_id {
+        Some(id) => {
             conn.execute(
-                "INSERT INTO cases (
-                    firestore_id, court_id, title, case_number, status, priority,
-                    date_created, case_type, description, court_name, date_filed,
-                    date_of_judgment, judge_name, complainant_name, accused_name,
-                    charge_description, applicable_law, verdict, sentence,
-                    mitigation_notes, prosecution_counsel, defense_witnesses,
-                    prosecution_witnesses, evidence_summary, appeal_status,
-                    location_of_offence, hearing_dates, court_assistant,
-                    uploaded_by_id, is_deleted, version,
-                    sync_status, is_new, has_changes, last_synced_at
+                "UPDATE offender_cases SET firestore_id = ?2, offender_id = ?3,
+                     case_id = ?4, role = ?5, notes = ?6, created_by = ?7,
+                     is_deleted = 0, court_id = ?8, version = ?9,
+                     sync_status = 'synced',
+                     last_modified = strftime('%Y-%m-%d %H:%M:%f', 'now')
+                 WHERE id = ?1",
+                params![
+                    id, firestore_id, offender_id, case_id, role, notes,
+                    created_by, court_id, version
+                ],
+            )
+            .context("Failed to update remote offender case link")?;
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO offender_cases (
+                    firestore_id, offender_id, case_id, role, notes, created_by,
+                    created_at, court_id, version, sync_status
                 ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5, ?6, datetime('now'), ?7, ?8, ?9, ?10, ?11, ?12,
-                    ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25,
-                    ?26, ?27, ?28, ?29, ?30, 'synced', 0, 0, strftime('%s','now')
-                )
-                ON CONFLICT(firestore_id) DO UPDATE SET
-                    title = excluded.title,
-                    case_number = excluded.case_number,
-                    status = excluded.status,
-                    priority = excluded.priority,
-                    case_type = excluded.case_type,
-                    description = excluded.description,
-                    court_name = excluded.court_name,
-                    date_filed = excluded.date_filed,
-                    date_of_judgment = excluded.date_of_judgment,
-                    judge_name = excluded.judge_name,
-                    complainant_name = excluded.complainant_name,
-                    accused_name = excluded.accused_name,
-                    charge_description = excluded.charge_description,
-                    applicable_law = excluded.applicable_law,
-                    verdict = excluded.verdict,
-                    sentence = excluded.sentence,
-                    mitigation_notes = excluded.mitigation_notes,
-                    prosecution_counsel = excluded.prosecution_counsel,
-                    defense_witnesses = excluded.defense_witnesses,
-                    prosecution_witnesses = excluded.prosecution_witnesses,
-                    evidence_summary = excluded.evidence_summary,
-                    appeal_status = excluded.appeal_status,
-                    location_of_offence = excluded.location_of_offence,
-                    hearing_dates = excluded.hearing_dates,
-                    court_assistant = excluded.court_assistant,
-                    uploaded_by_id = excluded.uploaded_by_id,
-                    version = excluded.version,
-                    sync_status = 'synced',
-                    is_new = 0,
-                    has_changes = 0,
-                    last_synced_at = strftime('%s','now')",
+                    ?1, ?2, ?3, ?4, ?5, ?6, COALESCE(?7, CURRENT_TIMESTAMP),
+                    ?8, ?9, 'synced'
+                )",
                 params![
-                    firestore_id, court_id, title, case_number, status, priority,
-                    case_type, description, court_name, date_filed, date_of_judgment,
-                    judge_name, complainant_name, accused_name, charge_description,
-                    applicable_law, verdict, sentence, mitigation_notes,
-                    prosecution_counsel, defense_witnesses, prosecution_witnesses,
-                    evidence_summary, appeal_status, location_of_offence,
-                    hearing_dates, court_assistant, uploaded_by_id, is_deleted, version
+                    firestore_id, offender_id, case_id, role, notes, created_by,
+                    created_at, court_id, version
                 ],
             )
-            .map_err(|e| format!("Failed to upsert remote case: {}", e))?;
+            .context("Failed to insert remote offender case link")?;
         }
-
-        Ok(count)
     }
+    Ok(())
 }
 
 // ============================================================================
@@ -789,6 +1196,20 @@ struct CaseRow {
     court_id: Option<String>,
 }
 
+struct OffenderCaseRow {
+    id: i64,
+    firestore_id: Option<String>,
+    offender_firestore_id: Option<String>,
+    case_firestore_id: Option<String>,
+    role: String,
+    notes: Option<String>,
+    created_by: Option<String>,
+    created_at: Option<String>,
+    is_deleted: Option<bool>,
+    version: Option<i64>,
+    last_modified: Option<String>,
+}
+
 // ============================================================================
 // Firestore field mapping helpers
 // ============================================================================
@@ -936,3 +1357,28 @@ fn case_to_firestore_fields(c: &CaseRow, court_id: &str) -> HashMap<String, Fire
 
     fields
 }
+
+/// Map an OffenderCaseRow to Firestore fields; the two sides by Firestore id
+fn offender_case_to_firestore_fields(
+    l: &OffenderCaseRow,
+    court_id: &str,
+) -> HashMap<String, FirestoreValue> {
+    let mut fields = HashMap::new();
+
+    set_str_required(&mut fields, "role", &l.role);
+    set_str_required(&mut fields, "courtId", court_id);
+    set_str(&mut fields, "offenderId", &l.offender_firestore_id);
+    set_str(&mut fields, "caseId", &l.case_firestore_id);
+    set_str(&mut fields, "notes", &l.notes);
+    set_str(&mut fields, "createdBy", &l.created_by);
+    set_str(&mut fields, "createdAt", &l.created_at);
+    set_bool(&mut fields, "isDeleted", &l.is_deleted);
+    set_int(&mut fields, "version", &l.version);
+
+    fields.insert(
+        "updatedAt".to_string(),
+        FirestoreValue::timestamp(&Utc::now().to_rfc3339()),
+    );
+
+    fields
+}
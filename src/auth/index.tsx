@@ -18,6 +18,8 @@ import { useEffect, useState } from "react";
 import * as Yup from "yup";
 import { useAuth } from "../context/auth_context";
 import { loginUser, registerUser, storeAuthData } from "../services/auth";
+import { getEncryptionStatus, unlockDatabase } from "../services/encryption";
+import { errorMessage } from "../services/errors";
 
 const ROLES = ["Judge", "Magistrate", "Court Admin", "Court Clerk", "Other"];
 
@@ -98,6 +100,128 @@ const Toast = ({
   );
 };
 
+// Shown instead of the login form while a passphrase-encrypted database is
+// still locked; nothing can be read until the passphrase is entered. When the
+// machine key could not unlock the database at startup, there is nothing to
+// enter, so the form gives way to the reason.
+const UnlockForm = ({
+  darkMode,
+  unlockError,
+  onUnlocked,
+}: {
+  darkMode: boolean;
+  unlockError: string | null;
+  onUnlocked: () => void;
+}) => {
+  const [toast, setToast] = useState<null | {
+    message: string;
+    type: "success" | "error";
+  }>(null);
+
+  const handleSubmit = async (
+    values: { passphrase: string },
+    {
+      setSubmitting,
+      resetForm,
+    }: {
+      setSubmitting: (isSubmitting: boolean) => void;
+      resetForm: () => void;
+    }
+  ) => {
+    try {
+      await unlockDatabase(values.passphrase);
+      onUnlocked();
+    } catch (error) {
+      resetForm();
+      setToast({ message: errorMessage(error), type: "error" });
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  return (
+    <div className="w-full max-w-md mx-auto">
+      {toast && (
+        <Toast
+          message={toast.message}
+          type={toast.type}
+          onClose={() => setToast(null)}
+        />
+      )}
+
+      <div className="bg-white/90 dark:bg-slate-800/90 backdrop-blur-xl rounded-3xl p-8 shadow-2xl border border-white/20 dark:border-slate-700/50">
+        <div className="text-center mb-8">
+          <div className="mx-auto mb-4 rounded-2xl flex items-center justify-center">
+            <img
+              src={darkMode ? "/logo/icon-dark.png" : "/logo/icon-light.png"}
+              alt="Kilungu Law Courts Logo"
+              className="w-20 h-20 rounded-sm m-1"
+            />
+          </div>
+          <h2 className="text-3xl font-bold bg-gradient-to-r from-blue-600 to-indigo-600 dark:from-blue-400 dark:to-indigo-400 bg-clip-text text-transparent">
+            Database Locked
+          </h2>
+          <p className="text-gray-600 dark:text-gray-400 mt-2">
+            {unlockError
+              ? "This PC could not unlock the court records"
+              : "Enter the encryption passphrase to open the court records"}
+          </p>
+        </div>
+
+        {unlockError ? (
+          <div className="flex items-start space-x-2 p-4 bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-xl text-red-700 dark:text-red-300">
+            <AlertCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+            <span>{unlockError}</span>
+          </div>
+        ) : (
+          <Formik
+            initialValues={{ passphrase: "" }}
+            validationSchema={Yup.object({
+              passphrase: Yup.string().required("Passphrase is required"),
+            })}
+            onSubmit={handleSubmit}
+          >
+            {({ isSubmitting }) => (
+              <Form className="space-y-6">
+                <div className="relative">
+                  <Lock className="absolute left-3 top-1/2 transform -translate-y-1/2 text-gray-400 w-5 h-5" />
+                  <Field
+                    type="password"
+                    name="passphrase"
+                    placeholder="Passphrase"
+                    autoFocus
+                    className="w-full pl-12 pr-4 py-3 bg-gray-50 dark:bg-slate-700 border border-gray-200 dark:border-slate-600 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent transition-all duration-300"
+                  />
+                  <ErrorMessage
+                    name="passphrase"
+                    component="div"
+                    className="text-red-500 text-sm mt-1"
+                  />
+                </div>
+
+                <button
+                  type="submit"
+                  disabled={isSubmitting}
+                  className="w-full py-3 bg-gradient-to-r from-blue-500 to-indigo-600 text-white font-semibold rounded-xl hover:from-blue-600 hover:to-indigo-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 transition-all duration-300 disabled:opacity-50 disabled:cursor-not-allowed"
+                >
+                  {isSubmitting ? (
+                    <div className="flex items-center justify-center space-x-2">
+                      <div className="w-5 h-5 border-2 border-white/30 border-t-white rounded-full animate-spin"></div>
+                      <span>Unlocking...</span>
+                    </div>
+                  ) : (
+                    "Unlock"
+                  )}
+                </button>
+              </Form>
+            )}
+          </Formik>
+        )}
+      </div>
+    </div>
+  );
+};
+
 const AuthForm = ({
   isLogin,
   onToggle,
@@ -160,13 +284,29 @@ const AuthForm = ({
     try {
       if (isLogin) {
         // Login user
-        const response = await loginUser({
+        let response = await loginUser({
           courtId: values.courtId,
           email: values.email,
           role: values.role,
           password: values.password,
         });
 
+        // Admin accounts with two-factor enabled need a second step
+        if (response.twoFactorRequired) {
+          const totpCode = window.prompt(
+            "Enter the 6-digit code from your authenticator app, or a recovery code"
+          );
+          if (totpCode) {
+            response = await loginUser({
+              courtId: values.courtId,
+              email: values.email,
+              role: values.role,
+              password: values.password,
+              totpCode,
+            });
+          }
+        }
+
         if (response.success && response.user) {
           // Store auth data and update context
           const authData = {
@@ -521,6 +661,20 @@ export default function Auth() {
   const [isLogin, setIsLogin] = useState(true);
   const [isOffline, setIsOffline] = useState(false);
   const [darkMode, setDarkMode] = useState(true);
+  const [locked, setLocked] = useState<boolean | null>(null);
+  const [unlockError, setUnlockError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getEncryptionStatus()
+      .then((status) => {
+        setLocked(status.enabled && !status.unlocked);
+        setUnlockError(status.unlock_error);
+      })
+      .catch((error) => {
+        console.error("Error fetching encryption status:", error);
+        setLocked(false);
+      });
+  }, []);
 
   useEffect(() => {
     if (darkMode) {
@@ -569,11 +723,19 @@ export default function Auth() {
       <div className="grid lg:grid-cols-5 min-h-screen">
         {/* Left sidebar - Auth Form */}
         <div className="lg:col-span-2 flex items-center justify-center p-8 bg-white/30 dark:bg-slate-900/30 backdrop-blur-sm">
-          <AuthForm
-            darkMode={darkMode}
-            isLogin={isLogin}
-            onToggle={() => setIsLogin(!isLogin)}
-          />
+          {locked === null ? null : locked ? (
+            <UnlockForm
+              darkMode={darkMode}
+              unlockError={unlockError}
+              onUnlocked={() => setLocked(false)}
+            />
+          ) : (
+            <AuthForm
+              darkMode={darkMode}
+              isLogin={isLogin}
+              onToggle={() => setIsLogin(!isLogin)}
+            />
+          )}
         </div>
 
         {/* Right content area */}
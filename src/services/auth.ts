@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import bcrypt from "bcryptjs";
+import { errorMessage } from "./errors";
 
 export interface User {
   user_id: number;
@@ -16,6 +17,7 @@ export interface LoginCredentials {
   email: string;
   role: string;
   password: string;
+  totpCode?: string;
 }
 
 export interface SignupData {
@@ -33,6 +35,7 @@ export interface AuthResponse {
   user?: Omit<User, "password_hash">;
   token?: string;
   message?: string;
+  twoFactorRequired?: boolean;
 }
 
 /**
@@ -60,16 +63,17 @@ export const loginUser = async (
   credentials: LoginCredentials
 ): Promise<AuthResponse> => {
   try {
-    const { courtId, email, role, password } = credentials;
+    const { courtId, email, role, password, totpCode } = credentials;
 
     // Step 1: Authenticate with Firebase
-    const firebaseResult: { success: boolean; firebase_uid: string; court_id: string | null; message: string } =
-      await invoke("firebase_login", { email, password, courtId });
+    const firebaseResult: { success: boolean; firebase_uid: string; court_id: string | null; message: string; two_factor_required?: boolean } =
+      await invoke("firebase_login", { email, password, courtId, totpCode: totpCode || null });
 
     if (!firebaseResult.success) {
       return {
         success: false,
         message: firebaseResult.message || "Firebase authentication failed",
+        twoFactorRequired: firebaseResult.two_factor_required || false,
       };
     }
 
@@ -120,10 +124,9 @@ export const loginUser = async (
   } catch (error) {
     console.error("Login error:", error);
     // Provide the actual error message from Firebase if available
-    const errorMessage = error instanceof Error ? error.message : String(error);
     return {
       success: false,
-      message: errorMessage || "An error occurred during login. Please try again.",
+      message: errorMessage(error) || "An error occurred during login. Please try again.",
     };
   }
 };
@@ -310,7 +313,7 @@ export const triggerSync = async (): Promise<{ success: boolean; message: string
   } catch (error) {
     return {
       success: false,
-      message: error instanceof Error ? error.message : String(error),
+      message: errorMessage(error),
     };
   }
 };
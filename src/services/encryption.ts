@@ -0,0 +1,34 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface EncryptionStatus {
+  enabled: boolean;
+  key_source: "passphrase" | "machine" | null;
+  database_encrypted: boolean;
+  unlocked: boolean;
+  /** Why the machine key could not unlock the database at startup */
+  unlock_error: string | null;
+}
+
+export interface UnlockResult {
+  message: string;
+  status: string;
+  photos_encrypted: number;
+}
+
+/**
+ * Whether encryption at rest is on and the database unlocked.
+ * Needs no sign-in: the unlock screen is shown before the login form.
+ */
+export const getEncryptionStatus = async (): Promise<EncryptionStatus> => {
+  return await invoke<EncryptionStatus>("get_encryption_status");
+};
+
+/**
+ * Unlock a passphrase-encrypted database. Rejects with a CommandError
+ * when the passphrase is wrong.
+ */
+export const unlockDatabase = async (
+  passphrase: string
+): Promise<UnlockResult> => {
+  return await invoke<UnlockResult>("unlock_database", { passphrase });
+};
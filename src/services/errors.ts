@@ -0,0 +1,38 @@
+/**
+ * Error shape returned by every Tauri command on failure.
+ * `code` is stable and safe to branch on; `message` is for display.
+ */
+export interface CommandError {
+  code:
+    | "VALIDATION_FAILED"
+    | "NOT_FOUND"
+    | "UNAUTHENTICATED"
+    | "FORBIDDEN"
+    | "CONFLICT"
+    | "DATABASE_BUSY"
+    | "DATABASE_LOCKED"
+    | "DATABASE_ERROR"
+    | "NETWORK_ERROR"
+    | "REMOTE_ERROR"
+    | "IO_ERROR"
+    | "CONFIG_ERROR"
+    | "INTERNAL_ERROR";
+  message: string;
+  field_errors: { field: string; message: string }[];
+}
+
+export const isCommandError = (error: unknown): error is CommandError =>
+  typeof error === "object" &&
+  error !== null &&
+  typeof (error as CommandError).code === "string" &&
+  typeof (error as CommandError).message === "string";
+
+/**
+ * Human-readable message for anything caught from `invoke`
+ */
+export const errorMessage = (error: unknown): string => {
+  if (isCommandError(error) || error instanceof Error) {
+    return error.message;
+  }
+  return String(error);
+};